@@ -13,6 +13,9 @@ declare global {
     page?: { module: any; chunks: string[] };
   } & {
     [componentKey in FileType]?: { module: any; chunks: string[] };
+  } & {
+    // the pages of parallel routes, keyed by `@slot`
+    [slotKey: `@${string}`]: { module: any; chunks: string[] };
   })[];
   // array of chunks for the bootstrap script
   const BOOTSTRAP: string[];
@@ -116,15 +119,26 @@ async function runOperation(renderData: RenderData) {
   for (let i = LAYOUT_INFO.length - 2; i >= 0; i--) {
     const info = LAYOUT_INFO[i];
     const components: ComponentsType = {};
+    const parallelRoutes: LoaderTree[1] = { children: tree };
     for (const key of Object.keys(info)) {
       if (key === "segment") {
         continue;
       }
+      if (key.startsWith("@")) {
+        const slot = info[key as `@${string}`];
+        parallelRoutes[key.slice(1)] = [
+          "",
+          {},
+          { page: [() => slot.module.default, `${key}${i}.js`] },
+        ];
+        layoutInfoChunks[`${key}${i}`] = slot.chunks;
+        continue;
+      }
       const k = key as FileType;
       components[k] = [() => info[k]!.module.default, `${k}${i}.js`];
       layoutInfoChunks[`${k}${i}`] = info[k]!.chunks;
     }
-    tree = [info.segment, { children: tree }, components];
+    tree = [info.segment, parallelRoutes, components];
   }
 
   const proxyMethodsForModule = (
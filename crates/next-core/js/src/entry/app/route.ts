@@ -0,0 +1,162 @@
+// IPC need to be the first import to allow it to catch errors happening during
+// the other imports
+import { IPC } from "@vercel/turbopack-next/ipc/index";
+
+import type { Ipc } from "@vercel/turbopack-next/ipc/index";
+import type { RenderData } from "types/turbopack";
+import { Buffer } from "node:buffer";
+
+import "next/dist/server/node-polyfill-fetch.js";
+import "next/dist/server/node-polyfill-web-streams";
+
+import * as allExports from ".";
+
+const ipc = IPC as Ipc<IpcIncomingMessage, IpcOutgoingMessage>;
+
+type IpcIncomingMessage =
+  | {
+      type: "headers";
+      data: RenderData;
+    }
+  | {
+      type: "bodyChunk";
+      data: Array<number>;
+    }
+  | { type: "bodyEnd" };
+
+type IpcOutgoingMessage =
+  | {
+      type: "headers";
+      data: ResponseHeaders;
+    }
+  | {
+      type: "body";
+      data: Array<number>;
+    };
+
+type ResponseHeaders = {
+  status: number;
+  headers: string[];
+};
+
+type RouteHandler = (
+  request: Request,
+  context: { params: RenderData["params"] }
+) => Response | Promise<Response>;
+
+const HTTP_METHODS = [
+  "GET",
+  "HEAD",
+  "OPTIONS",
+  "POST",
+  "PUT",
+  "DELETE",
+  "PATCH",
+];
+
+(async () => {
+  while (true) {
+    const msg = await ipc.recv();
+
+    let renderData: RenderData;
+    switch (msg.type) {
+      case "headers": {
+        renderData = msg.data;
+        break;
+      }
+      default: {
+        console.error("unexpected message type", msg.type);
+        process.exit(1);
+      }
+    }
+
+    const chunks: Buffer[] = [];
+    loop: while (true) {
+      const msg = await ipc.recv();
+
+      switch (msg.type) {
+        case "bodyChunk": {
+          chunks.push(Buffer.from(msg.data));
+          break;
+        }
+        case "bodyEnd": {
+          break loop;
+        }
+        default: {
+          console.error("unexpected message type", msg.type);
+          process.exit(1);
+        }
+      }
+    }
+
+    const response = await runOperation(renderData, Buffer.concat(chunks));
+
+    const headers: string[] = [];
+    response.headers.forEach((value, name) => {
+      headers.push(name, value);
+    });
+    ipc.send({
+      type: "headers",
+      data: {
+        status: response.status,
+        headers,
+      },
+    });
+    ipc.send({
+      type: "body",
+      data: Array.from(new Uint8Array(await response.arrayBuffer())),
+    });
+  }
+})().catch((err) => {
+  ipc.sendError(err);
+});
+
+async function runOperation(
+  renderData: RenderData,
+  body: Buffer
+): Promise<Response> {
+  const method = renderData.method.toUpperCase();
+  const allowedMethods = HTTP_METHODS.filter(
+    (method) => typeof getHandler(method) === "function"
+  );
+
+  const handler =
+    getHandler(method) ?? (method === "HEAD" ? getHandler("GET") : undefined);
+  if (typeof handler !== "function") {
+    return new Response(null, {
+      // OPTIONS requests are answered automatically with the allowed methods.
+      status: method === "OPTIONS" ? 204 : 405,
+      headers: { Allow: allowedMethods.join(", ") },
+    });
+  }
+
+  const headers = new Headers();
+  for (const [name, value] of Object.entries(renderData.headers)) {
+    for (const item of Array.isArray(value) ? value : [value]) {
+      if (typeof item === "string") {
+        headers.append(name, item);
+      }
+    }
+  }
+  const url = new URL(
+    renderData.url,
+    `http://${headers.get("host") ?? "localhost"}`
+  );
+  const request = new Request(url, {
+    method,
+    headers,
+    body: method === "GET" || method === "HEAD" ? undefined : body,
+  });
+
+  const response = await handler(request, { params: renderData.params });
+  if (!(response instanceof Response)) {
+    throw new Error(
+      `No response is returned from route handler '${renderData.path}'. Ensure you return a \`Response\` from the handler.`
+    );
+  }
+  return response;
+}
+
+function getHandler(method: string): RouteHandler | undefined {
+  return (allExports as Record<string, RouteHandler | undefined>)[method];
+}
@@ -0,0 +1,232 @@
+import { adapter } from "next/dist/server/web/adapter";
+import { pathToRegexp } from "next/dist/compiled/path-to-regexp";
+
+import * as middlewareExports from ".";
+
+import type { NextMiddleware } from "next/server";
+
+type MiddlewareRequest = {
+  method: string;
+  url: string;
+  headers: Record<string, string | string[]>;
+};
+
+type MiddlewareResult =
+  | {
+      type: "next";
+      requestHeaders: string[] | null;
+      responseHeaders: string[];
+    }
+  | {
+      type: "rewrite";
+      url: string;
+      path: string;
+      query: Record<string, string | string[]>;
+      requestHeaders: string[] | null;
+      responseHeaders: string[];
+    }
+  | {
+      type: "response";
+      status: number;
+      headers: string[];
+      body: number[];
+    };
+
+type Matcher = string | { source: string };
+
+const DATA_PATH_PREFIX = "/_next/data/development";
+const INTERNAL_HEADERS = [
+  "x-middleware-next",
+  "x-middleware-override-headers",
+  "x-middleware-rewrite",
+];
+
+const handler: NextMiddleware | undefined =
+  (middlewareExports as any).middleware ?? (middlewareExports as any).default;
+const matchers = getMatchers((middlewareExports as any).config?.matcher);
+
+/**
+ * Runs the middleware for a request in the edge sandbox and reports how the
+ * request should be continued.
+ */
+export default async function runMiddleware(
+  _ipc: unknown,
+  request: MiddlewareRequest
+): Promise<MiddlewareResult> {
+  if (typeof handler !== "function") {
+    throw new Error(
+      "The middleware must export a `middleware` or a `default` function"
+    );
+  }
+
+  const headers = new Headers();
+  for (const [name, value] of Object.entries(request.headers)) {
+    for (const item of Array.isArray(value) ? value : [value]) {
+      headers.append(name, item);
+    }
+  }
+  const url = new URL(
+    request.url,
+    `http://${headers.get("host") ?? "localhost"}`
+  );
+
+  const pagePathname = getPagePathname(url.pathname);
+  if (
+    matchers != null &&
+    !matchers.some((matcher) => matcher.test(pagePathname ?? url.pathname))
+  ) {
+    return { type: "next", requestHeaders: null, responseHeaders: [] };
+  }
+
+  const { response } = await adapter({
+    handler,
+    page: "/middleware",
+    request: {
+      method: request.method,
+      url: url.toString(),
+      headers: Object.fromEntries(headers),
+    },
+  });
+
+  if (response == null) {
+    return { type: "next", requestHeaders: null, responseHeaders: [] };
+  }
+
+  const rewrite = response.headers.get("x-middleware-rewrite");
+  if (rewrite != null) {
+    const rewriteUrl = new URL(rewrite, url);
+    if (rewriteUrl.origin !== url.origin) {
+      // External rewrites are proxied directly.
+      return toResult(
+        await fetch(rewriteUrl, {
+          method: request.method,
+          headers: getRequestHeaders(response) ?? headers,
+        })
+      );
+    }
+    if (pagePathname != null && getPagePathname(rewriteUrl.pathname) == null) {
+      // Data requests of client side navigations need to be rewritten to the
+      // data of the target page.
+      rewriteUrl.pathname = `${DATA_PATH_PREFIX}${
+        rewriteUrl.pathname === "/" ? "/index" : rewriteUrl.pathname
+      }.json`;
+    }
+    return {
+      type: "rewrite",
+      url: rewriteUrl.pathname + rewriteUrl.search,
+      path: decodeURIComponent(rewriteUrl.pathname.slice(1)),
+      query: getQuery(rewriteUrl),
+      requestHeaders: flattenRequestHeaders(response),
+      responseHeaders: flattenHeaders(response.headers, true),
+    };
+  }
+
+  if (response.headers.get("x-middleware-next") != null) {
+    return {
+      type: "next",
+      requestHeaders: flattenRequestHeaders(response),
+      responseHeaders: flattenHeaders(response.headers, true),
+    };
+  }
+
+  return toResult(response);
+}
+
+function getMatchers(matcher: Matcher | Matcher[] | undefined) {
+  if (matcher == null) {
+    return null;
+  }
+  return (Array.isArray(matcher) ? matcher : [matcher]).map((matcher) => {
+    const source = typeof matcher === "string" ? matcher : matcher.source;
+    if (!source.startsWith("/")) {
+      throw new Error(
+        `The middleware matcher \`${source}\` needs to start with a \`/\``
+      );
+    }
+    return pathToRegexp(source, [], { delimiter: "/", sensitive: false });
+  });
+}
+
+/**
+ * Returns the pathname of the page a `_next/data` request of a client side
+ * navigation fetches the data for, or null for other requests.
+ */
+function getPagePathname(pathname: string): string | null {
+  if (
+    !pathname.startsWith(`${DATA_PATH_PREFIX}/`) ||
+    !pathname.endsWith(".json")
+  ) {
+    return null;
+  }
+  const page = pathname.slice(DATA_PATH_PREFIX.length, -".json".length);
+  return page === "/index" ? "/" : page;
+}
+
+function getQuery(url: URL): Record<string, string | string[]> {
+  const query: Record<string, string | string[]> = {};
+  for (const [key, value] of url.searchParams) {
+    const existing = query[key];
+    if (existing == null) {
+      query[key] = value;
+    } else if (Array.isArray(existing)) {
+      existing.push(value);
+    } else {
+      query[key] = [existing, value];
+    }
+  }
+  return query;
+}
+
+/**
+ * Returns the request headers set with `NextResponse.next({ request })`, which
+ * replace the original request headers. Returns null when the middleware did
+ * not change them.
+ */
+function getRequestHeaders(response: Response): Headers | null {
+  const overridden = response.headers.get("x-middleware-override-headers");
+  if (overridden == null) {
+    return null;
+  }
+  const headers = new Headers();
+  for (const name of overridden.split(",")) {
+    const value = response.headers.get(`x-middleware-request-${name.trim()}`);
+    if (value != null) {
+      headers.set(name.trim(), value);
+    }
+  }
+  return headers;
+}
+
+function flattenHeaders(headers: Headers, withoutInternal = false): string[] {
+  const result: string[] = [];
+  headers.forEach((value, name) => {
+    if (!withoutInternal || !isInternalHeader(name)) {
+      result.push(name, value);
+    }
+  });
+  return result;
+}
+
+/**
+ * Whether the header is used by `NextResponse` to tell the server how to
+ * continue, instead of being sent to the client.
+ */
+function isInternalHeader(name: string): boolean {
+  return (
+    INTERNAL_HEADERS.includes(name) || name.startsWith("x-middleware-request-")
+  );
+}
+
+function flattenRequestHeaders(response: Response): string[] | null {
+  const headers = getRequestHeaders(response);
+  return headers == null ? null : flattenHeaders(headers);
+}
+
+async function toResult(response: Response): Promise<MiddlewareResult> {
+  return {
+    type: "response",
+    status: response.status,
+    headers: flattenHeaders(response.headers, true),
+    body: Array.from(new Uint8Array(await response.arrayBuffer())),
+  };
+}
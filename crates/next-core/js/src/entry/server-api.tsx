@@ -39,7 +39,7 @@ type IpcOutgoingMessage =
 
 type RenderData = {
   method: string;
-  params: Record<string, string>;
+  params: Record<string, string | string[]>;
   path: string;
   query: NextParsedUrlQuery;
 };
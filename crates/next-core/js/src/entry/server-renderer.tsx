@@ -31,7 +31,14 @@ type IpcIncomingMessage = {
 
 type IpcOutgoingMessage = {
   type: "result";
-  result: string | { body: string; contentType?: string };
+  result:
+    | string
+    | {
+        body: string;
+        contentType?: string;
+        status?: number;
+        headers?: string[];
+      };
 };
 
 (async () => {
@@ -55,15 +62,57 @@ type IpcOutgoingMessage = {
     );
     const res = await runOperation(renderData, isDataReq);
 
-    if (res == null) {
+    if (res.isNotFound) {
+      ipc.send({
+        type: "result",
+        result: isDataReq
+          ? {
+              contentType: "application/json",
+              status: 404,
+              body: JSON.stringify({ notFound: true }),
+            }
+          : { status: 404, body: "<h1>404 - Page not found</h1>" },
+      });
+      continue;
+    }
+
+    const redirect = res.pageData?.pageProps?.__N_REDIRECT;
+    if (res.isRedirect && !isDataReq && redirect != null) {
+      // Data requests receive the redirect in the page data, which is followed
+      // by the client.
+      ipc.send({
+        type: "result",
+        result: {
+          status: res.pageData.pageProps.__N_REDIRECT_STATUS ?? 307,
+          headers: ["location", redirect],
+          body: "",
+        },
+      });
+      continue;
+    }
+
+    if (res.html == null) {
       throw new Error("no render result returned");
     }
 
+    const headers: string[] = [];
+    if (res.revalidate !== undefined) {
+      // Tells when the page is regenerated, like the Cache-Control header of
+      // statically generated pages in production.
+      headers.push(
+        "cache-control",
+        `s-maxage=${
+          res.revalidate === false ? 31536000 : res.revalidate
+        }, stale-while-revalidate`
+      );
+    }
+
     ipc.send({
       type: "result",
       result: {
         contentType: isDataReq ? "application/json" : undefined,
         body: isDataReq ? JSON.stringify(res.pageData) : res.html,
+        headers,
       },
     });
   }
@@ -72,14 +121,21 @@ type IpcOutgoingMessage = {
 });
 
 type OperationResult = {
-  html: string;
-  pageData: Object;
+  html: string | null;
+  pageData: any;
+  /**
+   * The revalidate period in seconds of a page with `getStaticProps`, or
+   * `false` when it's never revalidated.
+   */
+  revalidate?: number | false;
+  isNotFound: boolean;
+  isRedirect: boolean;
 };
 
 async function runOperation(
   renderData: RenderData,
   isDataReq: boolean
-): Promise<OperationResult | null> {
+): Promise<OperationResult> {
   // TODO(alexkirsz) This is missing *a lot* of data, but it's enough to get a
   // basic render working.
 
@@ -190,17 +246,17 @@ async function runOperation(
   const body = renderResult?.toUnchunkedString();
   // TODO(from next.js): change this to a different passing mechanism
   const pageData = (renderOpts as any).pageData;
-  // TODO: handle these
-  // const sprRevalidate = (renderOpts as any).revalidate;
-  // const isNotFound = (renderOpts as any).isNotFound;
-  // const isRedirect = (renderOpts as any).isRedirect;
-
-  if (body == null) {
-    return null;
-  }
+  const isNotFound = Boolean((renderOpts as any).isNotFound);
+  const isRedirect = Boolean((renderOpts as any).isRedirect);
 
   return {
-    html: body,
-    pageData: pageData,
+    html: body ?? null,
+    pageData,
+    revalidate:
+      "getStaticProps" in otherExports && !renderData.query.__nextFallback
+        ? (renderOpts as any).revalidate ?? false
+        : undefined,
+    isNotFound,
+    isRedirect,
   };
 }
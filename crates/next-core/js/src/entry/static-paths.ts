@@ -0,0 +1,53 @@
+import "next/dist/server/node-polyfill-fetch.js";
+
+import * as pageExports from ".";
+
+import type { GetStaticPaths } from "next";
+
+type StaticPath =
+  | { path: string }
+  | { params: Record<string, string | string[] | undefined | false> };
+
+type StaticPathsResult = {
+  /** Whether the page exports `getStaticProps`. */
+  isStatic: boolean;
+  /** The paths returned by `getStaticPaths`, or null without it. */
+  paths: StaticPath[] | null;
+  fallback: "false" | "true" | "blocking";
+};
+
+/**
+ * Calls `getStaticPaths` of a page to find the paths which are prerendered,
+ * and how other paths are handled.
+ */
+export default async function getStaticPaths(): Promise<StaticPathsResult> {
+  const { getStaticProps, getStaticPaths } = pageExports as {
+    getStaticProps?: unknown;
+    getStaticPaths?: GetStaticPaths;
+  };
+
+  if (typeof getStaticProps !== "function") {
+    return { isStatic: false, paths: null, fallback: "false" };
+  }
+  if (typeof getStaticPaths !== "function") {
+    return { isStatic: true, paths: null, fallback: "false" };
+  }
+
+  const { paths, fallback } = await getStaticPaths({});
+  if (fallback !== "blocking" && typeof fallback !== "boolean") {
+    throw new Error(
+      "The `fallback` key must be returned from getStaticPaths and be `true`, `false` or `'blocking'`"
+    );
+  }
+  if (!Array.isArray(paths)) {
+    throw new Error("The `paths` key must be returned from getStaticPaths");
+  }
+
+  return {
+    isStatic: true,
+    paths: paths.map((path) =>
+      typeof path === "string" ? { path } : { params: path.params }
+    ),
+    fallback: fallback === "blocking" ? "blocking" : fallback ? "true" : "false",
+  };
+}
@@ -1 +1,2 @@
 declare module "next/dist/compiled/react-server-dom-webpack/client";
+declare module "next/dist/compiled/path-to-regexp";
@@ -1,7 +1,7 @@
 import { NextParsedUrlQuery } from "next/dist/server/request-meta";
 
 export type RenderData = {
-  params: Record<string, string>;
+  params: Record<string, string | string[]>;
   method: string;
   url: string;
   path: string;
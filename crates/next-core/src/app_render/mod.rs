@@ -7,6 +7,9 @@ pub mod next_layout_entry_transition;
 #[turbo_tasks::value(shared)]
 pub struct LayoutSegment {
     pub files: HashMap<String, FileSystemPathVc>,
+    /// The directories of the parallel routes (`@slot`) of the layout, keyed
+    /// by slot name.
+    pub parallel_routes: HashMap<String, FileSystemPathVc>,
     pub target: FileSystemPathVc,
 }
 
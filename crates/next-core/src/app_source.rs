@@ -27,7 +27,9 @@ use turbopack_dev_server::{
     source::{
         combined::{CombinedContentSource, CombinedContentSourceVc},
         specificity::SpecificityVc,
-        ContentSourceData, ContentSourceVc, NoContentSourceVc,
+        ContentSource, ContentSourceContent, ContentSourceData, ContentSourceDataFilter,
+        ContentSourceDataVary, ContentSourceResultVc, ContentSourceVc, ContentSourcesVc,
+        NeededData, NoContentSourceVc,
     },
 };
 use turbopack_ecmascript::{
@@ -36,7 +38,11 @@ use turbopack_ecmascript::{
 };
 use turbopack_env::ProcessEnvAssetVc;
 use turbopack_node::{
-    execution_context::ExecutionContextVc, render::rendered_source::create_node_rendered_source,
+    execution_context::ExecutionContextVc,
+    path_regex::PathRegexVc,
+    render::{
+        node_api_source::create_node_api_source, rendered_source::create_node_rendered_source,
+    },
     NodeEntry, NodeEntryVc, NodeRenderingEntry, NodeRenderingEntryVc,
 };
 
@@ -45,7 +51,7 @@ use crate::{
         next_layout_entry_transition::NextLayoutEntryTransition, LayoutSegment, LayoutSegmentsVc,
     },
     embed_js::{next_js_file, wrap_with_next_js_fs},
-    env::env_for_js,
+    env::{add_client_env_check, env_for_js},
     fallback::get_fallback_page,
     next_client::{
         context::{
@@ -73,14 +79,20 @@ fn next_client_chunks_transition(
     execution_context: ExecutionContextVc,
     app_dir: FileSystemPathVc,
     server_root: FileSystemPathVc,
+    env: ProcessEnvVc,
     browserslist_query: &str,
+    next_config: NextConfigVc,
 ) -> TransitionVc {
     let ty = Value::new(ClientContextType::App { app_dir });
     let client_chunking_context = get_client_chunking_context(project_path, server_root, ty);
     let client_environment = get_client_environment(browserslist_query);
 
-    let client_module_options_context =
-        get_client_module_options_context(project_path, execution_context, client_environment, ty);
+    let client_module_options_context = add_client_env_check(
+        get_client_module_options_context(project_path, execution_context, client_environment, ty),
+        project_path,
+        env,
+        next_config,
+    );
     NextClientChunksTransition {
         client_chunking_context,
         client_module_options_context,
@@ -105,8 +117,12 @@ async fn next_client_transition(
     let ty = Value::new(ClientContextType::App { app_dir });
     let client_chunking_context = get_client_chunking_context(project_path, server_root, ty);
     let client_environment = get_client_environment(browserslist_query);
-    let client_module_options_context =
-        get_client_module_options_context(project_path, execution_context, client_environment, ty);
+    let client_module_options_context = add_client_env_check(
+        get_client_module_options_context(project_path, execution_context, client_environment, ty),
+        project_path,
+        env,
+        next_config,
+    );
     let client_runtime_entries = get_client_runtime_entries(project_path, env, ty, next_config);
     let client_resolve_options_context = get_client_resolve_options_context(project_path, ty);
 
@@ -228,7 +244,9 @@ fn app_context(
             execution_context,
             app_dir,
             server_root,
+            env,
             browserslist_query,
+            next_config,
         ),
     );
     transitions.insert(
@@ -253,6 +271,27 @@ fn app_context(
     .into()
 }
 
+/// The context of route handlers (`route.js`), which run in the react server
+/// layer.
+#[turbo_tasks::function]
+fn app_route_context(
+    project_path: FileSystemPathVc,
+    execution_context: ExecutionContextVc,
+    app_dir: FileSystemPathVc,
+    env: ProcessEnvVc,
+    next_config: NextConfigVc,
+    server_addr: ServerAddrVc,
+) -> AssetContextVc {
+    let ty = Value::new(ServerContextType::AppRSC { app_dir });
+    ModuleAssetContextVc::new(
+        TransitionsByNameVc::cell(HashMap::new()),
+        get_server_environment(ty, env, server_addr),
+        get_server_module_options_context(project_path, execution_context, ty),
+        get_server_resolve_options_context(project_path, ty, next_config),
+    )
+    .into()
+}
+
 /// Create a content source serving the `app` or `src/app` directory as
 /// Next.js app folder.
 #[turbo_tasks::function]
@@ -305,11 +344,11 @@ pub async fn create_app_source(
         server_addr,
     );
 
-    let server_runtime_entries =
-        vec![
-            ProcessEnvAssetVc::new(project_path, env_for_js(env, false, next_config))
-                .as_ecmascript_chunk_placeable(),
-        ];
+    let server_runtime_entries = vec![ProcessEnvAssetVc::new(
+        project_path,
+        env_for_js(project_path, env, false, next_config),
+    )
+    .as_ecmascript_chunk_placeable()];
 
     let fallback_page = get_fallback_page(
         project_path,
@@ -320,21 +359,40 @@ pub async fn create_app_source(
         next_config,
     );
 
-    Ok(create_app_source_for_directory(
-        context_ssr,
-        context,
+    let context_route = app_route_context(
         project_path,
-        SpecificityVc::exact(),
-        0,
+        execution_context,
         app_dir,
-        server_root,
-        EcmascriptChunkPlaceablesVc::cell(server_runtime_entries),
-        fallback_page,
-        server_root,
-        LayoutSegmentsVc::cell(Vec::new()),
-        output_path,
-    )
-    .into())
+        env,
+        next_config,
+        server_addr,
+    );
+
+    let runtime_entries = EcmascriptChunkPlaceablesVc::cell(server_runtime_entries);
+    let create_sources = |intercepting_routes| {
+        create_app_source_for_directory(
+            context_ssr,
+            context,
+            context_route,
+            project_path,
+            SpecificityVc::exact(),
+            0,
+            app_dir,
+            server_root,
+            runtime_entries,
+            fallback_page,
+            server_root,
+            LayoutSegmentsVc::cell(Vec::new()),
+            output_path,
+            intercepting_routes,
+            false,
+        )
+        .into()
+    };
+
+    // Intercepting routes come first, as they take precedence over the routes
+    // they intercept.
+    Ok(CombinedContentSourceVc::new(vec![create_sources(true), create_sources(false)]).into())
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -342,6 +400,7 @@ pub async fn create_app_source(
 async fn create_app_source_for_directory(
     context_ssr: AssetContextVc,
     context: AssetContextVc,
+    context_route: AssetContextVc,
     project_path: FileSystemPathVc,
     specificity: SpecificityVc,
     position: u32,
@@ -352,27 +411,42 @@ async fn create_app_source_for_directory(
     target: FileSystemPathVc,
     layouts: LayoutSegmentsVc,
     intermediate_output_path: FileSystemPathVc,
+    intercepting_routes: bool,
+    in_intercepting_route: bool,
 ) -> Result<CombinedContentSourceVc> {
     let mut layouts = layouts;
     let mut sources = Vec::new();
     let mut page = None;
+    let mut route = None;
     let mut files = HashMap::new();
+    let mut parallel_routes = HashMap::new();
     if let DirectoryContent::Entries(entries) = &*input_dir.read_dir().await? {
         for (name, entry) in entries.iter() {
-            if let &DirectoryEntry::File(file) = entry {
-                if let Some((name, _)) = name.rsplit_once('.') {
-                    match name {
-                        "page" => {
-                            page = Some(file);
-                        }
-                        "layout" | "error" | "loading" | "template" | "not-found" | "head" => {
-                            files.insert(name.to_string(), file);
-                        }
-                        _ => {
-                            // Any other file is ignored
+            match *entry {
+                DirectoryEntry::File(file) => {
+                    if let Some((name, _)) = name.rsplit_once('.') {
+                        match name {
+                            "page" => {
+                                page = Some(file);
+                            }
+                            "route" => {
+                                route = Some(file);
+                            }
+                            "layout" | "error" | "loading" | "template" | "not-found" | "head" => {
+                                files.insert(name.to_string(), file);
+                            }
+                            _ => {
+                                // Any other file is ignored
+                            }
                         }
                     }
                 }
+                DirectoryEntry::Directory(dir) => {
+                    if let Some(slot) = name.strip_prefix('@') {
+                        parallel_routes.insert(slot.to_string(), dir);
+                    }
+                }
+                _ => {}
             }
         }
 
@@ -418,13 +492,23 @@ async fn create_app_source_for_directory(
         }
 
         let mut list = layouts.await?.clone_value();
-        list.push(LayoutSegment { files, target }.cell());
+        list.push(
+            LayoutSegment {
+                files,
+                parallel_routes,
+                target,
+            }
+            .cell(),
+        );
         layouts = LayoutSegmentsVc::cell(list);
-        if let Some(page_path) = page {
-            let pathname = pathname_for_path(server_root, target, false);
-            let path_regex = regular_expression_for_path(pathname);
 
-            sources.push(create_node_rendered_source(
+        // Sources of intercepting routes are collected in a separate pass, so
+        // they can take precedence over the routes they intercept.
+        let serve_routes = intercepting_routes == in_intercepting_route;
+        let pathname = pathname_for_path(server_root, target, false);
+        let path_regex = regular_expression_for_path(pathname);
+        if let (Some(page_path), true) = (page, serve_routes) {
+            let source = create_node_rendered_source(
                 specificity,
                 server_root,
                 pathname,
@@ -443,29 +527,110 @@ async fn create_app_source_for_directory(
                 .into(),
                 runtime_entries,
                 fallback_page,
-            ));
+            );
+            sources.push(if in_intercepting_route {
+                InterceptingRouteContentSourceVc::new(path_regex, source).into()
+            } else {
+                source
+            });
         }
+        if let (Some(route_path), true, false) = (route, serve_routes, in_intercepting_route) {
+            if let Some(page_path) = page {
+                AppSourceIssue {
+                    severity: IssueSeverity::Error.into(),
+                    path: route_path,
+                    message: StringVc::cell(format!(
+                        "The route {} conflicts with the page {}. A route segment can only \
+                         contain either a page or a route.",
+                        route_path.await?.path,
+                        page_path.await?.path,
+                    )),
+                }
+                .cell()
+                .as_issue()
+                .emit();
+            } else {
+                sources.push(create_node_api_source(
+                    specificity,
+                    server_root,
+                    pathname,
+                    path_regex,
+                    AppRoute {
+                        context: context_route,
+                        server_root,
+                        route_path,
+                        project_path,
+                        intermediate_output_path,
+                    }
+                    .cell()
+                    .into(),
+                    runtime_entries,
+                ));
+            }
+        }
+
         for (name, entry) in entries.iter() {
             if let DirectoryEntry::Directory(dir) = entry {
                 let intermediate_output_path = intermediate_output_path.join(name);
-                let specificity = if name.starts_with("[[") || name.starts_with("[...") {
-                    specificity.with_catch_all(position)
-                } else if name.starts_with('[') {
-                    specificity.with_dynamic_segment(position)
-                } else {
-                    specificity
-                };
-                let (new_target, position) = if name.starts_with('(') && name.ends_with(')') {
-                    // This doesn't affect the url
-                    (target, position)
-                } else {
-                    // This adds to the url
-                    (target.join(name), position + 1)
-                };
+                let (specificity, new_target, position, in_intercepting_route) =
+                    if name.starts_with('@') {
+                        // Parallel routes are rendered as part of the layout.
+                        // Only intercepting routes inside of them are served.
+                        if !intercepting_routes {
+                            continue;
+                        }
+                        (specificity, target, position, in_intercepting_route)
+                    } else if is_intercepting_route(name) {
+                        if !intercepting_routes {
+                            continue;
+                        }
+                        let segments = intercepted_route_segments(server_root, target, name);
+                        let Some(segments) = segments.await? else {
+                            AppSourceIssue {
+                                severity: IssueSeverity::Error.into(),
+                                path: *dir,
+                                message: StringVc::cell(format!(
+                                    "The intercepting route {} points outside of the app \
+                                     directory.",
+                                    dir.await?.path,
+                                )),
+                            }
+                            .cell()
+                            .as_issue()
+                            .emit();
+                            continue;
+                        };
+                        // The intercepting route is served at the url of the
+                        // route it intercepts, so the specificity is computed
+                        // from that url.
+                        let mut specificity = SpecificityVc::exact();
+                        for (position, segment) in segments.iter().enumerate() {
+                            specificity =
+                                specificity_for_segment(specificity, segment, position as u32);
+                        }
+                        (
+                            specificity,
+                            server_root.join(&segments.join("/")),
+                            segments.len() as u32,
+                            true,
+                        )
+                    } else if name.starts_with('(') && name.ends_with(')') {
+                        // This doesn't affect the url
+                        (specificity, target, position, in_intercepting_route)
+                    } else {
+                        // This adds to the url
+                        (
+                            specificity_for_segment(specificity, name, position),
+                            target.join(name),
+                            position + 1,
+                            in_intercepting_route,
+                        )
+                    };
                 sources.push(
                     create_app_source_for_directory(
                         context_ssr,
                         context,
+                        context_route,
                         project_path,
                         specificity,
                         position,
@@ -476,6 +641,8 @@ async fn create_app_source_for_directory(
                         new_target,
                         layouts,
                         intermediate_output_path,
+                        intercepting_routes,
+                        in_intercepting_route,
                     )
                     .into(),
                 );
@@ -485,6 +652,157 @@ async fn create_app_source_for_directory(
     Ok(CombinedContentSource { sources }.cell())
 }
 
+/// Adds the specificity of the url segment `name` at `position`.
+fn specificity_for_segment(specificity: SpecificityVc, name: &str, position: u32) -> SpecificityVc {
+    if name.starts_with("[[") || name.starts_with("[...") {
+        specificity.with_catch_all(position)
+    } else if name.starts_with('[') {
+        specificity.with_dynamic_segment(position)
+    } else {
+        specificity
+    }
+}
+
+/// Returns true for the directory of an intercepting route, e.g. `(.)photo`,
+/// `(..)photo`, `(..)(..)photo` or `(...)photo`.
+fn is_intercepting_route(name: &str) -> bool {
+    name.starts_with("(.)") || name.starts_with("(..)") || name.starts_with("(...)")
+}
+
+/// The url segments of the route which is intercepted by the intercepting
+/// route `name` in the directory served at `target`. `(.)` is relative to the
+/// current segment, `(..)` to the parent segment and `(...)` to the root.
+/// Returns `None` when the route would be outside of the app directory.
+async fn intercepted_route_segments(
+    server_root: FileSystemPathVc,
+    target: FileSystemPathVc,
+    name: &str,
+) -> Result<Option<Vec<String>>> {
+    let server_root = server_root.await?;
+    let target = target.await?;
+    let mut segments = server_root
+        .get_path_to(&target)
+        .unwrap_or_default()
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| segment.to_string())
+        .collect::<Vec<_>>();
+
+    let rest = if let Some(rest) = name.strip_prefix("(...)") {
+        segments.clear();
+        rest
+    } else if let Some(rest) = name.strip_prefix("(.)") {
+        rest
+    } else {
+        let mut rest = name;
+        while let Some(next) = rest.strip_prefix("(..)") {
+            if segments.pop().is_none() {
+                return Ok(None);
+            }
+            rest = next;
+        }
+        rest
+    };
+    segments.push(rest.to_string());
+    Ok(Some(segments))
+}
+
+/// The page rendered in the parallel route `slot_dir` of the layout at
+/// `layout_target` when the page at `page_target` is requested. That's the page
+/// at the same path inside of the slot, or the `default` page of the slot.
+async fn parallel_route_page(
+    slot_dir: FileSystemPathVc,
+    layout_target: FileSystemPathVc,
+    page_target: FileSystemPathVc,
+) -> Result<Option<FileSystemPathVc>> {
+    let layout_target = layout_target.await?;
+    let page_target = page_target.await?;
+    let mut dir = slot_dir;
+    for segment in layout_target
+        .get_path_to(&page_target)
+        .unwrap_or_default()
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+    {
+        dir = dir.join(segment);
+    }
+    if let Some(page) = find_app_file(dir, "page").await? {
+        return Ok(Some(page));
+    }
+    find_app_file(slot_dir, "default").await
+}
+
+/// Finds the file named `name` with any extension in `dir`.
+async fn find_app_file(dir: FileSystemPathVc, name: &str) -> Result<Option<FileSystemPathVc>> {
+    if let DirectoryContent::Entries(entries) = &*dir.read_dir().await? {
+        for (file_name, entry) in entries.iter() {
+            if let &DirectoryEntry::File(file) = entry {
+                if matches!(file_name.rsplit_once('.'), Some((stem, _)) if stem == name) {
+                    return Ok(Some(file));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Serves an intercepting route in place of the route it intercepts. Only
+/// client-side navigations are intercepted, which request the RSC payload of
+/// the route. Loading the url directly still renders the intercepted route.
+#[turbo_tasks::value]
+struct InterceptingRouteContentSource {
+    path_regex: PathRegexVc,
+    source: ContentSourceVc,
+}
+
+#[turbo_tasks::value_impl]
+impl InterceptingRouteContentSourceVc {
+    #[turbo_tasks::function]
+    fn new(path_regex: PathRegexVc, source: ContentSourceVc) -> Self {
+        InterceptingRouteContentSource { path_regex, source }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ContentSource for InterceptingRouteContentSource {
+    #[turbo_tasks::function]
+    async fn get(
+        self_vc: InterceptingRouteContentSourceVc,
+        path: &str,
+        data: Value<ContentSourceData>,
+    ) -> Result<ContentSourceResultVc> {
+        let this = self_vc.await?;
+        if !this.path_regex.await?.is_match(path) {
+            return Ok(ContentSourceResultVc::not_found());
+        }
+        let Some(headers) = &data.headers else {
+            return Ok(ContentSourceResultVc::exact(
+                ContentSourceContent::NeedData(NeededData {
+                    source: self_vc.into(),
+                    path: path.to_string(),
+                    vary: ContentSourceDataVary {
+                        headers: Some(ContentSourceDataFilter::Subset(
+                            ["rsc".to_string()].into_iter().collect(),
+                        )),
+                        ..Default::default()
+                    },
+                })
+                .cell(),
+            ));
+        };
+        if headers.contains_key("rsc") {
+            Ok(this.source.get(path, Value::new(Default::default())))
+        } else {
+            Ok(ContentSourceResultVc::not_found())
+        }
+    }
+
+    #[turbo_tasks::function]
+    fn get_children(&self) -> ContentSourcesVc {
+        ContentSourcesVc::cell(vec![self.source])
+    }
+}
+
 #[turbo_tasks::value]
 struct AppRenderer {
     context_ssr: AssetContextVc,
@@ -512,24 +830,39 @@ impl AppRendererVc {
             .chain(std::iter::once(
                 LayoutSegment {
                     files: HashMap::from([("page".to_string(), page)]),
+                    parallel_routes: HashMap::new(),
                     target: this.target,
                 }
                 .cell(),
             ))
             .try_join()
             .await?;
-        let segments: Vec<_> = layout_and_page
+        // The pages of parallel routes are imported like the other files of the
+        // layout segment, with the `@slot` as key.
+        let mut segment_files = Vec::with_capacity(layout_and_page.len());
+        for segment in layout_and_page.iter() {
+            let mut files = segment.files.clone();
+            for (slot, &slot_dir) in segment.parallel_routes.iter() {
+                if let Some(slot_page) =
+                    parallel_route_page(slot_dir, segment.target, this.target).await?
+                {
+                    files.insert(format!("@{slot}"), slot_page);
+                }
+            }
+            segment_files.push((files, segment.target));
+        }
+        let segments: Vec<_> = segment_files
             .into_iter()
             .fold(
                 (this.server_root, Vec::new()),
-                |(last_path, mut futures), segment| {
-                    (segment.target, {
+                |(last_path, mut futures), (files, segment_target)| {
+                    (segment_target, {
                         futures.push(async move {
-                            let target = &*segment.target.await?;
+                            let target = &*segment_target.await?;
                             let segment_path =
                                 last_path.await?.get_path_to(target).unwrap_or_default();
                             let mut imports = BTreeMap::new();
-                            for (key, file) in segment.files.iter() {
+                            for (key, file) in files.iter() {
                                 let file_str = file.to_string().await?;
                                 let identifier = magic_identifier::encode(&format!(
                                     "imported namespace {}",
@@ -662,6 +995,61 @@ impl NodeEntry for AppRenderer {
     }
 }
 
+/// The node.js entry of a route handler (`route.js`).
+#[turbo_tasks::value]
+struct AppRoute {
+    context: AssetContextVc,
+    server_root: FileSystemPathVc,
+    route_path: FileSystemPathVc,
+    project_path: FileSystemPathVc,
+    intermediate_output_path: FileSystemPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl AppRouteVc {
+    #[turbo_tasks::function]
+    async fn entry(self) -> Result<NodeRenderingEntryVc> {
+        let this = self.await?;
+        // The entry imports the route handler from ".", which resolves to the
+        // route file.
+        let virtual_asset = VirtualAssetVc::new(
+            this.route_path.join("route.ts"),
+            next_js_file("entry/app/route.ts").into(),
+        );
+
+        let chunking_context = DevChunkingContextVc::builder(
+            this.project_path,
+            this.intermediate_output_path,
+            this.intermediate_output_path.join("chunks"),
+            this.server_root.join("_next/static/assets"),
+        )
+        .layer("ssr")
+        .build();
+
+        Ok(NodeRenderingEntry {
+            module: EcmascriptModuleAssetVc::new(
+                virtual_asset.into(),
+                this.context,
+                Value::new(EcmascriptModuleAssetType::Typescript),
+                EcmascriptInputTransformsVc::cell(vec![EcmascriptInputTransform::TypeScript]),
+                this.context.environment(),
+            ),
+            chunking_context,
+            intermediate_output_path: this.intermediate_output_path,
+        }
+        .cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl NodeEntry for AppRoute {
+    #[turbo_tasks::function]
+    fn entry(self_vc: AppRouteVc, _data: Value<ContentSourceData>) -> NodeRenderingEntryVc {
+        // Call without being keyed by data
+        self_vc.entry()
+    }
+}
+
 #[turbo_tasks::value(shared)]
 struct AppSourceIssue {
     pub severity: IssueSeverityVc,
@@ -1,15 +1,37 @@
 use anyhow::Result;
 use indexmap::indexmap;
-use turbo_tasks_env::{
-    CommandLineProcessEnvVc, CustomProcessEnvVc, EnvMapVc, FilterProcessEnvVc, ProcessEnvVc,
-};
+use turbo_tasks_env::{CommandLineProcessEnvVc, CustomProcessEnvVc, EnvMapVc, ProcessEnvVc};
 use turbo_tasks_fs::FileSystemPathVc;
-use turbopack_env::{EmbeddableProcessEnvVc, TryDotenvProcessEnvVc};
+use turbopack::module_options::{
+    ModuleOptionsContextVc, ModuleRule, ModuleRuleCondition, ModuleRuleEffect,
+};
+use turbopack_ecmascript::{
+    EcmascriptInputTransform, EcmascriptInputTransformsVc, TransformPluginVc,
+};
+use turbopack_env::{
+    ClientEnvCheck, EmbeddableProcessEnvVc, EnvSchemaVc, PublicProcessEnvVc, TryDotenvProcessEnvVc,
+    ValidatedProcessEnvVc,
+};
 
 use crate::next_config::NextConfigVc;
 
+/// The file in the project which declares its env variables, see
+/// [turbopack_env::EnvSchema].
+const ENV_SCHEMA_FILE: &str = "env.schema.json";
+
+/// Env variables with this prefix are embedded into client code, unless the
+/// env schema declares them as private.
+const PUBLIC_ENV_PREFIX: &str = "NEXT_PUBLIC_";
+
+#[turbo_tasks::function]
+fn env_schema(project_path: FileSystemPathVc) -> EnvSchemaVc {
+    EnvSchemaVc::read(project_path.join(ENV_SCHEMA_FILE))
+}
+
 /// Loads a series of dotenv files according to the precedence rules set by
 /// https://nextjs.org/docs/basic-features/environment-variables#environment-variable-load-order
+///
+/// The env is validated against the env schema of the project.
 #[turbo_tasks::function]
 pub async fn load_env(project_path: FileSystemPathVc) -> Result<ProcessEnvVc> {
     let env = CommandLineProcessEnvVc::new().as_process_env();
@@ -35,23 +57,26 @@ pub async fn load_env(project_path: FileSystemPathVc) -> Result<ProcessEnvVc> {
         TryDotenvProcessEnvVc::new(prior, path).as_process_env()
     });
 
-    Ok(env)
+    Ok(ValidatedProcessEnvVc::new(env, env_schema(project_path)).into())
 }
 
 /// Creates a ProcessEnvVc safe to use in JS, by stringifying and encoding as
 /// regular JS strings. Setting `client` to true will additionally filter the
-/// env to just the keys that are acceptable for the client to access.
+/// env to just the keys that are acceptable for the client to access, which
+/// are the ones starting with `NEXT_PUBLIC_` and the ones declared as public in
+/// the env schema of the project.
 ///
 /// For now, it also injects overridden values as if they were real JS code, eg
 /// an Object and not a String.
 #[turbo_tasks::function]
 pub async fn env_for_js(
+    project_path: FileSystemPathVc,
     env: ProcessEnvVc,
     client: bool,
     next_config: NextConfigVc,
 ) -> Result<ProcessEnvVc> {
     let env = if client {
-        FilterProcessEnvVc::new(env, "NEXT_PUBLIC_".to_string()).into()
+        PublicProcessEnvVc::new(env, env_schema(project_path), PUBLIC_ENV_PREFIX.to_string()).into()
     } else {
         env
     };
@@ -77,3 +102,45 @@ pub async fn env_for_js(
 
     Ok(CustomProcessEnvVc::new(env, EnvMapVc::cell(map)).into())
 }
+
+/// Adds a transform to client code which reports reads of env variables which
+/// are not available in the browser, like private ones.
+///
+/// This needs to be added after the Next.js page transforms, so that reads in
+/// the data exports of pages, which only run on the server, are stripped.
+#[turbo_tasks::function]
+pub async fn add_client_env_check(
+    module_options_context: ModuleOptionsContextVc,
+    project_path: FileSystemPathVc,
+    env: ProcessEnvVc,
+    next_config: NextConfigVc,
+) -> Result<ModuleOptionsContextVc> {
+    let check = ClientEnvCheck::new(
+        env_for_js(project_path, env, true, next_config),
+        env_schema(project_path),
+        PUBLIC_ENV_PREFIX.to_string(),
+    );
+
+    let mut module_options_context = module_options_context.await?.clone_value();
+    module_options_context.custom_rules.push(ModuleRule::new(
+        ModuleRuleCondition::all(vec![
+            ModuleRuleCondition::any(vec![
+                ModuleRuleCondition::ResourcePathEndsWith(".js".to_string()),
+                ModuleRuleCondition::ResourcePathEndsWith(".jsx".to_string()),
+                ModuleRuleCondition::ResourcePathEndsWith(".ts".to_string()),
+                ModuleRuleCondition::ResourcePathEndsWith(".tsx".to_string()),
+            ]),
+            // Dependencies read env variables for feature detection, those
+            // reads are not meant to be configured by the project.
+            ModuleRuleCondition::not(ModuleRuleCondition::ResourcePathInDirectory(
+                "node_modules".to_string(),
+            )),
+        ]),
+        vec![ModuleRuleEffect::AddEcmascriptTransforms(
+            EcmascriptInputTransformsVc::cell(vec![EcmascriptInputTransform::Custom(
+                TransformPluginVc::new(check),
+            )]),
+        )],
+    ));
+    Ok(module_options_context.cell())
+}
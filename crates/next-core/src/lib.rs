@@ -7,11 +7,14 @@ mod embed_js;
 pub mod env;
 mod fallback;
 pub mod manifest;
+mod middleware_source;
 mod next_build;
 pub mod next_client;
 mod next_client_component;
 pub mod next_config;
-mod next_font_google;
+pub mod next_edge;
+pub mod next_font_google;
+mod next_font_local;
 pub mod next_image;
 mod next_import_map;
 pub mod next_server;
@@ -20,10 +23,12 @@ mod page_loader;
 mod page_source;
 pub mod react_refresh;
 mod runtime;
+mod static_generation;
 mod util;
 mod web_entry_source;
 
 pub use app_source::create_app_source;
+pub use middleware_source::create_middleware_source;
 pub use page_source::create_page_source;
 pub use turbopack_node::source_map;
 pub use web_entry_source::create_web_entry_source;
@@ -0,0 +1,400 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{bail, Result};
+use serde::Deserialize;
+use turbo_tasks::Value;
+use turbo_tasks_env::ProcessEnvVc;
+use turbo_tasks_fs::{FileContent, FileSystemEntryType, FileSystemPathVc};
+use turbopack::{
+    evaluate_context::node_evaluate_asset_context, transition::TransitionsByNameVc,
+    ModuleAssetContextVc,
+};
+use turbopack_core::{
+    asset::{AssetContent, AssetVc},
+    context::AssetContextVc,
+    environment::ServerAddrVc,
+    reference_type::{EntryReferenceSubType, ReferenceType},
+    virtual_asset::VirtualAssetVc,
+};
+use turbopack_dev_server::source::{
+    query::Query, static_content_type, ContentSource, ContentSourceContent, ContentSourceData,
+    ContentSourceDataFilter, ContentSourceDataVary, ContentSourceResult, ContentSourceResultVc,
+    ContentSourceVc, ContentSourcesVc, HeaderValue, NeededData, ProxyResult,
+};
+use turbopack_ecmascript::chunk::EcmascriptChunkPlaceablesVc;
+use turbopack_env::ProcessEnvAssetVc;
+use turbopack_node::{
+    edge::evaluate_edge, evaluate::JavaScriptValue, execution_context::ExecutionContextVc,
+};
+
+use crate::{
+    embed_js::next_js_file,
+    env::env_for_js,
+    next_config::NextConfigVc,
+    next_edge::context::{
+        get_edge_environment, get_edge_module_options_context, get_edge_resolve_options_context,
+        EdgeContextType,
+    },
+};
+
+const MIDDLEWARE_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx"];
+
+/// Wraps `source` in a content source which runs the `middleware` file of the
+/// project before every request, like Next.js does. Returns `source` as is
+/// when there is no middleware.
+#[turbo_tasks::function]
+pub async fn create_middleware_source(
+    project_path: FileSystemPathVc,
+    execution_context: ExecutionContextVc,
+    output_path: FileSystemPathVc,
+    env: ProcessEnvVc,
+    next_config: NextConfigVc,
+    server_addr: ServerAddrVc,
+    source: ContentSourceVc,
+) -> Result<ContentSourceVc> {
+    let Some(middleware_path) = find_middleware_file(project_path).await? else {
+        return Ok(source);
+    };
+
+    let ty = Value::new(EdgeContextType::Middleware);
+    let edge_context: AssetContextVc = ModuleAssetContextVc::new(
+        TransitionsByNameVc::cell(HashMap::new()),
+        get_edge_environment(ty, server_addr),
+        get_edge_module_options_context(execution_context),
+        get_edge_resolve_options_context(),
+    )
+    .into();
+
+    // The runner imports the middleware with `import * from "."`.
+    let runner_asset = VirtualAssetVc::new(
+        middleware_path.join("edge-middleware.ts"),
+        next_js_file("entry/edge-middleware.ts").into(),
+    );
+    let module = edge_context.process(
+        runner_asset.into(),
+        Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+    );
+
+    let runtime_entries = EcmascriptChunkPlaceablesVc::cell(vec![ProcessEnvAssetVc::new(
+        project_path,
+        env_for_js(project_path, env, false, next_config),
+    )
+    .as_ecmascript_chunk_placeable()]);
+
+    Ok(MiddlewareContentSource {
+        project_path,
+        middleware_path,
+        module,
+        host_context: node_evaluate_asset_context(None),
+        edge_context,
+        intermediate_output_path: output_path,
+        runtime_entries,
+        source,
+    }
+    .cell()
+    .into())
+}
+
+/// Finds `middleware.{ts,tsx,js,jsx}` in the project or in its `src`
+/// directory.
+async fn find_middleware_file(project_path: FileSystemPathVc) -> Result<Option<FileSystemPathVc>> {
+    for dir in [project_path, project_path.join("src")] {
+        for extension in MIDDLEWARE_EXTENSIONS {
+            let path = dir.join(&format!("middleware.{extension}"));
+            if *path.get_type().await? == FileSystemEntryType::File {
+                return Ok(Some(path));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// How the middleware wants a request to continue, as reported by
+/// `entry/edge-middleware.ts`. Headers are arranged as contiguous (name,
+/// value) pairs.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum MiddlewareResult {
+    /// The request is served as usual.
+    #[serde(rename_all = "camelCase")]
+    Next {
+        request_headers: Option<Vec<String>>,
+        response_headers: Vec<String>,
+    },
+    /// The request is served from another path of the app.
+    #[serde(rename_all = "camelCase")]
+    Rewrite {
+        url: String,
+        path: String,
+        query: Query,
+        request_headers: Option<Vec<String>>,
+        response_headers: Vec<String>,
+    },
+    /// The middleware responded itself, e.g. with a redirect.
+    Response {
+        status: u16,
+        headers: Vec<String>,
+        body: Vec<u8>,
+    },
+}
+
+/// A content source which runs the Next.js middleware in the edge runtime
+/// before passing the request on to `source`.
+///
+/// The middleware gets the method, url and headers of the request, but not
+/// its body, which can only be read once and is left for `source`.
+#[turbo_tasks::value(shared)]
+pub struct MiddlewareContentSource {
+    project_path: FileSystemPathVc,
+    middleware_path: FileSystemPathVc,
+    module: AssetVc,
+    host_context: AssetContextVc,
+    edge_context: AssetContextVc,
+    intermediate_output_path: FileSystemPathVc,
+    runtime_entries: EcmascriptChunkPlaceablesVc,
+    source: ContentSourceVc,
+}
+
+#[turbo_tasks::value_impl]
+impl ContentSource for MiddlewareContentSource {
+    #[turbo_tasks::function]
+    async fn get(
+        self_vc: MiddlewareContentSourceVc,
+        path: &str,
+        data: Value<ContentSourceData>,
+    ) -> Result<ContentSourceResultVc> {
+        let this = self_vc.await?;
+        let ContentSourceData {
+            method: Some(method),
+            url: Some(url),
+            headers: Some(headers),
+            ..
+        } = &*data else {
+            return Ok(ContentSourceResultVc::exact(
+                ContentSourceContent::NeedData(NeededData {
+                    source: self_vc.into(),
+                    path: path.to_string(),
+                    vary: ContentSourceDataVary {
+                        method: true,
+                        url: true,
+                        headers: Some(ContentSourceDataFilter::All),
+                        // The middleware needs to run for every request.
+                        cache_buster: true,
+                        ..Default::default()
+                    },
+                })
+                .cell(),
+            ));
+        };
+
+        let request = serde_json::json!({
+            "method": method,
+            "url": url,
+            "headers": headers,
+        });
+        let output = evaluate_edge(
+            this.project_path,
+            this.module,
+            this.project_path,
+            this.middleware_path,
+            this.host_context,
+            this.edge_context,
+            this.intermediate_output_path,
+            Some(this.runtime_entries),
+            vec![&request],
+        )
+        .await?;
+        let result: MiddlewareResult = match output {
+            JavaScriptValue::Value(value) => serde_json::from_reader(value.read())?,
+            JavaScriptValue::Error => {
+                // The error is reported as an issue.
+                return Ok(ContentSourceResultVc::exact(
+                    ContentSourceContent::HttpProxy(
+                        ProxyResult {
+                            status: 500,
+                            headers: vec![
+                                "content-type".to_string(),
+                                "text/plain; charset=utf-8".to_string(),
+                            ],
+                            body: "An error occurred while running the middleware.".into(),
+                        }
+                        .cell(),
+                    )
+                    .cell(),
+                ));
+            }
+            JavaScriptValue::Stream(_) => {
+                bail!("streaming the middleware result is not supported");
+            }
+        };
+
+        let (path, continuation) = match result {
+            MiddlewareResult::Next {
+                request_headers,
+                response_headers,
+            } => (
+                path.to_string(),
+                MiddlewareContinuationContentSource {
+                    source: this.source,
+                    url: None,
+                    query: None,
+                    request_headers: request_headers.map(headers_to_map),
+                    response_headers,
+                },
+            ),
+            MiddlewareResult::Rewrite {
+                url,
+                path,
+                query,
+                request_headers,
+                response_headers,
+            } => (
+                path,
+                MiddlewareContinuationContentSource {
+                    source: this.source,
+                    url: Some(url),
+                    query: Some(query),
+                    request_headers: request_headers.map(headers_to_map),
+                    response_headers,
+                },
+            ),
+            MiddlewareResult::Response {
+                status,
+                headers,
+                body,
+            } => {
+                return Ok(ContentSourceResultVc::exact(
+                    ContentSourceContent::HttpProxy(
+                        ProxyResult {
+                            status,
+                            headers,
+                            body: body.into(),
+                        }
+                        .cell(),
+                    )
+                    .cell(),
+                ));
+            }
+        };
+        // The data of this request is specific to the middleware. The source
+        // requests the data it needs itself.
+        let continuation: ContentSourceVc = continuation.cell().into();
+        Ok(continuation.get(&path, Value::new(ContentSourceData::default())))
+    }
+
+    #[turbo_tasks::function]
+    fn get_children(&self) -> ContentSourcesVc {
+        ContentSourcesVc::cell(vec![self.source])
+    }
+}
+
+/// Serves a request after the middleware passed it on, with the url and the
+/// request headers the middleware set, and adds the response headers the
+/// middleware set to the response.
+#[turbo_tasks::value(shared)]
+#[derive(Clone)]
+struct MiddlewareContinuationContentSource {
+    source: ContentSourceVc,
+    url: Option<String>,
+    query: Option<Query>,
+    request_headers: Option<BTreeMap<String, HeaderValue>>,
+    response_headers: Vec<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl ContentSource for MiddlewareContinuationContentSource {
+    #[turbo_tasks::function]
+    async fn get(
+        self_vc: MiddlewareContinuationContentSourceVc,
+        path: &str,
+        data: Value<ContentSourceData>,
+    ) -> Result<ContentSourceResultVc> {
+        let this = self_vc.await?;
+        let mut data = data.into_value();
+        if let (Some(url), Some(_)) = (&this.url, &data.url) {
+            data.url = Some(url.clone());
+        }
+        if let (Some(query), Some(_)) = (&this.query, &data.query) {
+            data.query = Some(query.clone());
+        }
+        if let (Some(headers), Some(_)) = (&this.request_headers, &data.headers) {
+            data.headers = Some(headers.clone());
+        }
+
+        let result = this.source.get(path, Value::new(data));
+        let result_value = result.await?;
+        let content = match &*result_value.content.await? {
+            ContentSourceContent::NeedData(needed) => {
+                // Keep applying the changes of the middleware when the source
+                // is queried again with the data it needs.
+                ContentSourceContent::NeedData(NeededData {
+                    source: MiddlewareContinuationContentSource {
+                        source: needed.source,
+                        ..(*this).clone()
+                    }
+                    .cell()
+                    .into(),
+                    path: needed.path.clone(),
+                    vary: needed.vary.clone(),
+                })
+            }
+            // Static content is served without headers, so it's converted to a
+            // proxied response to add the response headers.
+            ContentSourceContent::Static(content) if !this.response_headers.is_empty() => {
+                let AssetContent::File(file) = &*content.content().await? else {
+                    return Ok(result);
+                };
+                let FileContent::Content(file) = &*file.await? else {
+                    return Ok(result);
+                };
+                let mut headers = vec!["content-type".to_string(), static_content_type(path, file)];
+                headers.extend(this.response_headers.iter().cloned());
+                ContentSourceContent::HttpProxy(
+                    ProxyResult {
+                        status: 200,
+                        headers,
+                        body: file.content().clone(),
+                    }
+                    .cell(),
+                )
+            }
+            ContentSourceContent::HttpProxy(proxy) if !this.response_headers.is_empty() => {
+                let proxy = proxy.await?;
+                let mut headers = proxy.headers.clone();
+                headers.extend(this.response_headers.iter().cloned());
+                ContentSourceContent::HttpProxy(
+                    ProxyResult {
+                        status: proxy.status,
+                        headers,
+                        body: proxy.body.clone(),
+                    }
+                    .cell(),
+                )
+            }
+            _ => return Ok(result),
+        };
+        Ok(ContentSourceResult {
+            specificity: result_value.specificity,
+            content: content.cell(),
+        }
+        .cell())
+    }
+
+    #[turbo_tasks::function]
+    fn get_children(&self) -> ContentSourcesVc {
+        ContentSourcesVc::cell(vec![self.source])
+    }
+}
+
+/// Converts contiguous (name, value) pairs to the headers of
+/// [ContentSourceData].
+fn headers_to_map(headers: Vec<String>) -> BTreeMap<String, HeaderValue> {
+    let mut map = BTreeMap::new();
+    for pair in headers.chunks_exact(2) {
+        let (name, value) = (&pair[0], &pair[1]);
+        map.entry(name.clone())
+            .and_modify(|existing: &mut HeaderValue| existing.extend_with_string(value.clone()))
+            .or_insert_with(|| HeaderValue::SingleString(value.clone()));
+    }
+    map
+}
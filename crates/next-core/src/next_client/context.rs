@@ -140,10 +140,10 @@ pub async fn get_client_module_options_context(
 pub async fn add_next_font_transform(
     module_options_context: ModuleOptionsContextVc,
 ) -> Result<ModuleOptionsContextVc> {
-    #[allow(unused_mut)] // This is mutated when next-font-local is enabled
-    let mut font_loaders = vec!["@next/font/google".to_owned()];
-    #[cfg(feature = "next-font-local")]
-    font_loaders.push("@next/font/local".to_owned());
+    let font_loaders = vec![
+        "@next/font/google".to_owned(),
+        "@next/font/local".to_owned(),
+    ];
 
     let mut module_options_context = module_options_context.await?.clone_value();
     module_options_context.custom_rules.push(ModuleRule::new(
@@ -239,7 +239,11 @@ pub async fn get_client_runtime_entries(
             .as_request();
 
     let mut runtime_entries = vec![RuntimeEntry::Ecmascript(
-        ProcessEnvAssetVc::new(project_root, env_for_js(env, true, next_config)).into(),
+        ProcessEnvAssetVc::new(
+            project_root,
+            env_for_js(project_root, env, true, next_config),
+        )
+        .into(),
     )
     .cell()];
 
@@ -50,7 +50,7 @@ pub struct TypeScriptConfig {
     pub ts_config_path: Option<String>,
 }
 
-#[turbo_tasks::value]
+#[turbo_tasks::value(shared)]
 #[derive(Clone, Debug, Ord, PartialOrd)]
 #[serde(rename_all = "camelCase")]
 pub struct ImageConfig {
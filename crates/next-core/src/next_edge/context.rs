@@ -0,0 +1,102 @@
+use turbo_tasks::Value;
+use turbo_tasks_fs::FileSystemPathVc;
+use turbopack::{
+    condition::ContextCondition,
+    module_options::{ModuleOptionsContext, ModuleOptionsContextVc},
+    resolve_options_context::{ResolveOptionsContext, ResolveOptionsContextVc},
+};
+use turbopack_core::{
+    chunk::{dev::DevChunkingContextVc, ChunkingContextVc},
+    environment::{
+        EdgeFunctionEnvironmentVc, EnvironmentIntention, EnvironmentVc, ExecutionEnvironment,
+        ServerAddrVc,
+    },
+};
+use turbopack_node::execution_context::ExecutionContextVc;
+
+use crate::next_client::context::add_next_font_transform;
+
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(Debug, Copy, Clone, Hash, PartialOrd, Ord)]
+pub enum EdgeContextType {
+    Middleware,
+    Api,
+}
+
+#[turbo_tasks::function]
+pub fn get_edge_resolve_options_context() -> ResolveOptionsContextVc {
+    // Node.js built-ins are not available, so they are not treated as externals.
+    let resolve_options_context = ResolveOptionsContext {
+        enable_node_modules: true,
+        custom_conditions: vec![
+            "development".to_string(),
+            "edge-light".to_string(),
+            "worker".to_string(),
+        ],
+        module: true,
+        ..Default::default()
+    };
+    ResolveOptionsContext {
+        enable_typescript: true,
+        enable_react: true,
+        rules: vec![(
+            ContextCondition::InDirectory("node_modules".to_string()),
+            resolve_options_context.clone().cell(),
+        )],
+        ..resolve_options_context
+    }
+    .cell()
+}
+
+#[turbo_tasks::function]
+pub fn get_edge_environment(
+    ty: Value<EdgeContextType>,
+    server_addr: ServerAddrVc,
+) -> EnvironmentVc {
+    EnvironmentVc::new(
+        Value::new(ExecutionEnvironment::EdgeFunction(
+            EdgeFunctionEnvironmentVc::new(server_addr),
+        )),
+        match ty.into_value() {
+            EdgeContextType::Middleware => Value::new(EnvironmentIntention::Middleware),
+            EdgeContextType::Api => Value::new(EnvironmentIntention::Api),
+        },
+    )
+}
+
+#[turbo_tasks::function]
+pub fn get_edge_module_options_context(
+    execution_context: ExecutionContextVc,
+) -> ModuleOptionsContextVc {
+    let module_options_context = ModuleOptionsContext {
+        execution_context: Some(execution_context),
+        ..Default::default()
+    };
+    let module_options_context = ModuleOptionsContext {
+        enable_jsx: true,
+        enable_typescript_transform: true,
+        rules: vec![(
+            ContextCondition::InDirectory("node_modules".to_string()),
+            module_options_context.clone().cell(),
+        )],
+        ..module_options_context
+    }
+    .cell();
+
+    add_next_font_transform(module_options_context)
+}
+
+#[turbo_tasks::function]
+pub fn get_edge_chunking_context(
+    project_path: FileSystemPathVc,
+    intermediate_output_path: FileSystemPathVc,
+) -> ChunkingContextVc {
+    let output_root = intermediate_output_path.join("edge");
+    DevChunkingContextVc::builder(
+        project_path,
+        output_root,
+        output_root.join("chunks"),
+        output_root.join("assets"),
+    )
+    .build()
+}
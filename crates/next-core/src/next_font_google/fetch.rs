@@ -0,0 +1,140 @@
+use std::io::Read;
+
+use anyhow::{bail, Result};
+use turbo_tasks::primitives::{OptionStringVc, StringVc};
+use turbo_tasks_fetch::{fetch_request, HttpCacheVc, HttpRequest, OptionHttpCacheVc};
+use turbo_tasks_fs::{File, FileContent, FileContentVc, FileSystemPath, FileSystemPathVc};
+use turbo_tasks_hash::hash_xxh3_hash64;
+use turbopack_core::issue::IssueSeverity;
+
+/// Fetches `url` through the HTTP `cache`, so the response is available when
+/// the network is unavailable.
+///
+/// When the request fails and there's no cached response, the issue is
+/// reported as a warning and [FileContent::NotFound] is returned, or as an
+/// error with `strict`.
+#[turbo_tasks::function]
+pub async fn fetch_font_resource(
+    url: StringVc,
+    user_agent: OptionStringVc,
+    cache: HttpCacheVc,
+    strict: bool,
+    issue_context: FileSystemPathVc,
+) -> Result<FileContentVc> {
+    let mut request = HttpRequest::get(&*url.await?);
+    if let Some(user_agent) = &*user_agent.await? {
+        request
+            .headers
+            .push(("User-Agent".to_owned(), user_agent.clone()));
+    }
+
+    match &*fetch_request(request.cell(), OptionHttpCacheVc::cell(Some(cache))).await? {
+        Ok(response) => {
+            let body = response.await?.body.await?.0.clone();
+            Ok(File::from(body).into())
+        }
+        Err(err) => {
+            let severity = if strict {
+                IssueSeverity::Error
+            } else {
+                IssueSeverity::Warning
+            };
+            err.to_issue(severity.into(), issue_context)
+                .as_issue()
+                .emit();
+            if strict {
+                bail!(
+                    "Failed to fetch `{}` and no cached response is available",
+                    url.await?
+                );
+            }
+            Ok(FileContent::NotFound.cell())
+        }
+    }
+}
+
+/// Downloads the font files referenced by `url()`s in the stylesheet to
+/// `fonts_dir` and replaces the urls with paths relative to `stylesheet_dir`,
+/// so the fonts are served by turbopack and available offline.
+#[turbo_tasks::function]
+pub async fn self_host_font_files(
+    stylesheet: StringVc,
+    cache: HttpCacheVc,
+    fonts_dir: FileSystemPathVc,
+    stylesheet_dir: FileSystemPathVc,
+    strict: bool,
+) -> Result<StringVc> {
+    let stylesheet = stylesheet.await?;
+    let stylesheet_dir_value = stylesheet_dir.await?;
+    let mut result = String::with_capacity(stylesheet.len());
+    let mut rest = stylesheet.as_str();
+    while let Some(start) = rest.find("url(") {
+        let (before, after) = rest.split_at(start + "url(".len());
+        result.push_str(before);
+        let Some(end) = after.find(')') else {
+            rest = after;
+            break;
+        };
+        let url = after[..end].trim().trim_matches(&['"', '\''][..]);
+        rest = &after[end..];
+
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            result.push_str(&after[..end]);
+            continue;
+        }
+        let content = fetch_font_resource(
+            StringVc::cell(url.to_string()),
+            OptionStringVc::cell(None),
+            cache,
+            strict,
+            stylesheet_dir,
+        );
+        if let FileContent::Content(file) = &*content.await? {
+            let mut bytes = Vec::new();
+            file.content().read().read_to_end(&mut bytes)?;
+            let font_path = self_hosted_font_path(fonts_dir, url);
+            font_path.write(File::from(bytes).into()).await?;
+            result.push_str(&relative_path(&stylesheet_dir_value, &*font_path.await?));
+        } else {
+            // Let the browser try to load the font itself.
+            result.push_str(url);
+        }
+    }
+    result.push_str(rest);
+    Ok(StringVc::cell(result))
+}
+
+/// The path in `fonts_dir` the font file at `url` is served from. Files are
+/// named by a hash of the url and keep its extension.
+pub fn self_hosted_font_path(fonts_dir: FileSystemPathVc, url: &str) -> FileSystemPathVc {
+    let extension = url
+        .split(|c| c == '?' || c == '#')
+        .next()
+        .and_then(|path| path.rsplit_once('/'))
+        .and_then(|(_, file_name)| file_name.rsplit_once('.'))
+        .map(|(_, extension)| extension)
+        .filter(|extension| extension.chars().all(|c| c.is_ascii_alphanumeric()));
+    let hash = hash_xxh3_hash64(url);
+    fonts_dir.join(&match extension {
+        Some(extension) => format!("{hash:016x}.{extension}"),
+        None => format!("{hash:016x}"),
+    })
+}
+
+/// The relative path from the directory `from` to `to`, e.g.
+/// `../../.next/cache/fonts/media/font.woff2`.
+fn relative_path(from: &FileSystemPath, to: &FileSystemPath) -> String {
+    let from = from.path.split('/').filter(|s| !s.is_empty());
+    let to_segments = to.path.split('/').filter(|s| !s.is_empty());
+    let common = from
+        .clone()
+        .zip(to_segments.clone())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut segments = from.skip(common).map(|_| "..").collect::<Vec<_>>();
+    if segments.is_empty() {
+        segments.push(".");
+    }
+    segments.extend(to_segments.skip(common));
+    segments.join("/")
+}
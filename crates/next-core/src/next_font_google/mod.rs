@@ -1,13 +1,15 @@
+use std::io::Read;
+
 use anyhow::{bail, Context, Result};
 use indexmap::IndexMap;
 use indoc::formatdoc;
 use once_cell::sync::Lazy;
-use turbo_tasks::primitives::{OptionStringVc, OptionU16Vc, StringVc, U32Vc};
-use turbo_tasks_fetch::fetch;
+use turbo_tasks::primitives::{BoolVc, OptionStringVc, OptionU16Vc, StringVc, U32Vc};
+use turbo_tasks_env::CommandLineProcessEnvVc;
+use turbo_tasks_fetch::HttpCacheVc;
 use turbo_tasks_fs::{FileContent, FileSystemPathVc};
 use turbo_tasks_hash::hash_xxh3_hash64;
 use turbopack_core::{
-    issue::IssueSeverity,
     resolve::{
         options::{
             ImportMapResult, ImportMapResultVc, ImportMapping, ImportMappingReplacement,
@@ -20,15 +22,21 @@ use turbopack_core::{
     virtual_asset::VirtualAssetVc,
 };
 
-use self::options::FontWeights;
+use self::{
+    fetch::{fetch_font_resource, self_host_font_files},
+    options::FontWeights,
+};
 use crate::{
     embed_js::attached_next_js_package_path,
     next_font_google::{
         options::FontDataEntry,
         util::{get_font_axes, get_stylesheet_url},
     },
+    next_font_local::font_fallback::{adjust_font_fallback_for, get_font_fallback},
+    util::next_http_cache,
 };
 
+pub mod fetch;
 pub(crate) mod options;
 pub(crate) mod request;
 mod util;
@@ -39,6 +47,14 @@ static FONT_DATA: Lazy<FontData> =
 
 type FontData = IndexMap<String, FontDataEntry>;
 
+/// Fonts are fetched from Google Fonts and cached in `.next/cache/fonts`, so
+/// they are available offline once they've been fetched. By default, failing
+/// to fetch a font without a cached copy is a warning and the fallback font
+/// stack is used instead, which is fine during development. Builds that must
+/// not silently ship without the font, like production builds, should set this
+/// to `1` to make it an error. `0` and `false` disable it.
+const STRICT_MODE_ENV_VAR: &str = "NEXT_FONT_GOOGLE_STRICT";
+
 #[turbo_tasks::value(shared)]
 pub struct NextFontGoogleReplacer {
     project_path: FileSystemPathVc,
@@ -60,7 +76,11 @@ impl ImportMappingReplacement for NextFontGoogleReplacer {
     }
 
     #[turbo_tasks::function]
-    async fn result(&self, request: RequestVc) -> Result<ImportMapResultVc> {
+    async fn result(
+        &self,
+        _context: FileSystemPathVc,
+        request: RequestVc,
+    ) -> Result<ImportMapResultVc> {
         let request = &*request.await?;
         let Request::Module {
             module: _,
@@ -71,9 +91,7 @@ impl ImportMappingReplacement for NextFontGoogleReplacer {
         };
 
         let query = &*query_vc.await?;
-        let options = font_options_from_query_map(*query_vc);
-        let properties =
-            get_font_css_properties(get_scoped_font_family(*query_vc), options).await?;
+        let properties = get_font_css_properties(self.project_path, *query_vc).await?;
         let js_asset = VirtualAssetVc::new(
                 attached_next_js_package_path(self.project_path)
                     .join(&format!("internal/font/google/{}.js", get_request_id(*query_vc).await?)),
@@ -134,7 +152,11 @@ impl ImportMappingReplacement for NextFontGoogleCssModuleReplacer {
     }
 
     #[turbo_tasks::function]
-    async fn result(&self, request: RequestVc) -> Result<ImportMapResultVc> {
+    async fn result(
+        &self,
+        _context: FileSystemPathVc,
+        request: RequestVc,
+    ) -> Result<ImportMapResultVc> {
         let request = &*request.await?;
         let Request::Module {
             module: _,
@@ -153,52 +175,58 @@ impl ImportMappingReplacement for NextFontGoogleCssModuleReplacer {
             get_request_id(*query_vc).await?
         ));
 
-        let stylesheet_res = fetch(
+        let cache = font_http_cache(self.project_path);
+        let strict = *is_strict_mode().await?;
+        let stylesheet = fetch_font_resource(
             stylesheet_url,
             OptionStringVc::cell(Some(
                 "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like \
                  Gecko) Chrome/104.0.0.0 Safari/537.36"
                     .to_owned(),
             )),
+            cache,
+            strict,
+            css_virtual_path,
         )
         .await?;
 
-        let stylesheet = match &*stylesheet_res {
-            Ok(r) => Some(
-                update_stylesheet(r.await?.body.to_string(), options, scoped_font_family)
-                    .await?
-                    .clone(),
-            ),
-            Err(err) => {
-                // Inform the user of the failure to retreive the stylesheet, but don't
-                // propagate this error. We don't want e.g. offline connections to prevent page
-                // renders during development. During production builds, however, this error
-                // should propagate.
-                //
-                // TODO(WEB-283): Use fallback in dev in this case
-                // TODO(WEB-293): Fail production builds (not dev) in this case
-                err.to_issue(IssueSeverity::Warning.into(), css_virtual_path)
-                    .as_issue()
-                    .emit();
-
-                None
+        // Without a stylesheet, only the fallback font stack is used.
+        let stylesheet = match &*stylesheet {
+            FileContent::Content(file) => {
+                let stylesheet = update_stylesheet(
+                    StringVc::cell(file.content().to_str()?.into_owned()),
+                    options,
+                    scoped_font_family,
+                );
+                self_host_font_files(
+                    stylesheet,
+                    cache,
+                    font_media_dir(self.project_path),
+                    css_virtual_path.parent(),
+                    strict,
+                )
+                .await?
+                .clone()
             }
+            FileContent::NotFound => "".to_owned(),
         };
+        let fallback_font_face = get_fallback_font_face(self.project_path, *query_vc).await?;
 
-        let properties = get_font_css_properties(scoped_font_family, options).await?;
+        let properties = get_font_css_properties(self.project_path, *query_vc).await?;
         let css_asset = VirtualAssetVc::new(
             css_virtual_path,
             FileContent::Content(
                 formatdoc!(
                     r#"
                         {}
-
+                        {}
                         .className {{
                             font-family: {};
                             {}{}
                         }}
                         "#,
-                    stylesheet.unwrap_or_else(|| "".to_owned()),
+                    stylesheet,
+                    fallback_font_face.as_deref().unwrap_or_default(),
                     properties.font_family.await?,
                     properties
                         .weight
@@ -304,13 +332,20 @@ struct FontCssProperties {
 
 #[turbo_tasks::function]
 async fn get_font_css_properties(
-    scoped_font_family: StringVc,
-    options: NextFontGoogleOptionsVc,
+    project_path: FileSystemPathVc,
+    query_vc: QueryMapVc,
 ) -> Result<FontCssPropertiesVc> {
-    let options = &*options.await?;
-    let scoped_font_family = &*scoped_font_family.await?;
+    let options = &*font_options_from_query_map(query_vc).await?;
+    let scoped_font_family = &*get_scoped_font_family(query_vc).await?;
 
     let mut font_families = vec![scoped_font_family.clone()];
+    // The fallback font family only exists when its metrics could be computed.
+    if get_fallback_font_face(project_path, query_vc)
+        .await?
+        .is_some()
+    {
+        font_families.push(fallback_font_family(scoped_font_family));
+    }
     if let Some(fallback) = &options.fallback {
         font_families.extend_from_slice(fallback);
     }
@@ -331,6 +366,104 @@ async fn get_font_css_properties(
     }))
 }
 
+fn fallback_font_family(scoped_font_family: &str) -> String {
+    format!("{} Fallback", scoped_font_family)
+}
+
+/// The `@font-face` of the font family used until the font has loaded, or
+/// when it couldn't be fetched. It's a system font with metrics adjusted to
+/// the font: Times New Roman for serif fonts and Arial otherwise.
+///
+/// Browsers are served WOFF2 files, whose metrics can't be read, so the
+/// metrics are computed from the TrueType file Google Fonts serves to clients
+/// without a user agent. Returns `None` when it couldn't be fetched.
+#[turbo_tasks::function]
+async fn get_fallback_font_face(
+    project_path: FileSystemPathVc,
+    query_vc: QueryMapVc,
+) -> Result<OptionStringVc> {
+    let options = font_options_from_query_map(query_vc);
+    if !options.await?.adjust_font_fallback {
+        return Ok(OptionStringVc::cell(None));
+    }
+
+    let cache = font_http_cache(project_path);
+    let strict = *is_strict_mode().await?;
+    let stylesheet_url = get_stylesheet_url_from_options(options);
+    let issue_context = attached_next_js_package_path(project_path).join(&format!(
+        "internal/font/google/{}.module.css",
+        get_request_id(query_vc).await?
+    ));
+    let stylesheet = fetch_font_resource(
+        stylesheet_url,
+        OptionStringVc::cell(None),
+        cache,
+        strict,
+        issue_context,
+    )
+    .await?;
+    let FileContent::Content(stylesheet) = &*stylesheet else {
+        return Ok(OptionStringVc::cell(None));
+    };
+    let stylesheet = stylesheet.content().to_str()?;
+    let Some(font_url) = first_font_url(&stylesheet) else {
+        return Ok(OptionStringVc::cell(None));
+    };
+    let font = fetch_font_resource(
+        StringVc::cell(font_url.to_owned()),
+        OptionStringVc::cell(None),
+        cache,
+        strict,
+        issue_context,
+    )
+    .await?;
+    let FileContent::Content(font) = &*font else {
+        return Ok(OptionStringVc::cell(None));
+    };
+
+    let mut content = Vec::new();
+    font.content().read().read_to_end(&mut content)?;
+    let fallback = get_font_fallback(&content, adjust_font_fallback_for(&content)?)?;
+    let scoped_font_family = &*get_scoped_font_family(query_vc).await?;
+    Ok(OptionStringVc::cell(fallback.map(|fallback| {
+        fallback.to_font_face(&fallback_font_family(scoped_font_family))
+    })))
+}
+
+/// The first remote url in the `src` of a `@font-face` in the stylesheet.
+fn first_font_url(stylesheet: &str) -> Option<&str> {
+    stylesheet.split("url(").skip(1).find_map(|rest| {
+        let url = rest[..rest.find(')')?]
+            .trim()
+            .trim_matches(&['"', '\''][..]);
+        (url.starts_with("http://") || url.starts_with("https://")).then_some(url)
+    })
+}
+
+/// The cache of the responses from Google Fonts.
+#[turbo_tasks::function]
+fn font_http_cache(project_path: FileSystemPathVc) -> HttpCacheVc {
+    next_http_cache(project_path, "fonts")
+}
+
+/// The directory the self-hosted font files are served from.
+#[turbo_tasks::function]
+fn font_media_dir(project_path: FileSystemPathVc) -> FileSystemPathVc {
+    project_path.join(".next/cache/fonts/media")
+}
+
+#[turbo_tasks::function]
+async fn is_strict_mode() -> Result<BoolVc> {
+    let value = CommandLineProcessEnvVc::new()
+        .as_process_env()
+        .read(STRICT_MODE_ENV_VAR)
+        .await?;
+    Ok(BoolVc::cell(matches!(
+        value.as_deref(),
+        Some(value) if value != "0" && value != "false"
+    )))
+}
+
 #[turbo_tasks::function]
 async fn font_options_from_query_map(query: QueryMapVc) -> Result<NextFontGoogleOptionsVc> {
     let query_map = &*query.await?;
@@ -351,3 +484,25 @@ async fn font_options_from_query_map(query: QueryMapVc) -> Result<NextFontGoogle
     self::options::options_from_request(&serde_json::from_str(json)?, &FONT_DATA)
         .map(NextFontGoogleOptionsVc::cell)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::first_font_url;
+
+    #[test]
+    fn test_first_font_url() {
+        assert_eq!(
+            first_font_url(
+                "@font-face {\n  font-family: 'Inter';\n  src: \
+                 url(https://fonts.gstatic.com/s/inter/v12/a.ttf) format('truetype');\n}\n\
+                 @font-face {\n  src: url(https://fonts.gstatic.com/s/inter/v12/b.ttf);\n}"
+            ),
+            Some("https://fonts.gstatic.com/s/inter/v12/a.ttf")
+        );
+        assert_eq!(
+            first_font_url("src: url(data:font/ttf;base64,AA==), url('https://x.test/a.ttf');"),
+            Some("https://x.test/a.ttf")
+        );
+        assert_eq!(first_font_url("@font-face { src: local(Inter); }"), None);
+    }
+}
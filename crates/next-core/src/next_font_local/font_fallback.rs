@@ -0,0 +1,356 @@
+use std::{borrow::Cow, io::Read};
+
+use anyhow::{bail, Context, Result};
+use flate2::read::ZlibDecoder;
+use ttf_parser::Face;
+
+use super::options::AdjustFontFallback;
+
+/// The characters used to compute the average character width, weighted like
+/// the letter frequency in English text. Same as in @next/font.
+const AVG_CHARACTERS: &str = "aaabcdeeeefghiijklmnnoopqrrssttuvwxyz      ";
+
+/// A system font the fallback font is based on, with its average character
+/// width in font units.
+struct SystemFont {
+    name: &'static str,
+    az_avg_width: f64,
+    units_per_em: f64,
+}
+
+const ARIAL: SystemFont = SystemFont {
+    name: "Arial",
+    az_avg_width: 934.5116279069767,
+    units_per_em: 2048.0,
+};
+
+const TIMES_NEW_ROMAN: SystemFont = SystemFont {
+    name: "Times New Roman",
+    az_avg_width: 854.3953488372093,
+    units_per_em: 2048.0,
+};
+
+/// The descriptors of a `@font-face` for a system font which is adjusted to
+/// take up the same space as the web font, to avoid layout shift while the
+/// web font is loading.
+#[derive(Debug, PartialEq)]
+pub struct FontFallback {
+    pub local_font: &'static str,
+    pub ascent_override: f64,
+    pub descent_override: f64,
+    pub line_gap_override: f64,
+    pub size_adjust: f64,
+}
+
+impl FontFallback {
+    /// Renders the `@font-face` rule for the fallback font family.
+    pub fn to_font_face(&self, font_family: &str) -> String {
+        format!(
+            "@font-face {{\n    font-family: '{}';\n    src: local(\"{}\");\n    ascent-override: \
+             {:.2}%;\n    descent-override: {:.2}%;\n    line-gap-override: {:.2}%;\n    \
+             size-adjust: {:.2}%;\n}}\n",
+            font_family,
+            self.local_font,
+            self.ascent_override * 100.0,
+            self.descent_override * 100.0,
+            self.line_gap_override * 100.0,
+            self.size_adjust * 100.0,
+        )
+    }
+}
+
+/// Computes the metrics of the fallback font from the content of a TrueType,
+/// OpenType or WOFF font file.
+pub fn get_font_fallback(
+    font: &[u8],
+    adjust_font_fallback: AdjustFontFallback,
+) -> Result<Option<FontFallback>> {
+    let system_font = match adjust_font_fallback {
+        AdjustFontFallback::Arial => ARIAL,
+        AdjustFontFallback::TimesNewRoman => TIMES_NEW_ROMAN,
+        AdjustFontFallback::None => return Ok(None),
+    };
+
+    let Some(font) = to_sfnt(font)? else {
+        return Ok(None);
+    };
+    let face = Face::from_slice(&font, 0).map_err(|err| anyhow::anyhow!("{err}"))?;
+
+    let units_per_em = face.units_per_em() as f64;
+    let widths = AVG_CHARACTERS
+        .chars()
+        .filter_map(|c| face.glyph_hor_advance(face.glyph_index(c)?))
+        .collect::<Vec<_>>();
+    let size_adjust = if widths.is_empty() {
+        1.0
+    } else {
+        let avg_width = widths.iter().map(|&width| width as f64).sum::<f64>() / widths.len() as f64;
+        (avg_width / units_per_em) / (system_font.az_avg_width / system_font.units_per_em)
+    };
+
+    let scale = units_per_em * size_adjust;
+    Ok(Some(FontFallback {
+        local_font: system_font.name,
+        ascent_override: face.ascender() as f64 / scale,
+        descent_override: (face.descender() as f64 / scale).abs(),
+        line_gap_override: face.line_gap() as f64 / scale,
+        size_adjust,
+    }))
+}
+
+/// Picks the system font for the fallback of a font whose category isn't
+/// known, based on the family class or PANOSE classification in its OS/2
+/// table: Times New Roman for serif fonts, Arial otherwise.
+pub fn adjust_font_fallback_for(font: &[u8]) -> Result<AdjustFontFallback> {
+    let Some(font) = to_sfnt(font)? else {
+        return Ok(AdjustFontFallback::Arial);
+    };
+    let Some(os2) = find_table(&font, b"OS/2") else {
+        return Ok(AdjustFontFallback::Arial);
+    };
+    let is_serif = match (os2.get(30), os2.get(32), os2.get(33)) {
+        // The classes 1 to 7 are serif styles, 8 is sans serif.
+        (Some(&family_class), ..) if (1..=7).contains(&family_class) => true,
+        (Some(&8), ..) => false,
+        // Latin text with one of the serif styles from cove to triangle.
+        (_, Some(&2), Some(&serif_style)) => (2..=10).contains(&serif_style),
+        _ => false,
+    };
+    Ok(if is_serif {
+        AdjustFontFallback::TimesNewRoman
+    } else {
+        AdjustFontFallback::Arial
+    })
+}
+
+/// The TrueType or OpenType content of a font file, or `None` for WOFF2 files.
+fn to_sfnt(font: &[u8]) -> Result<Option<Cow<'_, [u8]>>> {
+    Ok(if font.starts_with(b"wOFF") {
+        Some(Cow::Owned(woff_to_sfnt(font)?))
+    } else if font.starts_with(b"wOF2") {
+        // TODO: WOFF2 requires brotli and reconstructing the transformed
+        // glyf table.
+        None
+    } else {
+        Some(Cow::Borrowed(font))
+    })
+}
+
+/// The data of the table with the `tag` in a TrueType or OpenType font.
+fn find_table<'a>(sfnt: &'a [u8], tag: &[u8; 4]) -> Option<&'a [u8]> {
+    let num_tables = u16::from_be_bytes(sfnt.get(4..6)?.try_into().ok()?) as usize;
+    (0..num_tables).find_map(|index| {
+        let entry = sfnt.get(12 + index * 16..28 + index * 16)?;
+        if &entry[..4] != tag {
+            return None;
+        }
+        let offset = u32::from_be_bytes(entry[8..12].try_into().ok()?) as usize;
+        let length = u32::from_be_bytes(entry[12..16].try_into().ok()?) as usize;
+        sfnt.get(offset..offset + length)
+    })
+}
+
+/// Decompresses a WOFF file to the TrueType or OpenType font it contains.
+fn woff_to_sfnt(woff: &[u8]) -> Result<Vec<u8>> {
+    let read_u16 = |offset: usize| -> Result<u16> {
+        Ok(u16::from_be_bytes(
+            woff.get(offset..offset + 2)
+                .context("unexpected end of WOFF file")?
+                .try_into()?,
+        ))
+    };
+    let read_u32 = |offset: usize| -> Result<u32> {
+        Ok(u32::from_be_bytes(
+            woff.get(offset..offset + 4)
+                .context("unexpected end of WOFF file")?
+                .try_into()?,
+        ))
+    };
+
+    let flavor = read_u32(4)?;
+    let num_tables = read_u16(12)?;
+    let mut tables = Vec::with_capacity(num_tables as usize);
+    for index in 0..num_tables as usize {
+        let entry = 44 + index * 20;
+        let tag = read_u32(entry)?;
+        let offset = read_u32(entry + 4)? as usize;
+        let comp_length = read_u32(entry + 8)? as usize;
+        let orig_length = read_u32(entry + 12)? as usize;
+        let checksum = read_u32(entry + 16)?;
+        let data = woff
+            .get(offset..offset + comp_length)
+            .context("unexpected end of WOFF file")?;
+        let data = if comp_length < orig_length {
+            let mut table = Vec::with_capacity(orig_length);
+            ZlibDecoder::new(data).read_to_end(&mut table)?;
+            table
+        } else {
+            data.to_vec()
+        };
+        if data.len() != orig_length {
+            bail!("invalid length of a WOFF table");
+        }
+        tables.push((tag, checksum, data));
+    }
+
+    let entry_selector = (num_tables.max(1) as f64).log2().floor() as u16;
+    let search_range = 2u16.pow(entry_selector as u32) * 16;
+    let mut sfnt = Vec::new();
+    sfnt.extend(flavor.to_be_bytes());
+    sfnt.extend(num_tables.to_be_bytes());
+    sfnt.extend(search_range.to_be_bytes());
+    sfnt.extend(entry_selector.to_be_bytes());
+    sfnt.extend(
+        num_tables
+            .saturating_mul(16)
+            .saturating_sub(search_range)
+            .to_be_bytes(),
+    );
+
+    let mut offset = 12 + tables.len() * 16;
+    for (tag, checksum, data) in &tables {
+        sfnt.extend(tag.to_be_bytes());
+        sfnt.extend(checksum.to_be_bytes());
+        sfnt.extend((offset as u32).to_be_bytes());
+        sfnt.extend((data.len() as u32).to_be_bytes());
+        offset += (data.len() + 3) & !3;
+    }
+    for (_, _, data) in &tables {
+        sfnt.extend(data);
+        sfnt.resize((sfnt.len() + 3) & !3, 0);
+    }
+    Ok(sfnt)
+}
+
+#[cfg(test)]
+mod tests {
+    use ttf_parser::Face;
+
+    use super::{
+        adjust_font_fallback_for, find_table, get_font_fallback, woff_to_sfnt, FontFallback,
+    };
+    use crate::next_font_local::options::AdjustFontFallback;
+
+    const INTER_TTF: &[u8] = include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fonts/inter-v12-latin-700.ttf"
+    ));
+    const INTER_WOFF: &[u8] = include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fonts/inter-v12-latin-700.woff"
+    ));
+
+    #[test]
+    fn test_woff_to_sfnt() {
+        let sfnt = woff_to_sfnt(INTER_WOFF).unwrap();
+        let face = Face::from_slice(&sfnt, 0).unwrap();
+        let original = Face::from_slice(INTER_TTF, 0).unwrap();
+        assert_eq!(face.number_of_glyphs(), original.number_of_glyphs());
+        assert_eq!(face.units_per_em(), original.units_per_em());
+        assert_eq!(face.ascender(), original.ascender());
+        assert_eq!(face.descender(), original.descender());
+        for c in "Turbopack 123".chars() {
+            let glyph = face.glyph_index(c).unwrap();
+            assert_eq!(Some(glyph), original.glyph_index(c));
+            assert_eq!(
+                face.glyph_hor_advance(glyph),
+                original.glyph_hor_advance(glyph)
+            );
+        }
+
+        assert!(woff_to_sfnt(&INTER_WOFF[..100]).is_err());
+    }
+
+    #[test]
+    fn test_font_fallback() {
+        let fallback = get_font_fallback(INTER_TTF, AdjustFontFallback::Arial)
+            .unwrap()
+            .unwrap();
+        // The units per em, ascender and descender of Inter are 2816, 2728 and
+        // -680, the average width of the characters is 1430.84.
+        let expected = FontFallback {
+            local_font: "Arial",
+            ascent_override: 0.869_979_256_736_989,
+            descent_override: 0.216_856_999_479_894_6,
+            line_gap_override: 0.0,
+            size_adjust: 1.113_532_296_888_856_8,
+        };
+        assert_eq!(fallback.local_font, expected.local_font);
+        for (actual, expected) in [
+            (fallback.ascent_override, expected.ascent_override),
+            (fallback.descent_override, expected.descent_override),
+            (fallback.line_gap_override, expected.line_gap_override),
+            (fallback.size_adjust, expected.size_adjust),
+        ] {
+            assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+        }
+        assert_eq!(
+            fallback.to_font_face("__Inter_Fallback_123"),
+            "@font-face {\n    font-family: '__Inter_Fallback_123';\n    src: \
+             local(\"Arial\");\n    ascent-override: 87.00%;\n    descent-override: \
+             21.69%;\n    line-gap-override: 0.00%;\n    size-adjust: 111.35%;\n}\n"
+        );
+
+        // WOFF files contain the same metrics.
+        assert_eq!(
+            get_font_fallback(INTER_WOFF, AdjustFontFallback::Arial).unwrap(),
+            Some(fallback)
+        );
+
+        let times_new_roman = get_font_fallback(INTER_TTF, AdjustFontFallback::TimesNewRoman)
+            .unwrap()
+            .unwrap();
+        assert_eq!(times_new_roman.local_font, "Times New Roman");
+        assert!(times_new_roman.size_adjust > expected.size_adjust);
+
+        assert_eq!(
+            get_font_fallback(INTER_TTF, AdjustFontFallback::None).unwrap(),
+            None
+        );
+        // The metrics of WOFF2 files can't be read yet.
+        assert_eq!(
+            get_font_fallback(b"wOF2\0\x01\0\0", AdjustFontFallback::Arial).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_adjust_font_fallback_for() {
+        // Inter has no family class and a PANOSE serif style of "any".
+        assert_eq!(
+            adjust_font_fallback_for(INTER_TTF).unwrap(),
+            AdjustFontFallback::Arial
+        );
+        assert_eq!(
+            adjust_font_fallback_for(INTER_WOFF).unwrap(),
+            AdjustFontFallback::Arial
+        );
+
+        let os2_offset =
+            find_table(INTER_TTF, b"OS/2").unwrap().as_ptr() as usize - INTER_TTF.as_ptr() as usize;
+        let with_os2 = |offset: usize, value: u8| {
+            let mut font = INTER_TTF.to_vec();
+            font[os2_offset + offset] = value;
+            font
+        };
+        // Oldstyle serifs family class.
+        assert_eq!(
+            adjust_font_fallback_for(&with_os2(30, 1)).unwrap(),
+            AdjustFontFallback::TimesNewRoman
+        );
+        // Sans serif family class.
+        assert_eq!(
+            adjust_font_fallback_for(&with_os2(30, 8)).unwrap(),
+            AdjustFontFallback::Arial
+        );
+        // Latin text with a square serif style.
+        assert_eq!(
+            adjust_font_fallback_for(&with_os2(33, 6)).unwrap(),
+            AdjustFontFallback::TimesNewRoman
+        );
+        assert_eq!(
+            adjust_font_fallback_for(b"wOF2\0\x01\0\0").unwrap(),
+            AdjustFontFallback::Arial
+        );
+    }
+}
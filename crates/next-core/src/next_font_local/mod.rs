@@ -0,0 +1,436 @@
+use std::io::Read;
+
+use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
+use indoc::formatdoc;
+use turbo_tasks::primitives::{OptionStringVc, StringVc, U32Vc};
+use turbo_tasks_fs::{FileContent, FileSystemPathVc};
+use turbo_tasks_hash::hash_xxh3_hash64;
+use turbopack_core::{
+    resolve::{
+        options::{
+            ImportMapResult, ImportMapResultVc, ImportMapping, ImportMappingReplacement,
+            ImportMappingReplacementVc, ImportMappingVc,
+        },
+        parse::{Request, RequestVc},
+        pattern::QueryMapVc,
+        ResolveResult,
+    },
+    virtual_asset::VirtualAssetVc,
+};
+
+use self::{font_fallback::get_font_fallback, options::FontDescriptor};
+use crate::embed_js::attached_next_js_package_path;
+
+pub(crate) mod font_fallback;
+pub(crate) mod options;
+pub(crate) mod request;
+
+/// The query param which carries the directory of the module calling the font
+/// function, relative to the project, from the JS module to the CSS module.
+const CALLER_QUERY_PARAM: &str = "caller";
+
+#[turbo_tasks::value(shared)]
+pub struct NextFontLocalReplacer {
+    project_path: FileSystemPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl NextFontLocalReplacerVc {
+    #[turbo_tasks::function]
+    pub fn new(project_path: FileSystemPathVc) -> Self {
+        Self::cell(NextFontLocalReplacer { project_path })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMappingReplacement for NextFontLocalReplacer {
+    #[turbo_tasks::function]
+    fn replace(&self, _capture: &str) -> ImportMappingVc {
+        ImportMapping::Ignore.into()
+    }
+
+    #[turbo_tasks::function]
+    async fn result(
+        &self,
+        context: FileSystemPathVc,
+        request: RequestVc,
+    ) -> Result<ImportMapResultVc> {
+        let Request::Module { query, .. } = &*request.await? else {
+            return Ok(ImportMapResult::NoEntry.into());
+        };
+        let Some(query) = &*query.await? else {
+            return Ok(ImportMapResult::NoEntry.into());
+        };
+        let Some((json, _)) = query.iter().next() else {
+            return Ok(ImportMapResult::NoEntry.into());
+        };
+
+        // The font files are relative to the calling module, so its directory is
+        // passed along to the CSS module which references them.
+        let project_path = self.project_path.await?;
+        let context_value = context.await?;
+        let caller = if context_value.path == project_path.path {
+            ""
+        } else {
+            project_path
+                .get_path_to(&context_value)
+                .context("@next/font/local can only be used in the project")?
+        };
+        let mut query = IndexMap::new();
+        query.insert(json.clone(), String::new());
+        query.insert(CALLER_QUERY_PARAM.to_owned(), caller.to_owned());
+        let query_vc = QueryMapVc::cell(Some(query.clone()));
+
+        let options = font_options_from_query_map(query_vc).await?;
+        let properties = get_font_css_properties(self.project_path, query_vc).await?;
+        let js_asset = VirtualAssetVc::new(
+            attached_next_js_package_path(self.project_path).join(&format!(
+                "internal/font/local/{}.js",
+                get_request_id(query_vc).await?
+            )),
+            FileContent::Content(
+                formatdoc!(
+                    r#"
+                        import cssModule from "@vercel/turbopack-next/internal/font/local/cssmodule.module.css?{}";
+                        export default {{
+                            className: cssModule.className,
+                            style: {{
+                                fontFamily: "{}",
+                                {}{}
+                            }},{}
+                        }};
+                    "#,
+                    qstring::QString::new(query.iter().collect()),
+                    properties.font_family.await?,
+                    properties
+                        .weight
+                        .await?
+                        .as_ref()
+                        .map(|w| match w.parse::<u16>() {
+                            Ok(w) => format!("fontWeight: {},\n", w),
+                            Err(_) => format!("fontWeight: \"{}\",\n", w),
+                        })
+                        .unwrap_or_else(|| "".to_owned()),
+                    properties
+                        .style
+                        .await?
+                        .as_ref()
+                        .map(|s| format!("fontStyle: \"{}\",\n", s))
+                        .unwrap_or_else(|| "".to_owned()),
+                    if options.variable.is_some() {
+                        "\nvariable: cssModule.variable,"
+                    } else {
+                        ""
+                    },
+                )
+                .into(),
+            )
+            .into(),
+        );
+
+        Ok(ImportMapResult::Result(ResolveResult::Single(js_asset.into(), vec![]).into()).into())
+    }
+}
+
+#[turbo_tasks::value(shared)]
+pub struct NextFontLocalCssModuleReplacer {
+    project_path: FileSystemPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl NextFontLocalCssModuleReplacerVc {
+    #[turbo_tasks::function]
+    pub fn new(project_path: FileSystemPathVc) -> Self {
+        Self::cell(NextFontLocalCssModuleReplacer { project_path })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMappingReplacement for NextFontLocalCssModuleReplacer {
+    #[turbo_tasks::function]
+    fn replace(&self, _capture: &str) -> ImportMappingVc {
+        ImportMapping::Ignore.into()
+    }
+
+    #[turbo_tasks::function]
+    async fn result(
+        &self,
+        _context: FileSystemPathVc,
+        request: RequestVc,
+    ) -> Result<ImportMapResultVc> {
+        let Request::Module { query: query_vc, .. } = &*request.await? else {
+            return Ok(ImportMapResult::NoEntry.into());
+        };
+        let query_vc = *query_vc;
+
+        let options = font_options_from_query_map(query_vc).await?;
+        let caller = self.project_path.join(&get_caller(query_vc).await?);
+        let scoped_font_family = &*get_scoped_font_family(query_vc).await?;
+
+        let mut stylesheet = String::new();
+        for font in &options.fonts {
+            stylesheet.push_str(&font_face(
+                font,
+                scoped_font_family,
+                &options.display,
+                &options.declarations,
+            ));
+        }
+        if let Some(fallback_font_face) =
+            &*get_fallback_font_face(self.project_path, query_vc).await?
+        {
+            stylesheet.push_str(fallback_font_face);
+        }
+
+        let properties = get_font_css_properties(self.project_path, query_vc).await?;
+        let font_family = properties.font_family.await?;
+        stylesheet.push_str(&formatdoc!(
+            r#"
+
+                .className {{
+                    font-family: {};
+                    {}{}
+                }}
+            "#,
+            font_family,
+            properties
+                .weight
+                .await?
+                .as_ref()
+                .map(|w| format!("font-weight: {};\n", w))
+                .unwrap_or_else(|| "".to_owned()),
+            properties
+                .style
+                .await?
+                .as_ref()
+                .map(|s| format!("font-style: {};\n", s))
+                .unwrap_or_else(|| "".to_owned()),
+        ));
+        if let Some(variable) = &options.variable {
+            stylesheet.push_str(&formatdoc!(
+                r#"
+
+                    .variable {{
+                        {}: {};
+                    }}
+                "#,
+                variable,
+                font_family,
+            ));
+        }
+
+        // The stylesheet is placed next to the calling module so that the font
+        // files are resolved relative to it and emitted as static assets.
+        let css_asset = VirtualAssetVc::new(
+            caller.join(&format!(
+                "__next_font_local_{}.module.css",
+                get_request_id(query_vc).await?
+            )),
+            FileContent::Content(stylesheet.into()).into(),
+        );
+
+        Ok(ImportMapResult::Result(ResolveResult::Single(css_asset.into(), vec![]).into()).into())
+    }
+}
+
+fn font_face(
+    font: &FontDescriptor,
+    font_family: &str,
+    display: &str,
+    declarations: &[(String, String)],
+) -> String {
+    let mut font_face = format!(
+        "@font-face {{\n    font-family: '{}';\n    src: url(\"{}\") format(\"{}\");\n    \
+         font-display: {};\n",
+        font_family, font.path, font.format, display
+    );
+    if let Some(weight) = &font.weight {
+        font_face.push_str(&format!("    font-weight: {};\n", weight));
+    }
+    if let Some(style) = &font.style {
+        font_face.push_str(&format!("    font-style: {};\n", style));
+    }
+    for (prop, value) in declarations {
+        font_face.push_str(&format!("    {}: {};\n", prop, value));
+    }
+    font_face.push_str("}\n");
+    font_face
+}
+
+/// Picks the font closest to a regular weight and style, as the fallback
+/// font is used for all of the fonts.
+fn pick_font_for_fallback_generation(fonts: &[FontDescriptor]) -> Option<&FontDescriptor> {
+    fonts.iter().min_by_key(|font| {
+        let weight_distance = font
+            .weight
+            .as_ref()
+            // Variable fonts have a range like `100 900`.
+            .and_then(|weight| weight.split(' ').next()?.parse::<i32>().ok())
+            .map_or(0, |weight| (weight - 400).abs());
+        let is_italic = font
+            .style
+            .as_deref()
+            .map_or(false, |style| style != "normal");
+        (is_italic, weight_distance)
+    })
+}
+
+/// The `@font-face` of the fallback font family, with metrics adjusted to the
+/// font closest to a regular weight and style. `None` when the metrics can't
+/// be computed, e.g. for WOFF2 files, or `adjustFontFallback` is `false`.
+#[turbo_tasks::function]
+async fn get_fallback_font_face(
+    project_path: FileSystemPathVc,
+    query_vc: QueryMapVc,
+) -> Result<OptionStringVc> {
+    let options = font_options_from_query_map(query_vc).await?;
+    if matches!(
+        options.adjust_font_fallback,
+        self::options::AdjustFontFallback::None
+    ) {
+        return Ok(OptionStringVc::cell(None));
+    }
+    let Some(font_file) = pick_font_for_fallback_generation(&options.fonts) else {
+        return Ok(OptionStringVc::cell(None));
+    };
+    let caller = project_path.join(&get_caller(query_vc).await?);
+    let FileContent::Content(file) = &*caller.join(&font_file.path).read().await? else {
+        bail!("Font file not found: `{}`", font_file.path);
+    };
+    let mut content = Vec::new();
+    file.content().read().read_to_end(&mut content)?;
+    let scoped_font_family = &*get_scoped_font_family(query_vc).await?;
+    Ok(OptionStringVc::cell(
+        get_font_fallback(&content, options.adjust_font_fallback)?
+            .map(|fallback| fallback.to_font_face(&format!("{} Fallback", scoped_font_family))),
+    ))
+}
+
+#[turbo_tasks::function]
+async fn get_caller(query_vc: QueryMapVc) -> Result<StringVc> {
+    let query = &*query_vc.await?;
+    let query = query.as_ref().context("Query map must be present")?;
+    Ok(StringVc::cell(
+        query
+            .get(CALLER_QUERY_PARAM)
+            .context("@next/font/local queries must contain the caller")?
+            .clone(),
+    ))
+}
+
+#[turbo_tasks::function]
+async fn get_scoped_font_family(query_vc: QueryMapVc) -> Result<StringVc> {
+    let options = font_options_from_query_map(query_vc).await?;
+
+    Ok(StringVc::cell(format!(
+        "__{}_{:x?}",
+        options.variable_name,
+        *get_request_hash(query_vc).await?
+    )))
+}
+
+#[turbo_tasks::function]
+async fn get_request_id(query_vc: QueryMapVc) -> Result<StringVc> {
+    let options = font_options_from_query_map(query_vc).await?;
+
+    Ok(StringVc::cell(format!(
+        "{}_{:x?}",
+        options.variable_name.to_lowercase(),
+        get_request_hash(query_vc).await?,
+    )))
+}
+
+#[turbo_tasks::function]
+async fn get_request_hash(query_vc: QueryMapVc) -> Result<U32Vc> {
+    let query = &*query_vc.await?;
+    let query = query.as_ref().context("Query map must be present")?;
+    let mut to_hash = vec![];
+    for (k, v) in query {
+        to_hash.push(k);
+        to_hash.push(v);
+    }
+
+    Ok(U32Vc::cell(
+        // Truncate the has to u32. These hashes are ultimately displayed as 8-character
+        // hexadecimal values.
+        hash_xxh3_hash64(to_hash) as u32,
+    ))
+}
+
+#[turbo_tasks::value(transparent)]
+struct NextFontLocalOptions(self::options::NextFontLocalOptions);
+
+#[turbo_tasks::value(transparent)]
+struct FontCssProperties {
+    font_family: StringVc,
+    weight: OptionStringVc,
+    style: OptionStringVc,
+}
+
+#[turbo_tasks::function]
+async fn get_font_css_properties(
+    project_path: FileSystemPathVc,
+    query_vc: QueryMapVc,
+) -> Result<FontCssPropertiesVc> {
+    let options = &*font_options_from_query_map(query_vc).await?;
+    let scoped_font_family = &*get_scoped_font_family(query_vc).await?;
+
+    let mut font_families = vec![scoped_font_family.clone()];
+    // The fallback font family only exists when its metrics could be computed.
+    if get_fallback_font_face(project_path, query_vc)
+        .await?
+        .is_some()
+    {
+        font_families.push(format!("{} Fallback", scoped_font_family));
+    }
+    if let Some(fallback) = &options.fallback {
+        font_families.extend_from_slice(fallback);
+    }
+
+    // The weight and style can only be set on the class when they're the same
+    // for all fonts.
+    let first = options.fonts.first();
+    let weight = first.and_then(|font| font.weight.clone()).filter(|weight| {
+        options
+            .fonts
+            .iter()
+            .all(|font| font.weight.as_ref() == Some(weight))
+    });
+    let style = first.and_then(|font| font.style.clone()).filter(|style| {
+        options
+            .fonts
+            .iter()
+            .all(|font| font.style.as_ref() == Some(style))
+    });
+
+    Ok(FontCssPropertiesVc::cell(FontCssProperties {
+        font_family: StringVc::cell(
+            font_families
+                .iter()
+                .map(|f| format!("'{}'", f))
+                .collect::<Vec<String>>()
+                .join(", "),
+        ),
+        // Variable fonts have a range of weights, e.g. `100 900`.
+        weight: OptionStringVc::cell(weight.filter(|weight| !weight.contains(' '))),
+        style: OptionStringVc::cell(style),
+    }))
+}
+
+#[turbo_tasks::function]
+async fn font_options_from_query_map(query: QueryMapVc) -> Result<NextFontLocalOptionsVc> {
+    let query_map = &*query.await?;
+    // These are invariants from the next/font swc transform. Regular errors instead
+    // of Issues should be okay.
+    let query_map = query_map
+        .as_ref()
+        .context("@next/font/local queries must exist")?;
+
+    let Some((json, _)) = query_map.iter().next() else {
+        bail!("Expected one entry");
+    };
+
+    self::options::options_from_request(&serde_json::from_str(json)?)
+        .map(NextFontLocalOptionsVc::cell)
+}
@@ -0,0 +1,320 @@
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::trace::TraceRawVcs;
+
+use super::request::{AdjustFontFallbackRequest, NextFontLocalRequest, SrcRequest};
+
+const ALLOWED_DISPLAY_VALUES: &[&str] = &["auto", "block", "swap", "fallback", "optional"];
+
+/// `@font-face` descriptors which are generated from the other options and
+/// can't be overridden with `declarations`.
+const RESERVED_DECLARATIONS: &[&str] = &[
+    "src",
+    "font-display",
+    "font-family",
+    "font-weight",
+    "font-style",
+];
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub struct NextFontLocalOptions {
+    pub fonts: Vec<FontDescriptor>,
+    pub display: String,
+    pub preload: bool,
+    pub fallback: Option<Vec<String>>,
+    pub adjust_font_fallback: AdjustFontFallback,
+    /// The name of the CSS variable to define, e.g. `--font-inter`.
+    pub variable: Option<String>,
+    /// The name of the JS variable the font is assigned to. Used to derive
+    /// the font family.
+    pub variable_name: String,
+    pub declarations: Vec<(String, String)>,
+}
+
+/// A font file, which becomes a `@font-face`.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub struct FontDescriptor {
+    /// The path of the font file, relative to the module calling the font
+    /// function.
+    pub path: String,
+    pub weight: Option<String>,
+    pub style: Option<String>,
+    /// The format used in the `src` descriptor, e.g. `woff2`.
+    pub format: String,
+}
+
+/// The system font the metrics of the generated fallback font are based on.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, TraceRawVcs)]
+pub enum AdjustFontFallback {
+    Arial,
+    TimesNewRoman,
+    None,
+}
+
+// Transforms the request fields to a struct suitable for generating the
+// stylesheet. Similar to @next/font/local's validateData.
+pub fn options_from_request(request: &NextFontLocalRequest) -> Result<NextFontLocalOptions> {
+    if request.arguments.len() > 1 {
+        bail!("Only zero or one arguments to font functions are currently supported");
+    }
+    let Some(argument) = request.arguments.last() else {
+        bail!("Missing required `src` property");
+    };
+
+    let fonts = match &argument.src {
+        SrcRequest::One(path) => vec![font_descriptor(
+            path,
+            argument.weight.clone(),
+            argument.style.clone(),
+        )?],
+        SrcRequest::Many(descriptors) => descriptors
+            .iter()
+            .map(|descriptor| {
+                font_descriptor(
+                    &descriptor.path,
+                    descriptor
+                        .weight
+                        .clone()
+                        .or_else(|| argument.weight.clone()),
+                    descriptor.style.clone().or_else(|| argument.style.clone()),
+                )
+            })
+            .collect::<Result<_>>()?,
+    };
+    if fonts.is_empty() {
+        bail!("Missing required `src` property");
+    }
+
+    let display = argument
+        .display
+        .clone()
+        .unwrap_or_else(|| "optional".to_owned());
+    if !ALLOWED_DISPLAY_VALUES.contains(&display.as_ref()) {
+        return Err(anyhow!(
+            "Invalid display value {}.\nAvailable display values: {}",
+            display,
+            ALLOWED_DISPLAY_VALUES.join(", ")
+        ));
+    }
+
+    let adjust_font_fallback = match &argument.adjust_font_fallback {
+        None => AdjustFontFallback::Arial,
+        Some(AdjustFontFallbackRequest::Font(font)) if font == "Arial" => AdjustFontFallback::Arial,
+        Some(AdjustFontFallbackRequest::Font(font)) if font == "Times New Roman" => {
+            AdjustFontFallback::TimesNewRoman
+        }
+        Some(AdjustFontFallbackRequest::Enabled(false)) => AdjustFontFallback::None,
+        Some(_) => {
+            bail!(
+                "Invalid adjustFontFallback value. Expected \"Arial\", \"Times New Roman\" or \
+                 false"
+            )
+        }
+    };
+
+    let declarations = argument
+        .declarations
+        .iter()
+        .flatten()
+        .map(|declaration| {
+            if RESERVED_DECLARATIONS.contains(&declaration.prop.as_str()) {
+                bail!("Invalid declaration prop: `{}`", declaration.prop);
+            }
+            Ok((declaration.prop.clone(), declaration.value.clone()))
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(NextFontLocalOptions {
+        fonts,
+        display,
+        preload: argument.preload,
+        fallback: argument.fallback.clone(),
+        adjust_font_fallback,
+        variable: argument.variable.clone(),
+        variable_name: request.variable_name.clone(),
+        declarations,
+    })
+}
+
+fn font_descriptor(
+    path: &str,
+    weight: Option<String>,
+    style: Option<String>,
+) -> Result<FontDescriptor> {
+    let extension = path.rsplit_once('.').map(|(_, extension)| extension);
+    let format = match extension {
+        Some("woff2") => "woff2",
+        Some("woff") => "woff",
+        Some("ttf") => "truetype",
+        Some("otf") => "opentype",
+        Some("eot") => "embedded-opentype",
+        _ => bail!("Unexpected file `{}`", path),
+    };
+
+    Ok(FontDescriptor {
+        path: path.to_owned(),
+        weight,
+        style,
+        format: format.to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::{options_from_request, AdjustFontFallback, FontDescriptor, NextFontLocalOptions};
+    use crate::next_font_local::request::NextFontLocalRequest;
+
+    #[test]
+    fn test_errors_on_missing_src() -> Result<()> {
+        let request: NextFontLocalRequest = serde_json::from_str(
+            r#"
+            {
+                "import": "",
+                "path": "index.js",
+                "variableName": "myFont",
+                "arguments": []
+            }
+        "#,
+        )?;
+
+        match options_from_request(&request) {
+            Ok(_) => panic!(),
+            Err(err) => {
+                assert_eq!(err.to_string(), "Missing required `src` property")
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_values() -> Result<()> {
+        let request: NextFontLocalRequest = serde_json::from_str(
+            r#"
+            {
+                "import": "",
+                "path": "index.js",
+                "variableName": "myFont",
+                "arguments": [{ "src": "./my-font.woff2" }]
+            }
+        "#,
+        )?;
+
+        assert_eq!(
+            options_from_request(&request)?,
+            NextFontLocalOptions {
+                fonts: vec![FontDescriptor {
+                    path: "./my-font.woff2".to_owned(),
+                    weight: None,
+                    style: None,
+                    format: "woff2".to_owned(),
+                }],
+                display: "optional".to_owned(),
+                preload: true,
+                fallback: None,
+                adjust_font_fallback: AdjustFontFallback::Arial,
+                variable: None,
+                variable_name: "myFont".to_owned(),
+                declarations: vec![],
+            },
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_multiple_src() -> Result<()> {
+        let request: NextFontLocalRequest = serde_json::from_str(
+            r#"
+            {
+                "import": "",
+                "path": "index.js",
+                "variableName": "myFont",
+                "arguments": [{
+                    "src": [
+                        { "path": "./regular.ttf", "weight": "400" },
+                        { "path": "./italic.otf", "style": "italic" }
+                    ],
+                    "weight": "300",
+                    "adjustFontFallback": "Times New Roman",
+                    "variable": "--font-my-font"
+                }]
+            }
+        "#,
+        )?;
+
+        let options = options_from_request(&request)?;
+        assert_eq!(
+            options.fonts,
+            vec![
+                FontDescriptor {
+                    path: "./regular.ttf".to_owned(),
+                    weight: Some("400".to_owned()),
+                    style: None,
+                    format: "truetype".to_owned(),
+                },
+                FontDescriptor {
+                    path: "./italic.otf".to_owned(),
+                    weight: Some("300".to_owned()),
+                    style: Some("italic".to_owned()),
+                    format: "opentype".to_owned(),
+                },
+            ]
+        );
+        assert_eq!(
+            options.adjust_font_fallback,
+            AdjustFontFallback::TimesNewRoman
+        );
+        assert_eq!(options.variable, Some("--font-my-font".to_owned()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors_on_unknown_extension() -> Result<()> {
+        let request: NextFontLocalRequest = serde_json::from_str(
+            r#"
+            {
+                "import": "",
+                "path": "index.js",
+                "variableName": "myFont",
+                "arguments": [{ "src": "./my-font.png" }]
+            }
+        "#,
+        )?;
+
+        match options_from_request(&request) {
+            Ok(_) => panic!(),
+            Err(err) => {
+                assert_eq!(err.to_string(), "Unexpected file `./my-font.png`")
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors_on_reserved_declaration() -> Result<()> {
+        let request: NextFontLocalRequest = serde_json::from_str(
+            r#"
+            {
+                "import": "",
+                "path": "index.js",
+                "variableName": "myFont",
+                "arguments": [{
+                    "src": "./my-font.woff",
+                    "declarations": [{ "prop": "src", "value": "url(x)" }]
+                }]
+            }
+        "#,
+        )?;
+
+        match options_from_request(&request) {
+            Ok(_) => panic!(),
+            Err(err) => {
+                assert_eq!(err.to_string(), "Invalid declaration prop: `src`")
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use serde::Deserialize;
+
+/// The top-most structure encoded into the query param in requests to
+/// `@next/font/local` generated by the @next/font swc transform. e.g.
+/// `@next/font/local/target.css?{"path": "index.js", "import": "", ...`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NextFontLocalRequest {
+    pub path: String,
+    pub import: String,
+    pub arguments: Vec<NextFontLocalRequestArguments>,
+    pub variable_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NextFontLocalRequestArguments {
+    pub src: SrcRequest,
+    pub weight: Option<String>,
+    pub style: Option<String>,
+    pub display: Option<String>,
+    #[serde(default = "default_preload")]
+    pub preload: bool,
+    pub fallback: Option<Vec<String>>,
+    pub adjust_font_fallback: Option<AdjustFontFallbackRequest>,
+    pub variable: Option<String>,
+    pub declarations: Option<Vec<SrcDeclaration>>,
+}
+
+fn default_preload() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum SrcRequest {
+    One(String),
+    Many(Vec<SrcDescriptor>),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SrcDescriptor {
+    pub path: String,
+    pub weight: Option<String>,
+    pub style: Option<String>,
+}
+
+/// Either the name of a system font to adjust, or `false` to disable the
+/// adjusted fallback font.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum AdjustFontFallbackRequest {
+    Font(String),
+    Enabled(bool),
+}
+
+/// An additional descriptor of the generated `@font-face`.
+#[derive(Debug, Deserialize)]
+pub struct SrcDeclaration {
+    pub prop: String,
+    pub value: String,
+}
@@ -1,26 +1,59 @@
+pub mod optimize;
+pub mod remote;
+
 use std::collections::HashSet;
 
 use anyhow::Result;
 use turbo_tasks::{primitives::StringVc, Value};
-use turbopack_core::introspect::{Introspectable, IntrospectableVc};
+use turbo_tasks_fetch::HttpCacheVc;
+use turbo_tasks_fs::{FileContent, FileSystemPathVc};
+use turbopack_core::{
+    asset::AssetContent,
+    introspect::{Introspectable, IntrospectableVc},
+};
 use turbopack_dev_server::source::{
     query::QueryValue, ContentSource, ContentSourceContent, ContentSourceData,
     ContentSourceDataFilter, ContentSourceDataVary, ContentSourceResultVc, ContentSourceVc,
-    NeededData, ProxyResult,
+    HeaderValue, NeededData, ProxyResult,
 };
+use url::Url;
+
+use self::{
+    optimize::{optimize_image, EncodeFormat},
+    remote::{fetch_remote_image, is_allowed_remote_url},
+};
+use crate::{
+    next_config::{ImageConfig, ImageConfigVc, ImageFormat},
+    util::next_http_cache,
+};
+
+/// The quality used when the `q` query is missing, like in Next.js.
+const DEFAULT_QUALITY: u32 = 75;
 
 /// Serves, resizes, optimizes, and re-encodes images to be used with
 /// next/image.
 #[turbo_tasks::value(shared)]
 pub struct NextImageContentSource {
     asset_source: ContentSourceVc,
+    image_config: ImageConfigVc,
+    /// Caches the responses of remote images in `.next/cache/images`.
+    http_cache: HttpCacheVc,
 }
 
 #[turbo_tasks::value_impl]
 impl NextImageContentSourceVc {
     #[turbo_tasks::function]
-    pub fn new(asset_source: ContentSourceVc) -> NextImageContentSourceVc {
-        NextImageContentSource { asset_source }.cell()
+    pub fn new(
+        asset_source: ContentSourceVc,
+        image_config: ImageConfigVc,
+        project_path: FileSystemPathVc,
+    ) -> NextImageContentSourceVc {
+        NextImageContentSource {
+            asset_source,
+            image_config,
+            http_cache: next_http_cache(project_path, "images"),
+        }
+        .cell()
     }
 }
 
@@ -36,13 +69,11 @@ impl ContentSource for NextImageContentSource {
 
         let query = match &data.query {
             None => {
-                let queries = [
-                    "url".to_string(),
-                    // TODO: support q and w queries.
-                ]
-                .iter()
-                .cloned()
-                .collect::<HashSet<_>>();
+                let queries = ["url".to_string(), "w".to_string(), "q".to_string()]
+                    .iter()
+                    .cloned()
+                    .collect::<HashSet<_>>();
+                let headers = ["accept".to_string()].into_iter().collect::<HashSet<_>>();
 
                 return Ok(ContentSourceResultVc::exact(
                     ContentSourceContent::NeedData(NeededData {
@@ -51,6 +82,7 @@ impl ContentSource for NextImageContentSource {
                         vary: ContentSourceDataVary {
                             url: true,
                             query: Some(ContentSourceDataFilter::Subset(queries)),
+                            headers: Some(ContentSourceDataFilter::Subset(headers)),
                             ..Default::default()
                         },
                     })
@@ -62,27 +94,95 @@ impl ContentSource for NextImageContentSource {
 
         let url = match query.get("url") {
             Some(QueryValue::String(s)) => s,
-            _ => return Ok(ContentSourceResultVc::not_found()),
+            _ => return Ok(bad_request("\"url\" parameter is required")),
+        };
+        let image_config = this.image_config.await?;
+        let width = match parse_width(&image_config, query.get("w")) {
+            Ok(width) => width,
+            Err(message) => return Ok(bad_request(message)),
+        };
+        let quality = match parse_quality(query.get("q")) {
+            Ok(quality) => quality,
+            Err(message) => return Ok(bad_request(message)),
         };
 
-        // TODO: consume the assets, resize and reduce quality, re-encode into next-gen
-        // formats.
-        if let Some(path) = url.strip_prefix('/') {
+        let (file_content, max_age) = if let Some(path) = url.strip_prefix('/') {
             let asset = this.asset_source.get(path, Default::default());
             // THERE'S A HUGE PERFORMANCE ISSUE IF THIS MISSES
             let inner = asset.await?;
-            if matches!(&*inner.content.await?, ContentSourceContent::Static(..)) {
-                return Ok(asset);
+            let ContentSourceContent::Static(content) = &*inner.content.await? else {
+                return Ok(ContentSourceResultVc::not_found());
+            };
+            let AssetContent::File(file_content) = &*content.content().await? else {
+                return Ok(ContentSourceResultVc::not_found());
+            };
+            (*file_content, image_config.minimum_cache_ttl)
+        } else {
+            let Ok(remote_url) = Url::parse(url) else {
+                return Ok(bad_request("\"url\" parameter is invalid"));
+            };
+            if !is_allowed_remote_url(&image_config, &remote_url) {
+                return Ok(bad_request("\"url\" parameter is not allowed"));
+            }
+            let remote_image =
+                fetch_remote_image(url.clone(), this.image_config, this.http_cache).await?;
+            if !matches!(&*remote_image.content.await?, FileContent::Content(_)) {
+                return Ok(error_response(
+                    502,
+                    "\"url\" parameter is valid but upstream response is invalid",
+                ));
             }
+            (remote_image.content, remote_image.max_age)
+        };
+        let FileContent::Content(file) = &*file_content.await? else {
+            return Ok(ContentSourceResultVc::not_found());
+        };
+
+        let is_svg = file
+            .content_type()
+            .map_or(url.ends_with(".svg"), |content_type| {
+                content_type.essence_str() == "image/svg+xml"
+            });
+        if is_svg && !image_config.dangerously_allow_svg {
+            return Ok(bad_request(
+                "\"url\" parameter is valid but image type is not allowed",
+            ));
         }
 
-        // TODO: This should be downloaded by the server, and resized, etc.
+        let file_content = if is_svg || image_config.unoptimized {
+            file_content
+        } else {
+            let accept = match data.headers.as_ref().and_then(|h| h.get("accept")) {
+                Some(HeaderValue::SingleString(accept)) => accept.as_str(),
+                _ => "",
+            };
+            let format = negotiate_format(&image_config.formats, accept);
+            optimize_image(file_content, width, quality, Value::new(format))
+        };
+        let FileContent::Content(file) = &*file_content.await? else {
+            return Ok(ContentSourceResultVc::not_found());
+        };
+
+        let mut headers = vec![
+            "Cache-Control".to_string(),
+            format!("public, max-age={max_age}, must-revalidate"),
+            "Vary".to_string(),
+            "Accept".to_string(),
+        ];
+        if let Some(content_type) = file.content_type() {
+            headers.push("Content-Type".to_string());
+            headers.push(content_type.to_string());
+        }
+        if !image_config.content_security_policy.is_empty() {
+            headers.push("Content-Security-Policy".to_string());
+            headers.push(image_config.content_security_policy.clone());
+        }
         Ok(ContentSourceResultVc::exact(
             ContentSourceContent::HttpProxy(
                 ProxyResult {
-                    status: 302,
-                    headers: vec!["Location".to_string(), url.clone()],
-                    body: "".into(),
+                    status: 200,
+                    headers,
+                    body: file.content().clone(),
                 }
                 .cell(),
             )
@@ -91,6 +191,77 @@ impl ContentSource for NextImageContentSource {
     }
 }
 
+fn bad_request(message: &str) -> ContentSourceResultVc {
+    error_response(400, message)
+}
+
+fn error_response(status: u16, message: &str) -> ContentSourceResultVc {
+    ContentSourceResultVc::exact(
+        ContentSourceContent::HttpProxy(
+            ProxyResult {
+                status,
+                headers: vec![],
+                body: message.to_string().into(),
+            }
+            .cell(),
+        )
+        .cell(),
+    )
+}
+
+/// Parses the `w` query, which must be one of the configured device or image
+/// sizes.
+fn parse_width(image_config: &ImageConfig, w: Option<&QueryValue>) -> Result<u32, &'static str> {
+    match w {
+        Some(QueryValue::String(w)) => match w.parse::<u32>() {
+            Ok(w) if is_allowed_width(image_config, w) => Ok(w),
+            Ok(_) => Err("\"w\" parameter (width) is not allowed"),
+            Err(_) => Err("\"w\" parameter (width) must be a number greater than 0"),
+        },
+        _ => Err("\"w\" parameter (width) is required"),
+    }
+}
+
+/// Parses the optional `q` query, which must be between 1 and 100.
+fn parse_quality(q: Option<&QueryValue>) -> Result<u32, &'static str> {
+    match q {
+        Some(QueryValue::String(q)) => match q.parse::<u32>() {
+            Ok(q @ 1..=100) => Ok(q),
+            _ => Err("\"q\" parameter (quality) must be a number between 1 and 100"),
+        },
+        None => Ok(DEFAULT_QUALITY),
+        _ => Err("\"q\" parameter (quality) must be a number between 1 and 100"),
+    }
+}
+
+fn is_allowed_width(image_config: &ImageConfig, width: u32) -> bool {
+    image_config
+        .device_sizes
+        .iter()
+        .chain(image_config.image_sizes.iter())
+        .any(|&size| size as u32 == width)
+}
+
+/// Picks the first of the configured formats which is accepted by the
+/// client, falling back to the format of the source image.
+fn negotiate_format(formats: &[ImageFormat], accept: &str) -> EncodeFormat {
+    formats
+        .iter()
+        .map(|format| match format {
+            ImageFormat::Avif => EncodeFormat::Avif,
+            ImageFormat::Webp => EncodeFormat::Webp,
+        })
+        .find(|format| {
+            let mime_type = match format {
+                EncodeFormat::Avif => "image/avif",
+                EncodeFormat::Webp => "image/webp",
+                EncodeFormat::Source => return false,
+            };
+            format.is_supported() && accept.contains(mime_type)
+        })
+        .unwrap_or(EncodeFormat::Source)
+}
+
 #[turbo_tasks::value_impl]
 impl Introspectable for NextImageContentSource {
     #[turbo_tasks::function]
@@ -103,3 +274,82 @@ impl Introspectable for NextImageContentSource {
         StringVc::cell("suports dynamic serving of any statically imported image".to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use turbopack_dev_server::source::query::QueryValue;
+
+    use super::{negotiate_format, parse_quality, parse_width, EncodeFormat, DEFAULT_QUALITY};
+    use crate::next_config::{ImageConfig, ImageFormat};
+
+    fn value(s: &str) -> QueryValue {
+        QueryValue::String(s.to_string())
+    }
+
+    #[test]
+    fn test_width() {
+        let image_config = ImageConfig::default();
+        assert_eq!(parse_width(&image_config, Some(&value("640"))), Ok(640));
+        assert_eq!(parse_width(&image_config, Some(&value("16"))), Ok(16));
+        assert!(parse_width(&image_config, Some(&value("641"))).is_err());
+        assert!(parse_width(&image_config, Some(&value("0"))).is_err());
+        assert!(parse_width(&image_config, Some(&value("-1"))).is_err());
+        assert!(parse_width(&image_config, Some(&value("abc"))).is_err());
+        assert!(parse_width(&image_config, Some(&QueryValue::Array(vec![value("640")]))).is_err());
+        assert!(parse_width(&image_config, None).is_err());
+
+        let image_config = ImageConfig {
+            device_sizes: vec![100],
+            image_sizes: vec![],
+            ..Default::default()
+        };
+        assert_eq!(parse_width(&image_config, Some(&value("100"))), Ok(100));
+        assert!(parse_width(&image_config, Some(&value("640"))).is_err());
+    }
+
+    #[test]
+    fn test_quality() {
+        assert_eq!(parse_quality(None), Ok(DEFAULT_QUALITY));
+        assert_eq!(parse_quality(Some(&value("1"))), Ok(1));
+        assert_eq!(parse_quality(Some(&value("100"))), Ok(100));
+        assert!(parse_quality(Some(&value("0"))).is_err());
+        assert!(parse_quality(Some(&value("101"))).is_err());
+        assert!(parse_quality(Some(&value("high"))).is_err());
+        assert!(parse_quality(Some(&QueryValue::Array(vec![value("75")]))).is_err());
+    }
+
+    #[test]
+    fn test_negotiate_format() {
+        let both = [ImageFormat::Avif, ImageFormat::Webp];
+        let preferred = if EncodeFormat::Avif.is_supported() {
+            EncodeFormat::Avif
+        } else if EncodeFormat::Webp.is_supported() {
+            EncodeFormat::Webp
+        } else {
+            EncodeFormat::Source
+        };
+        assert_eq!(
+            negotiate_format(&both, "image/avif,image/webp,*/*"),
+            preferred
+        );
+
+        let webp = if EncodeFormat::Webp.is_supported() {
+            EncodeFormat::Webp
+        } else {
+            EncodeFormat::Source
+        };
+        assert_eq!(negotiate_format(&both, "image/webp,*/*"), webp);
+        assert_eq!(
+            negotiate_format(&[ImageFormat::Webp], "image/avif,image/webp"),
+            webp
+        );
+
+        // Formats the client doesn't accept or which aren't configured are
+        // never picked.
+        assert_eq!(
+            negotiate_format(&both, "image/png,*/*"),
+            EncodeFormat::Source
+        );
+        assert_eq!(negotiate_format(&[], "image/webp"), EncodeFormat::Source);
+    }
+}
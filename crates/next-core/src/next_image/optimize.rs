@@ -0,0 +1,126 @@
+use std::io::{Cursor, Read};
+
+use anyhow::{Context, Result};
+use image::{imageops::FilterType, DynamicImage, ImageFormat, ImageOutputFormat};
+use mime::Mime;
+use turbo_tasks::Value;
+use turbo_tasks_fs::{File, FileContent, FileContentVc};
+
+/// The format an optimized image is encoded to.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(PartialOrd, Ord, Hash, Debug, Copy, Clone)]
+pub enum EncodeFormat {
+    Avif,
+    Webp,
+    /// Keeps the format of the source image.
+    Source,
+}
+
+impl EncodeFormat {
+    /// Whether turbopack was compiled with an encoder for this format.
+    pub fn is_supported(&self) -> bool {
+        match self {
+            EncodeFormat::Avif => cfg!(feature = "image-avif"),
+            EncodeFormat::Webp => cfg!(feature = "image-webp"),
+            EncodeFormat::Source => true,
+        }
+    }
+}
+
+/// Resizes the image to at most `width` pixels wide and re-encodes it with
+/// the given quality (1-100). The result is a file with the content type of
+/// the new format.
+#[turbo_tasks::function]
+pub async fn optimize_image(
+    content: FileContentVc,
+    width: u32,
+    quality: u32,
+    format: Value<EncodeFormat>,
+) -> Result<FileContentVc> {
+    let FileContent::Content(file) = &*content.await? else {
+        return Ok(FileContent::NotFound.cell());
+    };
+    let mut bytes = Vec::new();
+    file.content().read().read_to_end(&mut bytes)?;
+
+    let source_format = image::guess_format(&bytes).context("unknown image format")?;
+    // Re-encoding would drop the animation.
+    if source_format == ImageFormat::Gif {
+        return Ok(content);
+    }
+    let image = image::load_from_memory_with_format(&bytes, source_format)
+        .context("unable to decode image")?;
+    let image = if image.width() > width {
+        image.resize(width, u32::MAX, FilterType::Lanczos3)
+    } else {
+        image
+    };
+
+    let quality = quality.clamp(1, 100) as u8;
+    let format = format.into_value();
+    let (bytes, content_type) = match format {
+        EncodeFormat::Avif if format.is_supported() => encode_avif(&image, quality)?,
+        EncodeFormat::Webp if format.is_supported() => encode_webp(&image, quality)?,
+        // Keeps the source format when the encoder is not compiled in.
+        _ => encode_source(&image, source_format, quality)?,
+    };
+    Ok(File::from(bytes).with_content_type(content_type).into())
+}
+
+fn encode_source(
+    image: &DynamicImage,
+    source_format: ImageFormat,
+    quality: u8,
+) -> Result<(Vec<u8>, Mime)> {
+    let mut bytes = Vec::new();
+    let (output_format, content_type) = match source_format {
+        ImageFormat::Jpeg => (ImageOutputFormat::Jpeg(quality), mime::IMAGE_JPEG),
+        // Lossless formats and formats we can't encode are stored as PNG.
+        _ => (ImageOutputFormat::Png, mime::IMAGE_PNG),
+    };
+    image.write_to(&mut Cursor::new(&mut bytes), output_format)?;
+    Ok((bytes, content_type))
+}
+
+#[cfg(feature = "image-webp")]
+fn encode_webp(image: &DynamicImage, quality: u8) -> Result<(Vec<u8>, Mime)> {
+    use image::{
+        codecs::webp::{WebPEncoder, WebPQuality},
+        ColorType,
+    };
+
+    let mut bytes = Vec::new();
+    let rgba = image.to_rgba8();
+    WebPEncoder::new_with_quality(&mut bytes, WebPQuality::lossy(quality)).encode(
+        &rgba,
+        rgba.width(),
+        rgba.height(),
+        ColorType::Rgba8,
+    )?;
+    Ok((bytes, "image/webp".parse()?))
+}
+
+#[cfg(not(feature = "image-webp"))]
+fn encode_webp(_image: &DynamicImage, _quality: u8) -> Result<(Vec<u8>, Mime)> {
+    anyhow::bail!("WebP encoding requires the image-webp feature")
+}
+
+#[cfg(feature = "image-avif")]
+fn encode_avif(image: &DynamicImage, quality: u8) -> Result<(Vec<u8>, Mime)> {
+    use image::{codecs::avif::AvifEncoder, ColorType, ImageEncoder};
+
+    let mut bytes = Vec::new();
+    let rgba = image.to_rgba8();
+    AvifEncoder::new_with_speed_quality(&mut bytes, 6, quality).write_image(
+        &rgba,
+        rgba.width(),
+        rgba.height(),
+        ColorType::Rgba8,
+    )?;
+    Ok((bytes, "image/avif".parse()?))
+}
+
+#[cfg(not(feature = "image-avif"))]
+fn encode_avif(_image: &DynamicImage, _quality: u8) -> Result<(Vec<u8>, Mime)> {
+    anyhow::bail!("AVIF encoding requires the image-avif feature")
+}
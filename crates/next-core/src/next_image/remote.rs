@@ -0,0 +1,309 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use mime::Mime;
+use turbo_tasks::get_invalidator;
+use turbo_tasks_fetch::{fetch_request_now, HttpCacheVc, HttpRequest, HttpResponse};
+use turbo_tasks_fs::{File, FileContent, FileContentVc};
+use url::Url;
+
+use crate::next_config::{ImageConfig, ImageConfigVc, RemotePattern, RemotePatternProtocal};
+
+/// Whether `url` may be fetched and optimized, i.e. it's listed in
+/// `images.domains` or matches one of `images.remotePatterns`.
+pub fn is_allowed_remote_url(image_config: &ImageConfig, url: &Url) -> bool {
+    if !matches!(url.scheme(), "http" | "https") {
+        return false;
+    }
+    let Some(hostname) = url.host_str() else {
+        return false;
+    };
+    image_config.domains.iter().any(|domain| domain == hostname)
+        || image_config
+            .remote_patterns
+            .iter()
+            .any(|pattern| matches_remote_pattern(pattern, url))
+}
+
+/// Matches like Next.js: missing parts of the pattern match anything, and the
+/// hostname and pathname support `*` (a single subdomain or path segment)
+/// and `**` (any number of them).
+fn matches_remote_pattern(pattern: &RemotePattern, url: &Url) -> bool {
+    if let Some(protocol) = &pattern.protocol {
+        let scheme = match protocol {
+            RemotePatternProtocal::Http => "http",
+            RemotePatternProtocal::Https => "https",
+        };
+        if url.scheme() != scheme {
+            return false;
+        }
+    }
+    if let Some(port) = &pattern.port {
+        let url_port = url.port().map(|port| port.to_string()).unwrap_or_default();
+        if *port != url_port {
+            return false;
+        }
+    }
+    let Some(hostname) = url.host_str() else {
+        return false;
+    };
+    if !matches_glob(&pattern.hostname, hostname, '.') {
+        return false;
+    }
+    match &pattern.pathname {
+        Some(pathname) => matches_glob(pathname, url.path(), '/'),
+        None => true,
+    }
+}
+
+/// Matches `value` against `pattern` segment by segment, where segments are
+/// delimited by `separator`. A `**` segment matches one or more segments, a
+/// `*` within a segment matches any characters but the separator.
+fn matches_glob(pattern: &str, value: &str, separator: char) -> bool {
+    let pattern = pattern.split(separator).collect::<Vec<_>>();
+    let value = value.split(separator).collect::<Vec<_>>();
+    matches_segments(&pattern, &value)
+}
+
+fn matches_segments(pattern: &[&str], value: &[&str]) -> bool {
+    match (pattern.split_first(), value.split_first()) {
+        (None, None) => true,
+        (Some((&"**", rest)), Some(_)) => {
+            (1..=value.len()).any(|skip| matches_segments(rest, &value[skip..]))
+        }
+        (Some((segment, rest)), Some((value_segment, value_rest))) => {
+            matches_segment(segment, value_segment) && matches_segments(rest, value_rest)
+        }
+        _ => false,
+    }
+}
+
+fn matches_segment(pattern: &str, value: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == value,
+        Some((prefix, rest)) => {
+            let Some(value) = value.strip_prefix(prefix) else {
+                return false;
+            };
+            value
+                .char_indices()
+                .map(|(index, _)| index)
+                .chain([value.len()])
+                .any(|index| matches_segment(rest, &value[index..]))
+        }
+    }
+}
+
+/// Reads the number of seconds a response may be cached from a
+/// `Cache-Control` header. `s-maxage` takes precedence, like in Next.js.
+fn max_age(cache_control: &str) -> Option<u32> {
+    let mut max_age = None;
+    for directive in cache_control.split(',') {
+        let (name, value) = directive.split_once('=').unwrap_or((directive, ""));
+        let value = value.trim().trim_matches('"');
+        match name.trim().to_ascii_lowercase().as_str() {
+            "s-maxage" => return value.parse().ok(),
+            "max-age" => max_age = value.parse().ok(),
+            _ => {}
+        }
+    }
+    max_age
+}
+
+#[turbo_tasks::value(shared)]
+pub struct RemoteImage {
+    /// The image, or [FileContent::NotFound] when the upstream request failed,
+    /// redirected to a url which is not allowed, or didn't respond with an
+    /// image.
+    pub content: FileContentVc,
+    /// The number of seconds the image is cached for.
+    pub max_age: u32,
+}
+
+/// The number of redirects followed for a remote image, like Next.js'
+/// `images.maximumRedirects`.
+const MAXIMUM_REDIRECTS: usize = 3;
+
+/// The maximum size of a remote image in bytes, like Next.js'
+/// `images.maximumResponseBody`.
+const MAXIMUM_RESPONSE_BODY: u64 = 50_000_000;
+
+/// Fetches `url`, following redirects only to urls which are allowed by the
+/// `image_config` themselves. Returns `None` when the request fails.
+async fn fetch_allowed(
+    url: &str,
+    image_config: &ImageConfig,
+    cache: HttpCacheVc,
+) -> Result<Option<HttpResponse>> {
+    let mut url = url.to_string();
+    for _ in 0..=MAXIMUM_REDIRECTS {
+        let request = HttpRequest {
+            follow_redirects: false,
+            max_body_size: Some(MAXIMUM_RESPONSE_BODY),
+            ..HttpRequest::get(&url)
+        };
+        let Ok(response) = fetch_request_now(&request, Some(cache)).await? else {
+            return Ok(None);
+        };
+        if !(300..400).contains(&response.status) {
+            return Ok(Some(response));
+        }
+        let Some(location) = response.header("location") else {
+            return Ok(None);
+        };
+        let Ok(location) = Url::parse(&url).and_then(|url| url.join(location)) else {
+            return Ok(None);
+        };
+        if !is_allowed_remote_url(image_config, &location) {
+            return Ok(None);
+        }
+        url = location.to_string();
+    }
+    Ok(None)
+}
+
+/// Fetches an image of an allowed remote url. The result is cached for the
+/// longer of `images.minimumCacheTTL` and the `max-age` of the upstream
+/// response and fetched again afterwards. Responses are stored in the HTTP
+/// `cache`, so they survive restarts while they're fresh.
+#[turbo_tasks::function]
+pub async fn fetch_remote_image(
+    url: String,
+    image_config: ImageConfigVc,
+    cache: HttpCacheVc,
+) -> Result<RemoteImageVc> {
+    let image_config = image_config.await?;
+    let minimum_cache_ttl = image_config.minimum_cache_ttl;
+    let (content, max_age) = match fetch_allowed(&url, &image_config, cache).await? {
+        Some(response) => {
+            let content_type = response
+                .header("content-type")
+                .and_then(|content_type| content_type.parse::<Mime>().ok())
+                .filter(|content_type| content_type.type_() == mime::IMAGE);
+            let max_age = response
+                .header("cache-control")
+                .and_then(max_age)
+                .unwrap_or_default()
+                .max(minimum_cache_ttl);
+            let content = match content_type {
+                Some(content_type) => {
+                    let body = response.body.await?.0.clone();
+                    FileContent::Content(File::from(body).with_content_type(content_type))
+                }
+                None => FileContent::NotFound,
+            };
+            (content, max_age)
+        }
+        None => (FileContent::NotFound, minimum_cache_ttl),
+    };
+
+    let invalidator = get_invalidator();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(max_age.max(1) as u64)).await;
+        invalidator.invalidate();
+    });
+
+    Ok(RemoteImage {
+        content: content.cell(),
+        max_age,
+    }
+    .cell())
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::{is_allowed_remote_url, matches_glob, max_age};
+    use crate::next_config::{ImageConfig, RemotePattern, RemotePatternProtocal};
+
+    fn allowed(image_config: &ImageConfig, url: &str) -> bool {
+        is_allowed_remote_url(image_config, &Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn test_domains() {
+        let image_config = ImageConfig {
+            domains: vec!["example.com".to_string()],
+            ..Default::default()
+        };
+        assert!(allowed(&image_config, "https://example.com/a.png"));
+        assert!(allowed(&image_config, "http://example.com:8080/a/b.png"));
+        assert!(!allowed(&image_config, "https://sub.example.com/a.png"));
+        assert!(!allowed(
+            &image_config,
+            "https://example.com.evil.org/a.png"
+        ));
+        assert!(!allowed(&image_config, "ftp://example.com/a.png"));
+    }
+
+    #[test]
+    fn test_remote_patterns() {
+        let image_config = ImageConfig {
+            remote_patterns: vec![RemotePattern {
+                protocol: Some(RemotePatternProtocal::Https),
+                hostname: "**.example.com".to_string(),
+                port: None,
+                pathname: Some("/account123/**".to_string()),
+            }],
+            ..Default::default()
+        };
+        assert!(allowed(
+            &image_config,
+            "https://img.example.com/account123/a.png"
+        ));
+        assert!(allowed(
+            &image_config,
+            "https://a.b.example.com/account123/x/y.png"
+        ));
+        assert!(!allowed(
+            &image_config,
+            "http://img.example.com/account123/a.png"
+        ));
+        assert!(!allowed(
+            &image_config,
+            "https://example.com/account123/a.png"
+        ));
+        assert!(!allowed(
+            &image_config,
+            "https://img.example.com/account456/a.png"
+        ));
+    }
+
+    #[test]
+    fn test_remote_pattern_port() {
+        let image_config = ImageConfig {
+            remote_patterns: vec![RemotePattern {
+                protocol: None,
+                hostname: "localhost".to_string(),
+                port: Some("3000".to_string()),
+                pathname: None,
+            }],
+            ..Default::default()
+        };
+        assert!(allowed(&image_config, "http://localhost:3000/a.png"));
+        assert!(!allowed(&image_config, "http://localhost:4000/a.png"));
+        assert!(!allowed(&image_config, "http://localhost/a.png"));
+    }
+
+    #[test]
+    fn test_glob() {
+        assert!(matches_glob("*.example.com", "img.example.com", '.'));
+        assert!(!matches_glob("*.example.com", "a.img.example.com", '.'));
+        assert!(matches_glob("img-*.example.com", "img-1.example.com", '.'));
+        assert!(!matches_glob("img-*.example.com", "cdn.example.com", '.'));
+        assert!(matches_glob("/images/*.png", "/images/a.png", '/'));
+        assert!(!matches_glob("/images/*.png", "/images/a/b.png", '/'));
+        assert!(matches_glob("/images/**", "/images/a/b.png", '/'));
+        assert!(!matches_glob("/images/**", "/images", '/'));
+    }
+
+    #[test]
+    fn test_max_age() {
+        assert_eq!(max_age("public, max-age=3600"), Some(3600));
+        assert_eq!(max_age("max-age=60, s-maxage=600"), Some(600));
+        assert_eq!(max_age("s-maxage=600, max-age=60"), Some(600));
+        assert_eq!(max_age("no-cache"), None);
+        assert_eq!(max_age("max-age=\"120\""), Some(120));
+    }
+}
@@ -11,6 +11,7 @@ use crate::{
     next_client::context::ClientContextType,
     next_config::NextConfigVc,
     next_font_google::{NextFontGoogleCssModuleReplacerVc, NextFontGoogleReplacerVc},
+    next_font_local::{NextFontLocalCssModuleReplacerVc, NextFontLocalReplacerVc},
     next_server::context::ServerContextType,
 };
 
@@ -262,6 +263,17 @@ pub fn insert_next_shared_aliases(import_map: &mut ImportMap, project_path: File
         AliasPattern::exact("@vercel/turbopack-next/internal/font/google/cssmodule.module.css"),
         ImportMapping::Dynamic(NextFontGoogleCssModuleReplacerVc::new(project_path).into()).into(),
     );
+
+    import_map.insert_alias(
+        // Request path from js via next-font swc transform
+        AliasPattern::exact("@next/font/local/target.css"),
+        ImportMapping::Dynamic(NextFontLocalReplacerVc::new(project_path).into()).into(),
+    );
+
+    import_map.insert_alias(
+        AliasPattern::exact("@vercel/turbopack-next/internal/font/local/cssmodule.module.css"),
+        ImportMapping::Dynamic(NextFontLocalCssModuleReplacerVc::new(project_path).into()).into(),
+    );
 }
 
 /// Inserts an alias to an alternative of import mappings into an import map.
@@ -43,7 +43,7 @@ use turbopack_node::{
 
 use crate::{
     embed_js::{next_js_file, wrap_with_next_js_fs},
-    env::env_for_js,
+    env::{add_client_env_check, env_for_js},
     fallback::get_fallback_page,
     next_client::{
         context::{
@@ -60,6 +60,7 @@ use crate::{
     },
     next_shared::transforms::{add_next_transforms_to_pages, PageTransformType},
     page_loader::create_page_loader,
+    static_generation::{get_static_paths, StaticGenerationContentSourceVc},
     util::{get_asset_path_from_route, pathname_for_path, regular_expression_for_path},
 };
 
@@ -152,6 +153,12 @@ pub async fn create_page_source(
         pages_dir,
         Value::new(PageTransformType::Client),
     );
+    let client_module_options_context = add_client_env_check(
+        client_module_options_context,
+        project_path,
+        env,
+        next_config,
+    );
     let client_resolve_options_context = get_client_resolve_options_context(project_path, ty);
     let client_context: AssetContextVc = ModuleAssetContextVc::new(
         TransitionsByNameVc::cell(HashMap::new()),
@@ -216,11 +223,11 @@ pub async fn create_page_source(
     )
     .into();
 
-    let server_runtime_entries =
-        vec![
-            ProcessEnvAssetVc::new(project_path, env_for_js(env, false, next_config))
-                .as_ecmascript_chunk_placeable(),
-        ];
+    let server_runtime_entries = vec![ProcessEnvAssetVc::new(
+        project_path,
+        env_for_js(project_path, env, false, next_config),
+    )
+    .as_ecmascript_chunk_placeable()];
 
     let fallback_page = get_fallback_page(
         project_path,
@@ -361,25 +368,43 @@ async fn create_page_source_for_file(
         .cell()
         .into();
 
+        let static_paths = get_static_paths(
+            context_path,
+            page_file,
+            server_context,
+            intermediate_output_path.join("static-paths"),
+            runtime_entries,
+            path_regex,
+        );
+
         CombinedContentSourceVc::new(vec![
-            create_node_rendered_source(
-                specificity,
-                server_root,
-                pathname,
+            StaticGenerationContentSourceVc::new(
+                CombinedContentSourceVc::new(vec![
+                    create_node_rendered_source(
+                        specificity,
+                        server_root,
+                        pathname,
+                        path_regex,
+                        ssr_entry,
+                        runtime_entries,
+                        fallback_page,
+                    ),
+                    create_node_rendered_source(
+                        specificity,
+                        server_root,
+                        data_pathname,
+                        data_path_regex,
+                        ssr_data_entry,
+                        runtime_entries,
+                        fallback_page,
+                    ),
+                ])
+                .into(),
                 path_regex,
-                ssr_entry,
-                runtime_entries,
-                fallback_page,
-            ),
-            create_node_rendered_source(
-                specificity,
-                server_root,
-                data_pathname,
                 data_path_regex,
-                ssr_data_entry,
-                runtime_entries,
-                fallback_page,
-            ),
+                static_paths,
+            )
+            .into(),
             create_page_loader(
                 server_root,
                 client_context,
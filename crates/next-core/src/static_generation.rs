@@ -0,0 +1,527 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use turbo_tasks::{trace::TraceRawVcs, State, Value};
+use turbo_tasks_fs::FileSystemPathVc;
+use turbopack::evaluate_context::node_evaluate_asset_context;
+use turbopack_core::{
+    context::AssetContextVc,
+    reference_type::{EntryReferenceSubType, ReferenceType},
+    virtual_asset::VirtualAssetVc,
+};
+use turbopack_dev_server::source::{
+    query::{Query, QueryValue},
+    ContentSource, ContentSourceContent, ContentSourceData, ContentSourceDataVary,
+    ContentSourceResultVc, ContentSourceVc, ContentSourcesVc, NeededData,
+};
+use turbopack_ecmascript::chunk::EcmascriptChunkPlaceablesVc;
+use turbopack_node::{
+    evaluate::{evaluate, JavaScriptValue},
+    path_regex::{Param, PathRegexVc},
+};
+
+use crate::embed_js::next_js_file;
+
+/// The `s-maxage` the renderer reports for pages which are never revalidated.
+const NEVER_REVALIDATE: u64 = 31536000;
+
+/// How requests for paths which are not returned by `getStaticPaths` are
+/// handled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "lowercase")]
+pub enum StaticFallback {
+    /// The path is not found.
+    False,
+    /// A fallback version of the page is served, which fetches the data of
+    /// the page on the client. Fetching the data generates the page.
+    True,
+    /// The page is generated on the first request.
+    Blocking,
+}
+
+/// The paths of a page which are statically generated.
+#[turbo_tasks::value(shared)]
+pub struct StaticPaths {
+    /// Whether the page exports `getStaticProps`.
+    pub is_static: bool,
+    /// The params of the paths returned by `getStaticPaths`, or `None` when
+    /// the page has no `getStaticPaths`.
+    pub params: Option<Vec<IndexMap<String, Param>>>,
+    pub fallback: StaticFallback,
+}
+
+/// The result of `entry/static-paths.ts`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StaticPathsResult {
+    is_static: bool,
+    paths: Option<Vec<StaticPath>>,
+    fallback: StaticFallback,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StaticPath {
+    Path { path: String },
+    Params { params: IndexMap<String, JsonValue> },
+}
+
+/// Calls `getStaticPaths` of the page in `page_file`.
+#[turbo_tasks::function]
+pub async fn get_static_paths(
+    project_path: FileSystemPathVc,
+    page_file: FileSystemPathVc,
+    context: AssetContextVc,
+    intermediate_output_path: FileSystemPathVc,
+    runtime_entries: EcmascriptChunkPlaceablesVc,
+    path_regex: PathRegexVc,
+) -> Result<StaticPathsVc> {
+    // The entry imports the page with `import * from "."`.
+    let entry_asset = context.process(
+        VirtualAssetVc::new(
+            page_file.join("static-paths.ts"),
+            next_js_file("entry/static-paths.ts").into(),
+        )
+        .into(),
+        Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+    );
+    let result = evaluate(
+        project_path,
+        entry_asset,
+        project_path,
+        page_file,
+        node_evaluate_asset_context(None),
+        intermediate_output_path,
+        Some(runtime_entries),
+        vec![],
+    )
+    .await?;
+    let result: StaticPathsResult = match &*result {
+        JavaScriptValue::Value(value) => serde_json::from_reader(value.read())?,
+        JavaScriptValue::Error => {
+            // The error is reported as an issue. The page is rendered on every
+            // request instead, which shows errors of the page itself.
+            return Ok(StaticPaths {
+                is_static: false,
+                params: None,
+                fallback: StaticFallback::False,
+            }
+            .cell());
+        }
+        JavaScriptValue::Stream(_) => {
+            bail!("streaming the result of getStaticPaths is not supported");
+        }
+    };
+
+    let params = match result.paths {
+        Some(paths) => {
+            let path_regex = path_regex.await?;
+            let params = paths
+                .into_iter()
+                .map(|path| match path {
+                    StaticPath::Path { path } => path_regex
+                        .get_matches(path.trim_matches('/'))
+                        .map(normalize_params)
+                        .with_context(|| {
+                            format!(
+                                "the path \"{path}\" returned by getStaticPaths does not match \
+                                 the page"
+                            )
+                        }),
+                    StaticPath::Params { params } => Ok(normalize_params(
+                        params
+                            .into_iter()
+                            .filter_map(|(name, value)| {
+                                // Omitted optional catch all params are `undefined`,
+                                // `null` or `false`.
+                                Some((name, serde_json::from_value(value).ok()?))
+                            })
+                            .collect(),
+                    )),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Some(params)
+        }
+        None => None,
+    };
+
+    Ok(StaticPaths {
+        is_static: result.is_static,
+        params,
+        fallback: result.fallback,
+    }
+    .cell())
+}
+
+/// Removes empty optional catch all params, so that params of the same path
+/// compare equal.
+fn normalize_params(params: IndexMap<String, Param>) -> IndexMap<String, Param> {
+    params
+        .into_iter()
+        .filter(|(_, param)| !matches!(param, Param::Multi(segments) if segments.is_empty()))
+        .collect()
+}
+
+/// A generated version of a page.
+#[derive(Clone, Debug, TraceRawVcs)]
+struct Generation {
+    /// Renders of the page are cached by this number, which is passed as the
+    /// cache buster.
+    number: u64,
+    #[turbo_tasks(trace_ignore)]
+    generated_at: Instant,
+    /// How long the page is served before it's generated again, or `None`
+    /// when it's never generated again.
+    revalidate: Option<Duration>,
+    /// Whether the next generation is being rendered in the background.
+    regenerating: bool,
+}
+
+impl Generation {
+    fn is_stale(&self) -> bool {
+        self.revalidate.map_or(false, |revalidate| {
+            self.generated_at.elapsed() >= revalidate
+        })
+    }
+}
+
+/// Serves pages with `getStaticProps` from `source` like statically generated
+/// pages in production.
+///
+/// The HTML and the `_next/data` JSON of a page are rendered independently of
+/// the request and cached until the page is revalidated. Requests for a stale
+/// page are served the previous version while the next one is rendered in the
+/// background. Paths which are not returned by `getStaticPaths` are handled
+/// according to its `fallback`.
+#[turbo_tasks::value(serialization = "none", eq = "manual", cell = "new")]
+pub struct StaticGenerationContentSource {
+    source: ContentSourceVc,
+    path_regex: PathRegexVc,
+    data_path_regex: PathRegexVc,
+    static_paths: StaticPathsVc,
+    /// The current generation of every generated page, by its path.
+    generations: State<HashMap<String, Generation>>,
+}
+
+#[turbo_tasks::value_impl]
+impl StaticGenerationContentSourceVc {
+    #[turbo_tasks::function]
+    pub fn new(
+        source: ContentSourceVc,
+        path_regex: PathRegexVc,
+        data_path_regex: PathRegexVc,
+        static_paths: StaticPathsVc,
+    ) -> Self {
+        StaticGenerationContentSource {
+            source,
+            path_regex,
+            data_path_regex,
+            static_paths,
+            generations: State::new(HashMap::new()),
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ContentSource for StaticGenerationContentSource {
+    #[turbo_tasks::function]
+    async fn get(
+        self_vc: StaticGenerationContentSourceVc,
+        path: &str,
+        data: Value<ContentSourceData>,
+    ) -> Result<ContentSourceResultVc> {
+        let this = self_vc.await?;
+        let (page_path, params, is_data) =
+            if let Some(params) = this.path_regex.await?.get_matches(path) {
+                (path.to_string(), params, false)
+            } else if let Some(params) = this.data_path_regex.await?.get_matches(path) {
+                (page_path_for_data_path(path), params, true)
+            } else {
+                return Ok(this.source.get(path, data));
+            };
+        let static_paths = this.static_paths.await?;
+        if !static_paths.is_static {
+            return Ok(this.source.get(path, data));
+        }
+
+        let Some(method) = &data.method else {
+            return Ok(ContentSourceResultVc::exact(
+                ContentSourceContent::NeedData(NeededData {
+                    source: self_vc.into(),
+                    path: path.to_string(),
+                    vary: ContentSourceDataVary {
+                        method: true,
+                        // Whether a page is stale depends on the time of the
+                        // request.
+                        cache_buster: true,
+                        ..Default::default()
+                    },
+                })
+                .cell(),
+            ));
+        };
+        if method != "GET" && method != "HEAD" {
+            return Ok(this.source.get(path, data));
+        }
+
+        let generation = this.generations.get().get(&page_path).cloned();
+        let Some(generation) = generation else {
+            let is_prerendered = static_paths
+                .params
+                .as_ref()
+                .map_or(true, |paths| paths.contains(&normalize_params(params)));
+            if !is_prerendered {
+                match static_paths.fallback {
+                    StaticFallback::False => return Ok(ContentSourceResultVc::not_found()),
+                    StaticFallback::True if !is_data => {
+                        return Ok(this.source.get(path, render_data(path, is_data, true, 0)));
+                    }
+                    StaticFallback::True | StaticFallback::Blocking => {}
+                }
+            }
+
+            let result = this.source.get(path, render_data(path, is_data, false, 0));
+            if let Some(revalidate) = generated_revalidate(result).await? {
+                this.generations.update_conditionally(|generations| {
+                    generations.entry(page_path).or_insert(Generation {
+                        number: 0,
+                        generated_at: Instant::now(),
+                        revalidate,
+                        regenerating: false,
+                    });
+                    true
+                });
+            }
+            return Ok(result);
+        };
+
+        if generation.is_stale() && !generation.regenerating {
+            this.generations.update_conditionally(|generations| {
+                match generations.get_mut(&page_path) {
+                    Some(generation) => {
+                        generation.regenerating = true;
+                        true
+                    }
+                    None => false,
+                }
+            });
+            regenerate(
+                self_vc,
+                path.to_string(),
+                page_path,
+                is_data,
+                generation.number + 1,
+            );
+        }
+        Ok(this
+            .source
+            .get(path, render_data(path, is_data, false, generation.number)))
+    }
+
+    #[turbo_tasks::function]
+    fn get_children(&self) -> ContentSourcesVc {
+        ContentSourcesVc::cell(vec![self.source])
+    }
+}
+
+/// Renders the next generation of a page in the background and serves it once
+/// it's rendered. The previous generation is served until then, and when the
+/// rendering fails.
+fn regenerate(
+    source_vc: StaticGenerationContentSourceVc,
+    path: String,
+    page_path: String,
+    is_data: bool,
+    number: u64,
+) {
+    turbo_tasks::turbo_tasks().run_once(Box::pin(async move {
+        // Nothing waits for this task, so errors are logged instead.
+        if let Err(err) = regenerate_page(source_vc, &path, page_path, is_data, number).await {
+            println!("Failed to regenerate /{path}: {err:?}");
+        }
+        Ok(())
+    }));
+}
+
+async fn regenerate_page(
+    source_vc: StaticGenerationContentSourceVc,
+    path: &str,
+    page_path: String,
+    is_data: bool,
+    number: u64,
+) -> Result<()> {
+    let this = source_vc.await?;
+    let result = this
+        .source
+        .get(path, render_data(path, is_data, false, number));
+    let revalidate = generated_revalidate(result).await;
+    this.generations.update_conditionally(|generations| {
+        let Some(generation) = generations.get_mut(&page_path) else {
+            return false;
+        };
+        match &revalidate {
+            Ok(Some(revalidate)) => {
+                *generation = Generation {
+                    number,
+                    generated_at: Instant::now(),
+                    revalidate: *revalidate,
+                    regenerating: false,
+                }
+            }
+            _ => generation.regenerating = false,
+        }
+        true
+    });
+    revalidate?;
+    Ok(())
+}
+
+/// Returns the revalidate period of a rendered page, see
+/// [revalidate_from_headers]. Returns `None` when the page was not generated,
+/// e.g. because rendering failed.
+async fn generated_revalidate(result: ContentSourceResultVc) -> Result<Option<Option<Duration>>> {
+    let ContentSourceContent::HttpProxy(proxy) = &*result.await?.content.await? else {
+        return Ok(None);
+    };
+    let proxy = proxy.await?;
+    Ok(revalidate_from_headers(proxy.status, &proxy.headers))
+}
+
+/// Returns the revalidate period of a page from the `s-maxage` of the
+/// `Cache-Control` header the renderer adds to pages with `getStaticProps`,
+/// or `None` when the response is not a generated page. The period is `None`
+/// for pages which are never revalidated.
+fn revalidate_from_headers(status: u16, headers: &[String]) -> Option<Option<Duration>> {
+    if status != 200 {
+        return None;
+    }
+    let s_maxage = headers
+        .chunks_exact(2)
+        .filter(|pair| pair[0].eq_ignore_ascii_case("cache-control"))
+        .flat_map(|pair| pair[1].split(','))
+        .find_map(|directive| {
+            let (name, value) = directive.trim().split_once('=')?;
+            if name.eq_ignore_ascii_case("s-maxage") {
+                value.parse::<u64>().ok()
+            } else {
+                None
+            }
+        })?;
+    Some((s_maxage != NEVER_REVALIDATE).then(|| Duration::from_secs(s_maxage)))
+}
+
+/// The data pages are rendered with. It doesn't depend on the request, so
+/// that a render is cached for a generation of the page.
+fn render_data(
+    path: &str,
+    is_data: bool,
+    is_fallback: bool,
+    generation: u64,
+) -> Value<ContentSourceData> {
+    let mut query = Query::default();
+    if is_data {
+        query.insert(
+            "__nextDataReq".to_string(),
+            QueryValue::String("1".to_string()),
+        );
+    }
+    if is_fallback {
+        query.insert(
+            "__nextFallback".to_string(),
+            QueryValue::String("true".to_string()),
+        );
+    }
+    Value::new(ContentSourceData {
+        method: Some("GET".to_string()),
+        url: Some(format!("/{path}")),
+        query: Some(query),
+        headers: Some(BTreeMap::new()),
+        cache_buster: generation,
+        ..Default::default()
+    })
+}
+
+/// Returns the path of the page whose data is requested by the
+/// `_next/data/development/{page}.json` path.
+fn page_path_for_data_path(path: &str) -> String {
+    let page = path
+        .strip_prefix("_next/data/development/")
+        .and_then(|path| path.strip_suffix(".json"))
+        .unwrap_or(path);
+    if page == "index" {
+        String::new()
+    } else {
+        // Pages starting with `index/` are prefixed with another `index/`.
+        page.strip_prefix("index/").unwrap_or(page).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{page_path_for_data_path, revalidate_from_headers, NEVER_REVALIDATE};
+
+    fn headers(cache_control: &str) -> Vec<String> {
+        vec![
+            "content-type".to_string(),
+            "text/html".to_string(),
+            "Cache-Control".to_string(),
+            cache_control.to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_page_path_for_data_path() {
+        assert_eq!(
+            page_path_for_data_path("_next/data/development/index.json"),
+            ""
+        );
+        assert_eq!(
+            page_path_for_data_path("_next/data/development/blog/post.json"),
+            "blog/post"
+        );
+        assert_eq!(
+            page_path_for_data_path("_next/data/development/index/index.json"),
+            "index"
+        );
+    }
+
+    #[test]
+    fn test_revalidate_from_headers() {
+        assert_eq!(
+            revalidate_from_headers(200, &headers("s-maxage=10, stale-while-revalidate")),
+            Some(Some(Duration::from_secs(10)))
+        );
+        assert_eq!(
+            revalidate_from_headers(200, &headers("private, S-MAXAGE=1")),
+            Some(Some(Duration::from_secs(1)))
+        );
+        assert_eq!(
+            revalidate_from_headers(
+                200,
+                &headers(&format!(
+                    "s-maxage={NEVER_REVALIDATE}, stale-while-revalidate"
+                ))
+            ),
+            Some(None)
+        );
+    }
+
+    #[test]
+    fn test_revalidate_from_headers_not_generated() {
+        assert_eq!(revalidate_from_headers(200, &[]), None);
+        assert_eq!(
+            revalidate_from_headers(200, &headers("private, no-cache, no-store")),
+            None
+        );
+        assert_eq!(revalidate_from_headers(500, &headers("s-maxage=10")), None);
+    }
+}
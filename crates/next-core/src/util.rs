@@ -1,8 +1,28 @@
 use anyhow::{anyhow, bail, Result};
 use turbo_tasks::{primitives::StringVc, ValueToString};
+use turbo_tasks_env::CommandLineProcessEnvVc;
+use turbo_tasks_fetch::HttpCacheVc;
 use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_node::path_regex::{PathRegexBuilder, PathRegexVc};
 
+/// Setting this to `1` puts the caches of remote resources, like Google Fonts
+/// and remote images, into offline mode: cached responses are always used and
+/// no requests are sent. `0` and `false` disable it.
+const OFFLINE_ENV_VAR: &str = "NEXT_OFFLINE";
+
+/// The cache of HTTP responses in `.next/cache/{name}` of the project.
+#[turbo_tasks::function]
+pub async fn next_http_cache(project_path: FileSystemPathVc, name: &str) -> Result<HttpCacheVc> {
+    let offline = CommandLineProcessEnvVc::new()
+        .as_process_env()
+        .read(OFFLINE_ENV_VAR)
+        .await?;
+    Ok(HttpCacheVc::new(
+        project_path.join(&format!(".next/cache/{name}")),
+        matches!(offline.as_deref(), Some(value) if value != "0" && value != "false"),
+    ))
+}
+
 /// Converts a filename within the server root into a next pathname.
 #[turbo_tasks::function]
 pub async fn pathname_for_path(
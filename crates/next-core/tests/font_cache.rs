@@ -0,0 +1,153 @@
+#![cfg(test)]
+
+use next_core::next_font_google::fetch::{
+    fetch_font_resource, self_host_font_files, self_hosted_font_path,
+};
+use turbo_tasks::primitives::{OptionStringVc, StringVc};
+use turbo_tasks_fetch::HttpCacheVc;
+use turbo_tasks_fs::{DiskFileSystemVc, FileContent, FileSystemVc};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+#[tokio::test]
+async fn uses_cache_when_offline() {
+    let cache = tempfile::tempdir().unwrap();
+    let server = httpmock::MockServer::start();
+    let url = server.url("/css2?family=Inter");
+    let stylesheet_mock = server.mock(|when, then| {
+        when.path("/css2");
+        then.status(200)
+            .body("@font-face { font-family: 'Inter'; }");
+    });
+
+    {
+        let root = cache.path().to_string_lossy().to_string();
+        let url = url.clone();
+        run! {
+            next_core::register();
+
+            let content = fetch_font_resource(
+                StringVc::cell(url.clone()),
+                OptionStringVc::cell(None),
+                get_cache(&root),
+                true,
+                get_fs(&root).root(),
+            )
+            .await?;
+            match &*content {
+                FileContent::Content(file) => {
+                    assert_eq!(file.content().to_str()?, "@font-face { font-family: 'Inter'; }")
+                }
+                FileContent::NotFound => panic!("expected the fetched stylesheet"),
+            }
+        }
+    }
+    stylesheet_mock.assert();
+    drop(server);
+
+    let root = cache.path().to_string_lossy().to_string();
+    run! {
+        next_core::register();
+
+        let content = fetch_font_resource(
+            StringVc::cell(url.clone()),
+            OptionStringVc::cell(None),
+            get_cache(&root),
+            true,
+            get_fs(&root).root(),
+        )
+        .await?;
+        match &*content {
+            FileContent::Content(file) => {
+                assert_eq!(file.content().to_str()?, "@font-face { font-family: 'Inter'; }")
+            }
+            FileContent::NotFound => panic!("expected the cached stylesheet"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn falls_back_without_cache() {
+    let cache = tempfile::tempdir().unwrap();
+    let server = httpmock::MockServer::start();
+    let url = server.url("/css2?family=Inter");
+    server.mock(|when, then| {
+        when.path("/css2");
+        then.status(500);
+    });
+
+    let root = cache.path().to_string_lossy().to_string();
+    run! {
+        next_core::register();
+
+        let content = fetch_font_resource(
+            StringVc::cell(url.clone()),
+            OptionStringVc::cell(None),
+            get_cache(&root),
+            false,
+            get_fs(&root).root(),
+        )
+        .await?;
+        assert!(matches!(&*content, FileContent::NotFound));
+
+        let strict_content = fetch_font_resource(
+            StringVc::cell(url.clone()),
+            OptionStringVc::cell(None),
+            get_cache(&root),
+            true,
+            get_fs(&root).root(),
+        )
+        .await;
+        assert!(strict_content.is_err());
+    }
+}
+
+#[tokio::test]
+async fn self_hosts_font_files() {
+    let cache = tempfile::tempdir().unwrap();
+    let server = httpmock::MockServer::start();
+    let font_url = server.url("/s/inter/v12/font.woff2");
+    let font_mock = server.mock(|when, then| {
+        when.path("/s/inter/v12/font.woff2");
+        then.status(200).body("font");
+    });
+
+    let root = cache.path().to_string_lossy().to_string();
+    run! {
+        next_core::register();
+
+        let fs = get_fs(&root);
+        let fonts_dir = fs.root().join(".next/cache/fonts/media");
+        let stylesheet = self_host_font_files(
+            StringVc::cell(format!("@font-face {{ src: url({}) format('woff2'); }}", font_url)),
+            get_cache(&root),
+            fonts_dir,
+            fs.root().join("internal/font/google"),
+            true,
+        )
+        .await?;
+
+        let font_path = self_hosted_font_path(fonts_dir, &font_url);
+        let file_name = font_path.await?.file_name().to_owned();
+        assert!(file_name.ends_with(".woff2"));
+        assert_eq!(
+            *stylesheet,
+            format!("@font-face {{ src: url(../../../.next/cache/fonts/media/{}) format('woff2'); }}", file_name)
+        );
+        // Reads don't see the write as the filesystem isn't watched.
+        assert_eq!(
+            std::fs::read_to_string(format!("{root}/.next/cache/fonts/media/{file_name}"))?,
+            "font"
+        );
+    }
+    font_mock.assert();
+}
+
+fn get_fs(root: &str) -> FileSystemVc {
+    DiskFileSystemVc::new("cache".to_owned(), root.to_owned()).into()
+}
+
+fn get_cache(root: &str) -> HttpCacheVc {
+    HttpCacheVc::new(get_fs(root).root().join("fonts"), false)
+}
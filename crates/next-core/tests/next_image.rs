@@ -0,0 +1,131 @@
+#![cfg(test)]
+
+use std::io::Cursor;
+
+use anyhow::Result;
+use image::{ImageOutputFormat, RgbaImage};
+use next_core::{
+    next_config::{ImageConfig, RemotePattern},
+    next_image::NextImageContentSourceVc,
+};
+use turbo_tasks::Value;
+use turbo_tasks_fs::{DiskFileSystemVc, FileSystemVc};
+use turbo_tasks_testing::{register, run};
+use turbopack_dev_server::source::{
+    query::{Query, QueryValue},
+    ContentSourceContent, ContentSourceData, ContentSourceVc, NoContentSourceVc,
+};
+
+register!();
+
+fn png() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    RgbaImage::new(2, 2)
+        .write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png)
+        .unwrap();
+    bytes
+}
+
+/// Requests the image at `url` with a width of 640 and returns the status
+/// and the content type of the response.
+async fn get_image(source: ContentSourceVc, url: &str) -> Result<(u16, Option<String>)> {
+    let mut query = Query::default();
+    query.insert("url".to_string(), QueryValue::String(url.to_string()));
+    query.insert("w".to_string(), QueryValue::String("640".to_string()));
+    let result = source
+        .get(
+            "_next/image",
+            Value::new(ContentSourceData {
+                query: Some(query),
+                headers: Some(Default::default()),
+                ..Default::default()
+            }),
+        )
+        .await?;
+    let ContentSourceContent::HttpProxy(proxy) = &*result.content.await? else {
+        panic!("expected a response for {url}");
+    };
+    let proxy = proxy.await?;
+    let content_type = proxy
+        .headers
+        .chunks(2)
+        .find(|header| header[0] == "Content-Type")
+        .map(|header| header[1].clone());
+    Ok((proxy.status, content_type))
+}
+
+#[tokio::test]
+async fn remote_images() {
+    let server = httpmock::MockServer::start();
+    let other_server = httpmock::MockServer::start();
+    server.mock(|when, then| {
+        when.path("/a.png");
+        then.status(200)
+            .header("Content-Type", "image/png")
+            .body(png());
+    });
+    server.mock(|when, then| {
+        when.path("/redirect");
+        then.status(302).header("Location", "/a.png");
+    });
+    let other_url = other_server.url("/a.png");
+    server.mock(|when, then| {
+        when.path("/redirect-away");
+        then.status(302).header("Location", &other_url);
+    });
+    server.mock(|when, then| {
+        when.path("/text");
+        then.status(200)
+            .header("Content-Type", "text/plain")
+            .body("not an image");
+    });
+    let other_mock = other_server.mock(|when, then| {
+        when.path("/a.png");
+        then.status(200)
+            .header("Content-Type", "image/png")
+            .body(png());
+    });
+
+    let project = tempfile::tempdir().unwrap();
+    let project_dir = project.path().to_string_lossy().to_string();
+    run! {
+        next_core::register();
+
+        // Only the first server is allowed, the other one runs on a different
+        // port of the same host.
+        let image_config = ImageConfig {
+            remote_patterns: vec![RemotePattern {
+                protocol: None,
+                hostname: "127.0.0.1".to_string(),
+                port: Some(server.port().to_string()),
+                pathname: None,
+            }],
+            ..Default::default()
+        };
+        // Remote images are cached in the project's `.next/cache/images`.
+        let project_fs: FileSystemVc =
+            DiskFileSystemVc::new("project".to_string(), project_dir).into();
+        let project_path = project_fs.root();
+        let source: ContentSourceVc = NextImageContentSourceVc::new(
+            NoContentSourceVc::new().into(),
+            image_config.cell(),
+            project_path,
+        )
+        .into();
+
+        assert_eq!(
+            get_image(source, &server.url("/a.png")).await?,
+            (200, Some("image/png".to_string()))
+        );
+        // Redirects to allowed urls are followed.
+        assert_eq!(
+            get_image(source, &server.url("/redirect")).await?,
+            (200, Some("image/png".to_string()))
+        );
+        // Redirects to other urls are not.
+        assert_eq!(get_image(source, &server.url("/redirect-away")).await?, (502, None));
+        assert_eq!(get_image(source, &other_url).await?, (400, None));
+        assert_eq!(get_image(source, &server.url("/text")).await?, (502, None));
+    }
+    other_mock.assert_hits(0);
+}
@@ -42,6 +42,29 @@ pub struct DevServerOptions {
     #[cfg_attr(feature = "serializable", serde(default = "default_host"))]
     pub hostname: IpAddr,
 
+    /// Serve the application over HTTPS. Unless a certificate is provided, a
+    /// self-signed certificate is generated and cached in
+    /// `.next/certificates`.
+    #[cfg_attr(feature = "cli", clap(long))]
+    #[cfg_attr(feature = "serializable", serde(default))]
+    pub experimental_https: bool,
+
+    /// Path to a PEM-encoded certificate to use with `--experimental-https`.
+    #[cfg_attr(
+        feature = "cli",
+        clap(long, value_parser, requires = "experimental_https_key")
+    )]
+    #[cfg_attr(feature = "serializable", serde(default))]
+    pub experimental_https_cert: Option<PathBuf>,
+
+    /// Path to a PEM-encoded private key to use with `--experimental-https`.
+    #[cfg_attr(
+        feature = "cli",
+        clap(long, value_parser, requires = "experimental_https_cert")
+    )]
+    #[cfg_attr(feature = "serializable", serde(default))]
+    pub experimental_https_key: Option<PathBuf>,
+
     /// Compile all, instead of only compiling referenced assets when their
     /// parent asset is requested
     #[cfg_attr(feature = "cli", clap(long))]
@@ -18,8 +18,8 @@ use std::{
 use anyhow::{anyhow, Context, Result};
 use devserver_options::DevServerOptions;
 use next_core::{
-    create_app_source, create_page_source, create_web_entry_source, env::load_env,
-    manifest::DevManifestContentSource, next_config::load_next_config,
+    create_app_source, create_middleware_source, create_page_source, create_web_entry_source,
+    env::load_env, manifest::DevManifestContentSource, next_config::load_next_config,
     next_image::NextImageContentSourceVc, source_map::NextSourceMapTraceContentSourceVc,
 };
 use owo_colors::OwoColorize;
@@ -36,13 +36,15 @@ use turbopack_core::{
     issue::IssueSeverity,
     resolve::{parse::RequestVc, pattern::QueryMapVc},
 };
+#[cfg(feature = "tls")]
+use turbopack_dev_server::tls::{TlsCertificate, TlsConfig};
 use turbopack_dev_server::{
     fs::DevServerFileSystemVc,
     introspect::IntrospectionSource,
     source::{
-        combined::CombinedContentSourceVc, router::RouterContentSource,
-        source_maps::SourceMapContentSourceVc, static_assets::StaticAssetsContentSourceVc,
-        ContentSourceVc,
+        combined::CombinedContentSourceVc, prefixed::PrefixedContentSource,
+        router::RouterContentSource, source_maps::SourceMapContentSourceVc,
+        static_assets::StaticAssetsContentSourceVc, ContentSourceVc,
     },
     DevServer, DevServerBuilder,
 };
@@ -67,6 +69,8 @@ pub struct NextDevServerBuilder {
     show_all: bool,
     log_detail: bool,
     allow_retry: bool,
+    #[cfg(feature = "tls")]
+    tls: Option<TlsConfig>,
 }
 
 impl NextDevServerBuilder {
@@ -90,6 +94,8 @@ impl NextDevServerBuilder {
             show_all: false,
             log_detail: false,
             allow_retry: false,
+            #[cfg(feature = "tls")]
+            tls: None,
         }
     }
 
@@ -138,6 +144,12 @@ impl NextDevServerBuilder {
         self
     }
 
+    #[cfg(feature = "tls")]
+    pub fn tls(mut self, tls: TlsConfig) -> NextDevServerBuilder {
+        self.tls = Some(tls);
+        self
+    }
+
     /// Attempts to find an open port to bind.
     fn find_port(&self, host: IpAddr, port: u16, max_attempts: u16) -> Result<DevServerBuilder> {
         // max_attempts of 1 means we loop 0 times.
@@ -183,6 +195,13 @@ impl NextDevServerBuilder {
         let host = self.hostname.context("hostname must be set")?;
 
         let server = self.find_port(host, port, 10)?;
+        #[cfg(feature = "tls")]
+        let (server, https) = match &self.tls {
+            Some(tls) => (server.tls(tls)?, true),
+            None => (server, false),
+        };
+        #[cfg(not(feature = "tls"))]
+        let https = false;
 
         let turbo_tasks = self.turbo_tasks;
         let project_dir = self.project_dir;
@@ -212,6 +231,7 @@ impl NextDevServerBuilder {
                 console_ui.clone().into(),
                 browserslist_query.clone(),
                 server_addr.clone().into(),
+                https,
             )
         };
 
@@ -258,6 +278,7 @@ async fn source(
     console_ui: TransientInstance<ConsoleUi>,
     browserslist_query: String,
     server_addr: TransientInstance<SocketAddr>,
+    https: bool,
 ) -> Result<ContentSourceVc> {
     let console_ui = (*console_ui).clone().cell();
     let output_fs = output_fs(&project_dir, console_ui);
@@ -276,7 +297,12 @@ async fn source(
     let next_config = load_next_config(execution_context.join("next_config"));
 
     let output_root = output_fs.root().join(".next/server");
-    let server_addr = ServerAddr::new(*server_addr).cell();
+    let server_addr = if https {
+        ServerAddr::new_https(*server_addr)
+    } else {
+        ServerAddr::new(*server_addr)
+    }
+    .cell();
 
     let dev_server_fs = DevServerFileSystemVc::new().as_file_system();
     let dev_server_root = dev_server_fs.root();
@@ -349,8 +375,27 @@ async fn source(
     let source_map_trace = NextSourceMapTraceContentSourceVc::new(main_source).into();
     let img_source = NextImageContentSourceVc::new(
         CombinedContentSourceVc::new(vec![static_source, page_source]).into(),
+        next_config.image_config(),
+        project_path,
     )
     .into();
+    let middleware_source = create_middleware_source(
+        project_path,
+        execution_context,
+        output_root.join("middleware"),
+        env,
+        next_config,
+        server_addr,
+        main_source,
+    );
+    // Like Next.js, the middleware doesn't run for static chunks and HMR
+    // updates, which are served from `_next/static`.
+    let next_static_source = PrefixedContentSource {
+        prefix: "_next/static/".to_string(),
+        source: main_source,
+    }
+    .cell()
+    .into();
     let source = RouterContentSource {
         routes: vec![
             ("__turbopack__/".to_string(), introspect),
@@ -362,8 +407,9 @@ async fn source(
             // TODO: Load path from next.config.js
             ("_next/image".to_string(), img_source),
             ("__turbopack_sourcemap__/".to_string(), source_maps),
+            ("_next/static/".to_string(), next_static_source),
         ],
-        fallback: main_source,
+        fallback: middleware_source,
     }
     .cell()
     .into();
@@ -418,6 +464,15 @@ pub async fn start_server(options: &DevServerOptions) -> Result<()> {
 
     let tt_clone = tt.clone();
 
+    #[cfg(feature = "tls")]
+    let tls = options
+        .experimental_https
+        .then(|| tls_config(options, std::path::Path::new(&dir)));
+    #[cfg(not(feature = "tls"))]
+    if options.experimental_https {
+        anyhow::bail!("HTTPS is not supported in this build of next-dev");
+    }
+
     #[allow(unused_mut)]
     let mut server = NextDevServerBuilder::new(tt, dir, root_dir)
         .entry_request(EntryRequest::Relative("src/index".into()))
@@ -437,10 +492,20 @@ pub async fn start_server(options: &DevServerOptions) -> Result<()> {
         server = server.allow_retry(options.allow_retry);
     }
 
+    #[cfg(feature = "tls")]
+    if let Some(tls) = tls {
+        server = server.tls(tls);
+    }
+
     let server = server.build().await?;
 
     {
-        let index_uri = ServerAddr::new(server.addr).to_string()?;
+        let index_uri = if server.https {
+            ServerAddr::new_https(server.addr)
+        } else {
+            ServerAddr::new(server.addr)
+        }
+        .to_string()?;
         println!(
             "{} - started server on {}:{}, url: {}",
             "ready".green(),
@@ -517,6 +582,30 @@ pub async fn start_server(options: &DevServerOptions) -> Result<()> {
     Ok(())
 }
 
+#[cfg(feature = "tls")]
+fn tls_config(options: &DevServerOptions, project_dir: &std::path::Path) -> TlsConfig {
+    let certificate = match (
+        &options.experimental_https_cert,
+        &options.experimental_https_key,
+    ) {
+        (Some(cert), Some(key)) => TlsCertificate::Files {
+            cert: cert.clone(),
+            key: key.clone(),
+        },
+        _ => {
+            let mut hostnames = vec!["localhost".to_string()];
+            if !options.hostname.is_unspecified() {
+                hostnames.push(options.hostname.to_string());
+            }
+            TlsCertificate::SelfSigned {
+                cache_dir: project_dir.join(".next/certificates"),
+                hostnames,
+            }
+        }
+    };
+    TlsConfig::new(certificate)
+}
+
 #[cfg(feature = "profile")]
 // When profiling, exits the process when no new updates have been received for
 // a given timeout and there are no more tasks in progress.
@@ -0,0 +1,5 @@
+export function GET(request, { params }) {
+  return new Response(JSON.stringify(params), {
+    headers: { "Content-Type": "application/json" },
+  });
+}
@@ -0,0 +1,9 @@
+export function GET() {
+  return new Response(JSON.stringify({ hello: "world" }), {
+    headers: { "Content-Type": "application/json" },
+  });
+}
+
+export async function POST(request) {
+  return new Response(await request.text(), { status: 201 });
+}
@@ -0,0 +1,3 @@
+export default function Team() {
+  return <div>team slot</div>;
+}
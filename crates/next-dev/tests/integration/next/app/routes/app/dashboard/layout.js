@@ -0,0 +1,8 @@
+export default function DashboardLayout({ children, team }) {
+  return (
+    <div>
+      {children}
+      {team}
+    </div>
+  );
+}
@@ -0,0 +1,3 @@
+export default function Dashboard() {
+  return <div>dashboard page</div>;
+}
@@ -0,0 +1,3 @@
+export default function InterceptedPost() {
+  return <div>intercepted post</div>;
+}
@@ -0,0 +1,3 @@
+export default function InterceptedPhoto({ params }) {
+  return <div>{`intercepted photo ${params.id}`}</div>;
+}
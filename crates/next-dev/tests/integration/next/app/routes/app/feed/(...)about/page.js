@@ -0,0 +1,3 @@
+export default function InterceptedAbout() {
+  return <div>intercepted about</div>;
+}
@@ -0,0 +1,3 @@
+export default function Feed() {
+  return <div>feed</div>;
+}
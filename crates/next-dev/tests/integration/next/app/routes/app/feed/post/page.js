@@ -0,0 +1,3 @@
+export default function Post() {
+  return <div>post</div>;
+}
@@ -0,0 +1,3 @@
+export default function Optional({ params }) {
+  return <div id="slug">{(params.slug ?? []).join(",")}</div>;
+}
@@ -0,0 +1,3 @@
+export default function Photo({ params }) {
+  return <div>{`photo ${params.id}`}</div>;
+}
@@ -0,0 +1,83 @@
+import { useEffect } from "react";
+
+import { Deferred } from "@turbo/pack-test-harness/deferred";
+
+let testResult = new Deferred();
+
+export default function Home() {
+  useEffect(() => {
+    // Only run on client
+    import("@turbo/pack-test-harness").then(runTests);
+  });
+
+  return <div>app routes</div>;
+}
+
+globalThis.waitForTests = function () {
+  return testResult.promise;
+};
+
+async function getHtml(url, headers) {
+  const res = await fetch(url, { headers });
+  expect(res.status).toBe(200);
+  return res.text();
+}
+
+function runTests() {
+  it("it should run route handlers", async function () {
+    const res = await fetch("/api/hello");
+    expect(await res.json()).toEqual({ hello: "world" });
+
+    const post = await fetch("/api/hello", { method: "POST", body: "ping" });
+    expect(post.status).toBe(201);
+    expect(await post.text()).toBe("ping");
+
+    const put = await fetch("/api/hello", { method: "PUT" });
+    expect(put.status).toBe(405);
+    expect(put.headers.get("allow")).toBe("GET, POST");
+  });
+
+  it("it should pass catch all params as arrays", async function () {
+    const res = await fetch("/api/echo/a/b");
+    expect(await res.json()).toEqual({ slug: ["a", "b"] });
+  });
+
+  it("it should match optional catch all params", async function () {
+    expect(await getHtml("/optional")).toContain('<div id="slug"></div>');
+    expect(await getHtml("/optional/a/b")).toContain(
+      '<div id="slug">a,b</div>'
+    );
+  });
+
+  it("it should pass catch all params of pages as arrays", async function () {
+    const res = await fetch("/api/pages/a/b");
+    expect(await res.json()).toEqual({ slug: ["a", "b"] });
+  });
+
+  it("it should render parallel routes", async function () {
+    const html = await getHtml("/dashboard");
+    expect(html).toContain("dashboard page");
+    expect(html).toContain("team slot");
+  });
+
+  it("it should not add route groups to the url", async function () {
+    expect(await getHtml("/pricing")).toContain("pricing");
+  });
+
+  it("it should intercept client side navigations", async function () {
+    const rsc = { RSC: "1" };
+    expect(await getHtml("/photo/1", rsc)).toContain("intercepted photo 1");
+    expect(await getHtml("/feed/post", rsc)).toContain("intercepted post");
+    expect(await getHtml("/about", rsc)).toContain("intercepted about");
+  });
+
+  it("it should render intercepted routes when loaded directly", async function () {
+    const photo = await getHtml("/photo/1");
+    expect(photo).toContain("photo 1");
+    expect(photo).not.toContain("intercepted");
+    expect(await getHtml("/feed/post")).not.toContain("intercepted");
+    expect(await getHtml("/about")).not.toContain("intercepted");
+  });
+
+  testResult.resolve(__jest__.run());
+}
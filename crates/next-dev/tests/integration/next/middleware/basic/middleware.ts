@@ -0,0 +1,23 @@
+import { NextResponse } from "next/server";
+import type { NextRequest } from "next/server";
+
+export function middleware(request: NextRequest) {
+  if (request.nextUrl.pathname === "/rewrite-me") {
+    return NextResponse.rewrite(new URL("/api/echo?via=rewrite", request.url));
+  }
+  if (request.nextUrl.pathname === "/redirect-me") {
+    return NextResponse.redirect(
+      new URL("/api/echo?via=redirect", request.url)
+    );
+  }
+
+  const headers = new Headers(request.headers);
+  headers.set("x-from-middleware", "hello");
+  const response = NextResponse.next({ request: { headers } });
+  response.headers.set("x-custom", "passed");
+  return response;
+}
+
+export const config = {
+  matcher: ["/api/:path*", "/static", "/rewrite-me", "/redirect-me"],
+};
@@ -0,0 +1,8 @@
+import type { NextApiRequest, NextApiResponse } from "next";
+
+export default function handler(req: NextApiRequest, res: NextApiResponse) {
+  res.status(200).json({
+    query: req.query,
+    header: req.headers["x-from-middleware"] ?? null,
+  });
+}
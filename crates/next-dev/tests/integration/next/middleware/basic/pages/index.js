@@ -0,0 +1,56 @@
+import { useEffect } from "react";
+
+import { Deferred } from "@turbo/pack-test-harness/deferred";
+
+let testResult = new Deferred();
+
+export default function Home() {
+  useEffect(() => {
+    // Only run on client
+    import("@turbo/pack-test-harness").then(runTests);
+  });
+
+  return <div>middleware</div>;
+}
+
+globalThis.waitForTests = function () {
+  return testResult.promise;
+};
+
+function runTests() {
+  it("it should pass request headers set by middleware", async function () {
+    const res = await fetch("/api/echo");
+    expect(res.headers.get("x-custom")).toBe("passed");
+    expect(await res.json()).toEqual({ query: {}, header: "hello" });
+  });
+
+  it("it should add response headers to static pages", async function () {
+    const res = await fetch("/static");
+    expect(res.status).toBe(200);
+    expect(res.headers.get("x-custom")).toBe("passed");
+    expect(res.headers.get("content-type")).toMatch(/^text\/html/);
+    expect(await res.text()).toContain("static");
+  });
+
+  it("it should rewrite requests", async function () {
+    const res = await fetch("/rewrite-me");
+    expect(res.redirected).toBe(false);
+    expect(await res.json()).toEqual({
+      query: { via: "rewrite" },
+      header: null,
+    });
+  });
+
+  it("it should redirect requests", async function () {
+    const res = await fetch("/redirect-me");
+    expect(res.redirected).toBe(true);
+    expect(res.url).toMatch(/\/api\/echo\?via=redirect$/);
+  });
+
+  it("it should not run the middleware for unmatched paths", async function () {
+    const res = await fetch("/");
+    expect(res.headers.get("x-custom")).toBe(null);
+  });
+
+  testResult.resolve(__jest__.run());
+}
@@ -0,0 +1,3 @@
+export default function Static() {
+  return <div>static</div>;
+}
@@ -0,0 +1,14 @@
+export default function Blocking({ id }) {
+  return <div>blocking {id}</div>;
+}
+
+export function getStaticPaths() {
+  return { paths: [], fallback: "blocking" };
+}
+
+export function getStaticProps({ params }) {
+  if (params.id === "missing") {
+    return { notFound: true };
+  }
+  return { props: { id: params.id } };
+}
@@ -0,0 +1,58 @@
+import { useEffect } from "react";
+
+import { Deferred } from "@turbo/pack-test-harness/deferred";
+
+let testResult = new Deferred();
+
+export default function Home() {
+  useEffect(() => {
+    // Only run on client
+    import("@turbo/pack-test-harness").then(runTests);
+  });
+
+  return <div>static generation</div>;
+}
+
+globalThis.waitForTests = function () {
+  return testResult.promise;
+};
+
+function runTests() {
+  it("it should serve paths returned by getStaticPaths", async function () {
+    for (const id of ["first", "second"]) {
+      const res = await fetch(`/posts/${id}`);
+      expect(res.status).toBe(200);
+      expect(res.headers.get("cache-control")).toBe(
+        "s-maxage=60, stale-while-revalidate"
+      );
+      expect(await res.text()).toContain(`post ${id} at`);
+    }
+  });
+
+  it("it should serve the same generation until it's revalidated", async function () {
+    const first = await fetch("/_next/data/development/posts/first.json");
+    const { pageProps } = await first.json();
+    expect(pageProps.id).toBe("first");
+
+    const second = await fetch("/_next/data/development/posts/first.json");
+    expect((await second.json()).pageProps).toEqual(pageProps);
+  });
+
+  it("it should not find other paths without fallback", async function () {
+    const res = await fetch("/posts/third");
+    expect(res.status).toBe(404);
+  });
+
+  it("it should generate other paths with a blocking fallback", async function () {
+    const res = await fetch("/blocking/other");
+    expect(res.status).toBe(200);
+    expect(await res.text()).toContain("blocking other");
+  });
+
+  it("it should respond with 404 when getStaticProps returns notFound", async function () {
+    const res = await fetch("/blocking/missing");
+    expect(res.status).toBe(404);
+  });
+
+  testResult.resolve(__jest__.run());
+}
@@ -0,0 +1,21 @@
+export default function Post({ id, generatedAt }) {
+  return (
+    <div>
+      post {id} at {generatedAt}
+    </div>
+  );
+}
+
+export function getStaticPaths() {
+  return {
+    paths: [{ params: { id: "first" } }, "/posts/second"],
+    fallback: false,
+  };
+}
+
+export function getStaticProps({ params }) {
+  return {
+    props: { id: params.id, generatedAt: Date.now() },
+    revalidate: 60,
+  };
+}
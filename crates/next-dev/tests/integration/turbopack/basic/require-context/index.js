@@ -0,0 +1,28 @@
+it("should require files of a directory recursively", () => {
+  const context = require.context("./context", true, /\.js$/);
+  expect(context.keys().sort()).toEqual(["./a.js", "./nested/b.js"]);
+  expect(context("./a.js")).toBe("a");
+  expect(context("./nested/b.js")).toBe("b");
+});
+
+it("should only require files of the directory itself when not recursive", () => {
+  const context = require.context("./context", false, /\.js$/);
+  expect(context.keys()).toEqual(["./a.js"]);
+});
+
+it("should filter the files by the regular expression", () => {
+  const context = require.context("./context", true, /\.json$/);
+  expect(context.keys()).toEqual(["./data.json"]);
+  expect(context("./data.json")).toEqual({ name: "data" });
+});
+
+it("should resolve keys of the context only", () => {
+  const context = require.context("./context", true, /\.js$/);
+  expect(context.resolve("./a.js")).toBe("./a.js");
+  expect(() => context.resolve("./missing.js")).toThrow(
+    "Cannot find module './missing.js'"
+  );
+  expect(() => context("./missing.js")).toThrow(
+    "Cannot find module './missing.js'"
+  );
+});
@@ -2,7 +2,7 @@ use std::env;
 
 use indexmap::IndexMap;
 
-use crate::{EnvMapVc, ProcessEnv, ProcessEnvVc, GLOBAL_ENV_LOCK};
+use crate::{EnvMapVc, ProcessEnv, ProcessEnvVc};
 
 /// Load the environment variables defined via command line.
 #[turbo_tasks::value]
@@ -18,7 +18,6 @@ impl CommandLineProcessEnvVc {
 
 /// Clones the current env vars into a IndexMap.
 fn env_snapshot() -> IndexMap<String, String> {
-    let _lock = GLOBAL_ENV_LOCK.lock().unwrap();
     env::vars().collect::<IndexMap<_, _>>()
 }
 
@@ -0,0 +1,412 @@
+use std::{fmt, iter::Peekable, str::Chars};
+
+use indexmap::IndexMap;
+
+/// An error in the syntax of a dotenv file, with the 1-based line and column
+/// where it occurred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DotenvParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for DotenvParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for DotenvParseError {}
+
+/// Parses the contents of a dotenv file and adds the variables it defines to
+/// `env`.
+///
+/// Variables which are already defined in `env` are not overridden. `${VAR}`
+/// and `$VAR` references in unquoted and double quoted values are expanded
+/// from `env`, including the variables defined earlier in the file. Undefined
+/// references expand to an empty string.
+pub fn parse_dotenv(
+    source: &str,
+    env: &mut IndexMap<String, String>,
+) -> Result<(), DotenvParseError> {
+    let mut parser = Parser {
+        chars: source.chars().peekable(),
+        line: 1,
+        column: 1,
+    };
+    while let Some((key, value)) = parser.next_variable(env)? {
+        env.entry(key).or_insert(value);
+    }
+    Ok(())
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, message: impl Into<String>) -> DotenvParseError {
+        DotenvParseError {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+
+    /// Skips spaces and tabs, but not line breaks.
+    fn skip_inline_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.bump();
+        }
+    }
+
+    fn skip_line(&mut self) {
+        while let Some(c) = self.bump() {
+            if c == '\n' {
+                break;
+            }
+        }
+    }
+
+    /// Consumes the rest of the line after a value, which may only contain
+    /// whitespace and a comment.
+    fn end_line(&mut self) -> Result<(), DotenvParseError> {
+        self.skip_inline_whitespace();
+        match self.peek() {
+            None | Some('#') => self.skip_line(),
+            Some('\r' | '\n') => {
+                self.bump();
+                if self.peek() == Some('\n') {
+                    self.bump();
+                }
+            }
+            Some(c) => return Err(self.error(format!("unexpected character '{c}' after value"))),
+        }
+        Ok(())
+    }
+
+    fn next_variable(
+        &mut self,
+        env: &IndexMap<String, String>,
+    ) -> Result<Option<(String, String)>, DotenvParseError> {
+        loop {
+            self.skip_inline_whitespace();
+            match self.peek() {
+                None => return Ok(None),
+                Some('#' | '\r' | '\n') => self.skip_line(),
+                Some(_) => break,
+            }
+        }
+
+        let mut key = self.key()?;
+        if key == "export" && matches!(self.peek(), Some(' ' | '\t')) {
+            self.skip_inline_whitespace();
+            key = self.key()?;
+        }
+
+        self.skip_inline_whitespace();
+        if self.peek() != Some('=') {
+            return Err(self.error(format!("expected '=' after the variable name {key}")));
+        }
+        self.bump();
+        self.skip_inline_whitespace();
+
+        let value = match self.peek() {
+            Some('\'') => self.single_quoted()?,
+            Some('"') => self.double_quoted(env)?,
+            _ => self.unquoted(env)?,
+        };
+        self.end_line()?;
+        Ok(Some((key, value)))
+    }
+
+    fn key(&mut self) -> Result<String, DotenvParseError> {
+        let mut key = String::new();
+        match self.peek() {
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+            Some(c) => return Err(self.error(format!("invalid character '{c}' in variable name"))),
+            None => return Err(self.error("expected a variable name")),
+        }
+        while let Some(c) = self.peek() {
+            if !(c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+                break;
+            }
+            key.push(c);
+            self.bump();
+        }
+        Ok(key)
+    }
+
+    /// A value in single quotes, taken literally. It can span multiple lines.
+    fn single_quoted(&mut self) -> Result<String, DotenvParseError> {
+        let (line, column) = (self.line, self.column);
+        self.bump();
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                Some('\'') => return Ok(value),
+                Some(c) => value.push(c),
+                None => {
+                    return Err(DotenvParseError {
+                        line,
+                        column,
+                        message: "unterminated single quoted value".to_string(),
+                    })
+                }
+            }
+        }
+    }
+
+    /// A value in double quotes, with escape sequences and variable
+    /// references. It can span multiple lines.
+    fn double_quoted(
+        &mut self,
+        env: &IndexMap<String, String>,
+    ) -> Result<String, DotenvParseError> {
+        let (line, column) = (self.line, self.column);
+        self.bump();
+        let mut value = String::new();
+        loop {
+            match self.peek() {
+                Some('"') => {
+                    self.bump();
+                    return Ok(value);
+                }
+                Some('\\') => {
+                    self.bump();
+                    match self.bump() {
+                        Some('n') => value.push('\n'),
+                        Some('r') => value.push('\r'),
+                        Some('t') => value.push('\t'),
+                        Some(c @ ('\\' | '"' | '\'' | '$')) => value.push(c),
+                        Some('\n') => {}
+                        Some(c) => {
+                            value.push('\\');
+                            value.push(c);
+                        }
+                        None => break,
+                    }
+                }
+                Some('$') => self.reference(env, &mut value)?,
+                Some(c) => {
+                    self.bump();
+                    value.push(c);
+                }
+                None => break,
+            }
+        }
+        Err(DotenvParseError {
+            line,
+            column,
+            message: "unterminated double quoted value".to_string(),
+        })
+    }
+
+    /// A value without quotes, which ends at the end of the line or at a
+    /// comment. `\` escapes the next character.
+    fn unquoted(&mut self, env: &IndexMap<String, String>) -> Result<String, DotenvParseError> {
+        let mut value = String::new();
+        // Trailing whitespace is trimmed, but escaped whitespace is kept.
+        let mut len = 0;
+        // A `#` starts a comment at the start of the value or after whitespace.
+        let mut comment_allowed = true;
+        while let Some(c) = self.peek() {
+            match c {
+                '\r' | '\n' => break,
+                '#' if comment_allowed => break,
+                '\\' => {
+                    self.bump();
+                    match self.bump() {
+                        Some('\r' | '\n') | None => {
+                            return Err(self.error("expected a character after '\\'"))
+                        }
+                        Some(c) => value.push(c),
+                    }
+                    len = value.len();
+                }
+                '$' => {
+                    self.reference(env, &mut value)?;
+                    len = value.len();
+                }
+                c => {
+                    self.bump();
+                    value.push(c);
+                    if !matches!(c, ' ' | '\t') {
+                        len = value.len();
+                    }
+                }
+            }
+            comment_allowed = value.len() != len;
+        }
+        value.truncate(len);
+        Ok(value)
+    }
+
+    /// Expands a `$VAR` or `${VAR}` reference into `value`. A `$` which isn't
+    /// followed by a variable name is kept as is.
+    fn reference(
+        &mut self,
+        env: &IndexMap<String, String>,
+        value: &mut String,
+    ) -> Result<(), DotenvParseError> {
+        let (line, column) = (self.line, self.column);
+        self.bump();
+        let braced = self.peek() == Some('{');
+        if braced {
+            self.bump();
+        }
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if !(c.is_ascii_alphanumeric() || c == '_') {
+                break;
+            }
+            name.push(c);
+            self.bump();
+        }
+        if braced {
+            if self.peek() != Some('}') || name.is_empty() {
+                return Err(DotenvParseError {
+                    line,
+                    column,
+                    message: "invalid variable reference, expected ${NAME}".to_string(),
+                });
+            }
+            self.bump();
+        } else if name.is_empty() {
+            value.push('$');
+            return Ok(());
+        }
+        if let Some(v) = env.get(&name) {
+            value.push_str(v);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::{indexmap, IndexMap};
+
+    use super::{parse_dotenv, DotenvParseError};
+
+    fn parse(source: &str) -> IndexMap<String, String> {
+        let mut env = IndexMap::new();
+        parse_dotenv(source, &mut env).unwrap();
+        env
+    }
+
+    fn parse_error(source: &str) -> DotenvParseError {
+        parse_dotenv(source, &mut IndexMap::new()).unwrap_err()
+    }
+
+    #[test]
+    fn unquoted() {
+        assert_eq!(
+            parse("# comment\n\nA=1\n  B = two words  \nexport C=3 # comment\nD=a#b\nE=\n"),
+            indexmap! {
+                "A".to_string() => "1".to_string(),
+                "B".to_string() => "two words".to_string(),
+                "C".to_string() => "3".to_string(),
+                "D".to_string() => "a#b".to_string(),
+                "E".to_string() => "".to_string(),
+            }
+        );
+        assert_eq!(parse("A=a\\ \\#b")["A"], "a #b");
+        assert_eq!(parse("export=1")["export"], "1");
+    }
+
+    #[test]
+    fn quoted() {
+        assert_eq!(
+            parse("A='${B}\\n'\nB=\"line\\n\\\"quoted\\\"\\t\\$B\" # comment\r\n"),
+            indexmap! {
+                "A".to_string() => "${B}\\n".to_string(),
+                "B".to_string() => "line\n\"quoted\"\t$B".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn multiline() {
+        assert_eq!(
+            parse("KEY=\"-----BEGIN-----\nabc\n-----END-----\"\nA='1\n2'\nB=3")
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "KEY".to_string(),
+                    "-----BEGIN-----\nabc\n-----END-----".to_string()
+                ),
+                ("A".to_string(), "1\n2".to_string()),
+                ("B".to_string(), "3".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn expansion() {
+        let mut env = indexmap! {
+            "HOST".to_string() => "localhost".to_string(),
+            "PORT".to_string() => "3000".to_string(),
+        };
+        parse_dotenv(
+            "PORT=4000\nPATH_PREFIX=/api\nURL=http://${HOST}:$PORT$PATH_PREFIX\nQUOTED=\"$HOST \
+             ${MISSING}$\"\n",
+            &mut env,
+        )
+        .unwrap();
+        assert_eq!(env["PORT"], "3000");
+        assert_eq!(env["URL"], "http://localhost:3000/api");
+        assert_eq!(env["QUOTED"], "localhost $");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse_error("A=1\nB=\"abc\n\ndef"),
+            DotenvParseError {
+                line: 2,
+                column: 3,
+                message: "unterminated double quoted value".to_string(),
+            }
+        );
+        assert_eq!(
+            parse_error("A='1' 2").to_string(),
+            "line 1, column 7: unexpected character '2' after value"
+        );
+        assert_eq!(
+            parse_error("\n  A 1").to_string(),
+            "line 2, column 5: expected '=' after the variable name A"
+        );
+        assert_eq!(
+            parse_error("1A=1").to_string(),
+            "line 1, column 1: invalid character '1' in variable name"
+        );
+        assert_eq!(
+            parse_error("A=${B").to_string(),
+            "line 1, column 3: invalid variable reference, expected ${NAME}"
+        );
+    }
+}
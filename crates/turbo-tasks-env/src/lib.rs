@@ -3,16 +3,20 @@
 mod command_line;
 mod custom;
 mod dotenv;
+mod dotenv_parser;
 mod filter;
 
-use std::{env, sync::Mutex};
+use std::env;
 
 use anyhow::Result;
 use indexmap::IndexMap;
 use turbo_tasks::primitives::OptionStringVc;
 
 pub use self::{
-    command_line::CommandLineProcessEnvVc, custom::CustomProcessEnvVc, dotenv::DotenvProcessEnvVc,
+    command_line::CommandLineProcessEnvVc,
+    custom::CustomProcessEnvVc,
+    dotenv::DotenvProcessEnvVc,
+    dotenv_parser::{parse_dotenv, DotenvParseError},
     filter::FilterProcessEnvVc,
 };
 
@@ -63,8 +67,6 @@ async fn to_uppercase_map(map: EnvMapVc) -> Result<EnvMapVc> {
     Ok(EnvMapVc::cell(new))
 }
 
-pub static GLOBAL_ENV_LOCK: Mutex<()> = Mutex::new(());
-
 pub fn register() {
     turbo_tasks::register();
     include!(concat!(env!("OUT_DIR"), "/register.rs"));
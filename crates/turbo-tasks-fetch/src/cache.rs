@@ -0,0 +1,227 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use turbo_tasks_fs::{to_sys_path, File, FileSystemPathVc};
+use turbo_tasks_hash::hash_xxh3_hash64;
+
+use crate::{
+    FetchError, HttpMethod, HttpRequest, HttpResponse, HttpResponseBody, HttpResponseBodyVc,
+};
+
+/// The headers of a cached response which are replaced by the headers of a
+/// `304 Not Modified` response.
+const REVALIDATED_HEADERS: &[&str] = &["cache-control", "date", "etag", "expires", "last-modified"];
+
+/// An on-disk cache of HTTP responses, which keeps them across restarts.
+///
+/// A fresh response according to its `Cache-Control` header is used without a
+/// request. A stale response is revalidated with its `ETag` or
+/// `Last-Modified` header, and still used when the request fails. In offline
+/// mode, cached responses are always used and requests are never sent.
+///
+/// `dir` must be on a [DiskFileSystem](turbo_tasks_fs::DiskFileSystem),
+/// otherwise nothing is cached.
+#[turbo_tasks::value(shared)]
+pub struct HttpCache {
+    pub dir: FileSystemPathVc,
+    pub offline: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl HttpCacheVc {
+    #[turbo_tasks::function]
+    pub fn new(dir: FileSystemPathVc, offline: bool) -> Self {
+        Self::cell(HttpCache { dir, offline })
+    }
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionHttpCache(Option<HttpCacheVc>);
+
+/// The metadata of a cached response. The body is stored next to it.
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    status: u16,
+    headers: Vec<(String, String)>,
+    /// When the response was received or last revalidated, in seconds since
+    /// the unix epoch.
+    stored_at: u64,
+}
+
+impl CacheEntry {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn is_fresh(&self, now: u64) -> bool {
+        let Some(cache_control) = self.header("cache-control") else {
+            return false;
+        };
+        if has_directive(cache_control, "no-cache") {
+            return false;
+        }
+        directive_value(cache_control, "max-age").map_or(false, |max_age| {
+            now < self.stored_at.saturating_add(max_age)
+        })
+    }
+
+    fn to_response(&self, body: Vec<u8>) -> HttpResponse {
+        HttpResponse {
+            status: self.status,
+            headers: self.headers.clone(),
+            body: HttpResponseBodyVc::cell(HttpResponseBody(body)),
+        }
+    }
+}
+
+pub(crate) async fn fetch_cached(
+    request: &HttpRequest,
+    cache: &HttpCache,
+) -> Result<Result<HttpResponse, FetchError>> {
+    if !matches!(request.method, HttpMethod::Get | HttpMethod::Head) {
+        if cache.offline {
+            return Ok(Err(FetchError::offline(&request.url)));
+        }
+        return crate::send(request, &[]).await;
+    }
+
+    let key = format!("{:016x}", hash_xxh3_hash64(cache_key(request)));
+    let entry_path = cache.dir.join(&format!("{key}.json"));
+    let body_path = cache.dir.join(&format!("{key}.body"));
+    let now = unix_now();
+
+    let cached = read_entry(entry_path, body_path).await?;
+    match &cached {
+        Some((entry, body)) if cache.offline || entry.is_fresh(now) => {
+            return Ok(Ok(entry.to_response(body.clone())));
+        }
+        None if cache.offline => return Ok(Err(FetchError::offline(&request.url))),
+        _ => {}
+    }
+
+    let mut validators = vec![];
+    if let Some((entry, _)) = &cached {
+        if let Some(etag) = entry.header("etag") {
+            validators.push(("If-None-Match".to_owned(), etag.to_owned()));
+        }
+        if let Some(last_modified) = entry.header("last-modified") {
+            validators.push(("If-Modified-Since".to_owned(), last_modified.to_owned()));
+        }
+    }
+
+    match crate::send(request, &validators).await? {
+        Ok(response) => {
+            if let (304, Some((mut entry, body))) = (response.status, cached) {
+                // The cached response is still valid. The 304 response carries
+                // the updated caching headers.
+                for (name, value) in response.headers.into_iter().filter(|(name, _)| {
+                    REVALIDATED_HEADERS
+                        .iter()
+                        .any(|header| name.eq_ignore_ascii_case(header))
+                }) {
+                    entry
+                        .headers
+                        .retain(|(key, _)| !key.eq_ignore_ascii_case(&name));
+                    entry.headers.push((name, value));
+                }
+                entry.stored_at = now;
+                write_entry(entry_path, &entry).await?;
+                return Ok(Ok(entry.to_response(body)));
+            }
+
+            let no_store = response
+                .header("cache-control")
+                .map_or(false, |cache_control| {
+                    has_directive(cache_control, "no-store")
+                });
+            if !no_store {
+                let entry = CacheEntry {
+                    status: response.status,
+                    headers: response.headers.clone(),
+                    stored_at: now,
+                };
+                body_path
+                    .write(File::from(response.body.await?.0.clone()).into())
+                    .await?;
+                write_entry(entry_path, &entry).await?;
+            }
+            Ok(Ok(response))
+        }
+        Err(err) => match cached {
+            // Better a stale response than none.
+            Some((entry, body)) => Ok(Ok(entry.to_response(body))),
+            None => Ok(Err(err)),
+        },
+    }
+}
+
+/// Identifies the response to a request. Includes the request headers, as
+/// e.g. the User-Agent can change the response.
+fn cache_key(request: &HttpRequest) -> String {
+    let mut key = format!("{} {}", request.method.as_str(), request.url);
+    for (name, value) in &request.headers {
+        key.push('\n');
+        key.push_str(&name.to_ascii_lowercase());
+        key.push_str(": ");
+        key.push_str(value);
+    }
+    key
+}
+
+/// Reads the metadata and body of a cached response. They are read from disk
+/// directly instead of through the file system, as the entry is rewritten with
+/// a new `stored_at` by the same task, which would invalidate it again.
+async fn read_entry(
+    entry_path: FileSystemPathVc,
+    body_path: FileSystemPathVc,
+) -> Result<Option<(CacheEntry, Vec<u8>)>> {
+    let (Some(entry_path), Some(body_path)) =
+        (to_sys_path(entry_path).await?, to_sys_path(body_path).await?) else {
+        return Ok(None);
+    };
+    let Ok(entry) = tokio::fs::read(&entry_path).await else {
+        return Ok(None);
+    };
+    // An unreadable entry, e.g. from an older version, is treated as missing.
+    let Ok(entry) = serde_json::from_slice::<CacheEntry>(&entry) else {
+        return Ok(None);
+    };
+    let Ok(body) = tokio::fs::read(&body_path).await else {
+        return Ok(None);
+    };
+    Ok(Some((entry, body)))
+}
+
+async fn write_entry(entry_path: FileSystemPathVc, entry: &CacheEntry) -> Result<()> {
+    entry_path
+        .write(File::from(serde_json::to_string(entry)?).into())
+        .await?;
+    Ok(())
+}
+
+fn has_directive(cache_control: &str, name: &str) -> bool {
+    cache_control
+        .split(',')
+        .any(|directive| directive.trim().eq_ignore_ascii_case(name))
+}
+
+fn directive_value(cache_control: &str, name: &str) -> Option<u64> {
+    cache_control.split(',').find_map(|directive| {
+        let (key, value) = directive.trim().split_once('=')?;
+        if key.trim().eq_ignore_ascii_case(name) {
+            value.trim().trim_matches('"').parse().ok()
+        } else {
+            None
+        }
+    })
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
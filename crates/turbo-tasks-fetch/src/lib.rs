@@ -1,10 +1,21 @@
 #![feature(min_specialization)]
 
+use std::time::Duration;
+
 use anyhow::Result;
-use turbo_tasks::primitives::{OptionStringVc, StringVc};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{
+    primitives::{OptionStringVc, StringVc},
+    trace::TraceRawVcs,
+};
 use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::issue::{Issue, IssueSeverityVc, IssueVc};
 
+pub use crate::cache::{HttpCache, HttpCacheVc, OptionHttpCache, OptionHttpCacheVc};
+
+mod cache;
+
 pub fn register() {
     turbo_tasks::register();
     turbo_tasks_fs::register();
@@ -19,9 +30,21 @@ pub struct FetchResult(Result<HttpResponseVc, FetchErrorVc>);
 #[derive(Debug)]
 pub struct HttpResponse {
     pub status: u16,
+    /// The response headers as (name, value) pairs, with lowercase names.
+    pub headers: Vec<(String, String)>,
     pub body: HttpResponseBodyVc,
 }
 
+impl HttpResponse {
+    /// The value of the first header called `name` (case insensitive).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
 #[turbo_tasks::value(shared)]
 #[derive(Debug)]
 pub struct HttpResponseBody(pub Vec<u8>);
@@ -35,34 +58,212 @@ impl HttpResponseBodyVc {
     }
 }
 
+/// The HTTP method of a [HttpRequest].
+#[derive(TraceRawVcs, PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum HttpMethod {
+    Get,
+    Head,
+    Post,
+    Put,
+    Patch,
+    Delete,
+}
+
+impl HttpMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HttpMethod::Get => "GET",
+            HttpMethod::Head => "HEAD",
+            HttpMethod::Post => "POST",
+            HttpMethod::Put => "PUT",
+            HttpMethod::Patch => "PATCH",
+            HttpMethod::Delete => "DELETE",
+        }
+    }
+
+    fn to_reqwest(self) -> reqwest::Method {
+        match self {
+            HttpMethod::Get => reqwest::Method::GET,
+            HttpMethod::Head => reqwest::Method::HEAD,
+            HttpMethod::Post => reqwest::Method::POST,
+            HttpMethod::Put => reqwest::Method::PUT,
+            HttpMethod::Patch => reqwest::Method::PATCH,
+            HttpMethod::Delete => reqwest::Method::DELETE,
+        }
+    }
+}
+
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: HttpMethod,
+    pub url: String,
+    /// Additional request headers as (name, value) pairs.
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+    /// The timeout of the whole request in milliseconds.
+    pub timeout: Option<u32>,
+    /// When false, redirect responses are returned as they are, so that the
+    /// caller can check the `Location` before following it.
+    pub follow_redirects: bool,
+    /// Responses with a larger body fail with [FetchErrorKind::TooLarge].
+    pub max_body_size: Option<u64>,
+}
+
+impl HttpRequest {
+    /// A GET request without additional headers.
+    pub fn get(url: impl Into<String>) -> Self {
+        HttpRequest {
+            method: HttpMethod::Get,
+            url: url.into(),
+            headers: vec![],
+            body: None,
+            timeout: None,
+            follow_redirects: true,
+            max_body_size: None,
+        }
+    }
+}
+
+lazy_static! {
+    /// Shared between requests so connections are reused.
+    static ref CLIENT: reqwest::Client = reqwest::Client::new();
+    static ref NO_REDIRECT_CLIENT: reqwest::Client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+}
+
 #[turbo_tasks::function]
 pub async fn fetch(url: StringVc, user_agent: OptionStringVc) -> Result<FetchResultVc> {
-    let url = url.await?.clone();
-    let user_agent = &*user_agent.await?;
-    let client = reqwest::Client::new();
+    let mut request = HttpRequest::get(&*url.await?);
+    if let Some(user_agent) = &*user_agent.await? {
+        request
+            .headers
+            .push(("User-Agent".to_owned(), user_agent.clone()));
+    }
+    Ok(fetch_request(request.cell(), OptionHttpCacheVc::cell(None)))
+}
+
+/// Performs `request`. With a `cache`, responses are stored on disk and reused
+/// according to their `Cache-Control` and `ETag` headers, see [HttpCache].
+#[turbo_tasks::function]
+pub async fn fetch_request(
+    request: HttpRequestVc,
+    cache: OptionHttpCacheVc,
+) -> Result<FetchResultVc> {
+    let response = fetch_request_now(&*request.await?, *cache.await?).await?;
+    Ok(FetchResultVc::cell(match response {
+        Ok(response) => Ok(response.cell()),
+        Err(err) => Err(err.cell()),
+    }))
+}
 
-    let mut builder = client.get(&url);
+/// Performs the request right away instead of in a separate task. Use this
+/// instead of [fetch] when the caller decides itself when the response is
+/// stale, as the result of [fetch] is cached for the lifetime of the
+/// process.
+pub async fn fetch_now(
+    url: &str,
+    user_agent: Option<&str>,
+) -> Result<Result<HttpResponse, FetchError>> {
+    let mut request = HttpRequest::get(url);
     if let Some(user_agent) = user_agent {
-        builder = builder.header("User-Agent", user_agent);
+        request
+            .headers
+            .push(("User-Agent".to_owned(), user_agent.to_owned()));
+    }
+    fetch_request_now(&request, None).await
+}
+
+/// Like [fetch_now], but performs an arbitrary [HttpRequest], optionally
+/// through a `cache` like [fetch_request].
+pub async fn fetch_request_now(
+    request: &HttpRequest,
+    cache: Option<HttpCacheVc>,
+) -> Result<Result<HttpResponse, FetchError>> {
+    match cache {
+        Some(cache) => cache::fetch_cached(request, &*cache.await?).await,
+        None => send(request, &[]).await,
+    }
+}
+
+/// Sends `request` with the `extra_headers` added.
+async fn send(
+    request: &HttpRequest,
+    extra_headers: &[(String, String)],
+) -> Result<Result<HttpResponse, FetchError>> {
+    let client = if request.follow_redirects {
+        &*CLIENT
+    } else {
+        &*NO_REDIRECT_CLIENT
+    };
+    let mut builder = client.request(request.method.to_reqwest(), &request.url);
+    for (name, value) in request.headers.iter().chain(extra_headers) {
+        builder = builder.header(name, value);
+    }
+    if let Some(body) = &request.body {
+        builder = builder.body(body.clone());
+    }
+    if let Some(timeout) = request.timeout {
+        builder = builder.timeout(Duration::from_millis(timeout as u64));
     }
 
     let response = builder.send().await.and_then(|r| r.error_for_status());
     match response {
         Ok(response) => {
             let status = response.status().as_u16();
-            let body = response.bytes().await?.to_vec();
+            let headers = response
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect();
+            let body = match request.max_body_size {
+                Some(max_body_size) => match read_body_with_limit(response, max_body_size).await {
+                    Ok(Some(body)) => Ok(body),
+                    Ok(None) => return Ok(Err(FetchError::too_large(&request.url, max_body_size))),
+                    Err(err) => Err(err),
+                },
+                None => response.bytes().await.map(|body| body.to_vec()),
+            };
+            // The connection can also fail while the body is streamed.
+            let body = match body {
+                Ok(body) => body,
+                Err(err) => return Ok(Err(FetchError::from_reqwest_error(&err, &request.url))),
+            };
 
-            Ok(FetchResultVc::cell(Ok(HttpResponse {
+            Ok(Ok(HttpResponse {
                 status,
+                headers,
                 body: HttpResponseBodyVc::cell(HttpResponseBody(body)),
-            }
-            .cell())))
+            }))
+        }
+        Err(err) => Ok(Err(FetchError::from_reqwest_error(&err, &request.url))),
+    }
+}
+
+/// Reads the body of `response`, or returns `None` as soon as it's known to be
+/// larger than `max_body_size` bytes.
+async fn read_body_with_limit(
+    mut response: reqwest::Response,
+    max_body_size: u64,
+) -> reqwest::Result<Option<Vec<u8>>> {
+    if response
+        .content_length()
+        .map_or(false, |length| length > max_body_size)
+    {
+        return Ok(None);
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if (body.len() + chunk.len()) as u64 > max_body_size {
+            return Ok(None);
         }
-        Err(err) => Ok(FetchResultVc::cell(Err(FetchError::from_reqwest_error(
-            &err, &url,
-        )
-        .cell()))),
+        body.extend_from_slice(&chunk);
     }
+    Ok(Some(body))
 }
 
 #[derive(Debug)]
@@ -71,6 +272,11 @@ pub enum FetchErrorKind {
     Connect,
     Timeout,
     Status(u16),
+    /// The request wasn't sent because of offline mode, and there's no cached
+    /// response.
+    Offline,
+    /// The response body is larger than [HttpRequest::max_body_size].
+    TooLarge,
     Other,
 }
 
@@ -99,6 +305,24 @@ impl FetchError {
             kind: kind.into(),
         }
     }
+
+    fn too_large(url: &str, max_body_size: u64) -> FetchError {
+        FetchError {
+            detail: StringVc::cell(format!(
+                "The response body is larger than {max_body_size} bytes"
+            )),
+            url: StringVc::cell(url.to_owned()),
+            kind: FetchErrorKind::TooLarge.into(),
+        }
+    }
+
+    fn offline(url: &str) -> FetchError {
+        FetchError {
+            detail: StringVc::cell("Requests are disabled in offline mode".to_owned()),
+            url: StringVc::cell(url.to_owned()),
+            kind: FetchErrorKind::Offline.into(),
+        }
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -169,6 +393,13 @@ impl Issue for FetchIssue {
                 )
             }
             FetchErrorKind::Timeout => format!("Connection timed out when requesting {}", url),
+            FetchErrorKind::Offline => format!(
+                "There is no cached response for {} and requests are disabled in offline mode.",
+                url
+            ),
+            FetchErrorKind::TooLarge => {
+                format!("The response is too large when requesting {}", url)
+            }
             FetchErrorKind::Other => format!("There was an issue requesting {}", url),
         }))
     }
@@ -1,7 +1,10 @@
 #![cfg(test)]
 
 use turbo_tasks::primitives::{OptionStringVc, StringVc};
-use turbo_tasks_fetch::{fetch, register, FetchErrorKind};
+use turbo_tasks_fetch::{
+    fetch, fetch_now, fetch_request, fetch_request_now, register, FetchErrorKind, HttpCacheVc,
+    HttpMethod, HttpRequest, OptionHttpCacheVc,
+};
 use turbo_tasks_fs::{DiskFileSystemVc, FileSystemPathVc, FileSystemVc};
 use turbo_tasks_testing::{register, run};
 use turbopack_core::issue::{Issue, IssueSeverity};
@@ -60,6 +63,57 @@ async fn sends_user_agent() {
     }
 }
 
+#[tokio::test]
+async fn returns_headers() {
+    run! {
+        register();
+
+        let server = httpmock::MockServer::start();
+        let resource_mock = server.mock(|when, then| {
+            when.path("/foo.png");
+            then.status(200)
+                .header("Cache-Control", "public, max-age=3600")
+                .body("responsebody");
+        });
+
+        let result = &*fetch(StringVc::cell(server.url("/foo.png")), OptionStringVc::cell(None)).await?;
+        resource_mock.assert();
+
+        let Ok(response) = result else {
+            panic!()
+        };
+
+        let response = response.await?;
+        assert_eq!(response.header("cache-control"), Some("public, max-age=3600"));
+        assert_eq!(response.header("Cache-Control"), Some("public, max-age=3600"));
+        assert_eq!(response.header("etag"), None);
+    }
+}
+
+#[tokio::test]
+async fn fetch_now_is_not_cached() {
+    run! {
+        register();
+
+        let server = httpmock::MockServer::start();
+        let resource_mock = server.mock(|when, then| {
+            when.path("/foo.png");
+            then.status(200)
+                .body("responsebody");
+        });
+
+        let url = server.url("/foo.png");
+        let Ok(response) = fetch_now(&url, None).await? else {
+            panic!()
+        };
+        assert_eq!(response.status, 200);
+        assert_eq!(*response.body.to_string().await?, "responsebody");
+
+        assert!(fetch_now(&url, None).await?.is_ok());
+        resource_mock.assert_hits(2);
+    }
+}
+
 // This is temporary behavior.
 // TODO: Implement invalidation that respects Cache-Control headers.
 #[tokio::test]
@@ -141,6 +195,289 @@ async fn errors_on_404() {
     }
 }
 
+#[tokio::test]
+async fn sends_method_headers_and_body() {
+    run! {
+        register();
+
+        let server = httpmock::MockServer::start();
+        let resource_mock = server.mock(|when, then| {
+            when.method(httpmock::Method::POST)
+                .path("/api")
+                .header("Content-Type", "application/json")
+                .body("{}");
+            then.status(201)
+                .body("created");
+        });
+
+        let request = HttpRequest {
+            method: HttpMethod::Post,
+            headers: vec![("Content-Type".to_owned(), "application/json".to_owned())],
+            body: Some(b"{}".to_vec()),
+            timeout: Some(10_000),
+            ..HttpRequest::get(server.url("/api"))
+        };
+        let result = &*fetch_request(request.cell(), OptionHttpCacheVc::cell(None)).await?;
+        resource_mock.assert();
+
+        let Ok(response) = result else {
+            panic!()
+        };
+        let response = response.await?;
+        assert_eq!(response.status, 201);
+        assert_eq!(*response.body.to_string().await?, "created");
+    }
+}
+
+#[tokio::test]
+async fn returns_redirects_when_not_following_them() {
+    run! {
+        register();
+
+        let server = httpmock::MockServer::start();
+        let redirect_mock = server.mock(|when, then| {
+            when.path("/old.png");
+            then.status(302)
+                .header("Location", "/new.png");
+        });
+        let target_mock = server.mock(|when, then| {
+            when.path("/new.png");
+            then.status(200)
+                .body("responsebody");
+        });
+
+        let request = HttpRequest {
+            follow_redirects: false,
+            ..HttpRequest::get(server.url("/old.png"))
+        };
+        let Ok(response) = fetch_request_now(&request, None).await? else {
+            panic!()
+        };
+        assert_eq!(response.status, 302);
+        assert_eq!(response.header("location"), Some("/new.png"));
+        redirect_mock.assert();
+        target_mock.assert_hits(0);
+
+        let Ok(response) = fetch_request_now(&HttpRequest::get(server.url("/old.png")), None).await? else {
+            panic!()
+        };
+        assert_eq!(response.status, 200);
+        target_mock.assert();
+    }
+}
+
+#[tokio::test]
+async fn errors_on_too_large_body() {
+    run! {
+        register();
+
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.path("/foo.png");
+            then.status(200)
+                .body("responsebody");
+        });
+
+        let request = HttpRequest {
+            max_body_size: Some(4),
+            ..HttpRequest::get(server.url("/foo.png"))
+        };
+        let Err(err) = fetch_request_now(&request, None).await? else {
+            panic!()
+        };
+        assert_eq!(*err.kind.await?, FetchErrorKind::TooLarge);
+
+        let request = HttpRequest {
+            max_body_size: Some(12),
+            ..HttpRequest::get(server.url("/foo.png"))
+        };
+        let Ok(response) = fetch_request_now(&request, None).await? else {
+            panic!()
+        };
+        assert_eq!(*response.body.to_string().await?, "responsebody");
+    }
+}
+
+#[tokio::test]
+async fn cache_serves_fresh_response_across_sessions() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let server = httpmock::MockServer::start();
+    let resource_mock = server.mock(|when, then| {
+        when.path("/foo.woff");
+        then.status(200)
+            .header("Cache-Control", "max-age=3600")
+            .body("responsebody");
+    });
+    let url = server.url("/foo.woff");
+
+    for _ in 0..2 {
+        let cache_dir = cache_dir.path().to_string_lossy().to_string();
+        let url = url.clone();
+        run! {
+            register();
+
+            let request = HttpRequest::get(url.clone()).cell();
+            let result = &*fetch_request(request, get_cache(cache_dir, false)).await?;
+            let Ok(response) = result else {
+                panic!()
+            };
+            assert_eq!(*response.await?.body.to_string().await?, "responsebody");
+        }
+    }
+
+    resource_mock.assert_hits(1);
+}
+
+#[tokio::test]
+async fn cache_revalidates_stale_response() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let server = httpmock::MockServer::start();
+    let mut resource_mock = server.mock(|when, then| {
+        when.path("/foo.woff");
+        then.status(200)
+            .header("ETag", "\"v1\"")
+            .body("responsebody");
+    });
+    let url = server.url("/foo.woff");
+
+    {
+        let cache_dir = cache_dir.path().to_string_lossy().to_string();
+        let url = url.clone();
+        run! {
+            register();
+
+            let request = HttpRequest::get(url.clone()).cell();
+            assert!(fetch_request(request, get_cache(cache_dir, false)).await?.is_ok());
+        }
+    }
+    resource_mock.assert();
+    resource_mock.delete();
+
+    let not_modified_mock = server.mock(|when, then| {
+        when.path("/foo.woff").header("If-None-Match", "\"v1\"");
+        then.status(304);
+    });
+
+    let cache_dir = cache_dir.path().to_string_lossy().to_string();
+    run! {
+        register();
+
+        let request = HttpRequest::get(url.clone()).cell();
+        let result = &*fetch_request(request, get_cache(cache_dir, false)).await?;
+        let Ok(response) = result else {
+            panic!()
+        };
+        let response = response.await?;
+        assert_eq!(response.status, 200);
+        assert_eq!(*response.body.to_string().await?, "responsebody");
+    }
+    not_modified_mock.assert();
+}
+
+#[tokio::test]
+async fn cache_does_not_invalidate_itself() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let server = httpmock::MockServer::start();
+    let resource_mock = server.mock(|when, then| {
+        when.path("/foo.woff").matches(|request| {
+            !request
+                .headers
+                .iter()
+                .flatten()
+                .any(|(name, _)| name.eq_ignore_ascii_case("if-none-match"))
+        });
+        then.status(200)
+            .header("ETag", "\"v1\"")
+            .body("responsebody");
+    });
+    let not_modified_mock = server.mock(|when, then| {
+        when.path("/foo.woff").header("If-None-Match", "\"v1\"");
+        then.status(304);
+    });
+    let url = server.url("/foo.woff");
+
+    let cache_dir = cache_dir.path().to_string_lossy().to_string();
+    run! {
+        register();
+
+        // Writing the stale response to the cache must not rerun the request,
+        // which would revalidate it over and over.
+        let request = HttpRequest::get(url.clone()).cell();
+        let result = &*fetch_request(request, get_cache(cache_dir, false))
+            .strongly_consistent()
+            .await?;
+        assert!(result.is_ok());
+    }
+
+    resource_mock.assert_hits(1);
+    not_modified_mock.assert_hits(0);
+}
+
+#[tokio::test]
+async fn offline_mode_serves_cached_response() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let server = httpmock::MockServer::start();
+    let resource_mock = server.mock(|when, then| {
+        when.path("/foo.woff");
+        then.status(200).body("responsebody");
+    });
+    let url = server.url("/foo.woff");
+
+    for offline in [false, true] {
+        let cache_dir = cache_dir.path().to_string_lossy().to_string();
+        let url = url.clone();
+        run! {
+            register();
+
+            let request = HttpRequest::get(url.clone()).cell();
+            let result = &*fetch_request(request, get_cache(cache_dir, offline)).await?;
+            let Ok(response) = result else {
+                panic!()
+            };
+            assert_eq!(*response.await?.body.to_string().await?, "responsebody");
+        }
+    }
+
+    resource_mock.assert_hits(1);
+}
+
+#[tokio::test]
+async fn offline_mode_errors_without_cached_response() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let server = httpmock::MockServer::start();
+    let resource_mock = server.mock(|when, then| {
+        when.path("/foo.woff");
+        then.status(200).body("responsebody");
+    });
+
+    let url = server.url("/foo.woff");
+
+    let cache_dir = cache_dir.path().to_string_lossy().to_string();
+    run! {
+        register();
+
+        let request = HttpRequest::get(url.clone()).cell();
+        let result = &*fetch_request(request, get_cache(cache_dir, true)).await?;
+        let Err(err_vc) = result else {
+            panic!()
+        };
+        assert_eq!(*err_vc.await?.kind.await?, FetchErrorKind::Offline);
+
+        let issue = err_vc.to_issue(IssueSeverity::Warning.into(), get_issue_context());
+        assert_eq!(*issue.category().await?, "fetch");
+        assert_eq!(*issue.description().await?, format!("There is no cached response for {} and requests are disabled in offline mode.", url));
+    }
+
+    resource_mock.assert_hits(0);
+}
+
+fn get_cache(dir: String, offline: bool) -> OptionHttpCacheVc {
+    let dir =
+        std::convert::Into::<FileSystemVc>::into(DiskFileSystemVc::new("cache".to_owned(), dir))
+            .root();
+    OptionHttpCacheVc::cell(Some(HttpCacheVc::new(dir, offline)))
+}
+
 fn get_issue_context() -> FileSystemPathVc {
     std::convert::Into::<FileSystemVc>::into(DiskFileSystemVc::new(
         "root".to_owned(),
@@ -0,0 +1,331 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::{Cursor, Read},
+    sync::Arc,
+};
+
+use anyhow::{bail, Context, Result};
+use auto_hash_map::AutoMap;
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{
+    primitives::StringVc, trace::TraceRawVcs, CompletionVc, ValueToString, ValueToStringVc,
+};
+use zip::ZipArchive;
+
+use crate::{
+    util::join_path, DirectoryContent, DirectoryContentVc, DirectoryEntry, File, FileContent,
+    FileContentVc, FileMeta, FileMetaVc, FileSystem, FileSystemPathVc, FileSystemVc, LinkContent,
+    LinkContentVc, LinkType,
+};
+
+/// The archive formats supported by [ArchiveFileSystem].
+#[derive(TraceRawVcs, PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    /// A gzipped tar archive, e.g. an npm package tarball.
+    Tgz,
+}
+
+impl ArchiveFormat {
+    /// Detects the format from the file name of the archive.
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else if name.ends_with(".tgz") || name.ends_with(".tar.gz") {
+            Some(ArchiveFormat::Tgz)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveFormat::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+/// A read-only filesystem exposing the content of a zip or tar archive, e.g.
+/// the packages in a Yarn Plug'n'Play cache or npm package tarballs.
+///
+/// The archive is indexed once when it's first accessed. Entries of zip
+/// archives are decompressed when they are read. Tar archives are stored
+/// uncompressed after indexing, since gzip doesn't allow random access.
+///
+/// The archive is read from another filesystem, so changes to it invalidate
+/// all reads from this filesystem.
+#[turbo_tasks::value]
+pub struct ArchiveFileSystem {
+    archive: FileSystemPathVc,
+    format: ArchiveFormat,
+}
+
+#[turbo_tasks::value_impl]
+impl ArchiveFileSystemVc {
+    /// Creates a filesystem for the archive at `archive`, detecting the
+    /// format from its file name.
+    #[turbo_tasks::function]
+    pub async fn new(archive: FileSystemPathVc) -> Result<Self> {
+        let archive_value = archive.await?;
+        let Some(format) = ArchiveFormat::from_file_name(archive_value.file_name()) else {
+            bail!("unsupported archive format of {}", archive_value.path);
+        };
+        Ok(ArchiveFileSystem { archive, format }.cell())
+    }
+
+    /// The path of the archive file this filesystem reads from.
+    #[turbo_tasks::function]
+    pub async fn archive_path(self) -> Result<FileSystemPathVc> {
+        Ok(self.await?.archive)
+    }
+
+    #[turbo_tasks::function]
+    async fn index(self) -> Result<ArchiveIndexVc> {
+        let this = self.await?;
+        let FileContent::Content(file) = &*this.archive.read().await? else {
+            return Ok(ArchiveIndex::default().cell());
+        };
+        let mut bytes = Vec::new();
+        file.content().read().read_to_end(&mut bytes)?;
+        let archive = this.archive.to_string().await?;
+
+        let index = match this.format {
+            ArchiveFormat::Zip => index_zip(bytes.into()),
+            ArchiveFormat::Tar => index_tar(bytes.into()),
+            ArchiveFormat::Tgz => {
+                // Gzip streams can't be seeked, so reading a single file would
+                // need to decompress everything in front of it. Decompressing
+                // once and keeping the tar archive avoids doing that for every
+                // file. Tarballs of packages are small, so the memory is well
+                // spent.
+                let mut tar = Vec::new();
+                GzDecoder::new(&*bytes)
+                    .read_to_end(&mut tar)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|_| index_tar(tar.into()))
+            }
+        };
+        Ok(index
+            .with_context(|| format!("unable to read archive {}", archive))?
+            .cell())
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, TraceRawVcs)]
+enum ArchiveEntry {
+    /// A file in a zip archive, by its index in the archive.
+    ZipFile(usize),
+    /// A file in a tar archive, by its position in the uncompressed archive.
+    TarFile {
+        offset: usize,
+        size: usize,
+    },
+    Directory,
+    Symlink(String),
+}
+
+#[turbo_tasks::value(serialization = "none", eq = "manual", cell = "new")]
+#[derive(Default)]
+struct ArchiveIndex {
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    zip: Option<ZipArchive<Cursor<Arc<[u8]>>>>,
+    /// The uncompressed tar archive.
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    tar: Option<Arc<[u8]>>,
+    entries: HashMap<String, ArchiveEntry>,
+    /// The names of the entries of all directories, by path.
+    dirs: HashMap<String, BTreeSet<String>>,
+}
+
+impl ArchiveIndex {
+    fn insert(&mut self, path: &str, entry: ArchiveEntry) {
+        let path = path.trim_start_matches("./").trim_matches('/');
+        if path.is_empty() {
+            return;
+        }
+        if entry == ArchiveEntry::Directory {
+            self.dirs.entry(path.to_string()).or_default();
+        }
+        self.entries.insert(path.to_string(), entry);
+        // Archives don't necessarily contain entries for all directories.
+        let mut child = path;
+        loop {
+            let (parent, name) = child.rsplit_once('/').unwrap_or(("", child));
+            let siblings = self.dirs.entry(parent.to_string()).or_default();
+            let known = !siblings.insert(name.to_string());
+            if known || parent.is_empty() {
+                break;
+            }
+            self.entries
+                .insert(parent.to_string(), ArchiveEntry::Directory);
+            child = parent;
+        }
+    }
+}
+
+fn index_zip(bytes: Arc<[u8]>) -> Result<ArchiveIndex> {
+    let mut zip = ZipArchive::new(Cursor::new(bytes))?;
+    let mut index = ArchiveIndex::default();
+    for i in 0..zip.len() {
+        let entry = zip.by_index_raw(i)?;
+        let kind = if entry.is_dir() {
+            ArchiveEntry::Directory
+        } else {
+            ArchiveEntry::ZipFile(i)
+        };
+        index.insert(entry.name(), kind);
+    }
+    index.zip = Some(zip);
+    Ok(index)
+}
+
+fn index_tar(bytes: Arc<[u8]>) -> Result<ArchiveIndex> {
+    let mut index = ArchiveIndex::default();
+    for entry in tar::Archive::new(&*bytes).entries()? {
+        let entry = entry?;
+        let path = entry.path()?.to_string_lossy().replace('\\', "/");
+        let kind = match entry.header().entry_type() {
+            ty if ty.is_dir() => ArchiveEntry::Directory,
+            ty if ty.is_file() => ArchiveEntry::TarFile {
+                offset: entry.raw_file_position() as usize,
+                size: entry.size() as usize,
+            },
+            ty if ty.is_symlink() => match entry.link_name()? {
+                Some(target) => ArchiveEntry::Symlink(target.to_string_lossy().replace('\\', "/")),
+                None => continue,
+            },
+            // Hard links, devices, etc. are not supported.
+            _ => continue,
+        };
+        index.insert(&path, kind);
+    }
+    index.tar = Some(bytes);
+    Ok(index)
+}
+
+#[turbo_tasks::value_impl]
+impl FileSystem for ArchiveFileSystem {
+    #[turbo_tasks::function]
+    async fn read(self_vc: ArchiveFileSystemVc, path: FileSystemPathVc) -> Result<FileContentVc> {
+        let index = self_vc.index().await?;
+        let path = &path.await?.path;
+        let content = match index.entries.get(path) {
+            Some(&ArchiveEntry::ZipFile(i)) => {
+                let Some(zip) = &index.zip else {
+                    return Ok(FileContent::NotFound.cell());
+                };
+                let mut zip = zip.clone();
+                let mut entry = zip.by_index(i)?;
+                let mut content = Vec::with_capacity(entry.size() as usize);
+                entry.read_to_end(&mut content)?;
+                content
+            }
+            Some(&ArchiveEntry::TarFile { offset, size }) => {
+                let Some(tar) = &index.tar else {
+                    return Ok(FileContent::NotFound.cell());
+                };
+                offset
+                    .checked_add(size)
+                    .and_then(|end| tar.get(offset..end))
+                    .with_context(|| format!("{path} exceeds the end of the archive"))?
+                    .to_vec()
+            }
+            _ => return Ok(FileContent::NotFound.cell()),
+        };
+        Ok(File::from(content).into())
+    }
+
+    #[turbo_tasks::function]
+    async fn read_link(
+        self_vc: ArchiveFileSystemVc,
+        path: FileSystemPathVc,
+    ) -> Result<LinkContentVc> {
+        let index = self_vc.index().await?;
+        let path = &path.await?.path;
+        let Some(ArchiveEntry::Symlink(target)) = index.entries.get(path) else {
+            return Ok(LinkContent::NotFound.cell());
+        };
+        let mut link_type = LinkType::UNSET;
+        let resolved = if let Some(target) = target.strip_prefix('/') {
+            link_type |= LinkType::ABSOLUTE;
+            Some(target.to_string())
+        } else {
+            let parent = path.rsplit_once('/').map_or("", |(parent, _)| parent);
+            join_path(parent, target)
+        };
+        if matches!(
+            resolved.and_then(|resolved| index.entries.get(&resolved)),
+            Some(ArchiveEntry::Directory)
+        ) {
+            link_type |= LinkType::DIRECTORY;
+        }
+        Ok(LinkContent::Link {
+            target: target.clone(),
+            link_type,
+        }
+        .cell())
+    }
+
+    #[turbo_tasks::function]
+    async fn read_dir(
+        self_vc: ArchiveFileSystemVc,
+        path: FileSystemPathVc,
+    ) -> Result<DirectoryContentVc> {
+        let index = self_vc.index().await?;
+        let path_str = &path.await?.path;
+        let Some(names) = index.dirs.get(path_str) else {
+            return Ok(DirectoryContent::NotFound.cell());
+        };
+        let entries: AutoMap<_, _> = names
+            .iter()
+            .map(|name| {
+                let entry_path = path.join(name);
+                let full_path = if path_str.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{path_str}/{name}")
+                };
+                (
+                    name.clone(),
+                    match index.entries.get(&full_path) {
+                        Some(ArchiveEntry::Directory) => DirectoryEntry::Directory(entry_path),
+                        Some(ArchiveEntry::Symlink(_)) => DirectoryEntry::Symlink(entry_path),
+                        Some(_) => DirectoryEntry::File(entry_path),
+                        None => DirectoryEntry::Error,
+                    },
+                )
+            })
+            .collect();
+        Ok(DirectoryContentVc::new(entries))
+    }
+
+    #[turbo_tasks::function]
+    fn write(&self, _path: FileSystemPathVc, _content: FileContentVc) -> Result<CompletionVc> {
+        bail!("Writing is not possible to an archive")
+    }
+
+    #[turbo_tasks::function]
+    fn write_link(&self, _path: FileSystemPathVc, _target: LinkContentVc) -> Result<CompletionVc> {
+        bail!("Writing is not possible to an archive")
+    }
+
+    #[turbo_tasks::function]
+    async fn metadata(self_vc: ArchiveFileSystemVc, path: FileSystemPathVc) -> Result<FileMetaVc> {
+        let index = self_vc.index().await?;
+        let path = &path.await?.path;
+        if !path.is_empty() && !index.entries.contains_key(path) {
+            bail!("path not found, can't read metadata");
+        }
+
+        Ok(FileMeta::default().cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ArchiveFileSystem {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(format!(
+            "archive {}",
+            self.archive.to_string().await?
+        )))
+    }
+}
@@ -49,9 +49,9 @@ pub struct Glob {
 impl Glob {
     pub fn execute(&self, path: &str) -> bool {
         let match_partial = path.ends_with('/');
+        // The whole path needs to be matched, not only a prefix of it.
         self.iter_matches(path, true, match_partial)
-            .next()
-            .is_some()
+            .any(|(rest, _)| rest.is_empty())
     }
 
     fn iter_matches<'a>(
@@ -213,7 +213,9 @@ impl GlobPart {
                 let mut index = 0;
                 for c in input.chars() {
                     if is_escaped {
+                        // An escaped character is matched literally.
                         is_escaped = false;
+                        literal.push(c);
                     } else if c == '\\' {
                         is_escaped = true;
                     } else if c == '/'
@@ -224,9 +226,10 @@ impl GlobPart {
                         || (inside_of_braces && (c == ',' || c == '}'))
                     {
                         break;
+                    } else {
+                        literal.push(c);
                     }
-                    literal.push(c);
-                    index += 1;
+                    index += c.len_utf8();
                 }
                 Ok((GlobPart::File(literal), &input[index..]))
             }
@@ -286,7 +289,17 @@ impl<'a> Iterator for GlobPartMatchesIterator<'a> {
                     None
                 }
             }
-            GlobPart::AnyFileChar => todo!(),
+            GlobPart::AnyFileChar => {
+                if self.index == 0 {
+                    self.index += 1;
+                    match self.path.chars().next() {
+                        Some('/') | None => None,
+                        Some(c) => Some((&self.path[c.len_utf8()..], false)),
+                    }
+                } else {
+                    None
+                }
+            }
             GlobPart::PathSeparator => {
                 if self.index == 0 {
                     self.index = 1;
@@ -391,6 +404,12 @@ mod tests {
         "**/*/next/dist/server/next.js",
         "node_modules/next/dist/server/next.js"
     )]
+    #[case::escaped_brackets("pages/\\[id\\].js", "pages/[id].js")]
+    #[case::escaped_brackets_dir("\\[slug]/*.js", "[slug]/file.js")]
+    #[case::question_mark("file?.js", "file1.js")]
+    #[case::question_mark_dir("v?/index.js", "v1/index.js")]
+    #[case::escaped_question_mark("file\\?.js", "file?.js")]
+    #[case::escaped_in_braces("{\\[id].js,other.js}", "[id].js")]
     fn glob_match(#[case] glob: &str, #[case] path: &str) {
         let glob = Glob::parse(glob).unwrap();
 
@@ -398,4 +417,19 @@ mod tests {
 
         assert!(glob.execute(path));
     }
+
+    #[rstest]
+    #[case::question_mark_one_char("file?.js", "file10.js")]
+    #[case::question_mark_no_char("file?.js", "file.js")]
+    #[case::question_mark_separator("dir?file.js", "dir/file.js")]
+    #[case::prefix("file.js", "file.js.map")]
+    #[case::star_extension("*.js", "file.json")]
+    #[case::star_dir("*", "dir/file.js")]
+    fn glob_not_match(#[case] glob: &str, #[case] path: &str) {
+        let glob = Glob::parse(glob).unwrap();
+
+        println!("{glob:?} {path}");
+
+        assert!(!glob.execute(path));
+    }
 }
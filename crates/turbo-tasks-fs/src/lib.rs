@@ -5,11 +5,13 @@
 #![feature(io_error_more)]
 #![feature(main_separator_str)]
 
+pub mod archive;
 pub mod attach;
 pub mod embed;
 pub mod glob;
 mod invalidator_map;
 mod mutex_map;
+pub mod overlay;
 mod read_glob;
 mod retry;
 pub mod rope;
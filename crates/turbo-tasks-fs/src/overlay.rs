@@ -0,0 +1,250 @@
+use std::{collections::HashMap, mem::take, sync::Mutex};
+
+use anyhow::{bail, Result};
+use auto_hash_map::AutoMap;
+use turbo_tasks::{
+    mark_stateful, primitives::StringVc, CompletionVc, ValueToString, ValueToStringVc,
+};
+
+use crate::{
+    invalidator_map::InvalidatorMap, DirectoryContent, DirectoryContentVc, DirectoryEntry,
+    FileContent, FileContentVc, FileMeta, FileMetaVc, FileSystem, FileSystemPathVc, FileSystemVc,
+    LinkContent, LinkContentVc,
+};
+
+/// A filesystem which layers in-memory file contents over another
+/// filesystem, e.g. to compile unsaved editor buffers.
+///
+/// Overrides are set and cleared with [OverlayFileSystem::set_override] and
+/// [OverlayFileSystem::clear_override], which invalidate only the reads of
+/// the affected file and its parent directories. Writes go through to the
+/// underlying filesystem and replace the override of the written file.
+#[turbo_tasks::value(cell = "new", eq = "manual", serialization = "none")]
+pub struct OverlayFileSystem {
+    name: String,
+    base: FileSystemVc,
+    /// The overridden contents by path. [FileContent::NotFound] hides a file
+    /// of the underlying filesystem.
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    overrides: Mutex<HashMap<String, FileContent>>,
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    invalidator_map: InvalidatorMap,
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    dir_invalidator_map: InvalidatorMap,
+}
+
+impl OverlayFileSystem {
+    /// Replaces the content of the file at `path` (relative to the root) with
+    /// `content`. Use [FileContent::NotFound] to hide the file.
+    pub fn set_override(&self, path: &str, content: FileContent) {
+        let exists = content.is_content();
+        let previous = self
+            .overrides
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), content);
+        let existence_changed = previous.map_or(true, |previous| previous.is_content() != exists);
+        self.invalidate(path, existence_changed);
+    }
+
+    /// Removes the override of the file at `path`, so reads see the content
+    /// of the underlying filesystem again.
+    pub fn clear_override(&self, path: &str) {
+        if self.overrides.lock().unwrap().remove(path).is_some() {
+            self.invalidate(path, true);
+        }
+    }
+
+    /// Removes all overrides.
+    pub fn clear_overrides(&self) {
+        let overrides = take(&mut *self.overrides.lock().unwrap());
+        for path in overrides.into_keys() {
+            self.invalidate(&path, true);
+        }
+    }
+
+    fn get_override(&self, path: &str) -> Option<FileContent> {
+        self.overrides.lock().unwrap().get(path).cloned()
+    }
+
+    /// Invalidates reads of the file at `path`. When the file may have been
+    /// added or removed, reads of its parent directories are invalidated too.
+    fn invalidate(&self, path: &str, existence_changed: bool) {
+        if let Some(invalidators) = self.invalidator_map.lock().unwrap().remove(path) {
+            invalidators.into_iter().for_each(|i| i.invalidate());
+        }
+        if existence_changed {
+            let mut dir_invalidator_map = self.dir_invalidator_map.lock().unwrap();
+            let mut dir = path;
+            while !dir.is_empty() {
+                dir = dir.rsplit_once('/').map_or("", |(parent, _)| parent);
+                if let Some(invalidators) = dir_invalidator_map.remove(dir) {
+                    invalidators.into_iter().for_each(|i| i.invalidate());
+                }
+            }
+        }
+    }
+
+    /// registers the path as an invalidator for the current task,
+    /// has to be called within a turbo-tasks function
+    fn register_invalidator(&self, path: &str, file: bool) {
+        let invalidator = turbo_tasks::get_invalidator();
+        if file {
+            self.invalidator_map.insert(path.to_string(), invalidator);
+        } else {
+            self.dir_invalidator_map
+                .insert(path.to_string(), invalidator);
+        }
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OverlayFileSystemVc {
+    #[turbo_tasks::function]
+    pub fn new(name: String, base: FileSystemVc) -> Self {
+        mark_stateful();
+        Self::cell(OverlayFileSystem {
+            name,
+            base,
+            overrides: Default::default(),
+            invalidator_map: InvalidatorMap::new(),
+            dir_invalidator_map: InvalidatorMap::new(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn base_path(self, path: FileSystemPathVc) -> Result<FileSystemPathVc> {
+        Ok(self.await?.base.root().join(&path.await?.path))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl FileSystem for OverlayFileSystem {
+    #[turbo_tasks::function]
+    async fn read(self_vc: OverlayFileSystemVc, path: FileSystemPathVc) -> Result<FileContentVc> {
+        let this = self_vc.await?;
+        let path_str = &path.await?.path;
+        this.register_invalidator(path_str, true);
+        if let Some(content) = this.get_override(path_str) {
+            return Ok(content.cell());
+        }
+        Ok(self_vc.base_path(path).read())
+    }
+
+    #[turbo_tasks::function]
+    async fn read_link(
+        self_vc: OverlayFileSystemVc,
+        path: FileSystemPathVc,
+    ) -> Result<LinkContentVc> {
+        let this = self_vc.await?;
+        let path_str = &path.await?.path;
+        this.register_invalidator(path_str, true);
+        if this.get_override(path_str).is_some() {
+            return Ok(LinkContent::NotFound.cell());
+        }
+        Ok(self_vc.base_path(path).read_link())
+    }
+
+    #[turbo_tasks::function]
+    async fn read_dir(
+        self_vc: OverlayFileSystemVc,
+        path: FileSystemPathVc,
+    ) -> Result<DirectoryContentVc> {
+        let this = self_vc.await?;
+        let path_str = &path.await?.path;
+        this.register_invalidator(path_str, false);
+
+        let base_content = self_vc.base_path(path).read_dir().await?;
+        let mut found = matches!(&*base_content, DirectoryContent::Entries(_));
+        let mut entries = AutoMap::new();
+        if let DirectoryContent::Entries(base_entries) = &*base_content {
+            for (name, entry) in base_entries {
+                let entry_path = path.join(name);
+                let entry = match entry {
+                    DirectoryEntry::File(_) => DirectoryEntry::File(entry_path),
+                    DirectoryEntry::Directory(_) => DirectoryEntry::Directory(entry_path),
+                    DirectoryEntry::Symlink(_) => DirectoryEntry::Symlink(entry_path),
+                    DirectoryEntry::Other(_) => DirectoryEntry::Other(entry_path),
+                    DirectoryEntry::Error => DirectoryEntry::Error,
+                };
+                entries.insert(name.clone(), entry);
+            }
+        }
+
+        let prefix = if path_str.is_empty() {
+            String::new()
+        } else {
+            format!("{path_str}/")
+        };
+        for (override_path, content) in this.overrides.lock().unwrap().iter() {
+            let Some(rest) = override_path.strip_prefix(&prefix) else {
+                continue;
+            };
+            match (rest.split_once('/'), content) {
+                (None, FileContent::NotFound) => {
+                    entries.remove(&rest.to_string());
+                }
+                (None, FileContent::Content(_)) => {
+                    found = true;
+                    entries.insert(rest.to_string(), DirectoryEntry::File(path.join(rest)));
+                }
+                // Overrides can add files in directories which don't exist
+                // in the underlying filesystem.
+                (Some((dir, _)), FileContent::Content(_)) => {
+                    found = true;
+                    if !entries.contains_key(&dir.to_string()) {
+                        entries.insert(dir.to_string(), DirectoryEntry::Directory(path.join(dir)));
+                    }
+                }
+                (Some(_), FileContent::NotFound) => {}
+            }
+        }
+
+        Ok(if found {
+            DirectoryContentVc::new(entries)
+        } else {
+            DirectoryContentVc::not_found()
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn write(
+        self_vc: OverlayFileSystemVc,
+        path: FileSystemPathVc,
+        content: FileContentVc,
+    ) -> Result<CompletionVc> {
+        let completion = self_vc.base_path(path).write(content);
+        completion.await?;
+        self_vc.await?.clear_override(&path.await?.path);
+        Ok(completion)
+    }
+
+    #[turbo_tasks::function]
+    fn write_link(
+        self_vc: OverlayFileSystemVc,
+        path: FileSystemPathVc,
+        target: LinkContentVc,
+    ) -> CompletionVc {
+        self_vc.base_path(path).write_link(target)
+    }
+
+    #[turbo_tasks::function]
+    async fn metadata(self_vc: OverlayFileSystemVc, path: FileSystemPathVc) -> Result<FileMetaVc> {
+        let this = self_vc.await?;
+        let path_str = &path.await?.path;
+        this.register_invalidator(path_str, true);
+        match this.get_override(path_str) {
+            Some(FileContent::Content(_)) => Ok(FileMeta::default().cell()),
+            Some(FileContent::NotFound) => bail!("path not found, can't read metadata"),
+            None => Ok(self_vc.base_path(path).metadata()),
+        }
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for OverlayFileSystem {
+    #[turbo_tasks::function]
+    fn to_string(&self) -> StringVc {
+        StringVc::cell(self.name.clone())
+    }
+}
@@ -0,0 +1,230 @@
+#![cfg(test)]
+
+use std::io::{Cursor, Write};
+
+use anyhow::Result;
+use flate2::{write::GzEncoder, Compression};
+use turbo_tasks::{
+    primitives::{StringVc, StringsVc},
+    trace::TraceRawVcs,
+    TurboTasks,
+};
+use turbo_tasks_fs::{
+    archive::ArchiveFileSystemVc, overlay::OverlayFileSystemVc, DirectoryContent, DirectoryEntry,
+    DiskFileSystemVc, File, FileContent, FileSystemPathVc, FileSystemVc, LinkContent,
+};
+use turbo_tasks_memory::MemoryBackend;
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+fn register() {
+    turbo_tasks_fs::register();
+    include!(concat!(env!("OUT_DIR"), "/register_test_archive.rs"));
+}
+
+#[turbo_tasks::function]
+async fn read_file(path: FileSystemPathVc) -> Result<StringVc> {
+    Ok(StringVc::cell(match &*path.read().await? {
+        FileContent::Content(file) => file.content().to_str()?.into_owned(),
+        FileContent::NotFound => "not found".to_string(),
+    }))
+}
+
+/// Lists a directory, with a `/` appended to directories and `@` to symlinks.
+#[turbo_tasks::function]
+async fn read_dir(path: FileSystemPathVc) -> Result<StringsVc> {
+    let mut names: Vec<String> = match &*path.read_dir().await? {
+        DirectoryContent::Entries(entries) => entries
+            .iter()
+            .map(|(name, entry)| match entry {
+                DirectoryEntry::Directory(_) => format!("{name}/"),
+                DirectoryEntry::Symlink(_) => format!("{name}@"),
+                _ => name.clone(),
+            })
+            .collect(),
+        DirectoryContent::NotFound => vec!["not found".to_string()],
+    };
+    names.sort();
+    Ok(StringsVc::cell(names))
+}
+
+#[turbo_tasks::function]
+async fn read_link(path: FileSystemPathVc) -> Result<StringVc> {
+    Ok(StringVc::cell(match &*path.read_link().await? {
+        LinkContent::Link { target, .. } => target.clone(),
+        _ => "not a link".to_string(),
+    }))
+}
+
+/// A zip archive of `files`. Parent directories have no entries of their own.
+fn zip(files: &[(&str, &str)]) -> Result<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for (path, content) in files {
+        zip.start_file(
+            *path,
+            FileOptions::default().compression_method(CompressionMethod::Deflated),
+        )?;
+        zip.write_all(content.as_bytes())?;
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
+/// A tar archive of `files` and `symlinks`, as (path, target) pairs. Parent
+/// directories have no entries of their own.
+fn tar(files: &[(&str, &str)], symlinks: &[(&str, &str)]) -> Result<Vec<u8>> {
+    let mut tar = tar::Builder::new(Vec::new());
+    for (path, content) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        tar.append_data(&mut header, path, content.as_bytes())?;
+    }
+    for (path, target) in symlinks {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_link_name(target)?;
+        header.set_size(0);
+        tar.append_data(&mut header, path, std::io::empty())?;
+    }
+    Ok(tar.into_inner()?)
+}
+
+fn tgz(files: &[(&str, &str)], symlinks: &[(&str, &str)]) -> Result<Vec<u8>> {
+    let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+    gz.write_all(&tar(files, symlinks)?)?;
+    Ok(gz.finish()?)
+}
+
+#[derive(TraceRawVcs)]
+struct ArchiveContent {
+    index: String,
+    util: String,
+    missing: String,
+    root: Vec<String>,
+    package: Vec<String>,
+    lib: Vec<String>,
+    link: String,
+}
+
+/// Reads the files and directories of the package in the archive at `name`.
+async fn read_archive(
+    tt: &TurboTasks<MemoryBackend>,
+    fs: OverlayFileSystemVc,
+    name: &'static str,
+) -> Result<ArchiveContent> {
+    tt.run_once(async move {
+        let archive = ArchiveFileSystemVc::new(FileSystemVc::from(fs).root().join(name));
+        let root = FileSystemVc::from(archive).root();
+        Ok(ArchiveContent {
+            index: read_file(root.join("package/index.js"))
+                .strongly_consistent()
+                .await?
+                .clone_value(),
+            util: read_file(root.join("package/lib/util.js"))
+                .strongly_consistent()
+                .await?
+                .clone_value(),
+            missing: read_file(root.join("package/missing.js"))
+                .strongly_consistent()
+                .await?
+                .clone_value(),
+            root: read_dir(root).strongly_consistent().await?.clone_value(),
+            package: read_dir(root.join("package"))
+                .strongly_consistent()
+                .await?
+                .clone_value(),
+            lib: read_dir(root.join("package/lib"))
+                .strongly_consistent()
+                .await?
+                .clone_value(),
+            link: read_link(root.join("package/link.js"))
+                .strongly_consistent()
+                .await?
+                .clone_value(),
+        })
+    })
+    .await
+}
+
+const FILES: &[(&str, &str)] = &[
+    ("package/index.js", "index"),
+    ("package/lib/util.js", "util"),
+];
+
+const SYMLINKS: &[(&str, &str)] = &[("package/link.js", "lib/util.js")];
+
+#[tokio::test]
+async fn archives() -> Result<()> {
+    register();
+
+    let base_dir = tempfile::tempdir()?;
+    let base = base_dir.path().to_str().unwrap().to_string();
+    let tt = TurboTasks::new(MemoryBackend::new());
+
+    let fs = tt
+        .run_once(async move {
+            let fs = OverlayFileSystemVc::new(
+                "test".to_string(),
+                DiskFileSystemVc::new("base".to_string(), base).into(),
+            );
+            let overlay = fs.await?;
+            overlay.set_override("pkg.zip", FileContent::Content(File::from(zip(FILES)?)));
+            overlay.set_override(
+                "pkg.tar",
+                FileContent::Content(File::from(tar(FILES, SYMLINKS)?)),
+            );
+            overlay.set_override(
+                "pkg.tgz",
+                FileContent::Content(File::from(tgz(FILES, SYMLINKS)?)),
+            );
+            Ok(fs)
+        })
+        .await?;
+
+    let strings = |strings: &[&str]| strings.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    let content = read_archive(&tt, fs, "pkg.zip").await?;
+    assert_eq!(content.index, "index");
+    // `package/lib` has no entry in the archive.
+    assert_eq!(content.util, "util");
+    assert_eq!(content.missing, "not found");
+    assert_eq!(content.root, strings(&["package/"]));
+    assert_eq!(content.package, strings(&["index.js", "lib/"]));
+    assert_eq!(content.lib, strings(&["util.js"]));
+    assert_eq!(content.link, "not a link");
+
+    for name in ["pkg.tar", "pkg.tgz"] {
+        let content = read_archive(&tt, fs, name).await?;
+        assert_eq!(content.index, "index", "{name}");
+        assert_eq!(content.util, "util", "{name}");
+        assert_eq!(content.missing, "not found", "{name}");
+        assert_eq!(content.root, strings(&["package/"]), "{name}");
+        assert_eq!(
+            content.package,
+            strings(&["index.js", "lib/", "link.js@"]),
+            "{name}"
+        );
+        assert_eq!(content.lib, strings(&["util.js"]), "{name}");
+        assert_eq!(content.link, "lib/util.js", "{name}");
+    }
+
+    // Changing the archive invalidates the reads from it.
+    tt.run_once(async move {
+        fs.await?.set_override(
+            "pkg.tgz",
+            FileContent::Content(File::from(tgz(
+                &[("package/index.js", "changed"), ("package/other.js", "")],
+                &[],
+            )?)),
+        );
+        Ok(())
+    })
+    .await?;
+    let content = read_archive(&tt, fs, "pkg.tgz").await?;
+    assert_eq!(content.index, "changed");
+    assert_eq!(content.util, "not found");
+    assert_eq!(content.package, strings(&["index.js", "other.js"]));
+    assert_eq!(content.lib, strings(&["not found"]));
+    assert_eq!(content.link, "not a link");
+
+    Ok(())
+}
@@ -0,0 +1,167 @@
+#![cfg(test)]
+
+use std::sync::Mutex;
+
+use anyhow::Result;
+use turbo_tasks::{
+    primitives::{StringVc, StringsVc},
+    trace::TraceRawVcs,
+    TurboTasks,
+};
+use turbo_tasks_fs::{
+    overlay::OverlayFileSystemVc, DirectoryContent, DiskFileSystemVc, File, FileContent,
+    FileSystemPathVc, FileSystemVc,
+};
+use turbo_tasks_memory::MemoryBackend;
+
+fn register() {
+    turbo_tasks_fs::register();
+    include!(concat!(env!("OUT_DIR"), "/register_test_overlay.rs"));
+}
+
+/// The reads which were executed since the last call of [take_executions].
+static EXECUTIONS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn take_executions() -> Vec<String> {
+    let mut executions = std::mem::take(&mut *EXECUTIONS.lock().unwrap());
+    executions.sort();
+    executions
+}
+
+#[turbo_tasks::function]
+async fn read_file(path: FileSystemPathVc) -> Result<StringVc> {
+    let path_str = path.await?.path.clone();
+    EXECUTIONS.lock().unwrap().push(format!("file {path_str}"));
+    Ok(StringVc::cell(match &*path.read().await? {
+        FileContent::Content(file) => file.content().to_str()?.into_owned(),
+        FileContent::NotFound => "not found".to_string(),
+    }))
+}
+
+#[turbo_tasks::function]
+async fn read_dir(path: FileSystemPathVc) -> Result<StringsVc> {
+    let path_str = path.await?.path.clone();
+    EXECUTIONS.lock().unwrap().push(format!("dir {path_str}"));
+    let mut names = match &*path.read_dir().await? {
+        DirectoryContent::Entries(entries) => {
+            entries.iter().map(|(name, _)| name.clone()).collect()
+        }
+        DirectoryContent::NotFound => vec!["not found".to_string()],
+    };
+    names.sort();
+    Ok(StringsVc::cell(names))
+}
+
+#[derive(TraceRawVcs, Debug, PartialEq)]
+struct OverlayContent {
+    a: String,
+    b: String,
+    root: Vec<String>,
+    dir: Vec<String>,
+}
+
+/// Reads `a.txt`, `dir/b.txt` and the directories containing them.
+async fn read_all(
+    tt: &TurboTasks<MemoryBackend>,
+    fs: OverlayFileSystemVc,
+) -> Result<OverlayContent> {
+    tt.run_once(async move {
+        let root = FileSystemVc::from(fs).root();
+        Ok(OverlayContent {
+            a: read_file(root.join("a.txt"))
+                .strongly_consistent()
+                .await?
+                .clone_value(),
+            b: read_file(root.join("dir/b.txt"))
+                .strongly_consistent()
+                .await?
+                .clone_value(),
+            root: read_dir(root).strongly_consistent().await?.clone_value(),
+            dir: read_dir(root.join("dir"))
+                .strongly_consistent()
+                .await?
+                .clone_value(),
+        })
+    })
+    .await
+}
+
+async fn set_override(
+    tt: &TurboTasks<MemoryBackend>,
+    fs: OverlayFileSystemVc,
+    path: &'static str,
+    content: FileContent,
+) -> Result<()> {
+    tt.run_once(async move {
+        fs.await?.set_override(path, content);
+        Ok(())
+    })
+    .await
+}
+
+#[tokio::test]
+async fn only_affected_reads_are_invalidated() -> Result<()> {
+    register();
+
+    let base_dir = tempfile::tempdir()?;
+    let base = base_dir.path().to_str().unwrap().to_string();
+    let tt = TurboTasks::new(MemoryBackend::new());
+
+    let fs = tt
+        .run_once(async move {
+            Ok(OverlayFileSystemVc::new(
+                "test".to_string(),
+                DiskFileSystemVc::new("base".to_string(), base).into(),
+            ))
+        })
+        .await?;
+    let content = |s: &str| FileContent::Content(File::from(s));
+    set_override(&tt, fs, "a.txt", content("a")).await?;
+    set_override(&tt, fs, "dir/b.txt", content("b")).await?;
+
+    assert_eq!(
+        read_all(&tt, fs).await?,
+        OverlayContent {
+            a: "a".to_string(),
+            b: "b".to_string(),
+            root: vec!["a.txt".to_string(), "dir".to_string()],
+            dir: vec!["b.txt".to_string()],
+        }
+    );
+    assert_eq!(
+        take_executions(),
+        vec!["dir ", "dir dir", "file a.txt", "file dir/b.txt"]
+    );
+
+    // Changing the content of a file doesn't invalidate directory reads.
+    set_override(&tt, fs, "a.txt", content("a2")).await?;
+    assert_eq!(read_all(&tt, fs).await?.a, "a2");
+    assert_eq!(take_executions(), vec!["file a.txt"]);
+
+    // Hiding a file invalidates the directories containing it.
+    set_override(&tt, fs, "dir/b.txt", FileContent::NotFound).await?;
+    let OverlayContent { b, root, dir, .. } = read_all(&tt, fs).await?;
+    assert_eq!(b, "not found");
+    assert_eq!(root, vec!["a.txt".to_string()]);
+    assert_eq!(dir, vec!["not found".to_string()]);
+    assert_eq!(take_executions(), vec!["dir ", "dir dir", "file dir/b.txt"]);
+
+    // So does showing it again.
+    set_override(&tt, fs, "dir/b.txt", content("b2")).await?;
+    let OverlayContent { b, root, dir, .. } = read_all(&tt, fs).await?;
+    assert_eq!(b, "b2");
+    assert_eq!(root, vec!["a.txt".to_string(), "dir".to_string()]);
+    assert_eq!(dir, vec!["b.txt".to_string()]);
+    assert_eq!(take_executions(), vec!["dir ", "dir dir", "file dir/b.txt"]);
+
+    // Clearing an override shows the (empty) underlying filesystem.
+    tt.run_once(async move {
+        fs.await?.clear_override("a.txt");
+        Ok(())
+    })
+    .await?;
+    assert_eq!(read_all(&tt, fs).await?.a, "not found");
+    assert_eq!(take_executions(), vec!["dir ", "file a.txt"]);
+
+    Ok(())
+}
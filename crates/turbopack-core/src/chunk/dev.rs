@@ -6,7 +6,10 @@ use turbo_tasks::{
 use turbo_tasks_fs::FileSystemPathVc;
 use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
 
-use super::{ChunkingContext, ChunkingContextVc};
+use super::{
+    module_id_strategies::{DevModuleIdStrategyVc, ModuleIdStrategyVc},
+    ChunkingContext, ChunkingContextVc,
+};
 use crate::asset::AssetVc;
 
 pub struct DevChunkingContextBuilder {
@@ -29,6 +32,11 @@ impl DevChunkingContextBuilder {
         self
     }
 
+    pub fn module_id_strategy(mut self, strategy: ModuleIdStrategyVc) -> Self {
+        self.context.module_id_strategy = Some(strategy);
+        self
+    }
+
     pub fn build(self) -> ChunkingContextVc {
         DevChunkingContextVc::new(Value::new(self.context)).into()
     }
@@ -56,6 +64,8 @@ pub struct DevChunkingContext {
     layer: Option<String>,
     /// Enable HMR for this chunking
     enable_hot_module_replacement: bool,
+    /// How module ids are assigned. Defaults to readable ids
+    module_id_strategy: Option<ModuleIdStrategyVc>,
 }
 
 impl DevChunkingContextVc {
@@ -74,6 +84,7 @@ impl DevChunkingContextVc {
                 asset_root_path,
                 layer: None,
                 enable_hot_module_replacement: false,
+                module_id_strategy: None,
             },
         }
     }
@@ -187,6 +198,12 @@ impl ChunkingContext for DevChunkingContext {
         StringVc::cell(self.layer.clone().unwrap_or_default())
     }
 
+    #[turbo_tasks::function]
+    fn module_id_strategy(&self) -> ModuleIdStrategyVc {
+        self.module_id_strategy
+            .unwrap_or_else(|| DevModuleIdStrategyVc::new().into())
+    }
+
     #[turbo_tasks::function]
     async fn with_layer(self_vc: DevChunkingContextVc, layer: &str) -> Result<ChunkingContextVc> {
         let mut context = self_vc.await?.clone_value();
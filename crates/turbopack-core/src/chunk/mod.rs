@@ -1,7 +1,11 @@
 pub mod dev;
+pub mod module_id_strategies;
 pub mod optimize;
 
-use std::{collections::VecDeque, fmt::Debug};
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Display},
+};
 
 use anyhow::{anyhow, Result};
 use indexmap::IndexSet;
@@ -15,7 +19,10 @@ use turbo_tasks::{
 use turbo_tasks_fs::FileSystemPathVc;
 use turbo_tasks_hash::DeterministicHash;
 
-use self::optimize::optimize;
+use self::{
+    module_id_strategies::{DevModuleIdStrategyVc, ModuleIdStrategyVc},
+    optimize::optimize,
+};
 use crate::{
     asset::{Asset, AssetVc, AssetsVc},
     reference::{AssetReference, AssetReferenceVc, AssetReferencesVc},
@@ -40,6 +47,15 @@ impl ModuleId {
     }
 }
 
+impl Display for ModuleId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModuleId::Number(i) => write!(f, "{}", i),
+            ModuleId::String(s) => write!(f, "{}", s),
+        }
+    }
+}
+
 /// A list of module ids.
 #[turbo_tasks::value(transparent, shared)]
 pub struct ModuleIds(Vec<ModuleIdVc>);
@@ -64,6 +80,10 @@ pub trait ChunkingContext {
     }
 
     fn with_layer(&self, layer: &str) -> ChunkingContextVc;
+
+    fn module_id_strategy(&self) -> ModuleIdStrategyVc {
+        DevModuleIdStrategyVc::new().into()
+    }
 }
 
 /// An [Asset] that can be converted into a [Chunk].
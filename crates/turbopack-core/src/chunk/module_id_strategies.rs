@@ -0,0 +1,342 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use turbo_tasks::{mark_stateful, primitives::StringVc, CompletionVc, Invalidator, ValueToString};
+use turbo_tasks_fs::{File, FileContent, FileJsonContent, FileSystemPathVc};
+use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
+
+use super::{ModuleId, ModuleIdVc};
+
+/// Decides which [ModuleId] a chunk item gets, based on its identifier.
+#[turbo_tasks::value_trait]
+pub trait ModuleIdStrategy {
+    fn get_module_id(&self, ident: StringVc) -> ModuleIdVc;
+}
+
+/// Uses the identifier itself as module id. This is readable, but leaks
+/// filesystem paths and makes the output larger.
+#[turbo_tasks::value]
+pub struct DevModuleIdStrategy;
+
+#[turbo_tasks::value_impl]
+impl DevModuleIdStrategyVc {
+    #[turbo_tasks::function]
+    pub fn new() -> Self {
+        DevModuleIdStrategy.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleIdStrategy for DevModuleIdStrategy {
+    #[turbo_tasks::function]
+    async fn get_module_id(&self, ident: StringVc) -> Result<ModuleIdVc> {
+        Ok(ModuleId::String(ident.await?.clone_value()).cell())
+    }
+}
+
+/// The number of hex digits of the identifier hash used as module id by
+/// default.
+pub const DEFAULT_MODULE_ID_HASH_LENGTH: usize = 8;
+
+/// Uses a short hash of the identifier as module id. When the short hash of
+/// two identifiers collides, or is already pinned in the records file, both
+/// get a longer prefix of their hash instead. This is independent of the
+/// order in which ids are requested, so the ids only depend on the set of
+/// modules of the build.
+///
+/// The records file (`{ "moduleIds": { "<identifier>": <id> } }`) pins ids
+/// of specific identifiers. This keeps ids stable for long-term caching when
+/// modules are moved, or when new modules lead to different collisions. It
+/// is updated with [HashedModuleIdStrategyVc::write_records].
+#[turbo_tasks::value(cell = "new", eq = "manual", serialization = "none")]
+pub struct HashedModuleIdStrategy {
+    records_path: Option<FileSystemPathVc>,
+    hash_length: usize,
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    claims: Mutex<ModuleIdClaims>,
+    /// The tasks which claimed a hash prefix, by prefix. They are invalidated
+    /// when another identifier claims the same prefix.
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    invalidators: Mutex<HashMap<String, HashSet<Invalidator>>>,
+}
+
+#[turbo_tasks::value_impl]
+impl HashedModuleIdStrategyVc {
+    #[turbo_tasks::function]
+    pub fn new(records_path: Option<FileSystemPathVc>) -> Self {
+        mark_stateful();
+        Self::cell(HashedModuleIdStrategy {
+            records_path,
+            hash_length: DEFAULT_MODULE_ID_HASH_LENGTH,
+            claims: Default::default(),
+            invalidators: Default::default(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn records(self) -> Result<ModuleIdRecordsVc> {
+        let Some(records_path) = self.await?.records_path else {
+            return Ok(ModuleIdRecords::default().cell());
+        };
+        let FileJsonContent::Content(json) = &*records_path.read_json().await? else {
+            return Ok(ModuleIdRecords::default().cell());
+        };
+        let RecordsFile { module_ids } = match RecordsFile::deserialize(json) {
+            Ok(records) => records,
+            Err(err) => {
+                return Err(err).context(format!(
+                    "invalid module id records file {}",
+                    records_path.to_string().await?
+                ))
+            }
+        };
+        let reserved = module_ids.values().map(|id| id.to_string()).collect();
+        Ok(ModuleIdRecords {
+            module_ids,
+            reserved,
+        }
+        .cell())
+    }
+}
+
+impl HashedModuleIdStrategyVc {
+    /// Writes the ids assigned so far to the records file, in addition to
+    /// the ids which are already recorded there. This is a no-op when the
+    /// strategy has no records file.
+    ///
+    /// This isn't a turbo-tasks function, as it has to run again after every
+    /// build to record the ids of new modules.
+    pub async fn write_records(self) -> Result<CompletionVc> {
+        let this = self.await?;
+        let Some(records_path) = this.records_path else {
+            return Ok(CompletionVc::new());
+        };
+        let records = self.records().await?;
+        let mut module_ids: BTreeMap<&str, &ModuleId> = records
+            .module_ids
+            .iter()
+            .map(|(ident, id)| (ident.as_str(), id))
+            .collect();
+        let claims = this.claims.lock().unwrap();
+        for (ident, id) in claims.assigned.iter() {
+            module_ids.entry(ident.as_str()).or_insert(id);
+        }
+        let json = serde_json::to_string_pretty(&serde_json::json!({ "moduleIds": module_ids }))?;
+        drop(claims);
+        Ok(records_path.write(FileContent::Content(File::from(json)).cell()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleIdStrategy for HashedModuleIdStrategy {
+    #[turbo_tasks::function]
+    async fn get_module_id(
+        self_vc: HashedModuleIdStrategyVc,
+        ident: StringVc,
+    ) -> Result<ModuleIdVc> {
+        let this = self_vc.await?;
+        let records = self_vc.records().await?;
+        let ident = ident.await?;
+        if let Some(id) = records.module_ids.get(&*ident) {
+            return Ok(id.clone().cell());
+        }
+
+        let hash = encode_hex(hash_xxh3_hash64(ident.as_bytes()));
+        let claim =
+            this.claims
+                .lock()
+                .unwrap()
+                .claim(&ident, &hash, this.hash_length, &records.reserved);
+        let contested = {
+            let mut invalidators = this.invalidators.lock().unwrap();
+            let contested: Vec<_> = claim
+                .contested
+                .iter()
+                .filter_map(|prefix| invalidators.remove(prefix))
+                .flatten()
+                .collect();
+            for prefix in claim.prefixes {
+                invalidators
+                    .entry(prefix)
+                    .or_default()
+                    .insert(turbo_tasks::get_invalidator());
+            }
+            contested
+        };
+        // The previous owners of a contested prefix need a longer id now.
+        contested.into_iter().for_each(|i| i.invalidate());
+        Ok(ModuleId::String(claim.id).cell())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RecordsFile {
+    #[serde(default)]
+    module_ids: HashMap<String, ModuleId>,
+}
+
+#[turbo_tasks::value]
+#[derive(Default)]
+struct ModuleIdRecords {
+    module_ids: HashMap<String, ModuleId>,
+    /// The string representation of all recorded ids.
+    reserved: HashSet<String>,
+}
+
+/// Tracks which identifiers claimed which hash prefixes.
+///
+/// Claims are never removed, so an identifier keeps a longer id for the
+/// lifetime of the strategy when a module it collided with is removed.
+#[derive(Default)]
+struct ModuleIdClaims {
+    /// The identifiers which claimed a hash prefix, by prefix.
+    claims: HashMap<String, HashSet<String>>,
+    /// The last id assigned to each identifier.
+    assigned: HashMap<String, ModuleId>,
+}
+
+struct ModuleIdClaim {
+    id: String,
+    /// All prefixes claimed by the identifier.
+    prefixes: Vec<String>,
+    /// Prefixes which were owned by a single other identifier before.
+    contested: Vec<String>,
+}
+
+impl ModuleIdClaims {
+    /// Claims the prefixes of `hash`, starting with `min_len` digits, until
+    /// one is neither reserved nor claimed by another identifier. That prefix
+    /// is the id. When the whole hash is taken, it is used nevertheless.
+    fn claim(
+        &mut self,
+        ident: &str,
+        hash: &str,
+        min_len: usize,
+        reserved: &HashSet<String>,
+    ) -> ModuleIdClaim {
+        let mut prefixes = Vec::new();
+        let mut contested = Vec::new();
+        let mut len = min_len.min(hash.len());
+        let id = loop {
+            let prefix = &hash[..len];
+            if !reserved.contains(prefix) {
+                let owners = self.claims.entry(prefix.to_string()).or_default();
+                if owners.insert(ident.to_string()) && owners.len() == 2 {
+                    contested.push(prefix.to_string());
+                }
+                prefixes.push(prefix.to_string());
+                if owners.len() == 1 {
+                    break prefix;
+                }
+            }
+            if len == hash.len() {
+                break prefix;
+            }
+            len += 1;
+        };
+        self.assigned
+            .insert(ident.to_string(), ModuleId::String(id.to_string()));
+        ModuleIdClaim {
+            id: id.to_string(),
+            prefixes,
+            contested,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::ModuleIdClaims;
+
+    #[test]
+    fn unique_hashes() {
+        let mut claims = ModuleIdClaims::default();
+        let a = claims.claim("a", "1234abcd", 4, &HashSet::new());
+        assert_eq!(a.id, "1234");
+        assert_eq!(a.prefixes, vec!["1234"]);
+        assert!(a.contested.is_empty());
+        let b = claims.claim("b", "5678abcd", 4, &HashSet::new());
+        assert_eq!(b.id, "5678");
+        // Claiming again doesn't change the id.
+        assert_eq!(claims.claim("a", "1234abcd", 4, &HashSet::new()).id, "1234");
+    }
+
+    #[test]
+    fn collision() {
+        let mut claims = ModuleIdClaims::default();
+        assert_eq!(claims.claim("a", "1234abcd", 4, &HashSet::new()).id, "1234");
+        let b = claims.claim("b", "1234efgh", 4, &HashSet::new());
+        assert_eq!(b.id, "1234e");
+        assert_eq!(b.prefixes, vec!["1234", "1234e"]);
+        assert_eq!(b.contested, vec!["1234"]);
+        // "a" is invalidated and claims again.
+        let a = claims.claim("a", "1234abcd", 4, &HashSet::new());
+        assert_eq!(a.id, "1234a");
+        assert!(a.contested.is_empty());
+
+        // A third identifier doesn't contest the prefix again.
+        let c = claims.claim("c", "1234abff", 4, &HashSet::new());
+        assert_eq!(c.id, "1234ab");
+        assert_eq!(c.contested, vec!["1234a"]);
+    }
+
+    #[test]
+    fn independent_of_order() {
+        let hashes = [("a", "1234abcd"), ("b", "1234abef"), ("c", "9999abcd")];
+        let ids = |order: &[usize]| {
+            let mut claims = ModuleIdClaims::default();
+            // Claim until no prefix is contested anymore, like the
+            // invalidated tasks would.
+            loop {
+                let mut contested = false;
+                for &i in order {
+                    let (ident, hash) = hashes[i];
+                    contested |= !claims
+                        .claim(ident, hash, 4, &HashSet::new())
+                        .contested
+                        .is_empty();
+                }
+                if !contested {
+                    break;
+                }
+            }
+            let mut assigned: Vec<_> = claims
+                .assigned
+                .iter()
+                .map(|(ident, id)| (ident.clone(), id.to_string()))
+                .collect();
+            assigned.sort();
+            assigned
+        };
+        let expected = vec![
+            ("a".to_string(), "1234abc".to_string()),
+            ("b".to_string(), "1234abe".to_string()),
+            ("c".to_string(), "9999".to_string()),
+        ];
+        assert_eq!(ids(&[0, 1, 2]), expected);
+        assert_eq!(ids(&[2, 1, 0]), expected);
+    }
+
+    #[test]
+    fn reserved() {
+        let mut claims = ModuleIdClaims::default();
+        let reserved = HashSet::from(["1234".to_string()]);
+        let a = claims.claim("a", "1234abcd", 4, &reserved);
+        assert_eq!(a.id, "1234a");
+        assert_eq!(a.prefixes, vec!["1234a"]);
+    }
+
+    #[test]
+    fn whole_hash_taken() {
+        let mut claims = ModuleIdClaims::default();
+        claims.claim("a", "1234", 4, &HashSet::new());
+        assert_eq!(claims.claim("b", "1234", 4, &HashSet::new()).id, "1234");
+    }
+}
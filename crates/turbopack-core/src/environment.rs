@@ -15,26 +15,49 @@ use turbo_tasks_env::ProcessEnvVc;
 use crate::target::CompileTargetVc;
 
 static DEFAULT_NODEJS_VERSION: &str = "16.0.0";
+/// The Chrome version whose features edge runtimes are assumed to support.
+/// They are based on recent versions of V8.
+static EDGE_RUNTIME_CHROME_VERSION: &str = "100.0.0";
 
 #[derive(Default)]
 #[turbo_tasks::value(shared)]
-pub struct ServerAddr(#[turbo_tasks(trace_ignore)] Option<SocketAddr>);
+pub struct ServerAddr {
+    #[turbo_tasks(trace_ignore)]
+    addr: Option<SocketAddr>,
+    https: bool,
+}
 
 impl ServerAddr {
     pub fn new(addr: SocketAddr) -> Self {
-        Self(Some(addr))
+        Self {
+            addr: Some(addr),
+            https: false,
+        }
+    }
+
+    /// Creates the address of a server which is served over HTTPS.
+    pub fn new_https(addr: SocketAddr) -> Self {
+        Self {
+            addr: Some(addr),
+            https: true,
+        }
     }
 
     pub fn to_string(&self) -> Result<String> {
-        let addr = &self.0.context("expected some server address")?;
+        let addr = &self.addr.context("expected some server address")?;
+        let (scheme, default_port) = if self.https {
+            ("https", 443)
+        } else {
+            ("http", 80)
+        };
         let uri = if addr.ip().is_loopback() || addr.ip().is_unspecified() {
-            match addr.port() {
-                80 => "http://localhost".to_string(),
-                443 => "https://localhost".to_string(),
-                _ => format!("http://localhost:{}", addr.port()),
+            if addr.port() == default_port {
+                format!("{scheme}://localhost")
+            } else {
+                format!("{scheme}://localhost:{}", addr.port())
             }
         } else {
-            format!("http://{}", addr)
+            format!("{scheme}://{}", addr)
         };
         Ok(uri)
     }
@@ -44,7 +67,7 @@ impl ServerAddr {
 impl ServerAddrVc {
     #[turbo_tasks::function]
     pub fn empty() -> Self {
-        ServerAddr(None).cell()
+        ServerAddr::default().cell()
     }
 }
 
@@ -112,7 +135,7 @@ pub enum EnvironmentIntention {
 pub enum ExecutionEnvironment {
     NodeJsBuildTime(NodeJsEnvironmentVc),
     NodeJsLambda(NodeJsEnvironmentVc),
-    EdgeFunction(NodeJsEnvironmentVc),
+    EdgeFunction(EdgeFunctionEnvironmentVc),
     Browser(BrowserEnvironmentVc),
     // TODO allow custom trait here
     Custom(u8),
@@ -126,8 +149,9 @@ impl EnvironmentVc {
         Ok(match this.execution {
             ExecutionEnvironment::NodeJsBuildTime(node_env, ..)
             | ExecutionEnvironment::NodeJsLambda(node_env) => node_env.await?.compile_target,
-            ExecutionEnvironment::Browser(_) => CompileTargetVc::unknown(),
-            ExecutionEnvironment::EdgeFunction(_) => todo!(),
+            ExecutionEnvironment::Browser(_) | ExecutionEnvironment::EdgeFunction(_) => {
+                CompileTargetVc::unknown()
+            }
             ExecutionEnvironment::Custom(_) => todo!(),
         })
     }
@@ -144,7 +168,13 @@ impl EnvironmentVc {
                     &browserslist::Opts::new(),
                 )?)?)
             }
-            ExecutionEnvironment::EdgeFunction(_) => todo!(),
+            ExecutionEnvironment::EdgeFunction(_) => RuntimeVersionsVc::cell(Versions {
+                chrome: Some(
+                    Version::from_str(EDGE_RUNTIME_CHROME_VERSION)
+                        .map_err(|_| anyhow!("Chrome version parse error"))?,
+                ),
+                ..Default::default()
+            }),
             ExecutionEnvironment::Custom(_) => todo!(),
         })
     }
@@ -173,9 +203,10 @@ impl EnvironmentVc {
                     ".json".to_string(),
                 ])
             }
-            ExecutionEnvironment::EdgeFunction(_) | ExecutionEnvironment::Browser(_) => {
-                StringsVc::empty()
+            ExecutionEnvironment::EdgeFunction(_) => {
+                StringsVc::cell(vec![".js".to_string(), ".json".to_string()])
             }
+            ExecutionEnvironment::Browser(_) => StringsVc::empty(),
             ExecutionEnvironment::Custom(_) => todo!(),
         })
     }
@@ -184,12 +215,10 @@ impl EnvironmentVc {
     pub async fn resolve_node_modules(self) -> Result<BoolVc> {
         let env = self.await?;
         Ok(match env.execution {
-            ExecutionEnvironment::NodeJsBuildTime(..) | ExecutionEnvironment::NodeJsLambda(_) => {
-                BoolVc::cell(true)
-            }
-            ExecutionEnvironment::EdgeFunction(_) | ExecutionEnvironment::Browser(_) => {
-                BoolVc::cell(false)
-            }
+            ExecutionEnvironment::NodeJsBuildTime(..)
+            | ExecutionEnvironment::NodeJsLambda(_)
+            | ExecutionEnvironment::EdgeFunction(_) => BoolVc::cell(true),
+            ExecutionEnvironment::Browser(_) => BoolVc::cell(false),
             ExecutionEnvironment::Custom(_) => todo!(),
         })
     }
@@ -201,13 +230,32 @@ impl EnvironmentVc {
             ExecutionEnvironment::NodeJsBuildTime(..) | ExecutionEnvironment::NodeJsLambda(_) => {
                 StringsVc::cell(vec!["node".to_string()])
             }
-            ExecutionEnvironment::EdgeFunction(_) | ExecutionEnvironment::Browser(_) => {
-                StringsVc::empty()
+            ExecutionEnvironment::EdgeFunction(_) => {
+                StringsVc::cell(vec!["edge-light".to_string(), "worker".to_string()])
             }
+            ExecutionEnvironment::Browser(_) => StringsVc::empty(),
             ExecutionEnvironment::Custom(_) => todo!(),
         })
     }
 
+    /// Whether the Node.js built-in modules (`fs`, `path`, ...) are available.
+    #[turbo_tasks::function]
+    pub async fn node_builtins(self) -> Result<BoolVc> {
+        let env = self.await?;
+        Ok(BoolVc::cell(matches!(
+            env.execution,
+            ExecutionEnvironment::NodeJsBuildTime(..) | ExecutionEnvironment::NodeJsLambda(_)
+        )))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn is_edge_function(self) -> Result<BoolVc> {
+        Ok(BoolVc::cell(matches!(
+            self.await?.execution,
+            ExecutionEnvironment::EdgeFunction(_)
+        )))
+    }
+
     #[turbo_tasks::function]
     pub async fn cwd(self) -> Result<OptionStringVc> {
         let env = self.await?;
@@ -223,8 +271,10 @@ impl EnvironmentVc {
         let env = self.await?;
         Ok(match env.execution {
             ExecutionEnvironment::NodeJsBuildTime(env)
-            | ExecutionEnvironment::NodeJsLambda(env)
-            | ExecutionEnvironment::EdgeFunction(env) => {
+            | ExecutionEnvironment::NodeJsLambda(env) => {
+                Rendering::Server(env.await?.server_addr).cell()
+            }
+            ExecutionEnvironment::EdgeFunction(env) => {
                 Rendering::Server(env.await?.server_addr).cell()
             }
             ExecutionEnvironment::Browser(_) => Rendering::Client.cell(),
@@ -298,6 +348,21 @@ impl Default for NodeJsVersionVc {
     }
 }
 
+/// An environment which only provides web APIs, like the Vercel Edge Runtime
+/// or Cloudflare Workers.
+#[turbo_tasks::value(shared)]
+pub struct EdgeFunctionEnvironment {
+    pub server_addr: ServerAddrVc,
+}
+
+#[turbo_tasks::value_impl]
+impl EdgeFunctionEnvironmentVc {
+    #[turbo_tasks::function]
+    pub fn new(server_addr: ServerAddrVc) -> Self {
+        Self::cell(EdgeFunctionEnvironment { server_addr })
+    }
+}
+
 #[turbo_tasks::value(shared)]
 pub struct BrowserEnvironment {
     pub dom: bool,
@@ -1,22 +1,19 @@
 use std::fmt::Display;
 
 // These enums list well known types, which we use internally. Plugins might add
-// custom types too.
-
-// TODO when plugins are supported, replace u8 with a trait that defines the
-// behavior.
+// custom types too, which are identified by their name.
 
 #[turbo_tasks::value(serialization = "auto_for_input")]
 #[derive(Debug, Clone, PartialOrd, Ord, Hash)]
 pub enum CommonJsReferenceSubType {
-    Custom(u8),
+    Custom(String),
     Undefined,
 }
 
 #[turbo_tasks::value(serialization = "auto_for_input")]
 #[derive(Debug, Clone, PartialOrd, Ord, Hash)]
 pub enum EcmaScriptModulesReferenceSubType {
-    Custom(u8),
+    Custom(String),
     Undefined,
 }
 
@@ -24,7 +21,7 @@ pub enum EcmaScriptModulesReferenceSubType {
 #[derive(Debug, Clone, PartialOrd, Ord, Hash)]
 pub enum CssReferenceSubType {
     AtImport,
-    Custom(u8),
+    Custom(String),
     Undefined,
 }
 
@@ -33,14 +30,14 @@ pub enum CssReferenceSubType {
 pub enum UrlReferenceSubType {
     EcmaScriptNewUrl,
     CssUrl,
-    Custom(u8),
+    Custom(String),
     Undefined,
 }
 
 #[turbo_tasks::value(serialization = "auto_for_input")]
 #[derive(Debug, Clone, PartialOrd, Ord, Hash)]
 pub enum TypeScriptReferenceSubType {
-    Custom(u8),
+    Custom(String),
     Undefined,
 }
 
@@ -50,7 +47,7 @@ pub enum EntryReferenceSubType {
     Web,
     Page,
     AppRoute,
-    Custom(u8),
+    Custom(String),
     Undefined,
 }
 
@@ -63,7 +60,7 @@ pub enum ReferenceType {
     Url(UrlReferenceSubType),
     TypeScript(TypeScriptReferenceSubType),
     Entry(EntryReferenceSubType),
-    Custom(u8),
+    Custom(String),
     Undefined,
 }
 
@@ -77,7 +74,7 @@ impl Display for ReferenceType {
             ReferenceType::Url(_) => "url",
             ReferenceType::TypeScript(_) => "typescript",
             ReferenceType::Entry(_) => "entry",
-            ReferenceType::Custom(_) => todo!(),
+            ReferenceType::Custom(name) => name.as_str(),
             ReferenceType::Undefined => "undefined",
         };
         f.write_str(str)
@@ -114,9 +111,8 @@ impl ReferenceType {
                 matches!(other, ReferenceType::Entry(_))
                     && matches!(sub_type, EntryReferenceSubType::Undefined)
             }
-            ReferenceType::Custom(_) => {
-                todo!()
-            }
+            // Custom reference types only include themselves
+            ReferenceType::Custom(_) => false,
             ReferenceType::Undefined => true,
         }
     }
@@ -20,10 +20,10 @@ use turbo_tasks_fs::{
 };
 
 use self::{
-    exports::{ExportsField, ExportsValue},
+    exports::{ExportsField, ExportsValue, ImportsField},
     options::{
-        resolve_modules_options, ImportMapResult, ResolveInPackage, ResolveIntoPackage,
-        ResolveModules, ResolveModulesOptionsVc, ResolveOptionsVc,
+        resolve_modules_options, ImportMapResult, ImportMapResultVc, ResolveInPackage,
+        ResolveIntoPackage, ResolveModules, ResolveModulesOptionsVc, ResolveOptionsVc,
     },
     origin::ResolveOriginVc,
     parse::{Request, RequestVc},
@@ -50,6 +50,7 @@ pub mod options;
 pub mod origin;
 pub mod parse;
 pub mod pattern;
+pub mod pnp;
 
 pub use alias_map::{
     AliasMap, AliasMapIntoIter, AliasMapLookupIterator, AliasMatch, AliasPattern, AliasTemplate,
@@ -386,6 +387,45 @@ async fn exports_field(
     }
 }
 
+#[turbo_tasks::value(shared)]
+enum ImportsFieldResult {
+    Some(
+        #[turbo_tasks(debug_ignore, trace_ignore)] ImportsField,
+        FileSystemPathVc,
+    ),
+    None,
+}
+
+/// Reads the "imports" field of the package.json closest to `context`.
+#[turbo_tasks::function]
+async fn imports_field(context: FileSystemPathVc) -> Result<ImportsFieldResultVc> {
+    let FindContextFileResult::Found(package_json_path, _refs) =
+        &*find_context_file(context, package_json()).await?
+    else {
+        return Ok(ImportsFieldResult::None.cell());
+    };
+    let FileJsonContent::Content(package_json) = &*package_json_path.read_json().await? else {
+        return Ok(ImportsFieldResult::None.cell());
+    };
+    let field_value = &package_json["imports"];
+    if let serde_json::Value::Null = field_value {
+        return Ok(ImportsFieldResult::None.cell());
+    }
+    let imports_field: Result<ImportsField> = field_value.try_into();
+    match imports_field {
+        Ok(imports_field) => Ok(ImportsFieldResult::Some(imports_field, *package_json_path).cell()),
+        Err(err) => {
+            let issue: PackageJsonIssueVc = PackageJsonIssue {
+                path: *package_json_path,
+                error_message: err.to_string(),
+            }
+            .into();
+            issue.as_issue().emit();
+            Ok(ImportsFieldResult::None.cell())
+        }
+    }
+}
+
 #[turbo_tasks::function]
 pub fn package_json() -> StringsVc {
     StringsVc::cell(vec!["package.json".to_string()])
@@ -478,7 +518,31 @@ async fn find_package(
                     packages.push(package_dir.resolve().await?);
                 }
             }
-            ResolveModules::Registry(_, _) => todo!(),
+            ResolveModules::Registry(registry, locked_versions) => {
+                let registry_value = registry.await?;
+                let context_value = context.await?;
+                // Dependencies of packages in the registry can be pinned to a
+                // different version than the top-level ones.
+                let dependent = registry_value
+                    .get_path_to(&context_value)
+                    .and_then(registry_package);
+                if let Some(version) = locked_versions.await?.version_of(dependent, &package_name) {
+                    let package_dir = registry.join(&package_name).join(version);
+                    if dir_exists(package_dir, &mut references).await?.is_some() {
+                        packages.push(package_dir.resolve().await?);
+                    }
+                }
+            }
+            ResolveModules::PnP(manifest) => {
+                references.push(AffectingResolvingAssetReferenceVc::new(*manifest).into());
+                let package_dir =
+                    *pnp::resolve_pnp_package(*manifest, context, package_name.clone()).await?;
+                if let Some(package_dir) = package_dir {
+                    if dir_exists(package_dir, &mut references).await?.is_some() {
+                        packages.push(package_dir.resolve().await?);
+                    }
+                }
+            }
         }
     }
     Ok(FindPackageResultVc::cell(FindPackageResult {
@@ -487,6 +551,18 @@ async fn find_package(
     }))
 }
 
+/// Extracts the package name and version from a path inside of a registry,
+/// which has the structure `@scope/module/version/<path-in-package>`.
+fn registry_package(path: &str) -> Option<(&str, &str)> {
+    let name_segments = if path.starts_with('@') { 2 } else { 1 };
+    let mut separators = path.match_indices('/').map(|(i, _)| i);
+    let name_end = separators.nth(name_segments - 1)?;
+    let version_end = separators.next().unwrap_or(path.len());
+    let name = &path[..name_end];
+    let version = &path[name_end + 1..version_end];
+    (!name.is_empty() && !version.is_empty()).then_some((name, version))
+}
+
 fn merge_results(results: Vec<ResolveResultVc>) -> ResolveResultVc {
     match results.len() {
         0 => ResolveResult::unresolveable().into(),
@@ -583,7 +659,9 @@ pub async fn resolve(
 
     // Apply import mappings if provided
     if let Some(import_map) = &options_value.import_map {
-        let result_ref = import_map.lookup(request).await?;
+        let result_ref = with_dynamic_import_mapping(import_map.lookup(request), context, request)
+            .await?
+            .await?;
         let result = &*result_ref;
         if !matches!(result, ImportMapResult::NoEntry) {
             let resolve_result_vc =
@@ -698,17 +776,15 @@ pub async fn resolve(
             ResolveResult::unresolveable().into()
         }
         Request::Empty => ResolveResult::unresolveable().into(),
-        Request::PackageInternal { path: _ } => {
-            let issue: ResolvingIssueVc = ResolvingIssue {
-                request_type: "package internal import: not implemented yet".to_string(),
-                request,
+        Request::PackageInternal { path } => {
+            resolve_package_internal_with_imports_field(
                 context,
-                resolve_options: options,
-                error_message: Some("package internal imports are not implemented yet".to_string()),
-            }
-            .into();
-            issue.as_issue().emit();
-            ResolveResult::unresolveable().into()
+                request,
+                options,
+                options_value,
+                path,
+            )
+            .await?
         }
         Request::Uri {
             protocol,
@@ -735,7 +811,10 @@ pub async fn resolve(
     // Apply fallback import mappings if provided
     if let Some(import_map) = &options_value.fallback_import_map {
         if *result.is_unresolveable().await? {
-            let result_ref = import_map.lookup(request).await?;
+            let result_ref =
+                with_dynamic_import_mapping(import_map.lookup(request), context, request)
+                    .await?
+                    .await?;
             let result = &*result_ref;
             if !matches!(result, ImportMapResult::NoEntry) {
                 let resolve_result_vc =
@@ -907,6 +986,19 @@ async fn resolve_module_request(
     ))
 }
 
+/// Evaluates a [ImportMapResult::Dynamic] result of an import map lookup with
+/// the context of the request.
+async fn with_dynamic_import_mapping(
+    result: ImportMapResultVc,
+    context: FileSystemPathVc,
+    request: RequestVc,
+) -> Result<ImportMapResultVc> {
+    Ok(match &*result.await? {
+        ImportMapResult::Dynamic(replacement) => replacement.result(context, request),
+        _ => result,
+    })
+}
+
 async fn resolve_import_map_result(
     result: &ImportMapResult,
     context: FileSystemPathVc,
@@ -953,6 +1045,23 @@ async fn resolve_import_map_result(
                 .await?;
             ResolveResultVc::select_first(results)
         }
+        ImportMapResult::Dynamic(replacement) => {
+            let result = replacement
+                .result(original_context, original_request)
+                .await?;
+            if matches!(&*result, ImportMapResult::NoEntry) {
+                ResolveResult::unresolveable().cell()
+            } else {
+                resolve_import_map_result_boxed(
+                    &result,
+                    context,
+                    original_context,
+                    original_request,
+                    options,
+                )
+                .await?
+            }
+        }
         ImportMapResult::NoEntry => unreachable!(),
     })
 }
@@ -1043,10 +1152,17 @@ async fn resolved(
                     }
                 }
             }
+            ResolveInPackage::ImportsField { .. } => {}
         }
     }
     if let Some(resolved_map) = resolved_map {
-        let result = resolved_map.lookup(*path, original_request).await?;
+        let result = with_dynamic_import_mapping(
+            resolved_map.lookup(*path, original_request),
+            original_context,
+            original_request,
+        )
+        .await?
+        .await?;
         if !matches!(&*result, ImportMapResult::NoEntry) {
             return resolve_import_map_result(
                 &result,
@@ -1068,21 +1184,15 @@ async fn resolved(
     .into())
 }
 
-fn handle_exports_field(
-    package_path: FileSystemPathVc,
-    package_json: FileSystemPathVc,
-    options: ResolveOptionsVc,
-    exports_field: &ExportsField,
-    path: &str,
+/// Walks the values matching a request in an "exports" or "imports" field and
+/// returns the deduplicated results for the given conditions.
+fn conditional_results<'a>(
+    values: &'a [Cow<'_, ExportsValue>],
     conditions: &BTreeMap<String, ConditionValue>,
     unspecified_conditions: &ConditionValue,
-) -> Result<ResolveResultVc> {
+) -> Vec<&'a str> {
     let mut results = Vec::new();
     let mut conditions_state = HashMap::new();
-    let values = exports_field
-        .lookup(path)
-        .map(AliasMatch::try_into_self)
-        .collect::<Result<Vec<Cow<'_, ExportsValue>>>>()?;
     for value in values.iter() {
         if value.add_results(
             conditions,
@@ -1093,10 +1203,25 @@ fn handle_exports_field(
             break;
         }
     }
-    {
-        let mut duplicates_set = HashSet::new();
-        results.retain(|item| duplicates_set.insert(*item));
-    }
+    let mut duplicates_set = HashSet::new();
+    results.retain(|item| duplicates_set.insert(*item));
+    results
+}
+
+fn handle_exports_field(
+    package_path: FileSystemPathVc,
+    package_json: FileSystemPathVc,
+    options: ResolveOptionsVc,
+    exports_field: &ExportsField,
+    path: &str,
+    conditions: &BTreeMap<String, ConditionValue>,
+    unspecified_conditions: &ConditionValue,
+) -> Result<ResolveResultVc> {
+    let values = exports_field
+        .lookup(path)
+        .map(AliasMatch::try_into_self)
+        .collect::<Result<Vec<Cow<'_, ExportsValue>>>>()?;
+    let results = conditional_results(&values, conditions, unspecified_conditions);
     let mut resolved_results = Vec::new();
     for path in results {
         if let Some(path) = normalize_path(path) {
@@ -1111,6 +1236,75 @@ fn handle_exports_field(
     ))
 }
 
+/// Resolves a `#subpath` request using the "imports" field of the closest
+/// package.json.
+async fn resolve_package_internal_with_imports_field(
+    context: FileSystemPathVc,
+    request: RequestVc,
+    resolve_options: ResolveOptionsVc,
+    options_value: &ResolveOptions,
+    pattern: &Pattern,
+) -> Result<ResolveResultVc> {
+    let imports_field_options = options_value.in_package.iter().find_map(|item| match item {
+        ResolveInPackage::ImportsField {
+            conditions,
+            unspecified_conditions,
+        } => Some((conditions, unspecified_conditions)),
+        _ => None,
+    });
+    let Some((conditions, unspecified_conditions)) = imports_field_options else {
+        // The "imports" field is not enabled for this context
+        return Ok(ResolveResult::unresolveable().into());
+    };
+    let Some(specifier) = pattern.clone().into_string() else {
+        let issue: ResolvingIssueVc = ResolvingIssue {
+            request_type: "package imports: dynamic request".to_string(),
+            request,
+            context,
+            resolve_options,
+            error_message: Some(
+                "dynamic requests into the package.json \"imports\" field are not supported"
+                    .to_string(),
+            ),
+        }
+        .into();
+        issue.as_issue().emit();
+        return Ok(ResolveResult::unresolveable().into());
+    };
+    let ImportsFieldResult::Some(imports_field, package_json_path) =
+        &*imports_field(context).await?
+    else {
+        return Ok(ResolveResult::unresolveable().into());
+    };
+
+    let values = imports_field
+        .lookup(&specifier)
+        .map(AliasMatch::try_into_self)
+        .collect::<Result<Vec<Cow<'_, ExportsValue>>>>()?;
+    let results = conditional_results(&values, conditions, unspecified_conditions);
+    let package_path = package_json_path.parent();
+    let mut resolved_results = Vec::new();
+    let mut references = vec![AffectingResolvingAssetReferenceVc::new(*package_json_path).into()];
+    for target in results {
+        if target.starts_with("./") {
+            // Relative targets point to exactly one file inside of the package, without
+            // probing extensions or directory indices
+            if let Some(path) = normalize_path(target) {
+                if let Some(path) = exists(package_path.join(&path), &mut references).await? {
+                    resolved_results.push(
+                        resolved(path, context, request, options_value, resolve_options).await?,
+                    );
+                }
+            }
+        } else if !target.starts_with('/') && !target.starts_with('#') && !target.starts_with('.') {
+            // Other targets are requests to packages, e.g. `"#dep": "dep-polyfill"`
+            let request = RequestVc::parse(Value::new(target.to_string().into()));
+            resolved_results.push(resolve(package_path, request, resolve_options));
+        }
+    }
+    Ok(merge_results_with_references(resolved_results, references))
+}
+
 #[turbo_tasks::value]
 pub struct AffectingResolvingAssetReference {
     path: FileSystemPathVc,
@@ -1179,3 +1373,25 @@ pub async fn handle_resolve_error(
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::registry_package;
+
+    #[test]
+    fn registry_package_name_and_version() {
+        assert_eq!(registry_package("react/18.2.0"), Some(("react", "18.2.0")));
+        assert_eq!(
+            registry_package("react-dom/18.2.0/cjs/react-dom.development.js"),
+            Some(("react-dom", "18.2.0"))
+        );
+        assert_eq!(
+            registry_package("@next/font/13.0.0/google"),
+            Some(("@next/font", "13.0.0"))
+        );
+        assert_eq!(registry_package("react"), None);
+        assert_eq!(registry_package("react/"), None);
+        assert_eq!(registry_package("@next/font"), None);
+        assert_eq!(registry_package(""), None);
+    }
+}
@@ -1,12 +1,17 @@
-use std::{collections::BTreeMap, future::Future, pin::Pin};
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    pin::Pin,
+};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
 use turbo_tasks::{
     debug::ValueDebugFormat, primitives::StringVc, trace::TraceRawVcs, TryJoinIterExt, Value,
     ValueToString, ValueToStringVc,
 };
-use turbo_tasks_fs::{glob::GlobVc, FileSystemPathVc};
+use turbo_tasks_fs::{glob::GlobVc, FileJsonContent, FileSystemPathVc};
 
 use super::{
     alias_map::{AliasMap, AliasTemplate},
@@ -14,9 +19,105 @@ use super::{
 };
 use crate::resolve::parse::RequestVc;
 
+/// The package versions pinned by a lockfile.
 #[turbo_tasks::value(shared)]
-#[derive(Hash, Debug)]
-pub struct LockedVersions {}
+#[derive(Hash, Debug, Default)]
+pub struct LockedVersions {
+    /// The versions of the top-level dependencies, by package name.
+    pub versions: BTreeMap<String, String>,
+    /// Versions that differ from the top-level one for the dependencies of a
+    /// specific package, by the `name@version` of the dependent package.
+    pub nested: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl LockedVersions {
+    /// The version of `package_name` when it's requested from within the
+    /// `dependent` package (name and version), or from the top-level when
+    /// `dependent` is `None`.
+    pub fn version_of(&self, dependent: Option<(&str, &str)>, package_name: &str) -> Option<&str> {
+        dependent
+            .and_then(|(name, version)| self.nested.get(&format!("{name}@{version}")))
+            .and_then(|versions| versions.get(package_name))
+            .or_else(|| self.versions.get(package_name))
+            .map(|version| version.as_str())
+    }
+
+    /// Collects the pinned versions from the `"packages"` field of a
+    /// `package-lock.json`.
+    ///
+    /// A package in a nested `node_modules` directory is visible to the
+    /// package containing that directory and to all packages nested below
+    /// it, unless one of them contains another version, like in Node.js
+    /// module resolution.
+    pub fn from_lockfile_packages(packages: &serde_json::Map<String, JsonValue>) -> Self {
+        let mut locked = LockedVersions::default();
+        // The path and `name@version` of every package in `node_modules`.
+        let mut locations = Vec::new();
+        // The packages in a nested `node_modules` directory, by the path of
+        // the package containing the directory.
+        let mut children: HashMap<&str, Vec<(&str, &str)>> = HashMap::new();
+        for (path, package) in packages {
+            // Skip the root and workspace packages, which are not in the registry.
+            if !path.starts_with("node_modules/") || package["link"].as_bool() == Some(true) {
+                continue;
+            }
+            let Some(version) = package["version"].as_str() else {
+                continue;
+            };
+            match path.rsplit_once("/node_modules/") {
+                Some((container, name)) => {
+                    children.entry(container).or_default().push((name, version));
+                    locations.push((path.as_str(), format!("{name}@{version}")));
+                }
+                None => {
+                    let name = &path["node_modules/".len()..];
+                    locked
+                        .versions
+                        .insert(name.to_string(), version.to_string());
+                    locations.push((path.as_str(), format!("{name}@{version}")));
+                }
+            }
+        }
+        for (path, key) in locations {
+            let mut container = Some(path);
+            while let Some(current) = container {
+                if let Some(children) = children.get(current) {
+                    let versions = locked.nested.entry(key.clone()).or_default();
+                    for (name, version) in children {
+                        // The closest `node_modules` directory takes precedence.
+                        versions
+                            .entry(name.to_string())
+                            .or_insert_with(|| version.to_string());
+                    }
+                }
+                container = current
+                    .rsplit_once("/node_modules/")
+                    .map(|(parent, _)| parent);
+            }
+        }
+        locked
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl LockedVersionsVc {
+    /// Reads the pinned versions from a `package-lock.json` (lockfile version
+    /// 2 or 3).
+    #[turbo_tasks::function]
+    pub async fn from_lockfile(lockfile: FileSystemPathVc) -> Result<Self> {
+        let FileJsonContent::Content(json) = &*lockfile.read_json().await? else {
+            bail!("unable to read lockfile {}", lockfile.to_string().await?);
+        };
+        let Some(packages) = json["packages"].as_object() else {
+            bail!(
+                "lockfile {} has no \"packages\" field, only lockfile version 2 and later is \
+                 supported",
+                lockfile.to_string().await?
+            );
+        };
+        Ok(LockedVersions::from_lockfile_packages(packages).cell())
+    }
+}
 
 /// A location where to resolve modules.
 #[derive(
@@ -32,6 +133,9 @@ pub enum ResolveModules {
     /// registry filesystem is assumed to have structure like
     /// @scope/module/version/<path-in-package>
     Registry(FileSystemPathVc, LockedVersionsVc),
+    /// resolve packages through the Yarn Plug'n'Play manifest at that path
+    /// (`.pnp.cjs` or `.pnp.data.json`)
+    PnP(FileSystemPathVc),
 }
 
 #[derive(TraceRawVcs, Hash, PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
@@ -77,6 +181,13 @@ pub enum ResolveIntoPackage {
 pub enum ResolveInPackage {
     /// Using a alias field which allows to map requests
     AliasField(String),
+    /// Using the [imports] field to resolve `#subpath` requests.
+    ///
+    /// [imports]: https://nodejs.org/api/packages.html#imports
+    ImportsField {
+        conditions: BTreeMap<String, ConditionValue>,
+        unspecified_conditions: ConditionValue,
+    },
 }
 
 #[turbo_tasks::value(shared)]
@@ -231,6 +342,10 @@ pub enum ImportMapResult {
     Result(ResolveResultVc),
     Alias(RequestVc, Option<FileSystemPathVc>),
     Alternatives(Vec<ImportMapResult>),
+    /// A [ImportMapping::Dynamic] replacement. It depends on the context of
+    /// the request, so it's evaluated by the resolver instead of in the
+    /// lookup, which is cached by request only.
+    Dynamic(ImportMappingReplacementVc),
     NoEntry,
 }
 
@@ -267,7 +382,7 @@ async fn import_mapping_to_result(
                 .try_join()
                 .await?,
         ),
-        ImportMapping::Dynamic(replacement) => (*replacement.result(request).await?).clone(),
+        ImportMapping::Dynamic(replacement) => ImportMapResult::Dynamic(*replacement),
     })
 }
 
@@ -301,6 +416,9 @@ impl ValueToString for ImportMapResult {
                     .collect::<Vec<_>>();
                 Ok(StringVc::cell(strings.join(" | ")))
             }
+            ImportMapResult::Dynamic(_) => Ok(StringVc::cell(
+                "Resolved by a dynamic import mapping".to_string(),
+            )),
             ImportMapResult::NoEntry => Ok(StringVc::cell("No import map entry".to_string())),
         }
     }
@@ -401,6 +519,17 @@ impl ResolveOptionsVc {
         Ok(resolve_options.into())
     }
 
+    /// Returns a new [ResolveOptionsVc] which resolves packages through the
+    /// given Yarn Plug'n'Play manifest before any other module location.
+    #[turbo_tasks::function]
+    pub async fn with_pnp_manifest(self, manifest: FileSystemPathVc) -> Result<Self> {
+        let mut resolve_options = self.await?.clone_value();
+        resolve_options
+            .modules
+            .insert(0, ResolveModules::PnP(manifest));
+        Ok(resolve_options.into())
+    }
+
     /// Returns a new [ResolveOptionsVc] with its fallback import map extended
     /// to include the given import map.
     #[turbo_tasks::function]
@@ -433,5 +562,53 @@ pub async fn resolve_modules_options(options: ResolveOptionsVc) -> Result<Resolv
 #[turbo_tasks::value_trait]
 pub trait ImportMappingReplacement {
     fn replace(&self, capture: &str) -> ImportMappingVc;
-    fn result(&self, request: RequestVc) -> ImportMapResultVc;
+    /// The result of a `request` which is resolved in `context`.
+    fn result(&self, context: FileSystemPathVc, request: RequestVc) -> ImportMapResultVc;
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::LockedVersions;
+
+    #[test]
+    fn lockfile_packages() {
+        let lockfile = json!({
+            "": { "name": "app", "dependencies": { "a": "^1.0.0", "b": "^1.0.0" } },
+            "packages/lib": { "name": "lib", "version": "0.0.0" },
+            "node_modules/lib": { "resolved": "packages/lib", "link": true },
+            "node_modules/a": { "version": "1.0.0" },
+            "node_modules/b": { "version": "1.0.0" },
+            "node_modules/c": { "version": "1.0.0" },
+            "node_modules/@scope/d": { "version": "1.0.0" },
+            "node_modules/a/node_modules/c": { "version": "2.0.0" },
+            "node_modules/a/node_modules/e": { "version": "1.0.0" },
+            "node_modules/a/node_modules/e/node_modules/c": { "version": "3.0.0" },
+            "node_modules/b/node_modules/c": { "version": "4.0.0" },
+            "node_modules/@scope/d/node_modules/c": { "version": "5.0.0" },
+        });
+        let locked = LockedVersions::from_lockfile_packages(lockfile.as_object().unwrap());
+
+        assert_eq!(locked.version_of(None, "a"), Some("1.0.0"));
+        assert_eq!(locked.version_of(None, "c"), Some("1.0.0"));
+        assert_eq!(locked.version_of(None, "@scope/d"), Some("1.0.0"));
+        assert_eq!(locked.version_of(None, "e"), None);
+        assert_eq!(locked.version_of(None, "lib"), None);
+
+        // Nested versions of the same package don't overwrite each other.
+        assert_eq!(locked.version_of(Some(("a", "1.0.0")), "c"), Some("2.0.0"));
+        assert_eq!(locked.version_of(Some(("b", "1.0.0")), "c"), Some("4.0.0"));
+        assert_eq!(
+            locked.version_of(Some(("@scope/d", "1.0.0")), "c"),
+            Some("5.0.0")
+        );
+        assert_eq!(locked.version_of(Some(("a", "1.0.0")), "e"), Some("1.0.0"));
+        assert_eq!(locked.version_of(Some(("e", "1.0.0")), "c"), Some("3.0.0"));
+        // Versions from the `node_modules` of a parent package are visible.
+        assert_eq!(locked.version_of(Some(("c", "2.0.0")), "e"), Some("1.0.0"));
+        // Falls back to the top-level version.
+        assert_eq!(locked.version_of(Some(("a", "1.0.0")), "b"), Some("1.0.0"));
+        assert_eq!(locked.version_of(Some(("c", "1.0.0")), "c"), Some("1.0.0"));
+    }
 }
@@ -0,0 +1,613 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use turbo_tasks::{
+    primitives::{StringVc, StringsVc},
+    trace::TraceRawVcs,
+    CompletionVc, ValueToString, ValueToStringVc,
+};
+use turbo_tasks_fs::{
+    archive::ArchiveFileSystemVc, DirectoryContent, DirectoryContentVc, DirectoryEntry,
+    FileContent, FileContentVc, FileMetaVc, FileSystem, FileSystemPathOptionVc, FileSystemPathVc,
+    FileSystemVc, LinkContentVc,
+};
+
+use super::{find_context_file, FindContextFileResult};
+
+/// The Yarn Plug'n'Play manifest files, in order of preference.
+#[turbo_tasks::function]
+fn pnp_manifest_files() -> StringsVc {
+    StringsVc::cell(vec![".pnp.data.json".to_string(), ".pnp.cjs".to_string()])
+}
+
+/// Finds the Yarn Plug'n'Play manifest of the project containing `context`.
+/// Paths inside of the zip archives of the cache belong to the project of the
+/// cache.
+#[turbo_tasks::function]
+pub async fn find_pnp_manifest(context: FileSystemPathVc) -> Result<FileSystemPathOptionVc> {
+    let context = disk_path(context).await?;
+    Ok(FileSystemPathOptionVc::cell(
+        match *find_context_file(context, pnp_manifest_files()).await? {
+            FindContextFileResult::Found(manifest, _) => Some(manifest),
+            FindContextFileResult::NotFound(_) => None,
+        },
+    ))
+}
+
+/// A package in the Plug'n'Play dependency tree, identified by its name and
+/// reference (usually the version). The top-level workspace has an empty
+/// name and reference.
+#[derive(TraceRawVcs, PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+struct PnpPackage {
+    /// The location of the package, relative to the project root, without a
+    /// leading `./` and with a trailing `/` (empty for the root). Virtual
+    /// instances of a package keep their virtual location, see
+    /// [PnpVirtualFileSystem].
+    location: String,
+    /// The dependencies of the package with the name and reference of the
+    /// package they resolve to. `None` marks an unfulfilled peer dependency.
+    dependencies: BTreeMap<String, Option<(String, String)>>,
+}
+
+#[turbo_tasks::value(shared)]
+#[derive(Debug)]
+pub struct PnpManifest {
+    packages: BTreeMap<String, BTreeMap<String, PnpPackage>>,
+    /// Dependencies which all packages can fall back to when they don't list
+    /// a dependency themselves.
+    fallback_pool: BTreeMap<String, (String, String)>,
+    enable_top_level_fallback: bool,
+}
+
+impl PnpManifest {
+    fn from_json(data: &JsonValue) -> Self {
+        let mut manifest = PnpManifest {
+            packages: BTreeMap::new(),
+            fallback_pool: BTreeMap::new(),
+            enable_top_level_fallback: data["enableTopLevelFallback"].as_bool().unwrap_or(false),
+        };
+        for (name, references) in iter_pairs(&data["packageRegistryData"]) {
+            let name = name.as_str().unwrap_or_default();
+            for (reference, info) in iter_pairs(references) {
+                let reference = reference.as_str().unwrap_or_default();
+                let Some(location) = info["packageLocation"].as_str() else {
+                    continue;
+                };
+                let dependencies = iter_pairs(&info["packageDependencies"])
+                    .filter_map(|(dependency, target)| {
+                        Some((
+                            dependency.as_str()?.to_string(),
+                            parse_target(dependency, target),
+                        ))
+                    })
+                    .collect();
+                manifest
+                    .packages
+                    .entry(name.to_string())
+                    .or_default()
+                    .insert(
+                        reference.to_string(),
+                        PnpPackage {
+                            location: normalize_location(location),
+                            dependencies,
+                        },
+                    );
+            }
+        }
+        for (dependency, target) in iter_pairs(&data["fallbackPool"]) {
+            if let (Some(dependency), Some(target)) =
+                (dependency.as_str(), parse_target(dependency, target))
+            {
+                manifest
+                    .fallback_pool
+                    .insert(dependency.to_string(), target);
+            }
+        }
+        manifest
+    }
+
+    fn package(&self, name: &str, reference: &str) -> Option<&PnpPackage> {
+        self.packages.get(name)?.get(reference)
+    }
+
+    /// Finds the package containing `path`, which is relative to the project
+    /// root.
+    fn find_issuer(&self, path: &str) -> Option<&PnpPackage> {
+        let path = format!("{path}/");
+        self.packages
+            .values()
+            .flat_map(|references| references.values())
+            .filter(|package| path.starts_with(&package.location))
+            .max_by_key(|package| package.location.len())
+    }
+
+    /// The location of the package `name` as seen from the package containing
+    /// `issuer_path`.
+    fn resolve(&self, issuer_path: &str, name: &str) -> Option<&str> {
+        let top_level = self.package("", "");
+        let issuer = self.find_issuer(issuer_path).or(top_level)?;
+        let target = match issuer.dependencies.get(name) {
+            Some(target) => target.as_ref()?,
+            None => {
+                let top_level_target = if self.enable_top_level_fallback {
+                    top_level
+                        .and_then(|top_level| top_level.dependencies.get(name))
+                        .and_then(|target| target.as_ref())
+                } else {
+                    None
+                };
+                top_level_target.or_else(|| self.fallback_pool.get(name))?
+            }
+        };
+        Some(&self.package(&target.0, &target.1)?.location)
+    }
+}
+
+/// Reads the manifest from `.pnp.data.json` or the state inlined into
+/// `.pnp.cjs`.
+#[turbo_tasks::function]
+pub async fn pnp_manifest(manifest_path: FileSystemPathVc) -> Result<PnpManifestVc> {
+    let FileContent::Content(file) = &*manifest_path.read().await? else {
+        bail!("unable to read {}", manifest_path.to_string().await?);
+    };
+    let content = file.content().to_str()?;
+    let data: JsonValue = if manifest_path.await?.path.ends_with(".json") {
+        serde_json::from_str(&content)?
+    } else if let Some(state) = extract_inlined_state(&content) {
+        serde_json::from_str(&state)?
+    } else {
+        let data_path = manifest_path.parent().join(".pnp.data.json");
+        return Ok(pnp_manifest(data_path));
+    };
+
+    Ok(PnpManifest::from_json(&data).cell())
+}
+
+/// Finds the package `package_name` as seen from `context`, which can be a
+/// path on disk or inside of a zip archive of the Plug'n'Play cache.
+#[turbo_tasks::function]
+pub async fn resolve_pnp_package(
+    manifest_path: FileSystemPathVc,
+    context: FileSystemPathVc,
+    package_name: String,
+) -> Result<FileSystemPathOptionVc> {
+    let root = manifest_path.parent();
+    let root_value = root.await?;
+    let context = disk_path(context).await?;
+    let Some(issuer_path) = root_value.get_path_to(&*context.await?).map(|p| p.to_string()) else {
+        return Ok(FileSystemPathOptionVc::cell(None));
+    };
+    let manifest = pnp_manifest(manifest_path).await?;
+    let Some(location) = manifest.resolve(&issuer_path, &package_name) else {
+        return Ok(FileSystemPathOptionVc::cell(None));
+    };
+    let physical_location = resolve_virtual(location);
+    let physical_location = physical_location.trim_end_matches('/');
+    let mut path = match physical_location.split_once(".zip/") {
+        Some((archive, inner)) => ArchiveFileSystemVc::new(root.join(&format!("{archive}.zip")))
+            .as_file_system()
+            .root()
+            .join(inner),
+        None => root.join(physical_location),
+    };
+    if is_virtual(location) {
+        path = PnpVirtualFileSystemVc::new(root.join(location.trim_end_matches('/')), path)
+            .as_file_system()
+            .root();
+    }
+    Ok(FileSystemPathOptionVc::cell(Some(path)))
+}
+
+/// Maps paths inside of an archive or a virtual package instance to the
+/// (virtual) path on the disk.
+async fn disk_path(path: FileSystemPathVc) -> Result<FileSystemPathVc> {
+    let fs = path.fs();
+    Ok(
+        if let Some(virtual_fs) = PnpVirtualFileSystemVc::resolve_from(fs).await? {
+            virtual_fs.virtual_path().join(&path.await?.path)
+        } else if let Some(archive) = ArchiveFileSystemVc::resolve_from(fs).await? {
+            archive.archive_path().join(&path.await?.path)
+        } else {
+            path
+        },
+    )
+}
+
+/// A filesystem for a virtual instance of a package, which Yarn creates for
+/// every set of peer dependencies a package is used with.
+///
+/// All instances read from the same physical location on the disk or in a
+/// zip archive, but each has its own identity. Requests from inside of an
+/// instance therefore resolve its peer dependencies to the packages of that
+/// instance.
+#[turbo_tasks::value]
+pub struct PnpVirtualFileSystem {
+    /// The virtual location of the package instance, which doesn't exist on
+    /// the disk.
+    virtual_path: FileSystemPathVc,
+    /// The physical location of the package.
+    target: FileSystemPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl PnpVirtualFileSystemVc {
+    #[turbo_tasks::function]
+    pub fn new(virtual_path: FileSystemPathVc, target: FileSystemPathVc) -> Self {
+        Self::cell(PnpVirtualFileSystem {
+            virtual_path,
+            target,
+        })
+    }
+
+    #[turbo_tasks::function]
+    pub async fn virtual_path(self) -> Result<FileSystemPathVc> {
+        Ok(self.await?.virtual_path)
+    }
+
+    /// The physical path of a path on this filesystem.
+    #[turbo_tasks::function]
+    async fn target_path(self, path: FileSystemPathVc) -> Result<FileSystemPathVc> {
+        Ok(self.await?.target.join(&path.await?.path))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl FileSystem for PnpVirtualFileSystem {
+    #[turbo_tasks::function]
+    fn read(self_vc: PnpVirtualFileSystemVc, path: FileSystemPathVc) -> FileContentVc {
+        self_vc.target_path(path).read()
+    }
+
+    #[turbo_tasks::function]
+    fn read_link(self_vc: PnpVirtualFileSystemVc, path: FileSystemPathVc) -> LinkContentVc {
+        self_vc.target_path(path).read_link()
+    }
+
+    #[turbo_tasks::function]
+    async fn read_dir(
+        self_vc: PnpVirtualFileSystemVc,
+        path: FileSystemPathVc,
+    ) -> Result<DirectoryContentVc> {
+        let DirectoryContent::Entries(entries) = &*self_vc.target_path(path).read_dir().await?
+        else {
+            return Ok(DirectoryContentVc::not_found());
+        };
+        let entries = entries
+            .iter()
+            .map(|(name, entry)| {
+                let entry_path = path.join(name);
+                let entry = match entry {
+                    DirectoryEntry::File(_) => DirectoryEntry::File(entry_path),
+                    DirectoryEntry::Directory(_) => DirectoryEntry::Directory(entry_path),
+                    DirectoryEntry::Symlink(_) => DirectoryEntry::Symlink(entry_path),
+                    DirectoryEntry::Other(_) => DirectoryEntry::Other(entry_path),
+                    DirectoryEntry::Error => DirectoryEntry::Error,
+                };
+                (name.clone(), entry)
+            })
+            .collect();
+        Ok(DirectoryContentVc::new(entries))
+    }
+
+    #[turbo_tasks::function]
+    fn write(&self, _path: FileSystemPathVc, _content: FileContentVc) -> Result<CompletionVc> {
+        bail!("Writing is not possible to a virtual package")
+    }
+
+    #[turbo_tasks::function]
+    fn write_link(&self, _path: FileSystemPathVc, _target: LinkContentVc) -> Result<CompletionVc> {
+        bail!("Writing is not possible to a virtual package")
+    }
+
+    #[turbo_tasks::function]
+    fn metadata(self_vc: PnpVirtualFileSystemVc, path: FileSystemPathVc) -> FileMetaVc {
+        self_vc.target_path(path).metadata()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for PnpVirtualFileSystem {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(format!(
+            "virtual package {}",
+            self.virtual_path.to_string().await?
+        )))
+    }
+}
+
+/// Iterates the `[key, value]` pairs Plug'n'Play uses to serialize maps.
+fn iter_pairs(value: &JsonValue) -> impl Iterator<Item = (&JsonValue, &JsonValue)> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|pair| Some((pair.get(0)?, pair.get(1)?)))
+}
+
+/// A dependency target is either a reference of the package with the same
+/// name, or `[name, reference]` for aliased packages.
+fn parse_target(dependency: &JsonValue, target: &JsonValue) -> Option<(String, String)> {
+    match target {
+        JsonValue::String(reference) => {
+            Some((dependency.as_str()?.to_string(), reference.to_string()))
+        }
+        JsonValue::Array(alias) => Some((
+            alias.get(0)?.as_str()?.to_string(),
+            alias.get(1)?.as_str()?.to_string(),
+        )),
+        _ => None,
+    }
+}
+
+/// Extracts the JSON state which Yarn inlines into `.pnp.cjs` as a single
+/// quoted string.
+fn extract_inlined_state(content: &str) -> Option<String> {
+    let start = content.find("RAW_RUNTIME_STATE =")?;
+    let rest = &content[start..];
+    let rest = &rest[rest.find('\'')? + 1..];
+    let mut state = String::new();
+    let mut chars = rest.chars();
+    loop {
+        match chars.next()? {
+            '\'' => return Some(state),
+            '\\' => match chars.next()? {
+                // line continuation
+                '\n' => {}
+                c => state.push(c),
+            },
+            c => state.push(c),
+        }
+    }
+}
+
+/// Whether `location` contains a `__virtual__/<hash>/<depth>/` segment.
+fn is_virtual(location: &str) -> bool {
+    location
+        .split('/')
+        .any(|segment| segment == "__virtual__" || segment == "$$virtual")
+}
+
+/// Normalizes a location to have no leading `./` and a trailing `/`, keeping
+/// virtual segments.
+fn normalize_location(location: &str) -> String {
+    let segments: Vec<&str> = location
+        .split('/')
+        .filter(|s| !s.is_empty() && *s != ".")
+        .collect();
+    if segments.is_empty() {
+        return String::new();
+    }
+    let mut location = segments.join("/");
+    location.push('/');
+    location
+}
+
+/// Resolves `__virtual__/<hash>/<depth>/<path>` segments, which Yarn uses to
+/// create multiple instances of packages with peer dependencies, to the
+/// physical location and normalizes it like [normalize_location].
+fn resolve_virtual(location: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+    let mut parts = location.split('/').filter(|s| !s.is_empty() && *s != ".");
+    while let Some(part) = parts.next() {
+        match part {
+            "__virtual__" | "$$virtual" => {
+                let _hash = parts.next();
+                let depth = parts
+                    .next()
+                    .and_then(|d| d.parse::<usize>().ok())
+                    .unwrap_or(0);
+                for _ in 0..depth {
+                    segments.pop();
+                }
+            }
+            ".." if segments.last().map_or(false, |s| *s != "..") => {
+                segments.pop();
+            }
+            part => segments.push(part),
+        }
+    }
+    if segments.is_empty() {
+        return String::new();
+    }
+    let mut location = segments.join("/");
+    location.push('/');
+    location
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{extract_inlined_state, is_virtual, resolve_virtual, PnpManifest};
+
+    fn manifest() -> PnpManifest {
+        PnpManifest::from_json(&json!({
+            "enableTopLevelFallback": true,
+            "fallbackPool": [["fallback", "npm:1.0.0"]],
+            "packageRegistryData": [
+                [null, [[null, {
+                    "packageLocation": "./",
+                    "packageDependencies": [
+                        ["app-a", "workspace:packages/app-a"],
+                        ["app-b", "workspace:packages/app-b"],
+                        ["top-level", "npm:1.0.0"],
+                    ],
+                }]]],
+                ["app-a", [["workspace:packages/app-a", {
+                    "packageLocation": "./packages/app-a/",
+                    "packageDependencies": [
+                        ["react", "npm:17.0.0"],
+                        ["ui", "virtual:aaa#npm:1.0.0"],
+                        ["alias", ["ui", "npm:1.0.0"]],
+                    ],
+                }]]],
+                ["app-b", [["workspace:packages/app-b", {
+                    "packageLocation": "./packages/app-b/",
+                    "packageDependencies": [
+                        ["react", "npm:18.0.0"],
+                        ["ui", "virtual:bbb#npm:1.0.0"],
+                    ],
+                }]]],
+                ["react", [
+                    ["npm:17.0.0", {
+                        "packageLocation": "./.yarn/cache/react-npm-17.0.0.zip/node_modules/react/",
+                        "packageDependencies": [],
+                    }],
+                    ["npm:18.0.0", {
+                        "packageLocation": "./.yarn/cache/react-npm-18.0.0.zip/node_modules/react/",
+                        "packageDependencies": [],
+                    }],
+                ]],
+                ["ui", [
+                    ["npm:1.0.0", {
+                        "packageLocation": "./.yarn/cache/ui-npm-1.0.0.zip/node_modules/ui/",
+                        "packageDependencies": [["react", null]],
+                    }],
+                    ["virtual:aaa#npm:1.0.0", {
+                        "packageLocation":
+                            "./.yarn/__virtual__/ui-virtual-aaa/0/cache/ui-npm-1.0.0.zip/node_modules/ui/",
+                        "packageDependencies": [["react", "npm:17.0.0"]],
+                    }],
+                    ["virtual:bbb#npm:1.0.0", {
+                        "packageLocation":
+                            "./.yarn/__virtual__/ui-virtual-bbb/0/cache/ui-npm-1.0.0.zip/node_modules/ui/",
+                        "packageDependencies": [["react", "npm:18.0.0"]],
+                    }],
+                ]],
+                ["top-level", [["npm:1.0.0", {
+                    "packageLocation": "./.yarn/cache/top-level-npm-1.0.0.zip/node_modules/top-level/",
+                    "packageDependencies": [],
+                }]]],
+                ["fallback", [["npm:1.0.0", {
+                    "packageLocation": "./.yarn/cache/fallback-npm-1.0.0.zip/node_modules/fallback/",
+                    "packageDependencies": [],
+                }]]],
+            ],
+        }))
+    }
+
+    #[test]
+    fn manifest_parsing() {
+        let manifest = manifest();
+        assert!(manifest.enable_top_level_fallback);
+        assert_eq!(
+            manifest.fallback_pool.get("fallback"),
+            Some(&("fallback".to_string(), "npm:1.0.0".to_string()))
+        );
+        let ui = manifest.package("ui", "virtual:aaa#npm:1.0.0").unwrap();
+        assert_eq!(
+            ui.location,
+            ".yarn/__virtual__/ui-virtual-aaa/0/cache/ui-npm-1.0.0.zip/node_modules/ui/"
+        );
+        assert_eq!(
+            ui.dependencies.get("react"),
+            Some(&Some(("react".to_string(), "npm:17.0.0".to_string())))
+        );
+        let app_a = manifest
+            .package("app-a", "workspace:packages/app-a")
+            .unwrap();
+        assert_eq!(
+            app_a.dependencies.get("alias"),
+            Some(&Some(("ui".to_string(), "npm:1.0.0".to_string())))
+        );
+        assert_eq!(manifest.package("", "").unwrap().location, "");
+    }
+
+    #[test]
+    fn peer_dependencies_of_virtual_instances() {
+        let manifest = manifest();
+        let ui_a = manifest.resolve("packages/app-a/src", "ui").unwrap();
+        let ui_b = manifest.resolve("packages/app-b", "ui").unwrap();
+        assert_eq!(
+            ui_a,
+            ".yarn/__virtual__/ui-virtual-aaa/0/cache/ui-npm-1.0.0.zip/node_modules/ui/"
+        );
+        assert_eq!(
+            ui_b,
+            ".yarn/__virtual__/ui-virtual-bbb/0/cache/ui-npm-1.0.0.zip/node_modules/ui/"
+        );
+        // Each instance resolves its peer dependency to the version of its
+        // dependent.
+        assert_eq!(
+            manifest.resolve(&format!("{ui_a}dist"), "react"),
+            Some(".yarn/cache/react-npm-17.0.0.zip/node_modules/react/")
+        );
+        assert_eq!(
+            manifest.resolve(ui_b.trim_end_matches('/'), "react"),
+            Some(".yarn/cache/react-npm-18.0.0.zip/node_modules/react/")
+        );
+        // The non-virtual instance has an unfulfilled peer dependency.
+        assert_eq!(
+            manifest.resolve(".yarn/cache/ui-npm-1.0.0.zip/node_modules/ui", "react"),
+            None
+        );
+    }
+
+    #[test]
+    fn fallbacks() {
+        let manifest = manifest();
+        assert_eq!(
+            manifest.resolve("packages/app-a", "top-level"),
+            Some(".yarn/cache/top-level-npm-1.0.0.zip/node_modules/top-level/")
+        );
+        assert_eq!(
+            manifest.resolve("packages/app-a", "fallback"),
+            Some(".yarn/cache/fallback-npm-1.0.0.zip/node_modules/fallback/")
+        );
+        assert_eq!(manifest.resolve("packages/app-a", "unknown"), None);
+    }
+
+    #[test]
+    fn inlined_state() {
+        let content = r#"#!/usr/bin/env node
+/* eslint-disable */
+"use strict";
+
+function $$SETUP_STATE(hydrateRuntimeState, basePath) {
+  return hydrateRuntimeState(JSON.parse(RAW_RUNTIME_STATE), {basePath: basePath || __dirname});
+}
+const RAW_RUNTIME_STATE =
+'{\
+  "__info": ["This file is automatically generated."],\
+  "packageRegistryData": [],\
+  "quote": "it\'s \\"quoted\\""\
+}';
+"#;
+        let state = extract_inlined_state(content).unwrap();
+        let state: serde_json::Value = serde_json::from_str(&state).unwrap();
+        assert_eq!(state["packageRegistryData"], json!([]));
+        assert_eq!(state["quote"], "it's \"quoted\"");
+        assert_eq!(extract_inlined_state("module.exports = {};"), None);
+        assert_eq!(
+            extract_inlined_state("const RAW_RUNTIME_STATE = 'abc"),
+            None
+        );
+    }
+
+    #[test]
+    fn virtual_locations() {
+        assert_eq!(
+            resolve_virtual(
+                "./.yarn/__virtual__/ui-virtual-aaa/0/cache/ui-npm-1.0.0.zip/node_modules/ui/"
+            ),
+            ".yarn/cache/ui-npm-1.0.0.zip/node_modules/ui/"
+        );
+        assert_eq!(
+            resolve_virtual("./.yarn/__virtual__/ui-virtual-aaa/1/packages/ui/"),
+            "packages/ui/"
+        );
+        assert_eq!(
+            resolve_virtual("./packages/a/$$virtual/ui-virtual-aaa/1/b/"),
+            "packages/b/"
+        );
+        assert_eq!(resolve_virtual("./packages/a/../b"), "packages/b/");
+        assert_eq!(resolve_virtual("./"), "");
+        assert!(is_virtual("./.yarn/__virtual__/ui-virtual-aaa/0/cache/"));
+        assert!(!is_virtual(
+            "./.yarn/cache/ui-npm-1.0.0.zip/node_modules/ui/"
+        ));
+    }
+}
@@ -11,7 +11,7 @@ pub mod embed;
 mod module_asset;
 pub(crate) mod parse;
 mod path_visitor;
-pub(crate) mod references;
+pub mod references;
 pub(crate) mod transform;
 pub(crate) mod util;
 
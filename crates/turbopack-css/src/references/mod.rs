@@ -27,8 +27,8 @@ use crate::{
     CssInputTransformsVc, CssModuleAssetType,
 };
 
-pub(crate) mod import;
-pub(crate) mod url;
+pub mod import;
+pub mod url;
 
 #[turbo_tasks::function]
 pub async fn analyze_css_stylesheet(
@@ -6,6 +6,8 @@ pub mod fs;
 pub mod html;
 pub mod introspect;
 pub mod source;
+#[cfg(feature = "tls")]
+pub mod tls;
 pub mod update;
 
 use std::{
@@ -25,11 +27,10 @@ use anyhow::{bail, Context, Result};
 use futures::{StreamExt, TryStreamExt};
 use hyper::{
     header::HeaderName,
-    server::{conn::AddrIncoming, Builder},
+    server::conn::AddrIncoming,
     service::{make_service_fn, service_fn},
     Request, Response, Server,
 };
-use mime_guess::mime;
 use source::{Body, Bytes};
 use turbo_tasks::{
     run_once, trace::TraceRawVcs, util::FormatDuration, RawVc, TransientValue, TurboTasksApi, Value,
@@ -40,8 +41,8 @@ use turbopack_core::asset::AssetContent;
 
 use self::{
     source::{
-        query::Query, ContentSourceContent, ContentSourceDataVary, ContentSourceResultVc,
-        ContentSourceVc, ProxyResultReadRef,
+        query::Query, static_content_type, ContentSourceContent, ContentSourceDataVary,
+        ContentSourceResultVc, ContentSourceVc, ProxyResultReadRef,
     },
     update::{protocol::ResourceIdentifier, UpdateServer},
 };
@@ -70,13 +71,18 @@ pub struct DevServerBuilder {
     #[turbo_tasks(trace_ignore)]
     pub addr: SocketAddr,
     #[turbo_tasks(trace_ignore)]
-    server: Builder<AddrIncoming>,
+    incoming: AddrIncoming,
+    #[cfg(feature = "tls")]
+    #[turbo_tasks(trace_ignore)]
+    tls_acceptor: Option<tls::ConfiguredTlsAcceptor>,
 }
 
 #[derive(TraceRawVcs)]
 pub struct DevServer {
     #[turbo_tasks(trace_ignore)]
     pub addr: SocketAddr,
+    /// Whether the server is serving HTTPS (and `wss://` for HMR).
+    pub https: bool,
     #[turbo_tasks(trace_ignore)]
     pub future: Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>,
 }
@@ -156,25 +162,7 @@ async fn process_request_with_content_source(
         match &*content_source_result.strongly_consistent().await? {
             GetFromSourceResult::Static(file) => {
                 if let FileContent::Content(content) = &**file {
-                    let content_type = content.content_type().map_or_else(
-                        || {
-                            let guess =
-                                mime_guess::from_path(asset_path.as_ref()).first_or_octet_stream();
-                            // If a text type, application/javascript, or application/json was
-                            // guessed, use a utf-8 charset as  we most likely generated it as
-                            // such.
-                            if (guess.type_() == mime::TEXT
-                                || guess.subtype() == mime::JAVASCRIPT
-                                || guess.subtype() == mime::JSON)
-                                && guess.get_param("charset").is_none()
-                            {
-                                guess.to_string() + "; charset=utf-8"
-                            } else {
-                                guess.to_string()
-                            }
-                        },
-                        |m| m.to_string(),
-                    );
+                    let content_type = static_content_type(&asset_path, content);
 
                     let content = content.content();
                     let bytes = content.read();
@@ -223,115 +211,151 @@ impl DevServer {
             .local_addr()
             .context("not able to get bound address")?;
 
-        let server = Server::from_tcp(listener).context("Not able to start server")?;
-        Ok(DevServerBuilder { addr, server })
+        listener
+            .set_nonblocking(true)
+            .context("Not able to start server")?;
+        let listener =
+            tokio::net::TcpListener::from_std(listener).context("Not able to start server")?;
+        let incoming = AddrIncoming::from_listener(listener).context("Not able to start server")?;
+        Ok(DevServerBuilder {
+            addr,
+            incoming,
+            #[cfg(feature = "tls")]
+            tls_acceptor: None,
+        })
     }
 }
 
 impl DevServerBuilder {
+    /// Serves HTTPS instead of plain HTTP. The certificate is loaded (or
+    /// generated) right away so that errors are reported before the server
+    /// starts.
+    #[cfg(feature = "tls")]
+    pub fn tls(mut self, config: &tls::TlsConfig) -> Result<Self> {
+        self.tls_acceptor = Some(config.acceptor()?);
+        Ok(self)
+    }
+
     pub fn serve(
         self,
         turbo_tasks: Arc<dyn TurboTasksApi>,
         source_provider: impl SourceProvider + Clone + Send + Sync,
         console_ui: Arc<ConsoleUi>,
     ) -> DevServer {
-        let make_svc = make_service_fn(move |_| {
+        // The handler doesn't depend on the kind of connection, but
+        // `make_service_fn` is typed over it, so we create it per connection.
+        let make_handler = move || {
             let tt = turbo_tasks.clone();
             let source_provider = source_provider.clone();
             let console_ui = console_ui.clone();
-            async move {
-                let handler = move |request: Request<hyper::Body>| {
-                    let console_ui = console_ui.clone();
-                    let start = Instant::now();
-                    let tt = tt.clone();
-                    let source_provider = source_provider.clone();
-                    let future = async move {
-                        if hyper_tungstenite::is_upgrade_request(&request) {
-                            let uri = request.uri();
-                            let path = uri.path();
-
-                            if path == "/turbopack-hmr" {
-                                let (response, websocket) =
-                                    hyper_tungstenite::upgrade(request, None)?;
-                                let update_server = UpdateServer::new(source_provider);
-                                update_server.run(&*tt, websocket);
-                                return Ok(response);
-                            }
-
-                            println!("[404] {} (WebSocket)", path);
-                            if path == "/_next/webpack-hmr" {
-                                // Special-case requests to webpack-hmr as these are made by Next.js
-                                // clients built without turbopack, which may be making requests in
-                                // development.
-                                println!("A non-turbopack next.js client is trying to connect.");
-                                println!(
-                                    "Make sure to reload/close any browser window which has been \
-                                     opened without --turbo."
-                                );
-                            }
-
-                            return Ok(Response::builder()
-                                .status(404)
-                                .body(hyper::Body::empty())?);
+            move |request: Request<hyper::Body>| {
+                let console_ui = console_ui.clone();
+                let start = Instant::now();
+                let tt = tt.clone();
+                let source_provider = source_provider.clone();
+                let future = async move {
+                    if hyper_tungstenite::is_upgrade_request(&request) {
+                        let uri = request.uri();
+                        let path = uri.path();
+
+                        if path == "/turbopack-hmr" {
+                            let (response, websocket) = hyper_tungstenite::upgrade(request, None)?;
+                            let update_server = UpdateServer::new(source_provider);
+                            update_server.run(&*tt, websocket);
+                            return Ok(response);
                         }
 
-                        run_once(tt, async move {
-                            let console_ui = (*console_ui).clone().cell();
-                            let uri = request.uri();
-                            let path = uri.path();
-                            // Remove leading slash.
-                            let path = &path[1..].to_string();
-                            let asset_path = urlencoding::decode(path)?;
-                            let source = source_provider.get_source();
-                            handle_issues(source, path, "get source", console_ui).await?;
-                            let resolved_source = source.resolve_strongly_consistent().await?;
-                            let response = process_request_with_content_source(
-                                path,
-                                resolved_source,
-                                asset_path,
-                                request,
-                                console_ui,
-                            )
-                            .await?;
-                            let status = response.status().as_u16();
-                            let success = response.status().is_success();
-                            let elapsed = start.elapsed();
-                            if !success
-                                || (cfg!(feature = "log_request_stats")
-                                    && elapsed > Duration::from_secs(1))
-                            {
-                                println!(
-                                    "[{status}] /{path} ({duration})",
-                                    duration = FormatDuration(elapsed)
-                                );
-                            }
-                            Ok(response)
-                        })
-                        .await
-                    };
-                    async move {
-                        match future.await {
-                            Ok(r) => Ok::<_, hyper::http::Error>(r),
-                            Err(e) => {
-                                println!(
-                                    "[500] error: {:?} ({})",
-                                    e,
-                                    FormatDuration(start.elapsed())
-                                );
-                                Ok(Response::builder()
-                                    .status(500)
-                                    .body(hyper::Body::from(format!("{:?}", e,)))?)
-                            }
+                        println!("[404] {} (WebSocket)", path);
+                        if path == "/_next/webpack-hmr" {
+                            // Special-case requests to webpack-hmr as these are made by Next.js
+                            // clients built without turbopack, which may be making requests in
+                            // development.
+                            println!("A non-turbopack next.js client is trying to connect.");
+                            println!(
+                                "Make sure to reload/close any browser window which has been \
+                                 opened without --turbo."
+                            );
                         }
+
+                        return Ok(Response::builder().status(404).body(hyper::Body::empty())?);
                     }
+
+                    run_once(tt, async move {
+                        let console_ui = (*console_ui).clone().cell();
+                        let uri = request.uri();
+                        let path = uri.path();
+                        // Remove leading slash.
+                        let path = &path[1..].to_string();
+                        let asset_path = urlencoding::decode(path)?;
+                        let source = source_provider.get_source();
+                        handle_issues(source, path, "get source", console_ui).await?;
+                        let resolved_source = source.resolve_strongly_consistent().await?;
+                        let response = process_request_with_content_source(
+                            path,
+                            resolved_source,
+                            asset_path,
+                            request,
+                            console_ui,
+                        )
+                        .await?;
+                        let status = response.status().as_u16();
+                        let success = response.status().is_success();
+                        let elapsed = start.elapsed();
+                        if !success
+                            || (cfg!(feature = "log_request_stats")
+                                && elapsed > Duration::from_secs(1))
+                        {
+                            println!(
+                                "[{status}] /{path} ({duration})",
+                                duration = FormatDuration(elapsed)
+                            );
+                        }
+                        Ok(response)
+                    })
+                    .await
                 };
-                anyhow::Ok(service_fn(handler))
+                async move {
+                    match future.await {
+                        Ok(r) => Ok::<_, hyper::http::Error>(r),
+                        Err(e) => {
+                            println!("[500] error: {:?} ({})", e, FormatDuration(start.elapsed()));
+                            Ok(Response::builder()
+                                .status(500)
+                                .body(hyper::Body::from(format!("{:?}", e,)))?)
+                        }
+                    }
+                }
             }
+        };
+
+        #[cfg(feature = "tls")]
+        if let Some(tls_acceptor) = self.tls_acceptor {
+            let make_svc = make_service_fn(move |_| {
+                let handler = make_handler();
+                async move { anyhow::Ok(service_fn(handler)) }
+            });
+            let server =
+                Server::builder(tls::tls_incoming(self.incoming, tls_acceptor)).serve(make_svc);
+
+            return DevServer {
+                addr: self.addr,
+                https: true,
+                future: Box::pin(async move {
+                    server.await?;
+                    Ok(())
+                }),
+            };
+        }
+
+        let make_svc = make_service_fn(move |_| {
+            let handler = make_handler();
+            async move { anyhow::Ok(service_fn(handler)) }
         });
-        let server = self.server.serve(make_svc);
+        let server = Server::builder(self.incoming).serve(make_svc);
 
         DevServer {
             addr: self.addr,
+            https: false,
             future: Box::pin(async move {
                 server.await?;
                 Ok(())
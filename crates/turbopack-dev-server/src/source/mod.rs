@@ -2,6 +2,7 @@ pub mod asset_graph;
 pub mod combined;
 pub mod conditional;
 pub mod lazy_instatiated;
+pub mod prefixed;
 pub mod query;
 pub mod router;
 pub mod source_maps;
@@ -15,9 +16,10 @@ use std::{
 };
 
 use anyhow::Result;
+use mime_guess::mime;
 use serde::{Deserialize, Serialize, Serializer};
 use turbo_tasks::{trace::TraceRawVcs, Value};
-use turbo_tasks_fs::rope::Rope;
+use turbo_tasks_fs::{rope::Rope, File};
 use turbopack_core::version::VersionedContentVc;
 
 use self::{query::Query, specificity::SpecificityVc};
@@ -33,6 +35,29 @@ pub struct ProxyResult {
     pub body: Rope,
 }
 
+/// The `Content-Type` a [ContentSourceContent::Static] file is served with.
+/// Unless the file has a content type, it's guessed from the `path`.
+pub fn static_content_type(path: &str, file: &File) -> String {
+    file.content_type().map_or_else(
+        || {
+            let guess = mime_guess::from_path(path).first_or_octet_stream();
+            // If a text type, application/javascript, or application/json was
+            // guessed, use a utf-8 charset as  we most likely generated it as
+            // such.
+            if (guess.type_() == mime::TEXT
+                || guess.subtype() == mime::JAVASCRIPT
+                || guess.subtype() == mime::JSON)
+                && guess.get_param("charset").is_none()
+            {
+                guess.to_string() + "; charset=utf-8"
+            } else {
+                guess.to_string()
+            }
+        },
+        |m| m.to_string(),
+    )
+}
+
 /// The return value of a content source when getting a path. A specificity is
 /// attached and when combining results this specificity should be used to order
 /// results.
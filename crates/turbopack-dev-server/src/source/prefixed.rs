@@ -0,0 +1,58 @@
+use anyhow::Result;
+use turbo_tasks::{primitives::StringVc, Value};
+use turbopack_core::introspect::{Introspectable, IntrospectableChildrenVc, IntrospectableVc};
+
+use super::{ContentSource, ContentSourceData, ContentSourceResultVc, ContentSourceVc};
+use crate::source::ContentSourcesVc;
+
+/// Prepends `prefix` to the path before passing it to `source`. Used as a
+/// route of a [RouterContentSource](super::router::RouterContentSource) to
+/// undo the stripping of the route prefix for a source that expects the full
+/// path.
+#[turbo_tasks::value(shared)]
+pub struct PrefixedContentSource {
+    pub prefix: String,
+    pub source: ContentSourceVc,
+}
+
+#[turbo_tasks::value_impl]
+impl ContentSource for PrefixedContentSource {
+    #[turbo_tasks::function]
+    fn get(&self, path: &str, data: Value<ContentSourceData>) -> ContentSourceResultVc {
+        self.source.get(&format!("{}{}", self.prefix, path), data)
+    }
+
+    #[turbo_tasks::function]
+    fn get_children(&self) -> ContentSourcesVc {
+        ContentSourcesVc::cell(vec![self.source])
+    }
+}
+
+#[turbo_tasks::function]
+fn introspectable_type() -> StringVc {
+    StringVc::cell("prefixed content source".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl Introspectable for PrefixedContentSource {
+    #[turbo_tasks::function]
+    fn ty(&self) -> StringVc {
+        introspectable_type()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(self.prefix.clone())
+    }
+
+    #[turbo_tasks::function]
+    async fn children(&self) -> Result<IntrospectableChildrenVc> {
+        Ok(IntrospectableChildrenVc::cell(
+            IntrospectableVc::resolve_from(self.source)
+                .await?
+                .map(|i| (StringVc::cell("source".to_string()), i))
+                .into_iter()
+                .collect(),
+        ))
+    }
+}
@@ -0,0 +1,202 @@
+use std::{
+    fmt, fs,
+    io::{self, BufReader, Write},
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+};
+
+use anyhow::{bail, Context, Result};
+use futures::{stream, StreamExt};
+use hyper::server::{
+    accept::{self, Accept},
+    conn::{AddrIncoming, AddrStream},
+};
+use rustls_pemfile::Item;
+use tokio_rustls::{
+    rustls::{Certificate, PrivateKey, ServerConfig},
+    server::TlsStream,
+    TlsAcceptor,
+};
+use turbo_tasks_hash::{encode_hex, Xxh3Hash64Hasher};
+
+/// How many TLS handshakes are allowed to be in flight at once. This prevents
+/// a slow client from blocking other connections from being accepted.
+const MAX_CONCURRENT_HANDSHAKES: usize = 64;
+
+/// Where the certificate used by the dev server comes from.
+#[derive(Debug, Clone)]
+pub enum TlsCertificate {
+    /// A PEM-encoded certificate chain and private key supplied by the user.
+    Files { cert: PathBuf, key: PathBuf },
+    /// A self-signed certificate for the given hostnames. It's generated on
+    /// first use and cached in `cache_dir`, so browsers only need to trust it
+    /// once.
+    SelfSigned {
+        cache_dir: PathBuf,
+        hostnames: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub certificate: TlsCertificate,
+    /// Advertises HTTP/2 via ALPN. HTTP/1.1 is always supported, which is
+    /// also what browsers use for the websocket HMR connection.
+    pub http2: bool,
+}
+
+impl TlsConfig {
+    pub fn new(certificate: TlsCertificate) -> Self {
+        TlsConfig {
+            certificate,
+            http2: true,
+        }
+    }
+
+    pub(crate) fn acceptor(&self) -> Result<ConfiguredTlsAcceptor> {
+        Ok(ConfiguredTlsAcceptor {
+            config: self.clone(),
+            acceptor: TlsAcceptor::from(Arc::new(self.server_config()?)),
+        })
+    }
+
+    fn server_config(&self) -> Result<ServerConfig> {
+        let (cert_pem, key_pem) = match &self.certificate {
+            TlsCertificate::Files { cert, key } => (
+                fs::read(cert)
+                    .with_context(|| format!("unable to read certificate {}", cert.display()))?,
+                fs::read(key)
+                    .with_context(|| format!("unable to read private key {}", key.display()))?,
+            ),
+            TlsCertificate::SelfSigned {
+                cache_dir,
+                hostnames,
+            } => self_signed_certificate(cache_dir, hostnames)?,
+        };
+
+        let certs = rustls_pemfile::certs(&mut BufReader::new(&*cert_pem))
+            .context("unable to parse certificate")?
+            .into_iter()
+            .map(Certificate)
+            .collect::<Vec<_>>();
+        if certs.is_empty() {
+            bail!("no certificate found in the certificate file");
+        }
+        let key = rustls_pemfile::read_all(&mut BufReader::new(&*key_pem))
+            .context("unable to parse private key")?
+            .into_iter()
+            .find_map(|item| match item {
+                Item::RSAKey(key) | Item::PKCS8Key(key) | Item::ECKey(key) => Some(PrivateKey(key)),
+                _ => None,
+            })
+            .context("no private key found in the private key file")?;
+
+        let mut config = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .context("invalid certificate or private key")?;
+        config.alpn_protocols = if self.http2 {
+            vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+        } else {
+            vec![b"http/1.1".to_vec()]
+        };
+        Ok(config)
+    }
+}
+
+/// A [TlsAcceptor] together with the [TlsConfig] it was created from.
+#[derive(Clone)]
+pub(crate) struct ConfiguredTlsAcceptor {
+    config: TlsConfig,
+    acceptor: TlsAcceptor,
+}
+
+impl fmt::Debug for ConfiguredTlsAcceptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfiguredTlsAcceptor")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Reads a cached self-signed certificate or generates and caches a new one.
+/// Returns the PEM-encoded certificate and private key.
+///
+/// Certificates are cached per set of hostnames, so changing the hostnames
+/// generates a new certificate instead of serving one which doesn't cover
+/// them.
+fn self_signed_certificate(cache_dir: &Path, hostnames: &[String]) -> Result<(Vec<u8>, Vec<u8>)> {
+    let mut hostnames = hostnames.to_vec();
+    hostnames.sort();
+    hostnames.dedup();
+    let mut hasher = Xxh3Hash64Hasher::new();
+    hasher.write_ref(&hostnames);
+    let hash = encode_hex(hasher.finish());
+    let cert_path = cache_dir.join(format!("localhost-{hash}.pem"));
+    let key_path = cache_dir.join(format!("localhost-{hash}-key.pem"));
+    if let (Ok(cert), Ok(key)) = (fs::read(&cert_path), fs::read(&key_path)) {
+        return Ok((cert, key));
+    }
+
+    let cert = rcgen::generate_simple_self_signed(hostnames)
+        .context("unable to generate a self-signed certificate")?;
+    let cert_pem = cert
+        .serialize_pem()
+        .context("unable to serialize the self-signed certificate")?;
+    let key_pem = cert.serialize_private_key_pem();
+
+    fs::create_dir_all(cache_dir)
+        .with_context(|| format!("unable to create {}", cache_dir.display()))?;
+    fs::write(&cert_path, &cert_pem)
+        .with_context(|| format!("unable to write {}", cert_path.display()))?;
+    write_private_key(&key_path, &key_pem)
+        .with_context(|| format!("unable to write {}", key_path.display()))?;
+    println!(
+        "Generated a self-signed certificate at {}. You might need to add it to the trusted \
+         certificates of your system or browser.",
+        cert_path.display()
+    );
+
+    Ok((cert_pem.into_bytes(), key_pem.into_bytes()))
+}
+
+/// Writes a private key file which is only readable and writable by the
+/// current user.
+fn write_private_key(path: &Path, key_pem: &str) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // The mode only applies to newly created files.
+        if path.exists() {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    options.open(path)?.write_all(key_pem.as_bytes())
+}
+
+/// Wraps the plain TCP connections accepted by `incoming` into TLS streams.
+/// Connections which fail the TLS handshake are dropped without affecting the
+/// server.
+pub(crate) fn tls_incoming(
+    mut incoming: AddrIncoming,
+    ConfiguredTlsAcceptor { acceptor, .. }: ConfiguredTlsAcceptor,
+) -> impl Accept<Conn = TlsStream<AddrStream>, Error = io::Error> {
+    let connections = stream::poll_fn(move |cx| Pin::new(&mut incoming).poll_accept(cx))
+        .filter_map(|conn| async move {
+            conn.map_err(|err| println!("[TLS] failed to accept connection: {err}"))
+                .ok()
+        })
+        .map(move |conn| acceptor.accept(conn))
+        .buffer_unordered(MAX_CONCURRENT_HANDSHAKES)
+        .filter_map(|conn| async move {
+            conn.map_err(|err| println!("[TLS] handshake failed: {err}"))
+                .ok()
+                .map(Ok::<_, io::Error>)
+        });
+    accept::from_stream(connections)
+}
@@ -143,6 +143,7 @@ pub fn create_graph(m: &Program, eval_context: &EvalContext) -> VarGraph {
             var_decl_kind: Default::default(),
             current_value: Default::default(),
             cur_fn_return_values: Default::default(),
+            worker_url_span: Default::default(),
         },
         &mut Default::default(),
     );
@@ -308,6 +309,11 @@ impl EvalContext {
 
             Expr::New(..) => JsValue::Unknown(None, "unknown new expression"),
 
+            Expr::MetaProp(MetaPropExpr {
+                kind: MetaPropKind::ImportMeta,
+                ..
+            }) => JsValue::FreeVar(FreeVarKind::ImportMeta),
+
             Expr::Seq(e) => {
                 if let Some(e) = e.exprs.last() {
                     self.eval(e)
@@ -444,6 +450,34 @@ struct Analyzer<'a> {
     /// This is configured to [Some] by function handlers and filled by the
     /// return statement handler.
     cur_fn_return_values: Option<Vec<JsValue>>,
+
+    /// The span of the `new URL(...)` passed to a worker constructor which is
+    /// currently visited. It's already handled by the worker's effect and must
+    /// not create a separate [Effect::Url].
+    worker_url_span: Option<Span>,
+}
+
+/// Returns the first argument of a `new URL("path", import.meta.url)`
+/// expression.
+fn new_url_with_import_meta_url(new_expr: &NewExpr, unresolved_mark: Mark) -> Option<&Expr> {
+    let NewExpr { callee: box Expr::Ident(callee), args: Some(args), .. } = new_expr else {
+        return None;
+    };
+    if &*callee.sym != "URL" || !is_unresolved(callee, unresolved_mark) || args.len() != 2 {
+        return None;
+    }
+    match &*args[1].expr {
+        Expr::Member(MemberExpr {
+            obj:
+                box Expr::MetaProp(MetaPropExpr {
+                    kind: MetaPropKind::ImportMeta,
+                    ..
+                }),
+            prop: MemberProp::Ident(prop),
+            ..
+        }) if &*prop.sym == "url" => Some(&args[0].expr),
+        _ => None,
+    }
 }
 
 pub fn as_parent_path(ast_path: &AstNodePath<AstParentNodeRef<'_>>) -> Vec<AstParentKind> {
@@ -825,31 +859,51 @@ impl VisitAstPath for Analyzer<'_> {
         new_expr: &'ast NewExpr,
         ast_path: &mut AstNodePath<AstParentNodeRef<'r>>,
     ) {
-        // new URL("path", import.meta.url)
+        let unresolved_mark = self.eval_context.unresolved_mark;
         if let box Expr::Ident(ref callee) = &new_expr.callee {
-            if &*callee.sym == "URL" && is_unresolved(callee, self.eval_context.unresolved_mark) {
-                if let Some(args) = &new_expr.args {
-                    if args.len() == 2 {
-                        if let Expr::Member(MemberExpr {
-                            obj:
-                                box Expr::MetaProp(MetaPropExpr {
-                                    kind: MetaPropKind::ImportMeta,
-                                    ..
-                                }),
-                            prop: MemberProp::Ident(prop),
-                            ..
-                        }) = &*args[1].expr
+            match &*callee.sym {
+                // new URL("path", import.meta.url)
+                "URL" if is_unresolved(callee, unresolved_mark) => {
+                    if self.worker_url_span != Some(new_expr.span()) {
+                        if let Some(input) = new_url_with_import_meta_url(new_expr, unresolved_mark)
                         {
-                            if &*prop.sym == "url" {
-                                self.data.effects.push(Effect::Url {
-                                    input: self.eval_context.eval(&args[0].expr),
+                            self.data.effects.push(Effect::Url {
+                                input: self.eval_context.eval(input),
+                                ast_path: as_parent_path(ast_path),
+                                span: new_expr.span(),
+                            });
+                        }
+                    }
+                }
+                // new Worker(new URL("path", import.meta.url), options)
+                "Worker" | "SharedWorker" if is_unresolved(callee, unresolved_mark) => {
+                    if let Some(args) = &new_expr.args {
+                        if let Some((first, rest)) = args.split_first() {
+                            let input = match (&*first.expr, first.spread) {
+                                (Expr::New(url), None) => {
+                                    new_url_with_import_meta_url(url, unresolved_mark)
+                                }
+                                _ => None,
+                            };
+                            if let Some(input) = input {
+                                let args = iter::once(self.eval_context.eval(input))
+                                    .chain(rest.iter().map(|arg| self.eval_context.eval(&arg.expr)))
+                                    .collect();
+                                self.data.effects.push(Effect::Call {
+                                    func: self.eval_context.eval(&new_expr.callee),
+                                    args,
                                     ast_path: as_parent_path(ast_path),
                                     span: new_expr.span(),
                                 });
+                                let old = self.worker_url_span.replace(first.expr.span());
+                                new_expr.visit_children_with_path(self, ast_path);
+                                self.worker_url_span = old;
+                                return;
                             }
                         }
                     }
                 }
+                _ => {}
             }
         }
         new_expr.visit_children_with_path(self, ast_path);
@@ -879,6 +879,10 @@ impl JsValue {
                         "require.cache",
                         "The CommonJS require.cache object: https://nodejs.org/api/modules.html#requirecache"
                     ),
+                    WellKnownObjectKind::ImportMeta => (
+                        "import.meta",
+                        "The import.meta object from the ESM specification: https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Operators/import.meta"
+                    ),
                 };
                 if depth > 0 {
                     let i = hints.len();
@@ -912,6 +916,18 @@ impl JsValue {
                     ),
                     WellKnownFunctionKind::Require => ("require".to_string(), "The require method from CommonJS"),
                     WellKnownFunctionKind::RequireResolve => ("require.resolve".to_string(), "The require.resolve method from CommonJS"),
+                    WellKnownFunctionKind::RequireContext => (
+                        "require.context".to_string(),
+                        "The require.context method from webpack: https://webpack.js.org/api/module-methods/#requirecontext"
+                    ),
+                    WellKnownFunctionKind::ImportMetaGlob => (
+                        "import.meta.glob".to_string(),
+                        "The import.meta.glob method from Vite: https://vitejs.dev/guide/features.html#glob-import"
+                    ),
+                    WellKnownFunctionKind::WorkerConstructor(name) => (
+                        format!("new {name}"),
+                        "A Web Worker constructor: https://developer.mozilla.org/en-US/docs/Web/API/Web_Workers_API",
+                    ),
                     WellKnownFunctionKind::Define => ("define".to_string(), "The define method from AMD"),
                     WellKnownFunctionKind::FsReadMethod(name) => (
                         format!("fs.{name}"),
@@ -1450,7 +1466,8 @@ impl JsValue {
                 | FreeVarKind::Require
                 | FreeVarKind::Define
                 | FreeVarKind::Import
-                | FreeVarKind::NodeProcess,
+                | FreeVarKind::NodeProcess
+                | FreeVarKind::ImportMeta,
             ) => false,
             JsValue::FreeVar(FreeVarKind::Other(_)) => false,
 
@@ -1856,6 +1873,9 @@ pub enum FreeVarKind {
     /// Node.js process
     NodeProcess,
 
+    /// `import.meta`
+    ImportMeta,
+
     /// `abc` `some_global`
     Other(JsWord),
 }
@@ -1879,6 +1899,7 @@ pub enum WellKnownObjectKind {
     NodeExpressApp,
     NodeProtobufLoader,
     RequireCache,
+    ImportMeta,
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -1891,6 +1912,10 @@ pub enum WellKnownFunctionKind {
     Import,
     Require,
     RequireResolve,
+    RequireContext,
+    ImportMetaGlob,
+    /// The `Worker` or `SharedWorker` constructor.
+    WorkerConstructor(JsWord),
     Define,
     FsReadMethod(JsWord),
     PathToFileUrl,
@@ -363,6 +363,9 @@ pub fn well_known_function_member(kind: WellKnownFunctionKind, prop: JsValue) ->
         (WellKnownFunctionKind::Require, Some("cache")) => {
             JsValue::WellKnownObject(WellKnownObjectKind::RequireCache)
         }
+        (WellKnownFunctionKind::Require, Some("context")) => {
+            JsValue::WellKnownFunction(WellKnownFunctionKind::RequireContext)
+        }
         (WellKnownFunctionKind::NodeStrongGlobalize, Some("SetRootDir")) => {
             JsValue::WellKnownFunction(WellKnownFunctionKind::NodeStrongGlobalizeSetRootDir)
         }
@@ -405,6 +408,7 @@ pub async fn well_known_object_member(
         WellKnownObjectKind::NodePreGyp => node_pre_gyp(prop),
         WellKnownObjectKind::NodeExpressApp => express(prop),
         WellKnownObjectKind::NodeProtobufLoader => protobuf_loader(prop),
+        WellKnownObjectKind::ImportMeta => import_meta(prop),
         #[allow(unreachable_patterns)]
         _ => JsValue::Unknown(
             Some(Arc::new(JsValue::member(
@@ -416,6 +420,19 @@ pub async fn well_known_object_member(
     })
 }
 
+fn import_meta(prop: JsValue) -> JsValue {
+    match prop.as_str() {
+        Some("glob") => JsValue::WellKnownFunction(WellKnownFunctionKind::ImportMetaGlob),
+        _ => JsValue::Unknown(
+            Some(Arc::new(JsValue::member(
+                box JsValue::WellKnownObject(WellKnownObjectKind::ImportMeta),
+                box prop,
+            ))),
+            "unsupported property on import.meta",
+        ),
+    }
+}
+
 fn global_object(prop: JsValue) -> JsValue {
     match prop.as_str() {
         Some("assign") => JsValue::WellKnownFunction(WellKnownFunctionKind::ObjectAssign),
@@ -2,8 +2,11 @@ use std::io::Write as _;
 
 use anyhow::{anyhow, bail, Result};
 use indexmap::IndexSet;
-use turbo_tasks::{primitives::StringVc, ValueToString, ValueToStringVc};
-use turbo_tasks_fs::FileSystemPathVc;
+use turbo_tasks::{
+    primitives::{StringVc, StringsVc},
+    ValueToString, ValueToStringVc,
+};
+use turbo_tasks_fs::{File, FileSystemPathVc};
 use turbopack_core::{
     asset::{Asset, AssetContentVc, AssetVc},
     chunk::{
@@ -165,6 +168,46 @@ impl ManifestChunkAssetVc {
         let chunk_group = ChunkGroupVc::from_asset(this.asset, this.chunking_context);
         Ok(chunk_group.chunks())
     }
+
+    /// The paths of all chunks of the chunk group, relative to the output
+    /// root, which is how the runtime loads them.
+    #[turbo_tasks::function]
+    async fn chunk_server_paths(self) -> Result<StringsVc> {
+        let this = self.await?;
+        let output_root = this.chunking_context.output_root().await?;
+        let mut chunk_server_paths = IndexSet::new();
+        for chunk in self.chunks().await?.iter() {
+            // The "path" in this case is the chunk's path, not the chunk item's path.
+            // The difference is a chunk is a file served by the dev server, and an
+            // item is one of several that are contained in that chunk file.
+            let chunk_path = &*chunk.path().await?;
+            // The pathname is the file path necessary to load the chunk from the server.
+            let chunk_server_path = if let Some(path) = output_root.get_path_to(chunk_path) {
+                path
+            } else {
+                bail!(
+                    "chunk path {} is not in output root {}",
+                    chunk.path().to_string().await?,
+                    this.chunking_context.output_root().to_string().await?
+                );
+            };
+            chunk_server_paths.insert(chunk_server_path.to_string());
+        }
+        Ok(StringsVc::cell(chunk_server_paths.into_iter().collect()))
+    }
+
+    #[turbo_tasks::function]
+    async fn chunk_references(self) -> Result<AssetReferencesVc> {
+        Ok(AssetReferencesVc::cell(
+            self.chunks()
+                .await?
+                .iter()
+                .copied()
+                .map(ChunkReferenceVc::new)
+                .map(Into::into)
+                .collect(),
+        ))
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -175,13 +218,15 @@ impl Asset for ManifestChunkAsset {
     }
 
     #[turbo_tasks::function]
-    fn content(&self) -> AssetContentVc {
-        todo!()
+    async fn content(self_vc: ManifestChunkAssetVc) -> Result<AssetContentVc> {
+        let chunking_context = self_vc.await?.chunking_context;
+        let content = self_vc.as_chunk_item(chunking_context).content().await?;
+        Ok(File::from(content.inner_code.clone()).into())
     }
 
     #[turbo_tasks::function]
-    fn references(&self) -> AssetReferencesVc {
-        todo!()
+    fn references(self_vc: ManifestChunkAssetVc) -> AssetReferencesVc {
+        self_vc.chunk_references()
     }
 }
 
@@ -240,35 +285,17 @@ impl EcmascriptChunkItem for ManifestChunkItem {
 
     #[turbo_tasks::function]
     async fn content(&self) -> Result<EcmascriptChunkItemContentVc> {
-        let chunks = self.manifest.chunks().await?;
-
-        let mut chunk_server_paths = IndexSet::new();
-        for chunk in chunks.iter() {
-            // The "path" in this case is the chunk's path, not the chunk item's path.
-            // The difference is a chunk is a file served by the dev server, and an
-            // item is one of several that are contained in that chunk file.
-            let chunk_path = &*chunk.path().await?;
-            // The pathname is the file path necessary to load the chunk from the server.
-            let output_root = self.context.output_root().await?;
-            let chunk_server_path = if let Some(path) = output_root.get_path_to(chunk_path) {
-                path
-            } else {
-                bail!(
-                    "chunk path {} is not in output root {}",
-                    chunk.path().to_string().await?,
-                    self.context.output_root().to_string().await?
-                );
-            };
-            chunk_server_paths.insert(chunk_server_path.to_string());
-        }
+        let chunk_server_paths = self.manifest.chunk_server_paths().await?;
 
         let mut code = b"const chunks = [\n".to_vec();
-        for pathname in chunk_server_paths {
-            writeln!(code, "    {},", stringify_str(&pathname))?;
+        for pathname in chunk_server_paths.iter() {
+            writeln!(code, "    {},", stringify_str(pathname))?;
         }
         writeln!(code, "];")?;
 
         // TODO: a dedent macro would be awesome.
+        // The chunks are loaded in parallel. `__turbopack_load__` skips chunks
+        // which are already loaded or currently loading.
         write!(
             code,
             "
@@ -286,17 +313,7 @@ __turbopack_export_value__(Promise.all(chunks.map(__turbopack_load__)));"
 #[turbo_tasks::value_impl]
 impl ChunkItem for ManifestChunkItem {
     #[turbo_tasks::function]
-    async fn references(&self) -> Result<AssetReferencesVc> {
-        let chunks = self.manifest.chunks();
-
-        Ok(AssetReferencesVc::cell(
-            chunks
-                .await?
-                .iter()
-                .copied()
-                .map(ChunkReferenceVc::new)
-                .map(Into::into)
-                .collect(),
-        ))
+    fn references(&self) -> AssetReferencesVc {
+        self.manifest.chunk_references()
     }
 }
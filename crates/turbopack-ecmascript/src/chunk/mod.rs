@@ -2,7 +2,12 @@ pub mod loader;
 pub(crate) mod optimize;
 pub mod source_map;
 
-use std::{fmt::Write, io::Write as _, slice::Iter};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt::Write,
+    io::Write as _,
+    slice::Iter,
+};
 
 use anyhow::{anyhow, bail, Result};
 use indexmap::{IndexMap, IndexSet};
@@ -30,6 +35,7 @@ use turbopack_core::{
         asset::{children_from_asset_references, content_to_details, IntrospectableAssetVc},
         Introspectable, IntrospectableChildrenVc, IntrospectableVc,
     },
+    issue::{code_gen::CodeGenerationIssue, IssueSeverity},
     reference::{AssetReferenceVc, AssetReferencesVc},
     source_map::{GenerateSourceMap, GenerateSourceMapVc, OptionSourceMapVc, SourceMapVc},
     version::{
@@ -446,6 +452,7 @@ impl EcmascriptChunkContentVc {
         let chunk_content = ecmascript_chunk_content(context, main_entries, omit_entries);
         let chunk_content = chunk_content.await?;
         let module_factories = chunk_content.chunk_items.to_entry_snapshot().await?;
+        check_module_id_collisions(&module_factories, chunk_path).await?;
         let output_root = context.output_root();
         Ok(EcmascriptChunkContent {
             module_factories,
@@ -457,6 +464,43 @@ impl EcmascriptChunkContentVc {
     }
 }
 
+/// Emits an issue when different chunk items of a chunk got the same module
+/// id, e.g. because the module id records file pins the same id for both.
+/// Collisions of hashed module ids are resolved by the strategy itself.
+async fn check_module_id_collisions(
+    entries: &EcmascriptChunkContentEntriesSnapshot,
+    chunk_path: FileSystemPathVc,
+) -> Result<()> {
+    let mut chunk_items = HashMap::new();
+    for entry in entries {
+        match chunk_items.entry(entry.id()) {
+            Entry::Vacant(vacant) => {
+                vacant.insert(entry.chunk_item);
+            }
+            Entry::Occupied(occupied) => {
+                let existing = occupied.get().to_string().await?;
+                let current = entry.chunk_item.to_string().await?;
+                if *existing != *current {
+                    CodeGenerationIssue {
+                        severity: IssueSeverity::Error.into(),
+                        title: StringVc::cell(format!("Module id {} is not unique", entry.id())),
+                        message: StringVc::cell(format!(
+                            "{existing} and {current} have the same module id {}. Pin a different \
+                             id for one of them in the module id records file.",
+                            entry.id()
+                        )),
+                        path: chunk_path,
+                    }
+                    .cell()
+                    .as_issue()
+                    .emit();
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 #[turbo_tasks::value(serialization = "none")]
 struct EcmascriptChunkContentEntry {
     chunk_item: EcmascriptChunkItemVc,
@@ -1088,7 +1132,8 @@ impl EcmascriptChunkContextVc {
 
     #[turbo_tasks::function]
     pub async fn chunk_item_id(self, chunk_item: EcmascriptChunkItemVc) -> Result<ModuleIdVc> {
-        let layer = &*self.await?.context.layer().await?;
+        let context = self.await?.context;
+        let layer = &*context.layer().await?;
         let mut s = chunk_item.to_string().await?.clone_value();
         if !layer.is_empty() {
             if s.ends_with(')') {
@@ -1098,7 +1143,9 @@ impl EcmascriptChunkContextVc {
                 write!(s, " ({layer})")?;
             }
         }
-        Ok(ModuleId::String(s).cell())
+        Ok(context
+            .module_id_strategy()
+            .get_module_id(StringVc::cell(s)))
     }
 }
 
@@ -6,6 +6,8 @@ pub mod failed_to_analyse {
         pub const FS_METHOD: &str = "TP1004";
         pub const CHILD_PROCESS_SPAWN: &str = "TP1005";
         pub const PATH_METHOD: &str = "TP1006";
+        pub const REQUIRE_CONTEXT: &str = "TP1007";
+        pub const IMPORT_META_GLOB: &str = "TP1008";
         pub const NODE_PRE_GYP_FIND: &str = "TP1100";
         pub const NODE_GYP_BUILD: &str = "TP1101";
         pub const NODE_BINDINGS: &str = "TP1102";
@@ -14,5 +16,6 @@ pub mod failed_to_analyse {
         pub const NODE_PROTOBUF_LOADER: &str = "TP1105";
         pub const AMD_DEFINE: &str = "TP1200";
         pub const NEW_URL_IMPORT_META: &str = "TP1201";
+        pub const NEW_WORKER: &str = "TP1202";
     }
 }
@@ -14,7 +14,7 @@ mod errors;
 pub mod magic_identifier;
 pub(crate) mod parse;
 mod path_visitor;
-pub(crate) mod references;
+pub mod references;
 pub mod resolve;
 pub(crate) mod special_cases;
 pub(crate) mod transform;
@@ -40,7 +40,8 @@ use swc_core::{
     },
 };
 pub use transform::{
-    EcmascriptInputTransform, EcmascriptInputTransformsVc, NextJsPageExportFilter,
+    CustomTransformer, EcmascriptInputTransform, EcmascriptInputTransformsVc,
+    NextJsPageExportFilter, TransformContext, TransformPlugin, TransformPluginVc,
 };
 use turbo_tasks::{primitives::StringVc, TryJoinIterExt, Value, ValueToString, ValueToStringVc};
 use turbo_tasks_fs::FileSystemPathVc;
@@ -271,6 +271,7 @@ async fn parse_content(
                 unresolved_mark,
                 file_name_str: fs_path.file_name(),
                 file_name_hash: file_path_hash,
+                file_path: source.path(),
             };
             for transform in transforms.iter() {
                 transform.apply(&mut parsed_program, &context).await?;
@@ -32,8 +32,8 @@ use crate::{
 /// referenced file to be imported/fetched/etc.
 #[turbo_tasks::value]
 pub struct UrlAssetReference {
-    origin: ResolveOriginVc,
-    request: RequestVc,
+    pub origin: ResolveOriginVc,
+    pub request: RequestVc,
     rendering: RenderingVc,
     ast_path: AstPathVc,
 }
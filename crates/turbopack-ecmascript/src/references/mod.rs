@@ -4,8 +4,10 @@ pub mod esm;
 pub mod node;
 pub mod pattern_mapping;
 pub mod raw;
+pub mod require_context;
 pub mod typescript;
 pub mod util;
+pub mod worker;
 
 use std::{
     collections::{BTreeMap, HashMap},
@@ -56,9 +58,11 @@ use self::{
     },
     node::{DirAssetReferenceVc, PackageJsonReferenceVc},
     raw::SourceAssetReferenceVc,
+    require_context::{ContextRequest, RequireContextAssetReferenceVc},
     typescript::{
         TsConfigReferenceVc, TsReferencePathAssetReferenceVc, TsReferenceTypeAssetReferenceVc,
     },
+    worker::WorkerAssetReferenceVc,
 };
 use super::{
     analyzer::{
@@ -185,6 +189,12 @@ pub(crate) async fn analyze_ecmascript_module(
 
     let parsed = parse(source, ty, transforms);
 
+    // The source itself can reference assets, e.g. files emitted by the
+    // webpack loaders which transformed it.
+    for reference in source.references().await?.iter() {
+        analysis.add_reference(*reference);
+    }
+
     match &*find_context_file(path.parent(), package_json()).await? {
         FindContextFileResult::Found(package_json, _) => {
             analysis.add_reference(PackageJsonReferenceVc::new(*package_json));
@@ -357,7 +367,7 @@ pub(crate) async fn analyze_ecmascript_module(
                 let request = RequestVc::parse(Value::new(request.into()));
                 let runtime = resolve_as_webpack_runtime(origin, request, transforms);
                 match &*runtime.await? {
-                    WebpackRuntime::Webpack5 { .. } => {
+                    WebpackRuntime::Webpack5 { .. } | WebpackRuntime::Webpack4 { .. } => {
                         ignore_effect_span = Some(span);
                         analysis.add_reference(
                             WebpackRuntimeAssetReference {
@@ -619,6 +629,74 @@ pub(crate) async fn analyze_ecmascript_module(
                         )
                     }
 
+                    JsValue::WellKnownFunction(WellKnownFunctionKind::RequireContext) => {
+                        let args = linked_args().await?;
+                        if let Some(request) = ContextRequest::require_context(&args) {
+                            analysis.add_reference(RequireContextAssetReferenceVc::new(
+                                origin,
+                                request.cell(),
+                                AstPathVc::cell(ast_path.to_vec()),
+                            ));
+                            return Ok(());
+                        }
+                        let (args, hints) = explain_args(&args);
+                        handler.span_warn_with_code(
+                            span,
+                            &format!(
+                                "require.context({args}) is not statically analyse-able{hints}",
+                            ),
+                            DiagnosticId::Error(
+                                errors::failed_to_analyse::ecmascript::REQUIRE_CONTEXT.to_string(),
+                            ),
+                        )
+                    }
+
+                    JsValue::WellKnownFunction(WellKnownFunctionKind::ImportMetaGlob) => {
+                        let args = linked_args().await?;
+                        if let Some(request) = ContextRequest::import_meta_glob(&args) {
+                            analysis.add_reference(RequireContextAssetReferenceVc::new(
+                                origin,
+                                request.cell(),
+                                AstPathVc::cell(ast_path.to_vec()),
+                            ));
+                            return Ok(());
+                        }
+                        let (args, hints) = explain_args(&args);
+                        handler.span_warn_with_code(
+                            span,
+                            &format!(
+                                "import.meta.glob({args}) is not statically analyse-able{hints}",
+                            ),
+                            DiagnosticId::Error(
+                                errors::failed_to_analyse::ecmascript::IMPORT_META_GLOB.to_string(),
+                            ),
+                        )
+                    }
+
+                    JsValue::WellKnownFunction(WellKnownFunctionKind::WorkerConstructor(name)) => {
+                        let args = linked_args().await?;
+                        if let Some(first) = args.first() {
+                            let pat = js_value_to_pattern(first);
+                            if !pat.has_constant_parts() {
+                                let (args, hints) = explain_args(&args);
+                                handler.span_warn_with_code(
+                                    span,
+                                    &format!("new {name}({args}) is very dynamic{hints}",),
+                                    DiagnosticId::Lint(
+                                        errors::failed_to_analyse::ecmascript::NEW_WORKER
+                                            .to_string(),
+                                    ),
+                                );
+                                return Ok(());
+                            }
+                            analysis.add_reference(WorkerAssetReferenceVc::new(
+                                origin,
+                                RequestVc::parse(Value::new(pat)),
+                                AstPathVc::cell(ast_path.to_vec()),
+                            ));
+                        }
+                    }
+
                     JsValue::WellKnownFunction(WellKnownFunctionKind::FsReadMethod(name)) => {
                         let args = linked_args().await?;
                         if !args.is_empty() {
@@ -1455,6 +1533,14 @@ async fn value_visitor_inner(
             JsValue::FreeVar(FreeVarKind::Object) => {
                 JsValue::WellKnownObject(WellKnownObjectKind::GlobalObject)
             }
+            JsValue::FreeVar(FreeVarKind::ImportMeta) => {
+                JsValue::WellKnownObject(WellKnownObjectKind::ImportMeta)
+            }
+            JsValue::FreeVar(FreeVarKind::Other(ref name))
+                if &**name == "Worker" || &**name == "SharedWorker" =>
+            {
+                JsValue::WellKnownFunction(WellKnownFunctionKind::WorkerConstructor(name.clone()))
+            }
             JsValue::FreeVar(_) => JsValue::Unknown(Some(Arc::new(v)), "unknown global"),
             JsValue::Module(ModuleValue {
                 module: ref name, ..
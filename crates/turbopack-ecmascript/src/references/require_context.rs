@@ -0,0 +1,516 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+use anyhow::Result;
+use regex::Regex;
+use swc_core::quote;
+use turbo_tasks::{primitives::StringVc, Value, ValueToString, ValueToStringVc};
+use turbo_tasks_fs::{glob::GlobVc, DirectoryEntry, File, FileContent, FileSystemPathVc};
+use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
+use turbopack_core::{
+    asset::{AssetContent, AssetVc},
+    chunk::{
+        ChunkableAssetReference, ChunkableAssetReferenceVc, ChunkingContextVc, FromChunkableAsset,
+    },
+    reference::{AssetReference, AssetReferenceVc},
+    resolve::{origin::ResolveOriginVc, ResolveResult, ResolveResultVc},
+    virtual_asset::VirtualAssetVc,
+};
+
+use crate::{
+    analyzer::{ConstantValue, JsValue, ObjectPart},
+    chunk::EcmascriptChunkItemVc,
+    code_gen::{CodeGenerateable, CodeGenerateableVc, CodeGeneration, CodeGenerationVc},
+    create_visitor,
+    references::AstPathVc,
+    utils::module_id_to_lit,
+    EcmascriptInputTransformsVc, EcmascriptModuleAssetType, EcmascriptModuleAssetVc,
+};
+
+/// A statically analyzed request for "all files matching X".
+#[turbo_tasks::value(shared)]
+#[derive(Hash, Debug)]
+pub enum ContextRequest {
+    /// `import.meta.glob(patterns, { eager, import })`
+    ///
+    /// Evaluates to an object which maps each matched file to its module
+    /// namespace (`eager`) or to a function importing it.
+    ImportMetaGlob {
+        /// Glob patterns relative to the importing file. Patterns starting
+        /// with `!` exclude files.
+        patterns: Vec<String>,
+        eager: bool,
+        /// Only pick this export instead of the whole module namespace.
+        import: Option<String>,
+    },
+    /// `require.context(directory, recursive, regExp, mode)`
+    ///
+    /// Evaluates to webpack's context function, which requires a file by its
+    /// key and exposes `keys()` and `resolve()`.
+    RequireContext {
+        /// Directory relative to the importing file.
+        directory: String,
+        recursive: bool,
+        /// The regular expression keys (`./path/to/file.js`) need to match,
+        /// in `regex` crate syntax.
+        filter: String,
+        /// Load the files in separate chunks and return promises.
+        lazy: bool,
+    },
+}
+
+impl ContextRequest {
+    /// Creates a request from the linked arguments of an `import.meta.glob`
+    /// call. Returns `None` when they can't be analyzed statically.
+    pub fn import_meta_glob(args: &[JsValue]) -> Option<Self> {
+        let patterns = match args.first()? {
+            JsValue::Array(_, items) => items
+                .iter()
+                .map(|item| item.as_str().map(|s| s.to_string()))
+                .collect::<Option<Vec<_>>>()?,
+            value => vec![value.as_str()?.to_string()],
+        };
+        if patterns.is_empty()
+            || !patterns
+                .iter()
+                .all(|pattern| is_relative(pattern.strip_prefix('!').unwrap_or(pattern)))
+        {
+            return None;
+        }
+
+        let mut eager = false;
+        let mut import = None;
+        match args.get(1) {
+            None => {}
+            Some(JsValue::Object(_, parts)) => {
+                for part in parts {
+                    let ObjectPart::KeyValue(key, value) = part else {
+                        return None;
+                    };
+                    match key.as_str()? {
+                        "eager" => eager = as_bool(value)?,
+                        "import" => import = Some(value.as_str()?.to_string()),
+                        _ => return None,
+                    }
+                }
+            }
+            Some(_) => return None,
+        }
+
+        Some(ContextRequest::ImportMetaGlob {
+            patterns,
+            eager,
+            import,
+        })
+    }
+
+    /// Creates a request from the linked arguments of a `require.context`
+    /// call. Returns `None` when they can't be analyzed statically.
+    pub fn require_context(args: &[JsValue]) -> Option<Self> {
+        let directory = args.first()?.as_str()?.trim_end_matches('/');
+        if !is_relative(directory) && directory != "." && directory != ".." {
+            return None;
+        }
+
+        let recursive = match args.get(1) {
+            None => true,
+            Some(value) => as_bool(value)?,
+        };
+
+        let filter = match args.get(2) {
+            None => r"^\./.*$".to_string(),
+            Some(JsValue::Constant(ConstantValue::Regex(exp, flags))) => regex_from_js(exp, flags)?,
+            Some(_) => return None,
+        };
+
+        let lazy = match args.get(3).map(|mode| mode.as_str()) {
+            None | Some(Some("sync")) => false,
+            Some(Some("lazy")) => true,
+            Some(_) => return None,
+        };
+
+        Some(ContextRequest::RequireContext {
+            directory: directory.to_string(),
+            recursive,
+            filter,
+            lazy,
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ContextRequest {
+    #[turbo_tasks::function]
+    fn to_string(&self) -> StringVc {
+        StringVc::cell(match self {
+            ContextRequest::ImportMetaGlob {
+                patterns,
+                eager,
+                import,
+            } => {
+                format!("import.meta.glob({patterns:?}, {{ eager: {eager}, import: {import:?} }})")
+            }
+            ContextRequest::RequireContext {
+                directory,
+                recursive,
+                filter,
+                lazy,
+            } => format!(
+                "require.context({directory:?}, {recursive}, /{filter}/, {})",
+                if *lazy { "lazy" } else { "sync" }
+            ),
+        })
+    }
+}
+
+fn is_relative(path: &str) -> bool {
+    path.starts_with("./") || path.starts_with("../")
+}
+
+fn as_bool(value: &JsValue) -> Option<bool> {
+    match value {
+        JsValue::Constant(ConstantValue::True) => Some(true),
+        JsValue::Constant(ConstantValue::False) => Some(false),
+        _ => None,
+    }
+}
+
+/// Converts a JavaScript regular expression literal into the syntax of the
+/// `regex` crate. Only the common subset of both is supported.
+fn regex_from_js(exp: &str, flags: &str) -> Option<String> {
+    let mut regex = String::new();
+    for flag in flags.chars() {
+        match flag {
+            'i' | 'm' | 's' => {
+                write!(regex, "(?{flag})").ok()?;
+            }
+            // Global, sticky, unicode and indices have no effect on matching a
+            // single key.
+            'g' | 'y' | 'u' | 'd' => {}
+            _ => return None,
+        }
+    }
+    // `/` needs to be escaped in JavaScript regular expression literals, but
+    // the regex crate doesn't allow escaping it.
+    regex.push_str(&exp.replace(r"\/", "/"));
+    Regex::new(&regex).ok()?;
+    Some(regex)
+}
+
+/// Splits a glob pattern into the directory it's relative to and the
+/// remaining glob, e.g. `./pages/**/*.tsx` into `./pages` and `**/*.tsx`.
+///
+/// `*`, `?` and `{a,b}` are glob syntax, like in Vite. Unlike Vite, `[` is
+/// matched literally instead of starting a character class, as it's common in
+/// file names of dynamic routes, e.g. `./[slug]/*.tsx`.
+fn split_glob_pattern(pattern: &str) -> (&str, &str) {
+    let mut base_end = 0;
+    for (index, segment) in pattern.split('/').enumerate() {
+        if segment.contains(['*', '?', '{']) {
+            break;
+        }
+        if index > 0 {
+            base_end += 1;
+        }
+        base_end += segment.len();
+    }
+    if base_end == pattern.len() {
+        // No glob characters, the pattern matches a single file.
+        return pattern.rsplit_once('/').unwrap_or((".", pattern));
+    }
+    (&pattern[..base_end], &pattern[base_end + 1..])
+}
+
+/// Escapes the `[` of a glob, which is matched literally, see
+/// [split_glob_pattern].
+fn escape_brackets(glob: &str) -> String {
+    let mut escaped = String::with_capacity(glob.len());
+    for c in glob.chars() {
+        if c == '[' {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Lists all files in `directory` matching `glob`, with their path relative to
+/// `directory`.
+async fn read_files(
+    directory: FileSystemPathVc,
+    glob: &str,
+) -> Result<Vec<(String, FileSystemPathVc)>> {
+    let mut files = Vec::new();
+    let glob = GlobVc::new(&escape_brackets(glob));
+    let mut queue = vec![directory.read_glob(glob, false)];
+    while let Some(result) = queue.pop() {
+        let result = result.await?;
+        for (path, entry) in result.results.iter() {
+            if let DirectoryEntry::File(file) = entry {
+                files.push((path.clone(), *file));
+            }
+        }
+        queue.extend(result.inner.values().copied());
+    }
+    Ok(files)
+}
+
+/// Maps the keys of a [ContextRequest] to requests relative to the importing
+/// file.
+#[turbo_tasks::value(transparent)]
+struct ContextMap(BTreeMap<String, String>);
+
+/// Reads the directories of a [ContextRequest]. This is invalidated when files
+/// are added to or removed from them.
+#[turbo_tasks::function]
+async fn context_map(
+    origin_path: FileSystemPathVc,
+    request: ContextRequestVc,
+) -> Result<ContextMapVc> {
+    let origin_dir = origin_path.parent();
+    let mut map = BTreeMap::new();
+    match &*request.await? {
+        ContextRequest::ImportMetaGlob { patterns, .. } => {
+            let origin_path = origin_path.await?;
+            let mut excluded = BTreeSet::new();
+            for pattern in patterns {
+                let (negated, pattern) = match pattern.strip_prefix('!') {
+                    Some(pattern) => (true, pattern),
+                    None => (false, pattern.as_str()),
+                };
+                let (base, glob) = split_glob_pattern(pattern);
+                for (file, path) in read_files(origin_dir.join(base), glob).await? {
+                    let key = format!("{base}/{file}");
+                    if negated {
+                        excluded.insert(key);
+                    } else if path.await?.path != origin_path.path {
+                        // Like Vite, never include the importing file itself.
+                        map.insert(key.clone(), key);
+                    }
+                }
+            }
+            map.retain(|key, _| !excluded.contains(key));
+        }
+        ContextRequest::RequireContext {
+            directory,
+            recursive,
+            filter,
+            ..
+        } => {
+            let filter = Regex::new(filter)?;
+            let glob = if *recursive { "**" } else { "*" };
+            for (file, _) in read_files(origin_dir.join(directory), glob).await? {
+                let key = format!("./{file}");
+                if filter.is_match(&key) {
+                    map.insert(key, format!("{directory}/{file}"));
+                }
+            }
+        }
+    }
+    Ok(ContextMapVc::cell(map))
+}
+
+/// Generates the module that a [ContextRequest] evaluates to. It references
+/// all matched files with regular `require()` and `import()` calls, so they
+/// are chunked like any other dependency.
+#[turbo_tasks::function]
+async fn context_module(origin: ResolveOriginVc, request: ContextRequestVc) -> Result<AssetVc> {
+    let origin_path = origin.origin_path();
+    let map = context_map(origin_path, request).await?;
+
+    let mut code = String::new();
+    match &*request.await? {
+        ContextRequest::ImportMetaGlob { eager, import, .. } => {
+            let member = import
+                .as_ref()
+                .map(|import| serde_json::to_string(import).map(|import| format!("[{import}]")))
+                .transpose()?
+                .unwrap_or_default();
+            writeln!(code, "module.exports = {{")?;
+            for (key, request) in map.iter() {
+                let key = serde_json::to_string(key)?;
+                let request = serde_json::to_string(request)?;
+                if *eager {
+                    writeln!(code, "  {key}: require({request}){member},")?;
+                } else if member.is_empty() {
+                    writeln!(code, "  {key}: () => import({request}),")?;
+                } else {
+                    writeln!(
+                        code,
+                        "  {key}: () => import({request}).then((m) => m{member}),"
+                    )?;
+                }
+            }
+            writeln!(code, "}};")?;
+        }
+        ContextRequest::RequireContext { lazy, .. } => {
+            writeln!(code, "var map = {{")?;
+            for (key, request) in map.iter() {
+                let key = serde_json::to_string(key)?;
+                let request = serde_json::to_string(request)?;
+                if *lazy {
+                    writeln!(code, "  {key}: () => import({request}),")?;
+                } else {
+                    writeln!(code, "  {key}: () => require({request}),")?;
+                }
+            }
+            writeln!(code, "}};")?;
+            code.push_str(
+                r#"function resolve(key) {
+  if (!Object.prototype.hasOwnProperty.call(map, key)) {
+    var e = new Error("Cannot find module '" + key + "'");
+    e.code = "MODULE_NOT_FOUND";
+    throw e;
+  }
+  return key;
+}
+"#,
+            );
+            if *lazy {
+                code.push_str(
+                    "function context(key) {\n  return Promise.resolve().then(() => \
+                     map[resolve(key)]());\n}\n",
+                );
+            } else {
+                code.push_str("function context(key) {\n  return map[resolve(key)]();\n}\n");
+            }
+            code.push_str(
+                "context.keys = () => Object.keys(map);\ncontext.resolve = \
+                 resolve;\nmodule.exports = context;\n",
+            );
+        }
+    }
+
+    let hash = encode_hex(hash_xxh3_hash64(
+        format!(
+            "{} {}",
+            origin_path.to_string().await?,
+            request.to_string().await?
+        )
+        .as_bytes(),
+    ));
+    let source = VirtualAssetVc::new(
+        origin_path
+            .parent()
+            .join(&format!("__turbopack_context_{hash}__.js")),
+        AssetContent::File(FileContent::Content(File::from(code)).cell()).cell(),
+    );
+    let context = origin.context();
+    Ok(EcmascriptModuleAssetVc::new(
+        source.into(),
+        context,
+        Value::new(EcmascriptModuleAssetType::Ecmascript),
+        EcmascriptInputTransformsVc::cell(vec![]),
+        context.environment(),
+    )
+    .into())
+}
+
+/// A reference to the module generated for an `import.meta.glob` or
+/// `require.context` call. The call is replaced by a require of that module.
+#[turbo_tasks::value]
+#[derive(Hash, Debug)]
+pub struct RequireContextAssetReference {
+    pub origin: ResolveOriginVc,
+    pub request: ContextRequestVc,
+    pub path: AstPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl RequireContextAssetReferenceVc {
+    #[turbo_tasks::function]
+    pub fn new(origin: ResolveOriginVc, request: ContextRequestVc, path: AstPathVc) -> Self {
+        Self::cell(RequireContextAssetReference {
+            origin,
+            request,
+            path,
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl AssetReference for RequireContextAssetReference {
+    #[turbo_tasks::function]
+    fn resolve_reference(&self) -> ResolveResultVc {
+        ResolveResult::Single(context_module(self.origin, self.request), Vec::new()).into()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for RequireContextAssetReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(self.request.to_string().await?.clone()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableAssetReference for RequireContextAssetReference {}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for RequireContextAssetReference {
+    #[turbo_tasks::function]
+    async fn code_generation(&self, context: ChunkingContextVc) -> Result<CodeGenerationVc> {
+        let module = context_module(self.origin, self.request);
+        let mut visitors = Vec::new();
+
+        if let Some(chunk_item) = EcmascriptChunkItemVc::from_asset(context, module).await? {
+            let id = chunk_item.id().await?;
+            let path = &self.path.await?;
+            visitors.push(create_visitor!(path, visit_mut_expr(expr: &mut Expr) {
+                *expr = quote!(
+                    "__turbopack_require__($id)" as Expr,
+                    id: Expr = module_id_to_lit(&id),
+                );
+            }));
+        }
+
+        Ok(CodeGeneration { visitors }.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape_brackets, regex_from_js, split_glob_pattern};
+
+    #[test]
+    fn test_split_glob_pattern() {
+        assert_eq!(split_glob_pattern("./pages/*.tsx"), ("./pages", "*.tsx"));
+        assert_eq!(
+            split_glob_pattern("../src/**/index.{js,ts}"),
+            ("../src", "**/index.{js,ts}")
+        );
+        assert_eq!(split_glob_pattern("./*.md"), (".", "*.md"));
+        assert_eq!(split_glob_pattern("./dir/file.js"), ("./dir", "file.js"));
+        assert_eq!(
+            split_glob_pattern("./pages/[id].tsx"),
+            ("./pages", "[id].tsx")
+        );
+        assert_eq!(split_glob_pattern("./[slug]/*.tsx"), ("./[slug]", "*.tsx"));
+        assert_eq!(
+            split_glob_pattern("./[[...slug]]/page?.tsx"),
+            ("./[[...slug]]", "page?.tsx")
+        );
+        assert_eq!(split_glob_pattern("./v?/index.js"), (".", "v?/index.js"));
+    }
+
+    #[test]
+    fn test_escape_brackets() {
+        assert_eq!(escape_brackets("[id].tsx"), r"\[id].tsx");
+        assert_eq!(escape_brackets("[[...slug]]/*.tsx"), r"\[\[...slug]]/*.tsx");
+        assert_eq!(escape_brackets("page?.{js,ts}"), "page?.{js,ts}");
+        assert_eq!(escape_brackets("**/*.js"), "**/*.js");
+    }
+
+    #[test]
+    fn test_regex_from_js() {
+        assert_eq!(
+            regex_from_js(r"^\.\/.*\.md$", "i").as_deref(),
+            Some(r"(?i)^\./.*\.md$")
+        );
+        assert_eq!(regex_from_js(r"\.js$", "g").as_deref(), Some(r"\.js$"));
+        assert_eq!(regex_from_js(r"(?<=a)b", ""), None);
+    }
+}
@@ -0,0 +1,386 @@
+use std::fmt::Write;
+
+use anyhow::{bail, Result};
+use swc_core::{
+    ecma::ast::{
+        Expr, ExprOrSpread, KeyValueProp, Lit, NewExpr, ObjectLit, Prop, PropName, PropOrSpread,
+    },
+    quote,
+};
+use turbo_tasks::{primitives::StringVc, ValueToString, ValueToStringVc};
+use turbo_tasks_fs::{File, FileContent, FileSystemPath, FileSystemPathVc};
+use turbopack_core::{
+    asset::{Asset, AssetContent, AssetContentVc, AssetVc},
+    chunk::{
+        ChunkGroupVc, ChunkItem, ChunkItemVc, ChunkReferenceVc, ChunkVc, ChunkableAsset,
+        ChunkableAssetReference, ChunkableAssetReferenceVc, ChunkableAssetVc, ChunkingContextVc,
+        ChunksVc, FromChunkableAsset,
+    },
+    reference::{AssetReference, AssetReferenceVc, AssetReferencesVc, SingleAssetReferenceVc},
+    resolve::{origin::ResolveOriginVc, parse::RequestVc, ResolveResultVc},
+};
+
+use crate::{
+    chunk::{
+        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkItemContentVc,
+        EcmascriptChunkItemVc, EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc,
+        EcmascriptChunkVc, EcmascriptExports, EcmascriptExportsVc,
+    },
+    code_gen::{CodeGenerateable, CodeGenerateableVc, CodeGeneration, CodeGenerationVc},
+    create_visitor,
+    references::AstPathVc,
+    resolve::esm_resolve,
+    utils::{module_id_to_lit, stringify_str},
+    EcmascriptModuleAssetVc,
+};
+
+/// A reference to the entry of a web worker, created from
+/// `new Worker(new URL("path", import.meta.url))` or the `SharedWorker`
+/// equivalent.
+///
+/// The worker entry is bundled into its own chunk group, which includes a
+/// runtime, and the `new URL(...)` is replaced with the URL of a script
+/// bootstrapping that chunk group.
+#[turbo_tasks::value]
+#[derive(Hash, Debug)]
+pub struct WorkerAssetReference {
+    pub origin: ResolveOriginVc,
+    pub request: RequestVc,
+    pub path: AstPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl WorkerAssetReferenceVc {
+    #[turbo_tasks::function]
+    pub fn new(origin: ResolveOriginVc, request: RequestVc, path: AstPathVc) -> Self {
+        Self::cell(WorkerAssetReference {
+            origin,
+            request,
+            path,
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl AssetReference for WorkerAssetReference {
+    #[turbo_tasks::function]
+    async fn resolve_reference(&self) -> Result<ResolveResultVc> {
+        let result = esm_resolve(self.origin, self.request).await?;
+        Ok(result
+            .map(
+                |asset| async move {
+                    Ok(
+                        if let Some(worker) = ChunkableAssetVc::resolve_from(asset).await? {
+                            WorkerLoaderAssetVc::new(worker).into()
+                        } else {
+                            asset
+                        },
+                    )
+                },
+                |reference| async move { Ok(reference) },
+            )
+            .await?
+            .into())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for WorkerAssetReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(format!(
+            "new Worker {}",
+            self.request.to_string().await?,
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableAssetReference for WorkerAssetReference {}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for WorkerAssetReference {
+    #[turbo_tasks::function]
+    async fn code_generation(
+        self_vc: WorkerAssetReferenceVc,
+        context: ChunkingContextVc,
+    ) -> Result<CodeGenerationVc> {
+        let this = self_vc.await?;
+        let mut visitors = Vec::new();
+
+        let assets = self_vc.resolve_reference().primary_assets().await?;
+        if let Some(&loader) = assets.first() {
+            if let Some(chunk_item) = EcmascriptChunkItemVc::from_asset(context, loader).await? {
+                let id = chunk_item.id().await?;
+                let path = &this.path.await?;
+                visitors.push(
+                    create_visitor!(path, visit_mut_expr(new_expr: &mut Expr) {
+                        if let Expr::New(NewExpr { args: Some(args), .. }) = new_expr {
+                            // The loader module exports the URL of the worker's
+                            // bootstrap script.
+                            if let Some(ExprOrSpread { box expr, spread: None }) = args.get_mut(0) {
+                                *expr = quote!(
+                                    "__turbopack_require__($id)" as Expr,
+                                    id: Expr = module_id_to_lit(&id),
+                                );
+                            }
+
+                            // The bootstrap script loads the chunks with
+                            // `importScripts`, which isn't available in module
+                            // workers.
+                            if let Some(ExprOrSpread { expr: box Expr::Object(ObjectLit { props, .. }), spread: None }) = args.get_mut(1) {
+                                for prop in props.iter_mut() {
+                                    if let PropOrSpread::Prop(box Prop::KeyValue(KeyValueProp { key, value })) = prop {
+                                        let is_type = match key {
+                                            PropName::Ident(ident) => &*ident.sym == "type",
+                                            PropName::Str(str) => &*str.value == "type",
+                                            _ => false,
+                                        };
+                                        if is_type {
+                                            if let box Expr::Lit(Lit::Str(_)) = value {
+                                                *value = box "classic".into();
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }),
+                );
+            }
+        }
+
+        Ok(CodeGeneration { visitors }.into())
+    }
+}
+
+/// An asset that exports the URL of a script which runs [worker] in a web
+/// worker. It's placed in the chunk of the module creating the worker.
+#[turbo_tasks::value]
+pub struct WorkerLoaderAsset {
+    pub worker: ChunkableAssetVc,
+}
+
+#[turbo_tasks::value_impl]
+impl WorkerLoaderAssetVc {
+    #[turbo_tasks::function]
+    pub fn new(worker: ChunkableAssetVc) -> Self {
+        Self::cell(WorkerLoaderAsset { worker })
+    }
+
+    /// The chunks of the worker. For ecmascript modules, this is an evaluated
+    /// chunk group which includes a runtime.
+    #[turbo_tasks::function]
+    async fn chunks(self, context: ChunkingContextVc) -> Result<ChunksVc> {
+        let worker = self.await?.worker;
+        let chunk_group = if let Some(ecma) = EcmascriptModuleAssetVc::resolve_from(worker).await? {
+            ChunkGroupVc::from_chunk(ecma.as_evaluated_chunk(context, None))
+        } else {
+            ChunkGroupVc::from_asset(worker, context)
+        };
+        Ok(chunk_group.chunks())
+    }
+
+    #[turbo_tasks::function]
+    async fn bootstrap(self, context: ChunkingContextVc) -> Result<WorkerBootstrapAssetVc> {
+        Ok(WorkerBootstrapAsset {
+            context,
+            worker: self.await?.worker,
+            chunks: self.chunks(context),
+        }
+        .cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for WorkerLoaderAsset {
+    #[turbo_tasks::function]
+    fn path(&self) -> FileSystemPathVc {
+        self.worker.path().join("worker-loader.js")
+    }
+
+    /// The code of the loader depends on the chunking context, so outside of a
+    /// chunk the loader stands for the worker entry.
+    #[turbo_tasks::function]
+    fn content(&self) -> AssetContentVc {
+        self.worker.content()
+    }
+
+    /// The worker entry, so that walking the module graph continues into the
+    /// worker. The chunks of the worker are referenced by the chunk item.
+    #[turbo_tasks::function]
+    async fn references(&self) -> Result<AssetReferencesVc> {
+        Ok(AssetReferencesVc::cell(vec![SingleAssetReferenceVc::new(
+            self.worker.into(),
+            StringVc::cell(format!("worker entry {}", self.worker.path().await?)),
+        )
+        .into()]))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableAsset for WorkerLoaderAsset {
+    #[turbo_tasks::function]
+    fn as_chunk(self_vc: WorkerLoaderAssetVc, context: ChunkingContextVc) -> ChunkVc {
+        EcmascriptChunkVc::new(context, self_vc.as_ecmascript_chunk_placeable()).into()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for WorkerLoaderAsset {
+    #[turbo_tasks::function]
+    fn as_chunk_item(
+        self_vc: WorkerLoaderAssetVc,
+        context: ChunkingContextVc,
+    ) -> EcmascriptChunkItemVc {
+        WorkerLoaderChunkItem {
+            context,
+            inner: self_vc,
+        }
+        .cell()
+        .into()
+    }
+
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> EcmascriptExportsVc {
+        EcmascriptExports::Value.cell()
+    }
+}
+
+#[turbo_tasks::value]
+struct WorkerLoaderChunkItem {
+    context: ChunkingContextVc,
+    inner: WorkerLoaderAssetVc,
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for WorkerLoaderChunkItem {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(format!(
+            "{} (worker)",
+            self.inner.await?.worker.path().to_string().await?
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for WorkerLoaderChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> ChunkingContextVc {
+        self.context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<EcmascriptChunkItemContentVc> {
+        let output_root = self.context.output_root().await?;
+        let bootstrap = self.inner.bootstrap(self.context);
+        let mut code = format!(
+            "__turbopack_export_value__({});\n",
+            stringify_str(&server_path(&output_root, bootstrap.path()).await?)
+        );
+
+        // Worker chunks can't be hot updated, since the worker's runtime is not
+        // connected to the HMR server. Reload the page, and with it the
+        // worker, when any of them changes instead.
+        if *self.context.is_hot_module_replacement_enabled().await? {
+            let chunks = self.inner.chunks(self.context).await?;
+            let mut chunk_paths = Vec::new();
+            for chunk in chunks.iter() {
+                if let Some(path) = output_root.get_path_to(&*chunk.path().await?) {
+                    chunk_paths.push(stringify_str(path));
+                }
+            }
+            writeln!(
+                code,
+                r#"if (typeof window !== "undefined") {{
+  const listeners = (self.TURBOPACK_CHUNK_UPDATE_LISTENERS = self.TURBOPACK_CHUNK_UPDATE_LISTENERS || []);
+  for (const chunkPath of [{}]) {{
+    listeners.push([chunkPath, () => window.location.reload()]);
+  }}
+}}"#,
+                chunk_paths.join(", ")
+            )?;
+        }
+
+        Ok(EcmascriptChunkItemContent {
+            inner_code: code.into(),
+            ..Default::default()
+        }
+        .cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for WorkerLoaderChunkItem {
+    #[turbo_tasks::function]
+    async fn references(&self) -> Result<AssetReferencesVc> {
+        let bootstrap = self.inner.bootstrap(self.context);
+        let mut references: Vec<AssetReferenceVc> = vec![SingleAssetReferenceVc::new(
+            bootstrap.into(),
+            StringVc::cell(format!("worker bootstrap {}", bootstrap.path().await?)),
+        )
+        .into()];
+        references.extend(
+            self.inner
+                .chunks(self.context)
+                .await?
+                .iter()
+                .copied()
+                .map(|chunk| AssetReferenceVc::from(ChunkReferenceVc::new(chunk))),
+        );
+        Ok(AssetReferencesVc::cell(references))
+    }
+}
+
+/// The script a worker is started with. It loads all chunks of the worker's
+/// chunk group, and the evaluated chunk then runs the worker entry.
+#[turbo_tasks::value]
+struct WorkerBootstrapAsset {
+    context: ChunkingContextVc,
+    worker: ChunkableAssetVc,
+    chunks: ChunksVc,
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for WorkerBootstrapAsset {
+    #[turbo_tasks::function]
+    fn path(&self) -> FileSystemPathVc {
+        self.context
+            .chunk_path(self.worker.path().join("worker-bootstrap.js"), ".js")
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<AssetContentVc> {
+        let output_root = self.context.output_root().await?;
+        let mut chunk_urls = Vec::new();
+        for chunk in self.chunks.await?.iter() {
+            chunk_urls.push(stringify_str(
+                &server_path(&output_root, chunk.path()).await?,
+            ));
+        }
+        let code = format!("importScripts({});\n", chunk_urls.join(", "));
+        Ok(AssetContent::File(FileContent::Content(File::from(code)).cell()).cell())
+    }
+
+    #[turbo_tasks::function]
+    async fn references(&self) -> Result<AssetReferencesVc> {
+        Ok(AssetReferencesVc::cell(
+            self.chunks
+                .await?
+                .iter()
+                .copied()
+                .map(|chunk| ChunkReferenceVc::new(chunk).into())
+                .collect(),
+        ))
+    }
+}
+
+/// Returns the URL an output asset is served from.
+async fn server_path(output_root: &FileSystemPath, path: FileSystemPathVc) -> Result<String> {
+    let path = path.await?;
+    let Some(server_path) = output_root.get_path_to(&path) else {
+        bail!("{} is not in output root {}", path, output_root);
+    };
+    Ok(format!("/{server_path}"))
+}
@@ -1,8 +1,9 @@
 mod server_to_client_proxy;
 
-use std::{path::Path, sync::Arc};
+use std::{fmt::Debug, path::Path, sync::Arc};
 
 use anyhow::Result;
+use async_trait::async_trait;
 use next_transform_strip_page_exports::{next_transform_strip_page_exports, ExportFilter};
 use serde::{Deserialize, Serialize};
 use swc_core::{
@@ -23,6 +24,7 @@ use turbo_tasks::{
     primitives::{StringVc, StringsVc},
     trace::TraceRawVcs,
 };
+use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::environment::EnvironmentVc;
 
 use self::server_to_client_proxy::{create_proxy_module, is_client_module};
@@ -52,7 +54,9 @@ impl From<NextJsPageExportFilter> for ExportFilter {
 pub enum EcmascriptInputTransform {
     ClientDirective(StringVc),
     CommonJs,
-    Custom,
+    /// A transform implemented outside of turbopack, see
+    /// [CustomTransformer].
+    Custom(TransformPluginVc),
     Emotion,
     /// This enables a Next.js transform which will eliminate some exports
     /// from a page file, as well as any imports exclusively used by these
@@ -85,6 +89,30 @@ impl EcmascriptInputTransformsVc {
     }
 }
 
+/// A transform of the ecmascript AST which can be implemented by users of
+/// turbopack, e.g. to compile a domain specific syntax.
+#[async_trait]
+pub trait CustomTransformer: Debug {
+    async fn transform(&self, program: &mut Program, ctx: &TransformContext<'_>) -> Result<()>;
+}
+
+#[turbo_tasks::value(
+    transparent,
+    serialization = "none",
+    eq = "manual",
+    into = "new",
+    cell = "new"
+)]
+pub struct TransformPlugin(
+    #[turbo_tasks(debug_ignore, trace_ignore)] Box<dyn CustomTransformer + Send + Sync>,
+);
+
+impl TransformPluginVc {
+    pub fn new(transformer: impl CustomTransformer + Send + Sync + 'static) -> Self {
+        TransformPluginVc::cell(Box::new(transformer))
+    }
+}
+
 pub struct TransformContext<'a> {
     pub comments: &'a SwcComments,
     pub top_level_mark: Mark,
@@ -92,21 +120,21 @@ pub struct TransformContext<'a> {
     pub source_map: &'a Arc<SourceMap>,
     pub file_name_str: &'a str,
     pub file_name_hash: u128,
+    /// The path of the transformed module, e.g. to emit issues for it.
+    pub file_path: FileSystemPathVc,
 }
 
 impl EcmascriptInputTransform {
-    pub async fn apply(
-        &self,
-        program: &mut Program,
-        &TransformContext {
+    pub async fn apply(&self, program: &mut Program, ctx: &TransformContext<'_>) -> Result<()> {
+        let &TransformContext {
             comments,
             source_map,
             top_level_mark,
             unresolved_mark,
             file_name_str,
             file_name_hash,
-        }: &TransformContext<'_>,
-    ) -> Result<()> {
+            ..
+        } = ctx;
         match *self {
             EcmascriptInputTransform::React { refresh } => {
                 program.visit_mut_with(&mut react(
@@ -221,7 +249,9 @@ impl EcmascriptInputTransform {
 
                 program.visit_mut_with(&mut next_font);
             }
-            EcmascriptInputTransform::Custom => todo!(),
+            EcmascriptInputTransform::Custom(transform) => {
+                transform.await?.transform(program, ctx).await?;
+            }
         }
         Ok(())
     }
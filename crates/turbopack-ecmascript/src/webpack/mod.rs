@@ -4,6 +4,7 @@ use turbo_tasks::{primitives::StringVc, Value, ValueToString, ValueToStringVc};
 use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::{
     asset::{Asset, AssetContentVc, AssetVc},
+    issue::{analyze::AnalyzeIssue, IssueSeverity},
     reference::{AssetReference, AssetReferenceVc, AssetReferencesVc},
     reference_type::{CommonJsReferenceSubType, ReferenceType},
     resolve::{origin::ResolveOriginVc, parse::RequestVc, resolve, ResolveResult, ResolveResultVc},
@@ -15,7 +16,12 @@ use self::{
     references::module_references,
 };
 use super::resolve::apply_cjs_specific_options;
-use crate::EcmascriptInputTransformsVc;
+use crate::{
+    analyzer::{
+        builtin::replace_builtin, ConstantNumber, ConstantValue, FreeVarKind, JsValue, ObjectPart,
+    },
+    EcmascriptInputTransformsVc,
+};
 
 pub mod parse;
 pub(crate) mod references;
@@ -74,28 +80,45 @@ impl AssetReference for WebpackChunkAssetReference {
     #[turbo_tasks::function]
     async fn resolve_reference(&self) -> Result<ResolveResultVc> {
         let runtime = self.runtime.await?;
-        Ok(match &*runtime {
+        let (chunk_request_expr, context_path) = match &*runtime {
             WebpackRuntime::Webpack5 {
-                chunk_request_expr: _,
+                chunk_request_expr,
                 context_path,
-            } => {
-                // TODO determine filename from chunk_request_expr
-                let chunk_id = match &self.chunk_id {
-                    Lit::Str(str) => str.value.to_string(),
-                    Lit::Num(num) => format!("{num}"),
-                    _ => todo!(),
-                };
-                let filename = format!("./chunks/{}.js", chunk_id);
-                let source = SourceAssetVc::new(context_path.join(&filename)).into();
-
-                ResolveResult::Single(
-                    WebpackModuleAssetVc::new(source, self.runtime, self.transforms).into(),
-                    Vec::new(),
-                )
-                .into()
             }
-            WebpackRuntime::None => ResolveResult::unresolveable().into(),
-        })
+            | WebpackRuntime::Webpack4 {
+                chunk_request_expr,
+                context_path,
+            } => (chunk_request_expr, *context_path),
+            WebpackRuntime::None => return Ok(ResolveResult::unresolveable().into()),
+        };
+
+        let Some(filename) = chunk_request(chunk_request_expr, &self.chunk_id) else {
+            AnalyzeIssue {
+                code: None,
+                category: StringVc::cell("analyze".to_string()),
+                message: StringVc::cell(format!(
+                    "The filename of the webpack chunk {} can't be determined from the chunk \
+                     loading code of the webpack runtime ({})",
+                    chunk_id_to_string(&self.chunk_id),
+                    chunk_request_expr
+                )),
+                path: context_path,
+                severity: IssueSeverity::Warning.into(),
+                source: None,
+                title: StringVc::cell("unknown webpack chunk".to_string()),
+            }
+            .cell()
+            .as_issue()
+            .emit();
+            return Ok(ResolveResult::unresolveable().into());
+        };
+
+        let source = SourceAssetVc::new(context_path.join(&filename)).into();
+        Ok(ResolveResult::Single(
+            WebpackModuleAssetVc::new(source, self.runtime, self.transforms).into(),
+            Vec::new(),
+        )
+        .into())
     }
 }
 
@@ -103,12 +126,64 @@ impl AssetReference for WebpackChunkAssetReference {
 impl ValueToString for WebpackChunkAssetReference {
     #[turbo_tasks::function]
     async fn to_string(&self) -> Result<StringVc> {
-        let chunk_id = match &self.chunk_id {
-            Lit::Str(str) => str.value.to_string(),
-            Lit::Num(num) => format!("{num}"),
-            _ => todo!(),
-        };
-        Ok(StringVc::cell(format!("webpack chunk {}", chunk_id)))
+        Ok(StringVc::cell(format!(
+            "webpack chunk {}",
+            chunk_id_to_string(&self.chunk_id)
+        )))
+    }
+}
+
+fn chunk_id_to_string(chunk_id: &Lit) -> String {
+    match chunk_id {
+        Lit::Str(str) => str.value.to_string(),
+        Lit::Num(num) => format!("{num}"),
+        lit => JsValue::Constant(lit.clone().into()).to_string(),
+    }
+}
+
+/// Evaluates the chunk request expression of the webpack runtime for the
+/// chunk id, e.g. `"./" + ({ 123: "page" }[chunkId] || chunkId) + ".js"`
+/// becomes `./page.js`.
+fn chunk_request(chunk_request_expr: &JsValue, chunk_id: &Lit) -> Option<String> {
+    // Property keys are strings in JavaScript, so numeric chunk ids and keys
+    // of chunk id maps are compared as strings.
+    let chunk_id = JsValue::from(chunk_id_to_string(chunk_id));
+    let mut value = chunk_request_expr.clone();
+    value.visit_mut(&mut |value| match value {
+        JsValue::FreeVar(FreeVarKind::Other(name)) if &**name == "chunkId" => {
+            *value = chunk_id.clone();
+            true
+        }
+        JsValue::Object(_, parts) => {
+            let mut modified = false;
+            for part in parts {
+                if let ObjectPart::KeyValue(key, _) = part {
+                    if let Some(key_str) = constant_string(key) {
+                        if key.as_str().is_none() {
+                            *key = key_str.into();
+                            modified = true;
+                        }
+                    }
+                }
+            }
+            modified
+        }
+        _ => false,
+    });
+    while value.visit_mut(&mut replace_builtin) {}
+    value.normalize();
+    constant_string(&value)
+}
+
+/// Converts a value to the string it evaluates to, when it's a constant.
+/// For alternatives (`a || b`) the first defined one is used.
+fn constant_string(value: &JsValue) -> Option<String> {
+    match value {
+        JsValue::Constant(ConstantValue::Num(ConstantNumber(num))) => Some(format!("{num}")),
+        JsValue::Constant(constant) => constant.as_str().map(|str| str.to_string()),
+        JsValue::Concat(_, parts) => parts.iter().map(constant_string).collect(),
+        JsValue::Alternatives(_, alts) => alts.iter().find_map(constant_string),
+        _ => None,
     }
 }
 
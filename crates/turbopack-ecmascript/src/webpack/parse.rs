@@ -6,7 +6,7 @@ use swc_core::{
     ecma::{
         ast::{
             ArrowExpr, AssignOp, BinExpr, BinaryOp, CallExpr, Callee, Expr, ExprOrSpread, ExprStmt,
-            FnExpr, Lit, Module, ModuleItem, Program, Script, Stmt,
+            FnExpr, Id, Lit, Module, Program, Script, Stmt,
         },
         visit::{Visit, VisitWith},
     },
@@ -16,7 +16,7 @@ use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::asset::AssetVc;
 
 use crate::{
-    analyzer::{graph::EvalContext, JsValue},
+    analyzer::{graph::EvalContext, FreeVarKind, JsValue},
     parse::{parse, ParseResult},
     utils::unparen,
     EcmascriptInputTransformsVc, EcmascriptModuleAssetType,
@@ -32,6 +32,14 @@ pub enum WebpackRuntime {
         chunk_request_expr: JsValue,
         context_path: FileSystemPathVc,
     },
+    /// A webpack 4 runtime, which requires chunks in `requireEnsure`.
+    Webpack4 {
+        /// There is a [JsValue]::FreeVar("chunkId") that need to be replaced
+        /// before converting to string
+        #[turbo_tasks(trace_ignore)]
+        chunk_request_expr: JsValue,
+        context_path: FileSystemPathVc,
+    },
     None,
 }
 
@@ -52,22 +60,59 @@ fn iife(stmt: &Stmt) -> Option<&Vec<Stmt>> {
     None
 }
 
-fn program_iife(program: &Program) -> Option<&Vec<Stmt>> {
-    match program {
-        Program::Module(Module { body, .. }) => {
-            if let [ModuleItem::Stmt(stmt)] = &body[..] {
-                return iife(stmt);
+fn is_directive(stmt: &Stmt) -> bool {
+    if let Stmt::Expr(ExprStmt { expr, .. }) = stmt {
+        return matches!(&**expr, Expr::Lit(Lit::Str(_)));
+    }
+    false
+}
+
+/// The webpack 4 bootstrap is an iife which receives the modules as argument
+/// and might be assigned to `module.exports`.
+fn bootstrap_iife(stmt: &Stmt) -> Option<&Vec<Stmt>> {
+    if let Stmt::Expr(ExprStmt { expr, .. }) = &stmt {
+        let mut expr = unparen(expr);
+        if let Some(assign) = expr.as_assign() {
+            if assign.op == AssignOp::Assign
+                && assign
+                    .left
+                    .as_expr()
+                    .and_then(get_expr_identifier)
+                    .map_or(false, |name| name == "module.exports")
+            {
+                expr = unparen(&assign.right);
             }
         }
-        Program::Script(Script { body, .. }) => {
-            if let [stmt] = &body[..] {
-                return iife(stmt);
-            }
+        if let Expr::Call(CallExpr {
+            callee: Callee::Expr(callee),
+            ..
+        }) = expr
+        {
+            return get_fn_body(callee);
         }
     }
     None
 }
 
+fn program_iife(program: &Program) -> Option<&Vec<Stmt>> {
+    let stmts: Vec<&Stmt> = match program {
+        Program::Module(Module { body, .. }) => body
+            .iter()
+            .map(|item| item.as_stmt())
+            .collect::<Option<_>>()?,
+        Program::Script(Script { body, .. }) => body.iter().collect(),
+    };
+    // Ignore directives like "use strict"
+    let stmts: Vec<&Stmt> = stmts
+        .into_iter()
+        .filter(|stmt| !is_directive(stmt))
+        .collect();
+    match stmts[..] {
+        [stmt] => iife(stmt).or_else(|| bootstrap_iife(stmt)),
+        _ => None,
+    }
+}
+
 fn is_webpack_require_decl(stmt: &Stmt) -> bool {
     if let Some(decl) = stmt.as_decl() {
         if let Some(fn_decl) = decl.as_fn_decl() {
@@ -131,12 +176,43 @@ fn get_fn_body(expr: &Expr) -> Option<&Vec<Stmt>> {
     None
 }
 
+/// The identifier of the first parameter of a function.
+fn get_fn_param(expr: &Expr) -> Option<Id> {
+    let expr = unparen(expr);
+    if let Some(FnExpr { function, .. }) = expr.as_fn_expr() {
+        return function.params.first()?.pat.as_ident().map(|i| i.to_id());
+    }
+    if let Some(ArrowExpr { params, .. }) = expr.as_arrow() {
+        return params.first()?.as_ident().map(|i| i.to_id());
+    }
+    None
+}
+
+/// Replaces the chunk id parameter of the runtime function with a
+/// [JsValue]::FreeVar("chunkId").
+fn replace_chunk_id_param(mut value: JsValue, param: Option<Id>) -> JsValue {
+    if let Some(param) = param {
+        value.visit_mut(&mut |value| {
+            if matches!(value, JsValue::Variable(id) if *id == param) {
+                *value = JsValue::FreeVar(FreeVarKind::Other("chunkId".into()));
+                true
+            } else {
+                false
+            }
+        });
+    }
+    value
+}
+
 fn get_javascript_chunk_filename(stmts: &Vec<Stmt>, eval_context: &EvalContext) -> Option<JsValue> {
     if let Some(expr) = get_assignment(stmts, "__webpack_require__.u") {
         if let Some(stmts) = get_fn_body(expr) {
             if let Some(ret) = stmts.iter().find_map(|stmt| stmt.as_return_stmt()) {
-                if let Some(expr) = &ret.arg {
-                    return Some(eval_context.eval(expr));
+                if let Some(ret_expr) = &ret.arg {
+                    return Some(replace_chunk_id_param(
+                        eval_context.eval(ret_expr),
+                        get_fn_param(expr),
+                    ));
                 }
             }
         }
@@ -180,6 +256,42 @@ fn get_require_prefix(stmts: &Vec<Stmt>) -> Option<Lit> {
     None
 }
 
+struct RequireRequestVisitor<'a> {
+    eval_context: &'a EvalContext,
+    result: Option<JsValue>,
+}
+
+impl<'a> Visit for RequireRequestVisitor<'a> {
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        if self.result.is_some() {
+            return;
+        }
+        if let Some(expr) = call.callee.as_expr() {
+            if let Some(name) = get_expr_identifier(expr) {
+                if name == "require" {
+                    if let [ExprOrSpread { spread: None, expr }] = &call.args[..] {
+                        self.result = Some(self.eval_context.eval(expr));
+                        return;
+                    }
+                }
+            }
+        }
+        call.visit_children_with(self);
+    }
+}
+
+/// Extracts the request of the chunk `require` in the webpack 4
+/// `requireEnsure` function, e.g. `"./" + ({}[chunkId]||chunkId) + ".js"`.
+fn get_require_ensure_request(stmts: &Vec<Stmt>, eval_context: &EvalContext) -> Option<JsValue> {
+    let expr = get_assignment(stmts, "__webpack_require__.e")?;
+    let mut visitor = RequireRequestVisitor {
+        eval_context,
+        result: None,
+    };
+    expr.visit_children_with(&mut visitor);
+    Some(replace_chunk_id_param(visitor.result?, get_fn_param(expr)))
+}
+
 #[turbo_tasks::function]
 pub async fn webpack_runtime(
     asset: AssetVc,
@@ -220,6 +332,18 @@ pub async fn webpack_runtime(
                         }
                         .into());
                     }
+
+                    // webpack 4 has no chunk filename function and requires
+                    // the chunk directly in `__webpack_require__.e`
+                    let require_request =
+                        GLOBALS.set(globals, || get_require_ensure_request(stmts, eval_context));
+                    if let Some(chunk_request_expr) = require_request {
+                        return Ok(WebpackRuntime::Webpack4 {
+                            chunk_request_expr,
+                            context_path: asset.path().parent().resolve().await?,
+                        }
+                        .into());
+                    }
                 }
             }
         }
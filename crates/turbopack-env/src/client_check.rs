@@ -0,0 +1,206 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use indexmap::IndexSet;
+use swc_core::{
+    common::Mark,
+    ecma::{
+        ast::{Expr, Lit, MemberExpr, MemberProp, Program},
+        visit::{Visit, VisitWith},
+    },
+};
+use turbo_tasks::primitives::StringVc;
+use turbo_tasks_env::ProcessEnvVc;
+use turbopack_core::issue::IssueSeverity;
+use turbopack_ecmascript::{CustomTransformer, TransformContext};
+
+use crate::{EnvSchemaIssue, EnvSchemaVc};
+
+/// Env variables which are defined in client code without being part of the
+/// env.
+const BUILTIN_VARIABLES: &[&str] = &["NODE_ENV"];
+
+/// Reports reads of `process.env` variables in client code which are not
+/// part of the client env, so that private variables are not expected to be
+/// available in the browser.
+///
+/// Reading a variable which is not public is an error, reading a public
+/// variable which is not defined is a warning.
+#[derive(Debug)]
+pub struct ClientEnvCheck {
+    /// The env which is embedded into client code.
+    client_env: ProcessEnvVc,
+    schema: EnvSchemaVc,
+    public_prefix: String,
+}
+
+impl ClientEnvCheck {
+    pub fn new(client_env: ProcessEnvVc, schema: EnvSchemaVc, public_prefix: String) -> Self {
+        Self {
+            client_env,
+            schema,
+            public_prefix,
+        }
+    }
+}
+
+#[async_trait]
+impl CustomTransformer for ClientEnvCheck {
+    async fn transform(&self, program: &mut Program, ctx: &TransformContext<'_>) -> Result<()> {
+        let mut reads = ProcessEnvReads {
+            unresolved_mark: ctx.unresolved_mark,
+            names: IndexSet::new(),
+        };
+        program.visit_with(&mut reads);
+        if reads.names.is_empty() {
+            return Ok(());
+        }
+
+        let schema = self.schema.await?;
+        for name in reads.names {
+            if BUILTIN_VARIABLES.contains(&name.as_str())
+                || self.client_env.read(&name).await?.is_some()
+            {
+                continue;
+            }
+            let (title, description, severity) = if !schema.is_public(&name, &self.public_prefix) {
+                (
+                    "Private env variable read in client code",
+                    format!(
+                        "{name} is not a public env variable and is undefined in the browser. \
+                         Only variables starting with {} or declared as public in the env schema \
+                         are available in client code.",
+                        self.public_prefix
+                    ),
+                    IssueSeverity::Error,
+                )
+            } else {
+                (
+                    "Undefined env variable read in client code",
+                    format!("{name} is not defined and is undefined in the browser."),
+                    IssueSeverity::Warning,
+                )
+            };
+            EnvSchemaIssue {
+                path: ctx.file_path,
+                title: StringVc::cell(title.to_string()),
+                description: StringVc::cell(description),
+                severity: severity.into(),
+            }
+            .cell()
+            .as_issue()
+            .emit();
+        }
+        Ok(())
+    }
+}
+
+/// Collects the names of the variables read with `process.env.NAME` or
+/// `process.env["NAME"]`, where `process` is the global.
+struct ProcessEnvReads {
+    unresolved_mark: Mark,
+    names: IndexSet<String>,
+}
+
+impl ProcessEnvReads {
+    fn is_process_env(&self, expr: &Expr) -> bool {
+        let Expr::Member(MemberExpr { obj, prop: MemberProp::Ident(prop), .. }) = expr else {
+            return false;
+        };
+        let Expr::Ident(obj) = &**obj else {
+            return false;
+        };
+        &*obj.sym == "process"
+            && obj.span.ctxt.outer() == self.unresolved_mark
+            && &*prop.sym == "env"
+    }
+}
+
+impl Visit for ProcessEnvReads {
+    fn visit_member_expr(&mut self, member: &MemberExpr) {
+        if self.is_process_env(&member.obj) {
+            let name = match &member.prop {
+                MemberProp::Ident(ident) => Some(ident.sym.to_string()),
+                MemberProp::Computed(computed) => match &*computed.expr {
+                    Expr::Lit(Lit::Str(str)) => Some(str.value.to_string()),
+                    _ => None,
+                },
+                MemberProp::PrivateName(_) => None,
+            };
+            self.names.extend(name);
+        }
+        member.visit_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexSet;
+    use swc_core::{
+        common::{FileName, Mark},
+        ecma::{
+            ast::EsVersion,
+            parser::parse_file_as_program,
+            transforms::base::resolver,
+            visit::{VisitMutWith, VisitWith},
+        },
+        testing::run_test,
+    };
+
+    use super::ProcessEnvReads;
+
+    fn reads(code: &str) -> Vec<String> {
+        run_test(false, |cm, handler| {
+            let fm = cm.new_source_file(FileName::Anon, code.to_string());
+            let mut program = parse_file_as_program(
+                &fm,
+                Default::default(),
+                EsVersion::latest(),
+                None,
+                &mut vec![],
+            )
+            .map_err(|err| err.into_diagnostic(handler).emit())?;
+
+            let unresolved_mark = Mark::new();
+            let top_level_mark = Mark::new();
+            program.visit_mut_with(&mut resolver(unresolved_mark, top_level_mark, false));
+
+            let mut reads = ProcessEnvReads {
+                unresolved_mark,
+                names: IndexSet::new(),
+            };
+            program.visit_with(&mut reads);
+            Ok(reads.names.into_iter().collect())
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_process_env_reads() {
+        assert_eq!(
+            reads(
+                r#"
+                const url = process.env.API_URL;
+                const id = process.env["ANALYTICS_ID"];
+                console.log(process.env.API_URL, process.env.PORT.length);
+                "#
+            ),
+            vec!["API_URL", "ANALYTICS_ID", "PORT"]
+        );
+    }
+
+    #[test]
+    fn test_process_env_reads_ignores_other_reads() {
+        assert_eq!(
+            reads(
+                r#"
+                const key = "SECRET";
+                console.log(process.env[key], process.argv, env.TOKEN);
+                function f(process) {
+                    return process.env.LOCAL;
+                }
+                "#
+            ),
+            Vec::<String>::new()
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use turbo_tasks::primitives::StringVc;
 use turbo_tasks_fs::FileSystemPathVc;
-use turbopack_core::issue::{Issue, IssueVc};
+use turbopack_core::issue::{Issue, IssueSeverityVc, IssueVc};
 
 /// An issue that occurred while resolving the parsing or evaluating the .env.
 #[turbo_tasks::value(shared)]
@@ -32,3 +32,41 @@ impl Issue for ProcessEnvIssue {
         self.description
     }
 }
+
+/// An issue with the env variables declared in an env schema, or with their
+/// use in client code.
+#[turbo_tasks::value(shared)]
+pub struct EnvSchemaIssue {
+    pub path: FileSystemPathVc,
+    pub title: StringVc,
+    pub description: StringVc,
+    pub severity: IssueSeverityVc,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for EnvSchemaIssue {
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        self.title
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("env".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        self.description
+    }
+
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        self.severity
+    }
+}
@@ -8,18 +8,29 @@
 //! have higher priority to define a environment variable (later dotenv files
 //! cannot override it). Later dotenv files can reference variables prior
 //! defined variables.
+//!
+//! An env schema declares the variables of a project, their types and
+//! whether they are public, i.e. embedded into client code. The env is
+//! validated against it, and reads of variables which are not public are
+//! reported in client code.
 
 #![feature(async_closure)]
 #![feature(min_specialization)]
 
 mod asset;
+mod client_check;
 mod embeddable;
 mod issue;
+mod schema;
 mod try_env;
 
 pub use asset::{ProcessEnvAsset, ProcessEnvAssetVc};
+pub use client_check::ClientEnvCheck;
 pub use embeddable::EmbeddableProcessEnvVc;
-pub use issue::{ProcessEnvIssue, ProcessEnvIssueVc};
+pub use issue::{EnvSchemaIssue, EnvSchemaIssueVc, ProcessEnvIssue, ProcessEnvIssueVc};
+pub use schema::{
+    EnvSchema, EnvSchemaVc, EnvVariable, EnvVariableType, PublicProcessEnvVc, ValidatedProcessEnvVc,
+};
 pub use try_env::TryDotenvProcessEnvVc;
 
 pub fn register() {
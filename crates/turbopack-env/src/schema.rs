@@ -0,0 +1,278 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{
+    primitives::{OptionStringVc, StringVc},
+    trace::TraceRawVcs,
+    CompletionVc,
+};
+use turbo_tasks_env::{EnvMapVc, ProcessEnv, ProcessEnvVc};
+use turbo_tasks_fs::{FileContent, FileSystemPathVc};
+use turbopack_core::issue::IssueSeverity;
+
+use crate::EnvSchemaIssue;
+
+/// The type of the value of an env variable. Env variables are always
+/// strings, the type declares how the string is interpreted.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvVariableType {
+    #[default]
+    String,
+    /// A decimal number, e.g. `3000` or `0.5`.
+    Number,
+    /// `true` or `false`.
+    Boolean,
+}
+
+impl EnvVariableType {
+    fn accepts(&self, value: &str) -> bool {
+        match self {
+            EnvVariableType::String => true,
+            EnvVariableType::Number => value.trim().parse::<f64>().is_ok(),
+            EnvVariableType::Boolean => value == "true" || value == "false",
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            EnvVariableType::String => "string",
+            EnvVariableType::Number => "number",
+            EnvVariableType::Boolean => "boolean",
+        }
+    }
+}
+
+/// The declaration of an env variable in an [EnvSchema].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(default)]
+pub struct EnvVariable {
+    #[serde(rename = "type")]
+    pub ty: EnvVariableType,
+    /// Whether the variable needs to be defined.
+    pub required: bool,
+    /// Whether the variable is embedded into client code. When it's not
+    /// declared, the default of the env applies, e.g. a `NEXT_PUBLIC_`
+    /// prefix.
+    pub public: Option<bool>,
+}
+
+/// Declares the env variables of a project, read from a JSON file like
+///
+/// ```json
+/// {
+///   "DATABASE_URL": { "required": true },
+///   "PORT": { "type": "number" },
+///   "ANALYTICS_ID": { "public": true }
+/// }
+/// ```
+#[turbo_tasks::value(shared)]
+#[derive(Default)]
+pub struct EnvSchema {
+    /// The path of the schema file, used as the context of issues.
+    pub path: Option<FileSystemPathVc>,
+    pub variables: IndexMap<String, EnvVariable>,
+}
+
+#[turbo_tasks::value_impl]
+impl EnvSchemaVc {
+    /// Reads the schema from a JSON file. A missing file declares no
+    /// variables.
+    #[turbo_tasks::function]
+    pub async fn read(path: FileSystemPathVc) -> Result<Self> {
+        let FileContent::Content(file) = &*path.read().await? else {
+            return Ok(EnvSchema::default().cell());
+        };
+        let variables = match serde_json::from_str(&file.content().to_str()?) {
+            Ok(variables) => variables,
+            Err(err) => {
+                EnvSchemaIssue {
+                    path,
+                    title: StringVc::cell("Error parsing env schema".to_string()),
+                    description: StringVc::cell(err.to_string()),
+                    severity: IssueSeverity::Error.into(),
+                }
+                .cell()
+                .as_issue()
+                .emit();
+                IndexMap::new()
+            }
+        };
+        Ok(EnvSchema {
+            path: Some(path),
+            variables,
+        }
+        .cell())
+    }
+
+    /// Emits issues for required variables which are not defined in `env`,
+    /// and for variables whose value doesn't match their type.
+    #[turbo_tasks::function]
+    pub async fn validate(self, env: ProcessEnvVc) -> Result<CompletionVc> {
+        let schema = self.await?;
+        let Some(path) = schema.path else {
+            return Ok(CompletionVc::new());
+        };
+        let env = env.read_all().await?;
+        for (name, variable) in &schema.variables {
+            let description = match env.get(name) {
+                None if variable.required => format!("The env variable {name} is not defined."),
+                Some(value) if !variable.ty.accepts(value) => format!(
+                    "The env variable {name} needs to be a {}, but is \"{value}\".",
+                    variable.ty.as_str()
+                ),
+                _ => continue,
+            };
+            EnvSchemaIssue {
+                path,
+                title: StringVc::cell("Invalid env variable".to_string()),
+                description: StringVc::cell(description),
+                severity: IssueSeverity::Error.into(),
+            }
+            .cell()
+            .as_issue()
+            .emit();
+        }
+        Ok(CompletionVc::new())
+    }
+}
+
+impl EnvSchema {
+    /// Whether the variable can be embedded into client code. Variables the
+    /// schema doesn't declare as public or private are public when they start
+    /// with `public_prefix`.
+    pub fn is_public(&self, name: &str, public_prefix: &str) -> bool {
+        self.variables
+            .get(name)
+            .and_then(|variable| variable.public)
+            .unwrap_or_else(|| {
+                name.to_uppercase()
+                    .starts_with(&public_prefix.to_uppercase())
+            })
+    }
+}
+
+/// Validates the env variables of `prior` against an [EnvSchema] whenever
+/// they are read.
+#[turbo_tasks::value]
+pub struct ValidatedProcessEnv {
+    prior: ProcessEnvVc,
+    schema: EnvSchemaVc,
+}
+
+#[turbo_tasks::value_impl]
+impl ValidatedProcessEnvVc {
+    #[turbo_tasks::function]
+    pub fn new(prior: ProcessEnvVc, schema: EnvSchemaVc) -> Self {
+        ValidatedProcessEnv { prior, schema }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ProcessEnv for ValidatedProcessEnv {
+    #[turbo_tasks::function]
+    async fn read_all(&self) -> Result<EnvMapVc> {
+        self.schema.validate(self.prior).await?;
+        Ok(self.prior.read_all())
+    }
+
+    #[turbo_tasks::function]
+    async fn read(&self, name: &str) -> Result<OptionStringVc> {
+        self.schema.validate(self.prior).await?;
+        Ok(self.prior.read(name))
+    }
+}
+
+/// Filters env variables down to the ones which are public according to an
+/// [EnvSchema], so that they can be embedded into client code.
+#[turbo_tasks::value]
+pub struct PublicProcessEnv {
+    prior: ProcessEnvVc,
+    schema: EnvSchemaVc,
+    public_prefix: String,
+}
+
+#[turbo_tasks::value_impl]
+impl PublicProcessEnvVc {
+    #[turbo_tasks::function]
+    pub fn new(prior: ProcessEnvVc, schema: EnvSchemaVc, public_prefix: String) -> Self {
+        PublicProcessEnv {
+            prior,
+            schema,
+            public_prefix,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ProcessEnv for PublicProcessEnv {
+    #[turbo_tasks::function]
+    async fn read_all(&self) -> Result<EnvMapVc> {
+        let schema = self.schema.await?;
+        let prior = self.prior.read_all().await?;
+        Ok(EnvMapVc::cell(
+            prior
+                .iter()
+                .filter(|(name, _)| schema.is_public(name, &self.public_prefix))
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    async fn read(&self, name: &str) -> Result<OptionStringVc> {
+        if self.schema.await?.is_public(name, &self.public_prefix) {
+            Ok(self.prior.read(name))
+        } else {
+            Ok(OptionStringVc::cell(None))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use super::{EnvSchema, EnvVariable, EnvVariableType};
+
+    #[test]
+    fn test_variable_type_accepts() {
+        assert!(EnvVariableType::String.accepts("anything"));
+        assert!(EnvVariableType::Number.accepts("3000"));
+        assert!(EnvVariableType::Number.accepts(" 0.5 "));
+        assert!(!EnvVariableType::Number.accepts("port"));
+        assert!(EnvVariableType::Boolean.accepts("false"));
+        assert!(!EnvVariableType::Boolean.accepts("1"));
+    }
+
+    #[test]
+    fn test_is_public() {
+        let schema = EnvSchema {
+            path: None,
+            variables: IndexMap::from([
+                (
+                    "ANALYTICS_ID".to_string(),
+                    EnvVariable {
+                        public: Some(true),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "NEXT_PUBLIC_SECRET".to_string(),
+                    EnvVariable {
+                        public: Some(false),
+                        ..Default::default()
+                    },
+                ),
+                ("NEXT_PUBLIC_URL".to_string(), EnvVariable::default()),
+            ]),
+        };
+
+        assert!(schema.is_public("ANALYTICS_ID", "NEXT_PUBLIC_"));
+        assert!(!schema.is_public("NEXT_PUBLIC_SECRET", "NEXT_PUBLIC_"));
+        assert!(schema.is_public("NEXT_PUBLIC_URL", "NEXT_PUBLIC_"));
+        assert!(schema.is_public("next_public_api", "NEXT_PUBLIC_"));
+        assert!(!schema.is_public("DATABASE_URL", "NEXT_PUBLIC_"));
+    }
+}
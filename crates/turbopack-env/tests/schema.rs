@@ -0,0 +1,77 @@
+#![cfg(test)]
+
+use indexmap::IndexMap;
+use turbo_tasks_env::{CommandLineProcessEnvVc, CustomProcessEnvVc, EnvMapVc};
+use turbo_tasks_fs::{DiskFileSystemVc, FileSystemVc};
+use turbo_tasks_testing::{register, run};
+use turbopack_core::issue::IssueVc;
+use turbopack_env::{register, EnvSchema, EnvVariable, EnvVariableType};
+
+register!();
+
+#[tokio::test]
+async fn validate_reports_invalid_variables() {
+    run! {
+        register();
+
+        let fs: FileSystemVc =
+            DiskFileSystemVc::new("project".to_string(), env!("CARGO_MANIFEST_DIR").to_string())
+                .into();
+        let schema = EnvSchema {
+            path: Some(fs.root().join("env.schema.json")),
+            variables: IndexMap::from([
+                (
+                    "TURBOPACK_ENV_TEST_REQUIRED".to_string(),
+                    EnvVariable {
+                        required: true,
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "TURBOPACK_ENV_TEST_PORT".to_string(),
+                    EnvVariable {
+                        ty: EnvVariableType::Number,
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "TURBOPACK_ENV_TEST_DEBUG".to_string(),
+                    EnvVariable {
+                        ty: EnvVariableType::Boolean,
+                        required: true,
+                        ..Default::default()
+                    },
+                ),
+            ]),
+        }
+        .cell();
+        let env = CustomProcessEnvVc::new(
+            CommandLineProcessEnvVc::new().into(),
+            EnvMapVc::cell(IndexMap::from([
+                ("TURBOPACK_ENV_TEST_PORT".to_string(), "http".to_string()),
+                ("TURBOPACK_ENV_TEST_DEBUG".to_string(), "true".to_string()),
+            ])),
+        )
+        .into();
+
+        let completion = schema.validate(env);
+        completion.await?;
+        let issues = IssueVc::peek_issues_with_path(completion)
+            .await?
+            .strongly_consistent()
+            .await?;
+        let mut descriptions = Vec::new();
+        for issue in issues.iter() {
+            descriptions.push((*issue.description().await?).clone());
+        }
+        descriptions.sort();
+        assert_eq!(
+            descriptions,
+            vec![
+                "The env variable TURBOPACK_ENV_TEST_PORT needs to be a number, but is \"http\"."
+                    .to_string(),
+                "The env variable TURBOPACK_ENV_TEST_REQUIRED is not defined.".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,84 @@
+import { readFileSync } from "fs";
+import { join } from "path";
+import { createContext, runInContext } from "vm";
+import { webcrypto } from "crypto";
+
+declare const TURBOPACK_EDGE_SANDBOX: {
+  // The directory chunk paths are relative to.
+  root: string;
+  // The chunks of the edge entry, in loading order.
+  chunks: string[];
+};
+
+// Only web APIs are exposed to code running in the edge runtime.
+const WEB_GLOBALS = [
+  "AbortController",
+  "AbortSignal",
+  "Blob",
+  "DOMException",
+  "Event",
+  "EventTarget",
+  "FormData",
+  "Headers",
+  "ReadableStream",
+  "Request",
+  "Response",
+  "TextDecoder",
+  "TextDecoderStream",
+  "TextEncoder",
+  "TextEncoderStream",
+  "TransformStream",
+  "URL",
+  "URLSearchParams",
+  "WritableStream",
+  "atob",
+  "btoa",
+  "clearInterval",
+  "clearTimeout",
+  "console",
+  "fetch",
+  "queueMicrotask",
+  "setInterval",
+  "setTimeout",
+  "structuredClone",
+];
+
+function createSandbox(root: string): Record<string, any> {
+  const sandbox: Record<string, any> = {};
+  for (const name of WEB_GLOBALS) {
+    if (name in globalThis) {
+      sandbox[name] = (globalThis as any)[name];
+    }
+  }
+  sandbox.crypto = webcrypto;
+  // The edge runtime only supports reading environment variables.
+  sandbox.process = { env: process.env };
+  sandbox.self = sandbox;
+  sandbox.globalThis = sandbox;
+  sandbox.importScripts = (...paths: string[]) => {
+    for (const path of paths) {
+      const filename = join(root, path);
+      runInContext(readFileSync(filename, "utf-8"), sandbox, { filename });
+    }
+  };
+  return createContext(sandbox);
+}
+
+let entry: any;
+
+/**
+ * Loads the edge chunks into a fresh sandbox on first use and returns the
+ * exports of the edge entry module.
+ */
+export function getEdgeEntry(): any {
+  if (entry == null) {
+    const { root, chunks } = TURBOPACK_EDGE_SANDBOX;
+    const sandbox = createSandbox(root);
+    sandbox.importScripts(...chunks.map((chunk) => `/${chunk}`));
+    entry = sandbox.TURBOPACK_EDGE_ENTRY;
+    if (entry == null) {
+      throw new Error("The edge entry was not registered in the sandbox");
+    }
+  }
+  return entry;
+}
@@ -0,0 +1,181 @@
+// @ts-ignore
+import { runLoaders } from "@vercel/turbopack/loader-runner";
+import { createRequire } from "module";
+import { dirname, join, relative, resolve, isAbsolute, sep } from "path";
+import type { Ipc } from "../ipc/evaluate";
+
+type LoaderConfig = {
+  loader: string;
+  options: { [k: string]: unknown };
+};
+
+type EmittedAsset = {
+  file: string;
+  content: string;
+  encoding?: "base64";
+  sourceMap?: string;
+};
+
+type ResolveCallback = (err: Error | null, result?: string) => void;
+
+const contextDir = process.cwd();
+// Returns the path of `file` relative to the root directory, or `undefined`
+// when it's outside of it.
+const toPath = (file: string) => {
+  const relPath = relative(contextDir, file);
+  if (
+    isAbsolute(relPath) ||
+    relPath === ".." ||
+    relPath.startsWith(`..${sep}`)
+  ) {
+    return undefined;
+  }
+  return sep !== "/" ? relPath.replaceAll(sep, "/") : relPath;
+};
+
+const toMessage = (error: Error | string) =>
+  typeof error === "string" ? error : error.message;
+
+const resolveFrom = (context: string, request: string) =>
+  createRequire(join(context, "noop.js")).resolve(request);
+
+const transform = (
+  ipc: Ipc,
+  content: string,
+  name: string,
+  loaders: LoaderConfig[]
+) => {
+  return new Promise((resolvePromise, reject) => {
+    const resource = resolve(contextDir, name);
+    const resourceDir = dirname(resource);
+    const assets: EmittedAsset[] = [];
+    const warnings: string[] = [];
+    const errors: string[] = [];
+
+    // Files outside of the root directory can't be watched, which is reported
+    // as a warning instead of failing the transform.
+    const addDependency = (
+      type: "fileDependency" | "dirDependency",
+      file: string
+    ) => {
+      const path = toPath(file);
+      if (path === undefined) {
+        warnings.push(
+          `Changes to ${file} are not watched, as it's outside of the root ` +
+            `directory (${contextDir})`
+        );
+        return;
+      }
+      if (type === "dirDependency") {
+        ipc.send({ type, path, glob: "**" });
+      } else {
+        ipc.send({ type, path });
+      }
+    };
+
+    const resolvedLoaders = loaders.map((loader) => {
+      const resolved = resolveFrom(resourceDir, loader.loader);
+      // Watch the loader module, so the resource is transformed again when
+      // the loader changes.
+      addDependency("fileDependency", resolved);
+      return { loader: resolved, options: loader.options };
+    });
+
+    const loaderResolve = (
+      context: string,
+      request: string,
+      callback?: ResolveCallback
+    ) => {
+      let result: string;
+      try {
+        result = resolveFrom(context, request);
+      } catch (err) {
+        if (callback) {
+          return callback(err as Error);
+        }
+        return Promise.reject(err);
+      }
+      addDependency("fileDependency", result);
+      if (callback) {
+        return callback(null, result);
+      }
+      return Promise.resolve(result);
+    };
+
+    runLoaders(
+      {
+        resource,
+        context: {
+          rootContext: contextDir,
+          mode: "development",
+          sourceMap: true,
+          getOptions(this: any) {
+            const loader = this.loaders[this.loaderIndex];
+            return loader.options || {};
+          },
+          resolve: loaderResolve,
+          getResolve() {
+            return loaderResolve;
+          },
+          emitFile(file: string, content: string | Buffer, sourceMap?: any) {
+            assets.push({
+              file,
+              // Buffers can contain binary data, e.g. images.
+              content:
+                typeof content === "string"
+                  ? content
+                  : content.toString("base64"),
+              encoding: typeof content === "string" ? undefined : "base64",
+              sourceMap:
+                sourceMap == null || typeof sourceMap === "string"
+                  ? sourceMap
+                  : JSON.stringify(sourceMap),
+            });
+          },
+          emitWarning(warning: Error | string) {
+            warnings.push(toMessage(warning));
+          },
+          emitError(error: Error | string) {
+            errors.push(toMessage(error));
+          },
+        },
+        loaders: resolvedLoaders,
+        readResource: (
+          _filename: string,
+          callback: (err: Error | null, buffer: Buffer) => void
+        ) => {
+          // TODO assuming the content is utf-8 here, binary resources would
+          // need to be passed differently.
+          callback(null, Buffer.from(content, "utf-8"));
+        },
+      },
+      (err: Error | null, result: any) => {
+        if (err) return reject(err);
+        for (const dep of result.contextDependencies) {
+          addDependency("dirDependency", dep);
+        }
+        for (const dep of result.fileDependencies) {
+          // The resource itself is already tracked by turbopack.
+          if (dep === resource) continue;
+          addDependency("fileDependency", dep);
+        }
+        for (const dep of result.missingDependencies) {
+          addDependency("fileDependency", dep);
+        }
+        if (!result.result) return reject(new Error("No result from loaders"));
+        const [source, map] = result.result;
+        resolvePromise({
+          source:
+            typeof source === "string" ? source : source.toString("utf-8"),
+          map:
+            map == null || typeof map === "string" ? map : JSON.stringify(map),
+          assets,
+          warnings,
+          errors,
+        });
+      }
+    );
+  });
+};
+
+export { transform as default };
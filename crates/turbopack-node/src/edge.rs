@@ -0,0 +1,231 @@
+use std::{collections::HashMap, fmt::Write, thread::available_parallelism};
+
+use anyhow::{bail, Result};
+use serde_json::Value as JsonValue;
+use turbo_tasks::Value;
+use turbo_tasks_fs::{to_sys_path, File, FileSystemPathVc};
+use turbopack_core::{
+    asset::{Asset, AssetContentVc, AssetVc},
+    chunk::{dev::DevChunkingContextVc, ChunkGroupVc, ChunkReferenceVc},
+    context::AssetContextVc,
+    reference::AssetReferencesVc,
+    source_asset::SourceAssetVc,
+    virtual_asset::VirtualAssetVc,
+};
+use turbopack_ecmascript::{
+    chunk::EcmascriptChunkPlaceablesVc, utils::stringify_str, EcmascriptInputTransform,
+    EcmascriptInputTransformsVc, EcmascriptModuleAssetType, EcmascriptModuleAssetVc, InnerAssetsVc,
+};
+
+use crate::{
+    embed_js::embed_file_path,
+    emit,
+    evaluate::{evaluate_with_pool, JavaScriptValue},
+    pool::{NodeJsPool, NodeJsPoolVc},
+};
+
+/// Creates a pool of Node.js processes which evaluate `module_asset` inside a
+/// sandbox that only provides web APIs, emulating the edge runtime locally.
+///
+/// `module_asset` is compiled with `edge_context`, which is expected to use an
+/// edge function environment, and needs to default-export a function. The
+/// pool uses the same protocol as
+/// [get_evaluate_pool](crate::evaluate::get_evaluate_pool).
+#[turbo_tasks::function]
+pub async fn get_edge_evaluate_pool(
+    context_path: FileSystemPathVc,
+    module_asset: AssetVc,
+    cwd: FileSystemPathVc,
+    host_context: AssetContextVc,
+    edge_context: AssetContextVc,
+    intermediate_output_path: FileSystemPathVc,
+    runtime_entries: Option<EcmascriptChunkPlaceablesVc>,
+) -> Result<NodeJsPoolVc> {
+    let host_chunking_context = DevChunkingContextVc::builder(
+        context_path,
+        intermediate_output_path,
+        intermediate_output_path.join("chunks"),
+        intermediate_output_path.join("assets"),
+    )
+    .build();
+    let edge_output_root = intermediate_output_path.join("edge");
+    let edge_chunking_context = DevChunkingContextVc::builder(
+        context_path,
+        edge_output_root,
+        edge_output_root.join("chunks"),
+        edge_output_root.join("assets"),
+    )
+    .build();
+
+    let typescript_module =
+        |source: AssetVc, context: AssetContextVc, inner_assets: HashMap<String, AssetVc>| {
+            EcmascriptModuleAssetVc::new_with_inner_assets(
+                source,
+                context,
+                Value::new(EcmascriptModuleAssetType::Typescript),
+                EcmascriptInputTransformsVc::cell(vec![EcmascriptInputTransform::TypeScript]),
+                context.environment(),
+                InnerAssetsVc::cell(inner_assets),
+            )
+        };
+
+    let runtime_asset: AssetVc = SourceAssetVc::new(embed_file_path("ipc/evaluate.ts")).into();
+    let sandbox_asset: AssetVc = SourceAssetVc::new(embed_file_path("edge/sandbox.ts")).into();
+    let host_module = typescript_module(
+        VirtualAssetVc::new(
+            sandbox_asset.path().join("host.js"),
+            File::from(
+                "import { run } from 'RUNTIME'; import { getEdgeEntry } from 'SANDBOX'; \
+                 run((...args) => getEdgeEntry().default(...args))",
+            )
+            .into(),
+        )
+        .into(),
+        host_context,
+        HashMap::from([
+            (
+                "RUNTIME".to_string(),
+                typescript_module(runtime_asset, host_context, HashMap::new()).as_asset(),
+            ),
+            (
+                "SANDBOX".to_string(),
+                typescript_module(sandbox_asset, host_context, HashMap::new()).as_asset(),
+            ),
+        ]),
+    );
+
+    let module_path = module_asset.path().await?;
+    let edge_module = typescript_module(
+        VirtualAssetVc::new(
+            module_asset.path().join("edge-entry.js"),
+            File::from("import * as entry from 'INNER'; self.TURBOPACK_EDGE_ENTRY = entry;").into(),
+        )
+        .into(),
+        edge_context,
+        HashMap::from([("INNER".to_string(), module_asset)]),
+    );
+
+    let path = intermediate_output_path.join(module_path.file_name());
+    let (Some(cwd), Some(entrypoint)) = (to_sys_path(cwd).await?, to_sys_path(path).await?) else {
+        panic!("can only evaluate from a disk filesystem");
+    };
+    let bootstrap = EdgeSandboxBootstrapAsset {
+        path,
+        host_chunk_group: ChunkGroupVc::from_chunk(
+            host_module.as_evaluated_chunk(host_chunking_context, None),
+        ),
+        edge_output_root,
+        edge_chunk_group: ChunkGroupVc::from_chunk(
+            edge_module.as_evaluated_chunk(edge_chunking_context, runtime_entries),
+        ),
+    };
+    emit(bootstrap.cell().into(), intermediate_output_path).await?;
+    let pool = NodeJsPool::new(
+        cwd,
+        entrypoint,
+        HashMap::new(),
+        available_parallelism().map_or(1, |v| v.get()),
+    );
+    Ok(pool.cell())
+}
+
+/// Evaluates `module_asset` in the edge sandbox with `args`, see
+/// [get_edge_evaluate_pool].
+///
+/// This is not a task, so the result is never cached. It is meant for work
+/// that needs to happen on every request, like running middleware.
+#[allow(clippy::too_many_arguments)]
+pub async fn evaluate_edge(
+    context_path: FileSystemPathVc,
+    module_asset: AssetVc,
+    cwd: FileSystemPathVc,
+    context_path_for_issue: FileSystemPathVc,
+    host_context: AssetContextVc,
+    edge_context: AssetContextVc,
+    intermediate_output_path: FileSystemPathVc,
+    runtime_entries: Option<EcmascriptChunkPlaceablesVc>,
+    args: Vec<&JsonValue>,
+) -> Result<JavaScriptValue> {
+    let pool = get_edge_evaluate_pool(
+        context_path,
+        module_asset,
+        cwd,
+        host_context,
+        edge_context,
+        intermediate_output_path,
+        runtime_entries,
+    )
+    .await?;
+    evaluate_with_pool(&pool, cwd, context_path_for_issue, args, false).await
+}
+
+/// Like [NodeJsBootstrapAsset](crate::bootstrap::NodeJsBootstrapAsset), but
+/// also tells the sandbox where to find the edge chunks.
+#[turbo_tasks::value(shared)]
+struct EdgeSandboxBootstrapAsset {
+    path: FileSystemPathVc,
+    host_chunk_group: ChunkGroupVc,
+    edge_output_root: FileSystemPathVc,
+    edge_chunk_group: ChunkGroupVc,
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for EdgeSandboxBootstrapAsset {
+    #[turbo_tasks::function]
+    fn path(&self) -> FileSystemPathVc {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<AssetContentVc> {
+        let context_path = self.path.parent().await?;
+        let edge_output_root = self.edge_output_root.await?;
+
+        let Some(root) = context_path.get_relative_path_to(&edge_output_root) else {
+            bail!("edge output root needs to be on the same filesystem as the bootstrap file");
+        };
+        let mut edge_chunks = Vec::new();
+        for chunk in self.edge_chunk_group.chunks().await?.iter() {
+            let path = &*chunk.path().await?;
+            if let Some(p) = edge_output_root.get_path_to(path) {
+                if p.ends_with(".js") {
+                    edge_chunks.push(stringify_str(p));
+                }
+            }
+        }
+
+        let mut output = "Error.stackTraceLimit = 100;\nglobal.self = global;\n".to_string();
+        writeln!(
+            &mut output,
+            "global.TURBOPACK_EDGE_SANDBOX = {{ root: require(\"path\").join(__dirname, {}), \
+             chunks: [{}] }};",
+            stringify_str(&root),
+            edge_chunks.join(", ")
+        )?;
+        for chunk in self.host_chunk_group.chunks().await?.iter() {
+            let path = &*chunk.path().await?;
+            if let Some(p) = context_path.get_relative_path_to(path) {
+                if p.ends_with(".js") {
+                    writeln!(&mut output, "require({});", stringify_str(&p))?;
+                }
+            }
+        }
+
+        Ok(File::from(output).into())
+    }
+
+    #[turbo_tasks::function]
+    async fn references(&self) -> Result<AssetReferencesVc> {
+        let mut references = Vec::new();
+        for chunk in self
+            .host_chunk_group
+            .chunks()
+            .await?
+            .iter()
+            .chain(self.edge_chunk_group.chunks().await?.iter())
+        {
+            references.push(ChunkReferenceVc::new(*chunk).into());
+        }
+        Ok(AssetReferencesVc::cell(references))
+    }
+}
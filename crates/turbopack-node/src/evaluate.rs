@@ -1,6 +1,7 @@
 use std::{collections::HashMap, thread::available_parallelism};
 
 use anyhow::Result;
+use serde_json::Value as JsonValue;
 use turbo_tasks::{
     primitives::{JsonValueVc, StringVc},
     CompletionVc, TryJoinIterExt, Value, ValueToString,
@@ -129,12 +130,36 @@ pub async fn evaluate(
         runtime_entries,
     )
     .await?;
-    let mut operation = pool.operation().await?;
+    // Assume this is a one-off operation, so we can kill the process
+    // TODO use a better way to decide that.
+    let kill = args.is_empty();
     let args = args.into_iter().try_join().await?;
+    let output = evaluate_with_pool(
+        &pool,
+        cwd,
+        context_path_for_issue,
+        args.iter().map(|v| &**v).collect(),
+        kill,
+    )
+    .await?;
+    Ok(output.cell())
+}
+
+/// Sends `args` to a process of the `pool` and waits for the evaluated value.
+/// Files the evaluation depends on become dependencies of the calling task.
+///
+/// With `kill`, the process is stopped afterwards instead of being returned to
+/// the pool.
+pub(crate) async fn evaluate_with_pool(
+    pool: &NodeJsPool,
+    cwd: FileSystemPathVc,
+    context_path_for_issue: FileSystemPathVc,
+    args: Vec<&JsonValue>,
+    kill: bool,
+) -> Result<JavaScriptValue> {
+    let mut operation = pool.operation().await?;
     operation
-        .send(EvalJavaScriptOutgoingMessage::Evaluate {
-            args: args.iter().map(|v| &**v).collect(),
-        })
+        .send(EvalJavaScriptOutgoingMessage::Evaluate { args })
         .await?;
     let mut file_dependencies = Vec::new();
     let mut dir_dependencies = Vec::new();
@@ -183,12 +208,10 @@ pub async fn evaluate(
     for dep in dir_dependencies {
         dep.await?;
     }
-    if args.is_empty() {
-        // Assume this is a one-off operation, so we can kill the process
-        // TODO use a better way to decide that.
+    if kill {
         operation.wait_or_kill().await?;
     }
-    Ok(output.cell())
+    Ok(output)
 }
 
 /// An issue that occurred while evaluating node code.
@@ -32,6 +32,7 @@ use self::{
 use crate::source_map::{SourceMapTraceVc, StackFrame, TraceResult};
 
 pub mod bootstrap;
+pub mod edge;
 mod embed_js;
 pub mod evaluate;
 pub mod execution_context;
@@ -1,7 +1,9 @@
 use anyhow::{Context, Result};
 use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
 use turbo_tasks::{
     primitives::{Regex, StringVc},
+    trace::TraceRawVcs,
     ValueToString, ValueToStringVc,
 };
 
@@ -11,7 +13,23 @@ use turbo_tasks::{
 #[derive(Debug)]
 pub struct PathRegex {
     regex: Regex,
-    named_params: Vec<String>,
+    named_params: Vec<NamedParam>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, TraceRawVcs, Serialize, Deserialize)]
+struct NamedParam {
+    name: String,
+    /// Whether the param matches multiple segments, e.g. `[...slug]`.
+    catch_all: bool,
+}
+
+/// The value of a dynamic part of a path. Catch all segments match a list of
+/// segments.
+#[derive(Debug, Clone, PartialEq, Eq, TraceRawVcs, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Param {
+    Single(String),
+    Multi(Vec<String>),
 }
 
 impl PathRegex {
@@ -22,17 +40,28 @@ impl PathRegex {
 
     /// Matches a path with the regular expression and returns a map with the
     /// named captures.
-    pub fn get_matches(&self, path: &str) -> Option<IndexMap<String, String>> {
+    pub fn get_matches(&self, path: &str) -> Option<IndexMap<String, Param>> {
         self.regex.captures(path).map(|capture| {
             self.named_params
                 .iter()
                 .enumerate()
-                .filter_map(|(idx, name)| {
-                    if name.is_empty() {
+                .filter_map(|(idx, param)| {
+                    if param.name.is_empty() {
                         return None;
                     }
-                    let value = capture.get(idx + 1)?;
-                    Some((name.to_string(), value.as_str().to_string()))
+                    let value = capture.get(idx + 1)?.as_str();
+                    let value = if param.catch_all {
+                        Param::Multi(
+                            value
+                                .split('/')
+                                .filter(|segment| !segment.is_empty())
+                                .map(|segment| segment.to_string())
+                                .collect(),
+                        )
+                    } else {
+                        Param::Single(value.to_string())
+                    };
+                    Some((param.name.to_string(), value))
                 })
                 .collect()
         })
@@ -50,7 +79,7 @@ impl ValueToString for PathRegex {
 /// Builder for [PathRegex].
 pub struct PathRegexBuilder {
     regex_str: String,
-    named_params: Vec<String>,
+    named_params: Vec<NamedParam>,
 }
 
 impl PathRegexBuilder {
@@ -82,7 +111,10 @@ impl PathRegexBuilder {
             "([^?]+)?"
         });
         self.push_str(&regex::escape(rem.as_ref()));
-        self.named_params.push(name.into());
+        self.named_params.push(NamedParam {
+            name: name.into(),
+            catch_all: true,
+        });
     }
 
     /// Pushes a catch all segment to the regex.
@@ -96,7 +128,10 @@ impl PathRegexBuilder {
         }
         self.push_str("([^?]+)");
         self.push_str(&regex::escape(rem.as_ref()));
-        self.named_params.push(name.into());
+        self.named_params.push(NamedParam {
+            name: name.into(),
+            catch_all: true,
+        });
     }
 
     /// Pushes a dynamic segment to the regex.
@@ -110,7 +145,10 @@ impl PathRegexBuilder {
         }
         self.push_str("([^?/]+)");
         self.push_str(&regex::escape(rem.as_ref()));
-        self.named_params.push(name.into());
+        self.named_params.push(NamedParam {
+            name: name.into(),
+            catch_all: false,
+        });
     }
 
     /// Pushes a static segment to the regex.
@@ -139,3 +177,73 @@ impl Default for PathRegexBuilder {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Param, PathRegex, PathRegexBuilder};
+
+    fn matches(regex: &PathRegex, path: &str) -> Option<Vec<(String, Param)>> {
+        regex
+            .get_matches(path)
+            .map(|matches| matches.into_iter().collect())
+    }
+
+    fn multi(segments: &[&str]) -> Param {
+        Param::Multi(segments.iter().map(|segment| segment.to_string()).collect())
+    }
+
+    #[test]
+    fn test_catch_all() {
+        let mut builder = PathRegexBuilder::new();
+        builder.push_static_segment("docs");
+        builder.push_catch_all("slug", "");
+        let regex = builder.build().unwrap();
+
+        assert_eq!(
+            matches(&regex, "docs/a"),
+            Some(vec![("slug".to_string(), multi(&["a"]))])
+        );
+        assert_eq!(
+            matches(&regex, "docs/a/b/c"),
+            Some(vec![("slug".to_string(), multi(&["a", "b", "c"]))])
+        );
+        assert_eq!(matches(&regex, "docs"), None);
+        assert_eq!(matches(&regex, "docs/"), None);
+    }
+
+    #[test]
+    fn test_optional_catch_all() {
+        let mut builder = PathRegexBuilder::new();
+        builder.push_static_segment("docs");
+        builder.push_optional_catch_all("slug", "");
+        let regex = builder.build().unwrap();
+
+        assert_eq!(
+            matches(&regex, "docs/a/b"),
+            Some(vec![("slug".to_string(), multi(&["a", "b"]))])
+        );
+        // A missing optional catch all isn't part of the params.
+        assert_eq!(matches(&regex, "docs"), Some(vec![]));
+        assert_eq!(matches(&regex, "other/a"), None);
+    }
+
+    #[test]
+    fn test_dynamic_segment_and_catch_all() {
+        let mut builder = PathRegexBuilder::new();
+        builder.push_dynamic_segment("lang", "");
+        builder.push_catch_all("slug", "");
+        let regex = builder.build().unwrap();
+
+        assert_eq!(
+            matches(&regex, "en/a/b"),
+            Some(vec![
+                ("lang".to_string(), Param::Single("en".to_string())),
+                ("slug".to_string(), multi(&["a", "b"])),
+            ])
+        );
+        assert_eq!(
+            serde_json::to_value(regex.get_matches("en/a/b").unwrap()).unwrap(),
+            serde_json::json!({ "lang": "en", "slug": ["a", "b"] })
+        );
+    }
+}
@@ -4,7 +4,7 @@ use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use turbopack_dev_server::source::{query::Query, HeaderValue};
 
-use crate::{ResponseHeaders, StructuredError};
+use crate::{path_regex::Param, ResponseHeaders, StructuredError};
 
 pub mod issue;
 pub mod node_api_source;
@@ -14,7 +14,7 @@ pub mod rendered_source;
 
 #[turbo_tasks::value(shared)]
 pub struct RenderData {
-    params: IndexMap<String, String>,
+    params: IndexMap<String, Param>,
     method: String,
     url: String,
     query: Query,
@@ -59,5 +59,12 @@ pub enum RenderResult {
         body: String,
         #[serde(rename = "contentType")]
         content_type: Option<String>,
+        /// The status code of the response, when it isn't 200.
+        #[serde(default)]
+        status: Option<u16>,
+        /// Additional response headers, arranged as contiguous (name, value)
+        /// pairs.
+        #[serde(default)]
+        headers: Vec<String>,
     },
 }
@@ -15,7 +15,11 @@ use turbopack_dev_server::source::{
 use turbopack_ecmascript::chunk::EcmascriptChunkPlaceablesVc;
 
 use super::{render_proxy::render_proxy, RenderData};
-use crate::{get_intermediate_asset, node_entry::NodeEntryVc, path_regex::PathRegexVc};
+use crate::{
+    get_intermediate_asset,
+    node_entry::NodeEntryVc,
+    path_regex::{Param, PathRegexVc},
+};
 
 /// Creates a [NodeApiContentSource].
 #[turbo_tasks::function]
@@ -71,7 +75,7 @@ impl NodeApiContentSource {
 
     /// Matches a path with the regular expression and returns a JSON object
     /// with the named captures
-    async fn get_matches(&self, path: &str) -> Result<Option<IndexMap<String, String>>> {
+    async fn get_matches(&self, path: &str) -> Result<Option<IndexMap<String, Param>>> {
         Ok(self.path_regex.await?.get_matches(path))
     }
 }
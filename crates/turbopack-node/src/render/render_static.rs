@@ -6,7 +6,10 @@ use turbopack_core::{
     asset::{Asset, AssetContentVc, AssetVc},
     chunk::ChunkingContextVc,
 };
-use turbopack_dev_server::html::DevHtmlAssetVc;
+use turbopack_dev_server::{
+    html::DevHtmlAssetVc,
+    source::{ContentSourceContent, ContentSourceContentVc, ProxyResult},
+};
 use turbopack_ecmascript::{chunk::EcmascriptChunkPlaceablesVc, EcmascriptModuleAssetVc};
 
 use super::{
@@ -16,6 +19,9 @@ use super::{
 use crate::{get_intermediate_asset, get_renderer_pool, pool::NodeJsOperation, trace_stack};
 
 /// Renders a module as static HTML in a node.js process.
+///
+/// The result is served as static content, unless the renderer reported a
+/// status code or response headers, e.g. for a page which is not found.
 #[turbo_tasks::function]
 pub async fn render_static(
     path: FileSystemPathVc,
@@ -25,7 +31,7 @@ pub async fn render_static(
     chunking_context: ChunkingContextVc,
     intermediate_output_path: FileSystemPathVc,
     data: RenderDataVc,
-) -> Result<AssetContentVc> {
+) -> Result<ContentSourceContentVc> {
     let intermediate_asset = get_intermediate_asset(
         module.as_evaluated_chunk(chunking_context, Some(runtime_entries)),
         intermediate_output_path,
@@ -47,7 +53,7 @@ pub async fn render_static(
     )
     .await
     {
-        Ok(asset) => Ok(asset),
+        Ok(content) => Ok(content),
         Err(err) => static_error(path, err, Some(operation), fallback_page).await,
     }
 }
@@ -57,7 +63,7 @@ async fn run_static_operation(
     data: RenderDataVc,
     intermediate_asset: AssetVc,
     intermediate_output_path: FileSystemPathVc,
-) -> Result<AssetContentVc> {
+) -> Result<ContentSourceContentVc> {
     let data = data.await?;
 
     operation
@@ -71,26 +77,50 @@ async fn run_static_operation(
     {
         RenderStaticIncomingMessage::Result {
             result: RenderResult::Simple(body),
-        } => Ok(FileContent::Content(File::from(body).with_content_type(TEXT_HTML_UTF_8)).into()),
+        } => Ok(static_content(body, TEXT_HTML_UTF_8)),
         RenderStaticIncomingMessage::Result {
-            result: RenderResult::Advanced { body, content_type },
-        } => Ok(FileContent::Content(
-            File::from(body)
-                .with_content_type(content_type.map_or(Ok(TEXT_HTML_UTF_8), |c| c.parse())?),
-        )
-        .into()),
+            result:
+                RenderResult::Advanced {
+                    body,
+                    content_type,
+                    status,
+                    headers,
+                },
+        } => {
+            let content_type = content_type.map_or(Ok(TEXT_HTML_UTF_8), |c| c.parse())?;
+            if status.is_none() && headers.is_empty() {
+                return Ok(static_content(body, content_type));
+            }
+            let mut response_headers = vec!["content-type".to_string(), content_type.to_string()];
+            response_headers.extend(headers);
+            Ok(ContentSourceContent::HttpProxy(
+                ProxyResult {
+                    status: status.unwrap_or(200),
+                    headers: response_headers,
+                    body: body.into(),
+                }
+                .cell(),
+            )
+            .cell())
+        }
         RenderStaticIncomingMessage::Error(error) => {
             bail!(trace_stack(error, intermediate_asset, intermediate_output_path).await?)
         }
     }
 }
 
+fn static_content(body: String, content_type: mime::Mime) -> ContentSourceContentVc {
+    let content: AssetContentVc =
+        FileContent::Content(File::from(body).with_content_type(content_type)).into();
+    ContentSourceContent::Static(content.into()).cell()
+}
+
 async fn static_error(
     path: FileSystemPathVc,
     error: anyhow::Error,
     operation: Option<NodeJsOperation>,
     fallback_page: DevHtmlAssetVc,
-) -> Result<AssetContentVc> {
+) -> Result<ContentSourceContentVc> {
     let message = format!("{error:?}");
     let status = match operation {
         Some(operation) => Some(operation.wait_or_kill().await?),
@@ -122,5 +152,5 @@ async fn static_error(
 
     let html = fallback_page.with_body(body);
 
-    Ok(html.content())
+    Ok(ContentSourceContent::Static(html.content().into()).cell())
 }
@@ -26,8 +26,9 @@ use turbopack_ecmascript::chunk::EcmascriptChunkPlaceablesVc;
 
 use super::{render_static::render_static, RenderData};
 use crate::{
-    external_asset_entrypoints, get_intermediate_asset, node_entry::NodeEntryVc,
-    path_regex::PathRegexVc,
+    external_asset_entrypoints, get_intermediate_asset,
+    node_entry::NodeEntryVc,
+    path_regex::{Param, PathRegexVc},
 };
 
 /// Creates a content source that renders something in Node.js with the passed
@@ -95,7 +96,7 @@ impl NodeRenderContentSource {
 
     /// Matches a path with the regular expression and returns a JSON object
     /// with the named captures
-    async fn get_matches(&self, path: &str) -> Result<Option<IndexMap<String, String>>> {
+    async fn get_matches(&self, path: &str) -> Result<Option<IndexMap<String, Param>>> {
         Ok(self.path_regex.await?.get_matches(path))
     }
 }
@@ -159,7 +160,7 @@ impl ContentSource for NodeRenderContentSource {
                 } = &*data
                 {
                     let entry = this.entry.entry(data.clone()).await?;
-                    let asset = render_static(
+                    render_static(
                         this.server_root.join(path),
                         entry.module,
                         this.runtime_entries,
@@ -175,8 +176,7 @@ impl ContentSource for NodeRenderContentSource {
                             path: format!("/{}", this.pathname.await?),
                         }
                         .cell(),
-                    );
-                    ContentSourceContent::Static(asset.into()).cell()
+                    )
                 } else {
                     ContentSourceContent::NeedData(NeededData {
                         source: self_vc.into(),
@@ -1 +1,3 @@
 pub mod postcss;
+mod util;
+pub mod webpack;
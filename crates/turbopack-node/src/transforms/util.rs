@@ -0,0 +1,63 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use turbo_tasks_fs::{File, FileContent, FileSystemPathVc};
+use turbopack_core::{asset::AssetContent, virtual_asset::VirtualAssetVc};
+
+/// An asset emitted by a transform running in Node.js, e.g. via PostCSS
+/// `asset` messages or a webpack loader's `this.emitFile`.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EmittedAsset {
+    file: String,
+    content: String,
+    #[serde(default)]
+    encoding: EmittedAssetEncoding,
+    source_map: Option<String>,
+}
+
+/// How the `content` of an [EmittedAsset] is encoded.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum EmittedAssetEncoding {
+    #[default]
+    Utf8,
+    /// Binary content, e.g. an image emitted by a webpack loader.
+    Base64,
+}
+
+pub fn emitted_assets_to_virtual_assets(
+    assets: Option<Vec<EmittedAsset>>,
+    root: FileSystemPathVc,
+) -> Result<Vec<VirtualAssetVc>> {
+    assets
+        .into_iter()
+        .flatten()
+        .map(
+            |EmittedAsset {
+                 file,
+                 content,
+                 encoding,
+                 source_map,
+             }| (file, (content, encoding, source_map)),
+        )
+        // Sort it to make it determinstic
+        .collect::<BTreeMap<_, _>>()
+        .into_iter()
+        .map(|(file, (content, encoding, _source_map))| -> Result<_> {
+            let content = match encoding {
+                EmittedAssetEncoding::Utf8 => File::from(content),
+                EmittedAssetEncoding::Base64 => File::from(
+                    base64::decode(content)
+                        .with_context(|| format!("Unable to decode emitted asset {file}"))?,
+                ),
+            };
+            // TODO handle SourceMap
+            Ok(VirtualAssetVc::new(
+                root.join(&file),
+                AssetContent::File(FileContent::Content(content).cell()).cell(),
+            ))
+        })
+        .collect()
+}
@@ -0,0 +1,298 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use sourcemap::SourceMap as CrateMap;
+use turbo_tasks::{
+    primitives::{JsonValueVc, StringVc},
+    trace::TraceRawVcs,
+    Value,
+};
+use turbo_tasks_fs::{File, FileContent, FileSystemPathVc};
+use turbopack_core::{
+    asset::{Asset, AssetContent, AssetContentVc, AssetVc},
+    context::AssetContextVc,
+    issue::{Issue, IssueSeverity, IssueSeverityVc, IssueVc},
+    reference::{AssetReferencesVc, SingleAssetReferenceVc},
+    source_map::{GenerateSourceMap, GenerateSourceMapVc, SourceMapVc},
+    source_transform::{SourceTransform, SourceTransformVc},
+    virtual_asset::VirtualAssetVc,
+};
+use turbopack_ecmascript::{
+    EcmascriptInputTransform, EcmascriptInputTransformsVc, EcmascriptModuleAssetType,
+    EcmascriptModuleAssetVc,
+};
+
+use super::util::{emitted_assets_to_virtual_assets, EmittedAsset};
+use crate::{
+    embed_js::embed_file,
+    evaluate::{evaluate, JavaScriptValue},
+    execution_context::{ExecutionContext, ExecutionContextVc},
+};
+
+/// A single webpack loader with its options, e.g.
+/// `{ loader: "@svgr/webpack", options: { icon: true } }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub struct WebpackLoaderItem {
+    pub loader: String,
+    #[serde(default)]
+    #[turbo_tasks(trace_ignore)]
+    pub options: JsonMap<String, JsonValue>,
+}
+
+/// A chain of webpack loaders. Like in webpack, the last loader is applied
+/// first.
+#[derive(Debug, Clone)]
+#[turbo_tasks::value(shared, transparent)]
+pub struct WebpackLoaderItems(pub Vec<WebpackLoaderItem>);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[turbo_tasks::value(transparent, serialization = "custom")]
+struct WebpackLoadersProcessingResult {
+    source: String,
+    map: Option<String>,
+    #[turbo_tasks(trace_ignore)]
+    assets: Option<Vec<EmittedAsset>>,
+    /// Messages of `this.emitWarning` calls.
+    #[serde(default)]
+    warnings: Vec<String>,
+    /// Messages of `this.emitError` calls.
+    #[serde(default)]
+    errors: Vec<String>,
+}
+
+/// A [SourceTransform] which runs a chain of webpack loaders on the source
+/// within a Node.js process.
+#[turbo_tasks::value]
+pub struct WebpackLoaders {
+    evaluate_context: AssetContextVc,
+    execution_context: ExecutionContextVc,
+    loaders: WebpackLoaderItemsVc,
+}
+
+#[turbo_tasks::value_impl]
+impl WebpackLoadersVc {
+    #[turbo_tasks::function]
+    pub fn new(
+        evaluate_context: AssetContextVc,
+        execution_context: ExecutionContextVc,
+        loaders: WebpackLoaderItemsVc,
+    ) -> Self {
+        WebpackLoaders {
+            evaluate_context,
+            execution_context,
+            loaders,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl SourceTransform for WebpackLoaders {
+    #[turbo_tasks::function]
+    fn transform(self_vc: WebpackLoadersVc, source: AssetVc) -> AssetVc {
+        WebpackLoadersProcessedAsset {
+            transform: self_vc,
+            source,
+        }
+        .cell()
+        .into()
+    }
+}
+
+#[turbo_tasks::value]
+struct WebpackLoadersProcessedAsset {
+    transform: WebpackLoadersVc,
+    source: AssetVc,
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for WebpackLoadersProcessedAsset {
+    #[turbo_tasks::function]
+    fn path(&self) -> FileSystemPathVc {
+        self.source.path()
+    }
+
+    #[turbo_tasks::function]
+    async fn content(self_vc: WebpackLoadersProcessedAssetVc) -> Result<AssetContentVc> {
+        Ok(self_vc.process().await?.content)
+    }
+
+    /// References the files emitted by the loaders with `this.emitFile`.
+    #[turbo_tasks::function]
+    async fn references(self_vc: WebpackLoadersProcessedAssetVc) -> Result<AssetReferencesVc> {
+        Ok(AssetReferencesVc::cell(
+            self_vc
+                .process()
+                .await?
+                .assets
+                .iter()
+                .map(|asset| {
+                    SingleAssetReferenceVc::new(
+                        (*asset).into(),
+                        StringVc::cell("asset emitted by webpack loader".to_string()),
+                    )
+                    .into()
+                })
+                .collect(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for WebpackLoadersProcessedAsset {
+    #[turbo_tasks::function]
+    async fn generate_source_map(self_vc: WebpackLoadersProcessedAssetVc) -> Result<SourceMapVc> {
+        Ok(self_vc.process().await?.source_map)
+    }
+}
+
+#[turbo_tasks::value]
+struct ProcessWebpackLoadersResult {
+    content: AssetContentVc,
+    source_map: SourceMapVc,
+    assets: Vec<VirtualAssetVc>,
+}
+
+#[turbo_tasks::function]
+fn webpack_loaders_executor(
+    project_root: FileSystemPathVc,
+    context: AssetContextVc,
+) -> EcmascriptModuleAssetVc {
+    EcmascriptModuleAssetVc::new(
+        VirtualAssetVc::new(
+            project_root.join("__turbopack__/webpack-loaders.ts"),
+            AssetContent::File(embed_file("transforms/webpack-loaders.ts")).cell(),
+        )
+        .into(),
+        context,
+        Value::new(EcmascriptModuleAssetType::Typescript),
+        EcmascriptInputTransformsVc::cell(vec![EcmascriptInputTransform::TypeScript]),
+        context.environment(),
+    )
+}
+
+#[turbo_tasks::value_impl]
+impl WebpackLoadersProcessedAssetVc {
+    #[turbo_tasks::function]
+    async fn process(self) -> Result<ProcessWebpackLoadersResultVc> {
+        let this = self.await?;
+        let transform = this.transform.await?;
+
+        let ExecutionContext {
+            project_root,
+            intermediate_output_path,
+        } = *transform.execution_context.await?;
+        let source_content = this.source.content();
+        let AssetContent::File(file) = *source_content.await? else {
+            bail!("Webpack Loaders transform only support transforming files");
+        };
+        let FileContent::Content(content) = &*file.await? else {
+            return Ok(ProcessWebpackLoadersResult {
+                content: AssetContent::File(FileContent::NotFound.cell()).cell(),
+                source_map: SourceMapVc::new_sectioned(vec![]),
+                assets: Vec::new()
+            }.cell());
+        };
+        let content = content.content().to_str()?;
+        let context = transform.evaluate_context;
+
+        let webpack_loaders_executor = webpack_loaders_executor(project_root, context);
+        let resource_fs_path = this.source.path().await?;
+        let resource_path = resource_fs_path.path.as_str();
+        let loaders = transform.loaders.await?;
+        let config_value = evaluate(
+            project_root,
+            webpack_loaders_executor.into(),
+            project_root,
+            this.source.path(),
+            context,
+            intermediate_output_path,
+            None,
+            vec![
+                JsonValueVc::cell(content.into()),
+                JsonValueVc::cell(resource_path.into()),
+                JsonValueVc::cell(serde_json::to_value(&*loaders)?),
+            ],
+        )
+        .await?;
+        let JavaScriptValue::Value(val) = &*config_value else {
+            // An error happened, which has already been converted into an issue.
+            return Ok(ProcessWebpackLoadersResult {
+                content: AssetContent::File(FileContent::NotFound.cell()).cell(),
+                source_map: SourceMapVc::new_sectioned(vec![]),
+                assets: Vec::new()
+            }.cell());
+        };
+        let processed: WebpackLoadersProcessingResult = serde_json::from_reader(val.read())
+            .context("Unable to deserializate response from webpack loaders transform")?;
+        for (messages, severity) in [
+            (&processed.warnings, IssueSeverity::Warning),
+            (&processed.errors, IssueSeverity::Error),
+        ] {
+            for message in messages {
+                WebpackLoaderIssue {
+                    path: this.source.path(),
+                    severity: severity.into(),
+                    message: message.clone(),
+                }
+                .cell()
+                .as_issue()
+                .emit();
+            }
+        }
+        let source_map = match processed.map {
+            Some(map) => SourceMapVc::new_regular(
+                CrateMap::from_slice(map.as_bytes())
+                    .context("Unable to parse source map returned by webpack loaders")?,
+            ),
+            None => SourceMapVc::new_sectioned(vec![]),
+        };
+        let assets = emitted_assets_to_virtual_assets(processed.assets, project_root)?;
+        let file = File::from(processed.source);
+        let content = AssetContent::File(FileContent::Content(file).cell()).cell();
+        Ok(ProcessWebpackLoadersResult {
+            content,
+            source_map,
+            assets,
+        }
+        .cell())
+    }
+}
+
+/// A warning or error reported by a webpack loader with `this.emitWarning` or
+/// `this.emitError`.
+#[turbo_tasks::value(shared)]
+pub struct WebpackLoaderIssue {
+    pub path: FileSystemPathVc,
+    pub severity: IssueSeverityVc,
+    pub message: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for WebpackLoaderIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        self.severity
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell("Issue reported by a webpack loader".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("loaders".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        StringVc::cell(self.message.clone())
+    }
+}
@@ -0,0 +1,202 @@
+#![cfg(test)]
+
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use turbo_tasks::{primitives::StringVc, TurboTasks, Value};
+use turbo_tasks_fs::{File, FileContent, FileSystemVc};
+use turbo_tasks_memory::MemoryBackend;
+use turbopack::{
+    module_options::ModuleOptionsContext, resolve_options_context::ResolveOptionsContext,
+    transition::TransitionsByNameVc, ModuleAssetContextVc,
+};
+use turbopack_core::{
+    asset::AssetContent,
+    context::AssetContextVc,
+    reference::all_referenced_assets,
+    reference_type::{EntryReferenceSubType, ReferenceType},
+    source_asset::SourceAssetVc,
+};
+
+#[macro_use]
+mod util;
+
+use util::{base_dir, browser_environment, test_fs};
+
+register!();
+
+/// Returns the code of the module generated for the `import.meta.glob` or
+/// `require.context` call in `index.js`.
+#[turbo_tasks::function]
+async fn context_module_code(fs: FileSystemVc) -> Result<StringVc> {
+    let context: AssetContextVc = ModuleAssetContextVc::new(
+        TransitionsByNameVc::cell(HashMap::new()),
+        browser_environment(),
+        ModuleOptionsContext::default().cell(),
+        ResolveOptionsContext::default().cell(),
+    )
+    .into();
+    let module = context.process(
+        SourceAssetVc::new(fs.root().join("index.js")).into(),
+        Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+    );
+
+    for asset in all_referenced_assets(module).await?.iter() {
+        if !asset.path().await?.path.contains("__turbopack_context_") {
+            continue;
+        }
+        if let AssetContent::File(file) = &*asset.content().await? {
+            if let FileContent::Content(file) = &*file.await? {
+                return Ok(StringVc::cell(file.content().to_str()?.into_owned()));
+            }
+        }
+    }
+    bail!("index.js doesn't reference a context module")
+}
+
+#[tokio::test]
+async fn import_meta_glob() -> Result<()> {
+    register();
+
+    let (_base_dir, base) = base_dir()?;
+    let tt = TurboTasks::new(MemoryBackend::new());
+
+    let fs = tt
+        .run_once(async move {
+            test_fs(
+                base,
+                &[
+                    (
+                        "index.js",
+                        r#"export const modules = import.meta.glob(["./pages/*.js", "./[slug]/[id].js", "./v?/index.js"], { eager: true });"#,
+                    ),
+                    ("pages/a.js", "export default 'a';"),
+                    ("pages/[id].js", "export default 'id';"),
+                    ("[slug]/[id].js", "export default 'slug id';"),
+                    ("[slug]/other.js", "export default 'other';"),
+                    // `?` matches a single character, `[` is matched literally.
+                    ("v1/index.js", "export default 'v1';"),
+                    ("v10/index.js", "export default 'v10';"),
+                ],
+            )
+            .await
+        })
+        .await?;
+
+    let code = tt
+        .run_once(async move {
+            Ok(context_module_code(fs.into())
+                .strongly_consistent()
+                .await?
+                .clone_value())
+        })
+        .await?;
+    assert_eq!(
+        code,
+        r#"module.exports = {
+  "./[slug]/[id].js": require("./[slug]/[id].js"),
+  "./pages/[id].js": require("./pages/[id].js"),
+  "./pages/a.js": require("./pages/a.js"),
+  "./v1/index.js": require("./v1/index.js"),
+};
+"#
+    );
+
+    // Adding a matching file re-evaluates the module.
+    let code = tt
+        .run_once(async move {
+            fs.await?.set_override(
+                "pages/b.js",
+                FileContent::Content(File::from("export default 'b';")),
+            );
+            Ok(context_module_code(fs.into())
+                .strongly_consistent()
+                .await?
+                .clone_value())
+        })
+        .await?;
+    assert_eq!(
+        code,
+        r#"module.exports = {
+  "./[slug]/[id].js": require("./[slug]/[id].js"),
+  "./pages/[id].js": require("./pages/[id].js"),
+  "./pages/a.js": require("./pages/a.js"),
+  "./pages/b.js": require("./pages/b.js"),
+  "./v1/index.js": require("./v1/index.js"),
+};
+"#
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn require_context() -> Result<()> {
+    register();
+
+    let (_base_dir, base) = base_dir()?;
+    let tt = TurboTasks::new(MemoryBackend::new());
+
+    let fs = tt
+        .run_once(async move {
+            test_fs(
+                base,
+                &[
+                    ("context/a.js", "module.exports = 'a';"),
+                    ("context/data.json", r#"{ "name": "data" }"#),
+                    ("context/nested/b.js", "module.exports = 'b';"),
+                ],
+            )
+            .await
+        })
+        .await?;
+
+    for (call, expected_map) in [
+        (
+            r#"require.context("./context", true, /\.js$/)"#,
+            r#"var map = {
+  "./a.js": () => require("./context/a.js"),
+  "./nested/b.js": () => require("./context/nested/b.js"),
+};
+"#,
+        ),
+        (
+            r#"require.context("./context", false, /\.js$/)"#,
+            r#"var map = {
+  "./a.js": () => require("./context/a.js"),
+};
+"#,
+        ),
+        // Recursive and matching all files by default.
+        (
+            r#"require.context("./context/")"#,
+            r#"var map = {
+  "./a.js": () => require("./context/a.js"),
+  "./data.json": () => require("./context/data.json"),
+  "./nested/b.js": () => require("./context/nested/b.js"),
+};
+"#,
+        ),
+    ] {
+        let code = tt
+            .run_once(async move {
+                fs.await?.set_override(
+                    "index.js",
+                    FileContent::Content(File::from(format!("const context = {call};"))),
+                );
+                Ok(context_module_code(fs.into())
+                    .strongly_consistent()
+                    .await?
+                    .clone_value())
+            })
+            .await?;
+        assert!(code.starts_with(expected_map), "{call}:\n{code}");
+        // The context function resolves keys of the map only.
+        assert!(
+            code.contains("context.keys = () => Object.keys(map);\ncontext.resolve = resolve;"),
+            "{call}:\n{code}"
+        );
+    }
+
+    Ok(())
+}
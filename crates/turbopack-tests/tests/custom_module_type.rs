@@ -0,0 +1,169 @@
+#![cfg(test)]
+#![feature(min_specialization)]
+
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use indexmap::IndexMap;
+use turbo_tasks::{primitives::StringVc, TurboTasks, Value, ValueToString};
+use turbo_tasks_fs::{File, FileContent, FileSystemVc};
+use turbo_tasks_memory::MemoryBackend;
+use turbopack::{
+    ecmascript::{EcmascriptInputTransformsVc, EcmascriptModuleAssetType, EcmascriptModuleAssetVc},
+    module_options::{
+        CustomModuleType, CustomModuleTypeVc, ModuleOptionsContext, ModuleRule,
+        ModuleRuleCondition, ModuleRuleEffect,
+    },
+    resolve_options_context::ResolveOptionsContext,
+    transition::TransitionsByNameVc,
+    ModuleAssetContextVc,
+};
+use turbopack_core::{
+    asset::{AssetContent, AssetContentVc, AssetVc},
+    chunk::{dev::DevChunkingContextVc, ChunkableAssetVc},
+    context::AssetContextVc,
+    reference::all_referenced_assets,
+    reference_type::{EntryReferenceSubType, ReferenceType},
+    source_asset::SourceAssetVc,
+    virtual_asset::VirtualAssetVc,
+};
+
+#[macro_use]
+mod util;
+
+use util::{base_dir, browser_environment, test_fs};
+
+register!();
+
+/// Compiles `.graphql` files into ecmascript modules exporting the query
+/// string.
+#[turbo_tasks::value]
+struct GraphQlModuleType;
+
+#[turbo_tasks::value_impl]
+impl CustomModuleType for GraphQlModuleType {
+    #[turbo_tasks::function]
+    async fn create_module(&self, source: AssetVc, context: AssetContextVc) -> Result<AssetVc> {
+        let query = match &*source.content().await? {
+            AssetContent::File(file) => match &*file.await? {
+                FileContent::Content(file) => file.content().to_str()?.into_owned(),
+                FileContent::NotFound => bail!("{} not found", source.path().to_string().await?),
+            },
+            AssetContent::Redirect { .. } => bail!("redirects are not supported"),
+        };
+        let code = format!("export default {};", serde_json::to_string(&query)?);
+        Ok(EcmascriptModuleAssetVc::new(
+            VirtualAssetVc::new(source.path(), AssetContentVc::from(File::from(code))).into(),
+            context,
+            Value::new(EcmascriptModuleAssetType::Ecmascript),
+            EcmascriptInputTransformsVc::cell(vec![]),
+            context.environment(),
+        )
+        .into())
+    }
+}
+
+/// Processes `index.js` with the `graphql` module type registered and returns
+/// the code of its chunk.
+#[turbo_tasks::function]
+async fn graphql_chunk_code(fs: FileSystemVc) -> Result<StringVc> {
+    let mut custom_module_types = IndexMap::new();
+    custom_module_types.insert(
+        "graphql".to_string(),
+        CustomModuleTypeVc::from(GraphQlModuleType.cell()),
+    );
+    let context: AssetContextVc = ModuleAssetContextVc::new(
+        TransitionsByNameVc::cell(HashMap::new()),
+        browser_environment(),
+        ModuleOptionsContext {
+            custom_rules: vec![ModuleRule::new(
+                ModuleRuleCondition::ResourcePathEndsWith(".graphql".to_string()),
+                vec![ModuleRuleEffect::Custom("graphql".to_string())],
+            )],
+            custom_module_types,
+            ..Default::default()
+        }
+        .cell(),
+        ResolveOptionsContext::default().cell(),
+    )
+    .into();
+    let module = context.process(
+        SourceAssetVc::new(fs.root().join("index.js")).into(),
+        Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+    );
+
+    // The import of the `.graphql` file is analyzed like any other.
+    let mut graphql_module = None;
+    for asset in all_referenced_assets(module).await?.iter() {
+        if asset.path().await?.path == "query.graphql" {
+            graphql_module = Some(*asset);
+        }
+    }
+    let Some(graphql_module) = graphql_module else {
+        bail!("index.js doesn't reference query.graphql");
+    };
+    if EcmascriptModuleAssetVc::resolve_from(graphql_module)
+        .await?
+        .is_none()
+    {
+        bail!("query.graphql wasn't compiled into an ecmascript module");
+    }
+
+    let Some(chunkable) = ChunkableAssetVc::resolve_from(module).await? else {
+        bail!("index.js is not chunkable");
+    };
+    let root = fs.root();
+    let chunking_context = DevChunkingContextVc::builder(
+        root,
+        root.join("out"),
+        root.join("out/chunks"),
+        root.join("out/static"),
+    )
+    .build();
+    let chunk = chunkable.as_chunk(chunking_context);
+    if let AssetContent::File(file) = &*chunk.as_asset().content().await? {
+        if let FileContent::Content(file) = &*file.await? {
+            return Ok(StringVc::cell(file.content().to_str()?.into_owned()));
+        }
+    }
+    bail!("the chunk has no content")
+}
+
+#[tokio::test]
+async fn graphql_module_type() -> Result<()> {
+    register();
+
+    let (_base_dir, base) = base_dir()?;
+    let tt = TurboTasks::new(MemoryBackend::new());
+
+    let fs = tt
+        .run_once(async move {
+            test_fs(
+                base,
+                &[
+                    (
+                        "index.js",
+                        "import query from './query.graphql';\nconsole.log(query);",
+                    ),
+                    ("query.graphql", "query { hello }"),
+                ],
+            )
+            .await
+        })
+        .await?;
+
+    let code = tt
+        .run_once(async move {
+            Ok(graphql_chunk_code(fs.into())
+                .strongly_consistent()
+                .await?
+                .clone_value())
+        })
+        .await?;
+    // Both modules end up in the chunk of index.js.
+    assert!(code.contains("[test]/index.js"), "{code}");
+    assert!(code.contains("[test]/query.graphql"), "{code}");
+    assert!(code.contains(r#""query { hello }""#), "{code}");
+
+    Ok(())
+}
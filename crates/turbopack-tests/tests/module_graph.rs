@@ -0,0 +1,262 @@
+#![cfg(test)]
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde_json::json;
+use turbo_tasks::{trace::TraceRawVcs, TurboTasks, Value};
+use turbo_tasks_fs::{DiskFileSystemVc, FileSystemVc};
+use turbo_tasks_memory::MemoryBackend;
+use turbopack::{
+    graph::module_graph::{
+        module_graph, ModuleGraph, ModuleGraphEdge, ModuleGraphEdgeKind, ModuleGraphNode,
+        ModuleGraphVc,
+    },
+    module_options::ModuleOptionsContext,
+    resolve_options_context::ResolveOptionsContext,
+    transition::TransitionsByNameVc,
+    ModuleAssetContextVc,
+};
+use turbopack_core::{
+    asset::AssetsVc,
+    context::AssetContextVc,
+    reference_type::{EntryReferenceSubType, ReferenceType},
+    source_asset::SourceAssetVc,
+};
+
+#[macro_use]
+mod util;
+
+use util::{base_dir, browser_environment, test_fs};
+
+register!();
+
+/// Builds the module graph with `index.js` as the entry.
+#[turbo_tasks::function]
+fn index_module_graph(fs: FileSystemVc) -> ModuleGraphVc {
+    let context: AssetContextVc = ModuleAssetContextVc::new(
+        TransitionsByNameVc::cell(HashMap::new()),
+        browser_environment(),
+        ModuleOptionsContext::default().cell(),
+        ResolveOptionsContext {
+            enable_node_modules: true,
+            ..Default::default()
+        }
+        .cell(),
+    )
+    .into();
+    let module = context.process(
+        SourceAssetVc::new(fs.root().join("index.js")).into(),
+        Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+    );
+    module_graph(AssetsVc::cell(vec![module]))
+}
+
+#[derive(TraceRawVcs, Debug, PartialEq)]
+struct Edge {
+    from: String,
+    to: String,
+    kind: ModuleGraphEdgeKind,
+    specifier: Option<String>,
+}
+
+#[derive(TraceRawVcs)]
+struct GraphOfEntry {
+    edges: Vec<Edge>,
+    chain_to_shared: Vec<String>,
+    importers_of_a: Vec<String>,
+    importers_of_index: usize,
+}
+
+#[tokio::test]
+async fn module_graph_of_entry() -> Result<()> {
+    register();
+
+    let (_base_dir, base) = base_dir()?;
+    let tt = TurboTasks::new(MemoryBackend::new());
+
+    let GraphOfEntry {
+        mut edges,
+        chain_to_shared,
+        mut importers_of_a,
+        importers_of_index,
+    } = tt
+        .run_once(async move {
+            let fs = test_fs(
+                base,
+                &[
+                    (
+                        "index.js",
+                        "import { a } from './a.js';\nimport dep from 'dep';\nimport('./lazy.js');",
+                    ),
+                    ("a.js", "import './b.js';\nexport const a = 1;"),
+                    ("b.js", "require('./shared.js');"),
+                    ("lazy.js", "import './shared.js';\nimport './a.js';"),
+                    ("shared.js", "module.exports = 1;"),
+                    ("node_modules/dep/index.js", "export default 'dep';"),
+                ],
+            )
+            .await?;
+
+            let graph = index_module_graph(fs.into()).strongly_consistent().await?;
+            let path = |index: usize| graph.modules[index].path.clone();
+            let find = |path: &str| graph.find_module(path).unwrap();
+
+            let edges: Vec<Edge> = graph
+                .edges
+                .iter()
+                .map(|edge| Edge {
+                    from: path(edge.from),
+                    to: path(edge.to),
+                    kind: edge.kind,
+                    specifier: edge.specifier.clone(),
+                })
+                .collect();
+            let chain_to_shared: Vec<String> = graph
+                .shortest_import_chain(find("[test]/shared.js"))
+                .unwrap()
+                .into_iter()
+                .map(path)
+                .collect();
+            let importers_of_a: Vec<String> = graph
+                .importers(find("[test]/a.js"))
+                .map(|edge| path(edge.from))
+                .collect();
+            let importers_of_index = graph.importers(find("[test]/index.js")).count();
+            Ok(GraphOfEntry {
+                edges,
+                chain_to_shared,
+                importers_of_a,
+                importers_of_index,
+            })
+        })
+        .await?;
+
+    let edge = |from: &str, to: &str, kind, specifier: &str| Edge {
+        from: format!("[test]/{from}"),
+        to: format!("[test]/{to}"),
+        kind,
+        specifier: Some(specifier.to_string()),
+    };
+    let mut expected = vec![
+        edge("index.js", "a.js", ModuleGraphEdgeKind::Esm, "./a.js"),
+        edge(
+            "index.js",
+            "node_modules/dep/index.js",
+            ModuleGraphEdgeKind::Esm,
+            "dep",
+        ),
+        edge(
+            "index.js",
+            "lazy.js",
+            ModuleGraphEdgeKind::Dynamic,
+            "./lazy.js",
+        ),
+        edge("a.js", "b.js", ModuleGraphEdgeKind::Esm, "./b.js"),
+        edge("b.js", "shared.js", ModuleGraphEdgeKind::Cjs, "./shared.js"),
+        edge(
+            "lazy.js",
+            "shared.js",
+            ModuleGraphEdgeKind::Esm,
+            "./shared.js",
+        ),
+        edge("lazy.js", "a.js", ModuleGraphEdgeKind::Esm, "./a.js"),
+    ];
+    edges.sort_by(|a, b| (&a.from, &a.to).cmp(&(&b.from, &b.to)));
+    expected.sort_by(|a, b| (&a.from, &a.to).cmp(&(&b.from, &b.to)));
+    assert_eq!(edges, expected);
+
+    // The dynamic import is shorter than the chain through `a.js` and `b.js`.
+    assert_eq!(
+        chain_to_shared,
+        vec!["[test]/index.js", "[test]/lazy.js", "[test]/shared.js"]
+    );
+    importers_of_a.sort();
+    assert_eq!(importers_of_a, vec!["[test]/index.js", "[test]/lazy.js"]);
+    assert_eq!(importers_of_index, 0);
+
+    Ok(())
+}
+
+#[derive(TraceRawVcs)]
+struct GraphExport {
+    json: serde_json::Value,
+    dot: String,
+    chain_to_a: Option<Vec<usize>>,
+    chain_to_b: Option<Vec<usize>>,
+}
+
+#[tokio::test]
+async fn module_graph_export() -> Result<()> {
+    register();
+
+    let tt = TurboTasks::new(MemoryBackend::new());
+    let GraphExport {
+        json,
+        dot,
+        chain_to_a,
+        chain_to_b,
+    } = tt
+        .run_once(async move {
+            let fs: FileSystemVc =
+                DiskFileSystemVc::new("test".to_string(), "/".to_string()).into();
+            let node = |path: &str| ModuleGraphNode {
+                asset: SourceAssetVc::new(fs.root().join(path)).into(),
+                path: format!("[test]/{path}"),
+            };
+            let graph = ModuleGraph {
+                // Quotes and backslashes need to be escaped in DOT labels.
+                modules: vec![node("index.js"), node("a.js"), node(r#"b\"c".js"#)],
+                entries: vec![0],
+                edges: vec![
+                    ModuleGraphEdge {
+                        from: 0,
+                        to: 1,
+                        kind: ModuleGraphEdgeKind::Esm,
+                        specifier: Some("./a.js".to_string()),
+                    },
+                    ModuleGraphEdge {
+                        from: 2,
+                        to: 1,
+                        kind: ModuleGraphEdgeKind::Other,
+                        specifier: None,
+                    },
+                ],
+            };
+            Ok(GraphExport {
+                json: graph.to_json(),
+                dot: graph.to_dot(),
+                chain_to_a: graph.shortest_import_chain(1),
+                // The last module is not reachable from the entry.
+                chain_to_b: graph.shortest_import_chain(2),
+            })
+        })
+        .await?;
+
+    assert_eq!(chain_to_a, Some(vec![0, 1]));
+    assert_eq!(chain_to_b, None);
+    assert_eq!(
+        json,
+        json!({
+            "modules": ["[test]/index.js", "[test]/a.js", r#"[test]/b\"c".js"#],
+            "entries": [0],
+            "edges": [
+                { "from": 0, "to": 1, "kind": "esm", "specifier": "./a.js" },
+                { "from": 2, "to": 1, "kind": "other", "specifier": null },
+            ],
+        })
+    );
+    assert_eq!(
+        dot,
+        r#"digraph modules {
+  0 [label="[test]/index.js", shape=box];
+  1 [label="[test]/a.js", shape=ellipse];
+  2 [label="[test]/b\\\"c\".js", shape=ellipse];
+  0 -> 1 [label="esm ./a.js"];
+  2 -> 1 [label="other"];
+}
+"#
+    );
+
+    Ok(())
+}
@@ -0,0 +1,104 @@
+#![cfg(test)]
+
+use anyhow::Result;
+use turbo_tasks::{primitives::StringVc, TurboTasks};
+use turbo_tasks_fs::{DiskFileSystemVc, FileSystemVc};
+use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
+use turbo_tasks_memory::MemoryBackend;
+use turbopack_core::chunk::module_id_strategies::{
+    HashedModuleIdStrategyVc, ModuleIdStrategyVc, DEFAULT_MODULE_ID_HASH_LENGTH,
+};
+
+#[macro_use]
+mod util;
+
+use util::base_dir;
+
+register!();
+
+/// Builds the ids of `idents` with the records in `records.json` and writes
+/// the records afterwards, like a build would.
+async fn build(base: &str, idents: &'static [&'static str]) -> Result<Vec<String>> {
+    let base = base.to_string();
+    let tt = TurboTasks::new(MemoryBackend::new());
+    tt.run_once(async move {
+        let fs: FileSystemVc = DiskFileSystemVc::new("test".to_string(), base).into();
+        let strategy = HashedModuleIdStrategyVc::new(Some(fs.root().join("records.json")));
+        let mut ids = Vec::new();
+        for ident in idents {
+            let id = ModuleIdStrategyVc::from(strategy)
+                .get_module_id(StringVc::cell(ident.to_string()))
+                .strongly_consistent()
+                .await?;
+            ids.push(id.to_string());
+        }
+        strategy.write_records().await?.await?;
+        Ok(ids)
+    })
+    .await
+}
+
+fn hash(ident: &str) -> String {
+    encode_hex(hash_xxh3_hash64(ident.as_bytes()))
+}
+
+#[tokio::test]
+async fn records_keep_ids_stable() -> Result<()> {
+    register();
+
+    let (_base_dir, base) = base_dir()?;
+
+    let first = build(&base, &["[project]/a.js", "[project]/b.js"]).await?;
+    assert_eq!(
+        first,
+        vec![
+            hash("[project]/a.js")[..DEFAULT_MODULE_ID_HASH_LENGTH].to_string(),
+            hash("[project]/b.js")[..DEFAULT_MODULE_ID_HASH_LENGTH].to_string(),
+        ]
+    );
+    let records: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(format!("{base}/records.json"))?)?;
+    assert_eq!(
+        records,
+        serde_json::json!({
+            "moduleIds": {
+                "[project]/a.js": first[0],
+                "[project]/b.js": first[1],
+            }
+        })
+    );
+
+    // A module which was moved keeps its id. The short hash of `c.js` is
+    // taken by it, so `c.js` gets a longer one.
+    let c_hash = hash("[project]/c.js");
+    let mut records = records;
+    records["moduleIds"]["[project]/old/c.js"] =
+        serde_json::Value::String(c_hash[..DEFAULT_MODULE_ID_HASH_LENGTH].to_string());
+    std::fs::write(format!("{base}/records.json"), records.to_string())?;
+
+    let second = build(
+        &base,
+        &[
+            "[project]/c.js",
+            "[project]/b.js",
+            "[project]/a.js",
+            "[project]/old/c.js",
+        ],
+    )
+    .await?;
+    assert_eq!(
+        second,
+        vec![
+            c_hash[..DEFAULT_MODULE_ID_HASH_LENGTH + 1].to_string(),
+            first[1].clone(),
+            first[0].clone(),
+            c_hash[..DEFAULT_MODULE_ID_HASH_LENGTH].to_string(),
+        ]
+    );
+
+    // The longer id is recorded as well, so it stays stable too.
+    let third = build(&base, &["[project]/c.js"]).await?;
+    assert_eq!(third, vec![second[0].clone()]);
+
+    Ok(())
+}
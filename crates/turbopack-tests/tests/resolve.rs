@@ -0,0 +1,101 @@
+#![cfg(test)]
+
+use anyhow::Result;
+use turbo_tasks::{primitives::StringsVc, TurboTasks};
+use turbo_tasks_fs::FileSystemVc;
+use turbo_tasks_memory::MemoryBackend;
+use turbopack::{resolve_options, resolve_options_context::ResolveOptionsContext};
+use turbopack_core::resolve::{parse::RequestVc, resolve};
+
+#[macro_use]
+mod util;
+
+use util::{base_dir, test_fs};
+
+register!();
+
+/// Returns the paths of the assets `request` resolves to from `src/`.
+#[turbo_tasks::function]
+async fn resolve_paths(fs: FileSystemVc, request: String, browser: bool) -> Result<StringsVc> {
+    let context = fs.root().join("src");
+    let options = resolve_options(
+        context,
+        ResolveOptionsContext {
+            enable_node_modules: true,
+            browser,
+            ..Default::default()
+        }
+        .cell(),
+    );
+    let result = resolve(context, RequestVc::parse_string(request), options);
+    let mut paths = Vec::new();
+    for asset in result.primary_assets().await?.iter() {
+        paths.push(asset.path().await?.path.clone());
+    }
+    Ok(StringsVc::cell(paths))
+}
+
+#[tokio::test]
+async fn package_imports() -> Result<()> {
+    register();
+
+    let (_base_dir, base) = base_dir()?;
+    let tt = TurboTasks::new(MemoryBackend::new());
+
+    let fs = tt
+        .run_once(async move {
+            test_fs(
+                base,
+                &[
+                    (
+                        "package.json",
+                        r##"{
+      "name": "pkg",
+      "imports": {
+        "#internal": "./src/internal.js",
+        "#conditional": {
+          "browser": "./src/browser.js",
+          "default": "./src/default.js"
+        },
+        "#without-extension": "./src/internal",
+        "#utils/*": "./src/utils/*.js",
+        "#dep": "dep"
+      }
+    }"##,
+                    ),
+                    ("src/internal.js", ""),
+                    ("src/browser.js", ""),
+                    ("src/default.js", ""),
+                    ("src/utils/a.js", ""),
+                    ("node_modules/dep/package.json", r#"{ "main": "main.js" }"#),
+                    ("node_modules/dep/main.js", ""),
+                ],
+            )
+            .await
+        })
+        .await?;
+
+    for (request, browser, expected) in [
+        ("#internal", false, vec!["src/internal.js"]),
+        ("#conditional", false, vec!["src/default.js"]),
+        ("#conditional", true, vec!["src/browser.js"]),
+        // Targets are exact paths, extensions are not probed.
+        ("#without-extension", false, vec![]),
+        ("#utils/a", false, vec!["src/utils/a.js"]),
+        ("#utils/missing", false, vec![]),
+        ("#dep", false, vec!["node_modules/dep/main.js"]),
+        ("#unknown", false, vec![]),
+    ] {
+        let paths = tt
+            .run_once(async move {
+                Ok(resolve_paths(fs.into(), request.to_string(), browser)
+                    .strongly_consistent()
+                    .await?
+                    .clone_value())
+            })
+            .await?;
+        assert_eq!(paths, expected, "resolving {request} (browser: {browser})");
+    }
+
+    Ok(())
+}
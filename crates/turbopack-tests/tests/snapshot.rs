@@ -7,6 +7,7 @@ use std::{
 };
 
 use anyhow::{anyhow, bail, Context, Result};
+use indexmap::IndexMap;
 use once_cell::sync::Lazy;
 use serde::Deserialize;
 use similar::TextDiff;
@@ -22,22 +23,31 @@ use turbo_tasks_memory::MemoryBackend;
 use turbopack::{
     condition::ContextCondition,
     ecmascript::{chunk::EcmascriptChunkPlaceablesVc, EcmascriptModuleAssetVc},
-    module_options::ModuleOptionsContext,
+    module_options::{ModuleOptionsContext, WebpackLoadersOptions},
     resolve_options_context::ResolveOptionsContext,
     transition::TransitionsByNameVc,
     ModuleAssetContextVc,
 };
 use turbopack_core::{
     asset::{AssetContent, AssetContentVc, AssetVc},
-    chunk::{dev::DevChunkingContextVc, ChunkableAssetVc},
+    chunk::{
+        dev::DevChunkingContextVc, module_id_strategies::HashedModuleIdStrategyVc, ChunkableAssetVc,
+    },
     context::AssetContextVc,
-    environment::{BrowserEnvironment, EnvironmentIntention, EnvironmentVc, ExecutionEnvironment},
+    environment::{
+        BrowserEnvironment, EnvironmentIntention, EnvironmentVc, ExecutionEnvironment,
+        NodeJsEnvironment,
+    },
     issue::IssueVc,
     reference::all_referenced_assets,
     reference_type::{EntryReferenceSubType, ReferenceType},
     source_asset::SourceAssetVc,
 };
 use turbopack_env::ProcessEnvAssetVc;
+use turbopack_node::{
+    execution_context::ExecutionContextVc,
+    transforms::webpack::{WebpackLoaderItem, WebpackLoaderItemsVc},
+};
 
 fn register() {
     turbopack::register();
@@ -66,6 +76,25 @@ struct SnapshotOptions {
     browserslist: String,
     #[serde(default = "default_entry")]
     entry: String,
+    /// Webpack loaders to apply to files ending with a given extension. They
+    /// are resolved from the directory of the transformed file.
+    #[serde(default)]
+    webpack_loaders: IndexMap<String, Vec<WebpackLoaderItem>>,
+    /// Use short hashes instead of paths as module ids. They are recorded in
+    /// `records.json`.
+    #[serde(default)]
+    hashed_module_ids: bool,
+    /// `"NodeJs"` builds the output for Node.js instead of the browser.
+    #[serde(default)]
+    environment: SnapshotEnvironment,
+}
+
+/// The environment the output of a snapshot test runs in.
+#[derive(Debug, Default, Deserialize)]
+enum SnapshotEnvironment {
+    #[default]
+    Browser,
+    NodeJs,
 }
 
 impl Default for SnapshotOptions {
@@ -73,6 +102,9 @@ impl Default for SnapshotOptions {
         SnapshotOptions {
             browserslist: default_browserslist(),
             entry: default_entry(),
+            webpack_loaders: IndexMap::new(),
+            hashed_module_ids: false,
+            environment: SnapshotEnvironment::Browser,
         }
     }
 }
@@ -142,18 +174,48 @@ async fn run_test(resource: String) -> Result<FileSystemPathVc> {
 
     let runtime_entries = maybe_load_env(project_fs.into(), fs_path).await?;
 
-    let env = EnvironmentVc::new(
-        Value::new(ExecutionEnvironment::Browser(
-            // TODO: load more from options.json
-            BrowserEnvironment {
-                dom: true,
-                web_worker: false,
-                service_worker: false,
-                browserslist_query: options.browserslist.to_owned(),
-            }
-            .into(),
+    let env = match options.environment {
+        SnapshotEnvironment::Browser => EnvironmentVc::new(
+            Value::new(ExecutionEnvironment::Browser(
+                // TODO: load more from options.json
+                BrowserEnvironment {
+                    dom: true,
+                    web_worker: false,
+                    service_worker: false,
+                    browserslist_query: options.browserslist.to_owned(),
+                }
+                .into(),
+            )),
+            Value::new(EnvironmentIntention::Client),
+        ),
+        SnapshotEnvironment::NodeJs => EnvironmentVc::new(
+            Value::new(ExecutionEnvironment::NodeJsLambda(
+                NodeJsEnvironment::default().into(),
+            )),
+            Value::new(EnvironmentIntention::ServerRendering),
+        ),
+    };
+
+    let enable_webpack_loaders = if options.webpack_loaders.is_empty() {
+        None
+    } else {
+        Some(WebpackLoadersOptions {
+            extension_to_loaders: options
+                .webpack_loaders
+                .into_iter()
+                .map(|(ext, loaders)| (ext, WebpackLoaderItemsVc::cell(loaders)))
+                .collect(),
+            ..Default::default()
+        })
+    };
+    // The loaders are executed from the build directory, so that
+    // `loader-runner` is resolved from the stub node_modules.
+    let execution_context = ExecutionContextVc::new(
+        project_root,
+        project_root.join(&format!(
+            "crates/turbopack-tests/tests/.build/{}",
+            test_path.strip_prefix("tests/snapshot")?.to_str().unwrap()
         )),
-        Value::new(EnvironmentIntention::Client),
     );
 
     let context: AssetContextVc = ModuleAssetContextVc::new(
@@ -164,6 +226,8 @@ async fn run_test(resource: String) -> Result<FileSystemPathVc> {
             enable_emotion: true,
             enable_styled_components: true,
             preset_env_versions: Some(env),
+            enable_webpack_loaders,
+            execution_context: Some(execution_context),
             rules: vec![(
                 ContextCondition::InDirectory("node_modules".to_string()),
                 ModuleOptionsContext {
@@ -196,9 +260,15 @@ async fn run_test(resource: String) -> Result<FileSystemPathVc> {
 
     let chunk_root_path = path.join("output");
     let static_root_path = path.join("static");
-    let chunking_context =
-        DevChunkingContextVc::builder(project_root, path, chunk_root_path, static_root_path)
-            .build();
+    let mut chunking_context =
+        DevChunkingContextVc::builder(project_root, path, chunk_root_path, static_root_path);
+    let module_id_strategy = options
+        .hashed_module_ids
+        .then(|| HashedModuleIdStrategyVc::new(Some(path.join("records.json"))));
+    if let Some(strategy) = module_id_strategy {
+        chunking_context = chunking_context.module_id_strategy(strategy.into());
+    }
+    let chunking_context = chunking_context.build();
 
     let expected_paths = expected(chunk_root_path)
         .await?
@@ -243,6 +313,12 @@ async fn run_test(resource: String) -> Result<FileSystemPathVc> {
 
     matches_expected(expected_paths, seen).await?;
 
+    // Like after a build, the ids of the emitted modules are recorded. They
+    // only change when modules are added.
+    if let Some(strategy) = module_id_strategy {
+        strategy.write_records().await?.await?;
+    }
+
     Ok(path)
 }
 
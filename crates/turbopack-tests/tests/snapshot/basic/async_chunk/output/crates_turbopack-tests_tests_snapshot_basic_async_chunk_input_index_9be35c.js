@@ -83,6 +83,13 @@ __turbopack_export_value__((__turbopack_import__) => {
    * @type {Map<ChunkPath, Loader>}
    */
   const chunkLoaders = new Map();
+  /**
+   * In Node.js, chunks are loaded with `require` relative to the output root,
+   * which is derived from the location of the chunk containing this runtime.
+   *
+   * @type {string | undefined}
+   */
+  const nodeOutputRoot = getNodeOutputRoot();
   /**
    * Maps module IDs to persisted data between executions of their hot module
    * implementation (`hot.data`).
@@ -270,6 +277,44 @@ __turbopack_export_value__((__turbopack_import__) => {
     };
     chunkLoaders.set(chunkPath, chunkLoader);
 
+    if (nodeOutputRoot != null) {
+      if (chunkPath.endsWith(".css")) {
+        // Styles have no effect outside the browser.
+        loadedChunks.add(chunkPath);
+        onLoad();
+      } else if (chunkPath.endsWith(".js")) {
+        try {
+          // The chunk is marked as loaded in `registerChunk`.
+          require(nodeOutputRoot + chunkPath);
+        } catch (err) {
+          onError();
+        }
+      } else {
+        throw new Error(`can't infer type of chunk from path ${chunkPath}`);
+      }
+      return chunkLoader;
+    }
+
+    // Web workers and the edge runtime don't have a DOM, but can load scripts
+    // synchronously.
+    if (typeof importScripts === "function") {
+      if (chunkPath.endsWith(".css")) {
+        // Styles have no effect outside the browser.
+        loadedChunks.add(chunkPath);
+        onLoad();
+      } else if (chunkPath.endsWith(".js")) {
+        try {
+          // The chunk is marked as loaded in `registerChunk`.
+          importScripts(`/${chunkPath}`);
+        } catch (err) {
+          onError();
+        }
+      } else {
+        throw new Error(`can't infer type of chunk from path ${chunkPath}`);
+      }
+      return chunkLoader;
+    }
+
     if (typeof document === "undefined") {
       throw new Error(
         "Loading chunks outside the browser is not currently supported. If using next/dynamic, try opting out of ssr for now: https://nextjs.org/docs/advanced-features/dynamic-import#with-no-ssr"
@@ -300,6 +345,22 @@ __turbopack_export_value__((__turbopack_import__) => {
     return chunkLoader;
   }
 
+  /**
+   * @returns {string | undefined}
+   */
+  function getNodeOutputRoot() {
+    if (typeof __filename !== "string" || typeof require !== "function") {
+      return undefined;
+    }
+    const filename = __filename.replace(/\\/g, "/");
+    for (const [chunkPath] of chunksToRegister) {
+      if (filename.endsWith(`/${chunkPath}`)) {
+        return filename.slice(0, filename.length - chunkPath.length);
+      }
+    }
+    return undefined;
+  }
+
   /**
    * @enum {number}
    */
@@ -1015,8 +1076,12 @@ __turbopack_export_value__((__turbopack_import__) => {
       }
       addModuleToChunk(moduleId, chunkPath);
     }
-    runnable.push(...run);
-    runnable = runnable.filter((r) => r(runtime));
+    // Running a module can load and register other chunks synchronously (with
+    // `require` in Node.js), so the pending runnables are taken before running
+    // them to not run them twice.
+    const pending = [...runnable, ...run];
+    runnable = [];
+    runnable.push(...pending.filter((r) => r(runtime)));
   }
 
   self.TURBOPACK_CHUNK_UPDATE_LISTENERS =
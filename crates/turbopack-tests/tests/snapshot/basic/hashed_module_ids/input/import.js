@@ -0,0 +1,5 @@
+import { foo } from "./foo";
+
+export function bar(value) {
+  foo(value);
+}
@@ -0,0 +1,6 @@
+import { foo } from "./foo";
+
+foo(true);
+import("./import").then(({ bar }) => {
+  bar(true);
+});
@@ -0,0 +1,29 @@
+(self.TURBOPACK = self.TURBOPACK || []).push(["output/crates_turbopack-tests_tests_snapshot_basic_hashed_module_ids_input_import.js", {
+
+"c3e98987": (({ r: __turbopack_require__, x: __turbopack_external_require__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, c: __turbopack_cache__, l: __turbopack_load__, p: process, g: global, __dirname }) => (() => {
+
+__turbopack_esm__({
+    "bar": ()=>bar
+});
+var __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$basic$2f$hashed_module_ids$2f$input$2f$foo$2e$js__ = __turbopack_import__("73396402");
+"__TURBOPACK__ecmascript__hoisting__location__";
+;
+function bar(value) {
+    __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$basic$2f$hashed_module_ids$2f$input$2f$foo$2e$js__["foo"](value);
+}
+
+})()),
+"73396402": (({ r: __turbopack_require__, x: __turbopack_external_require__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, c: __turbopack_cache__, l: __turbopack_load__, p: process, g: global, __dirname }) => (() => {
+
+__turbopack_esm__({
+    "foo": ()=>foo
+});
+function foo(value) {
+    console.assert(value);
+}
+
+})()),
+}]);
+
+
+//# sourceMappingURL=crates_turbopack-tests_tests_snapshot_basic_hashed_module_ids_input_import.js.map
\ No newline at end of file
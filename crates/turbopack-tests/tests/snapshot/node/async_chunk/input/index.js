@@ -0,0 +1,3 @@
+import("./lazy.js").then(({ message }) => {
+  console.log(message);
+});
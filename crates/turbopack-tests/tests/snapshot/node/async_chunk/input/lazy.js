@@ -0,0 +1 @@
+export const message = "loaded in Node.js";
@@ -0,0 +1,14 @@
+(self.TURBOPACK = self.TURBOPACK || []).push(["output/crates_turbopack-tests_tests_snapshot_node_async_chunk_input_lazy.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/node/async_chunk/input/lazy.js (ecmascript)": (({ r: __turbopack_require__, x: __turbopack_external_require__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, c: __turbopack_cache__, l: __turbopack_load__, p: process, g: global, __dirname }) => (() => {
+
+__turbopack_esm__({
+    "message": ()=>message
+});
+const message = "loaded in Node.js";
+
+})()),
+}]);
+
+
+//# sourceMappingURL=crates_turbopack-tests_tests_snapshot_node_async_chunk_input_lazy.js.map
\ No newline at end of file
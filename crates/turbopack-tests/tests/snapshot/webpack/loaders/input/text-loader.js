@@ -0,0 +1,20 @@
+const path = require("path");
+
+module.exports = function (source) {
+  const { uppercase } = this.getOptions();
+  const text = source.trim();
+  const emit = (file, content) =>
+    this.emitFile(
+      path.relative(
+        this.rootContext,
+        path.join(path.dirname(this.resourcePath), "../static", file)
+      ),
+      content
+    );
+  emit("message.json", JSON.stringify({ length: text.length }));
+  // Buffers are passed as binary data.
+  emit("message.bin", Buffer.from(text.split("").reverse().join("")));
+  return `export default ${JSON.stringify(
+    uppercase ? text.toUpperCase() : text
+  )};`;
+};
@@ -0,0 +1 @@
+importScripts("/output/crates_turbopack-tests_tests_snapshot_workers_basic_input_worker_29b6b5.js");
\ No newline at end of file
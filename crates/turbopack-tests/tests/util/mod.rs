@@ -0,0 +1,68 @@
+//! Helpers shared by the tests which build a small project in memory instead
+//! of using a snapshot fixture.
+
+// Not every test uses every helper.
+#![allow(dead_code)]
+
+use anyhow::Result;
+use tempfile::TempDir;
+use turbo_tasks::Value;
+use turbo_tasks_fs::{overlay::OverlayFileSystemVc, DiskFileSystemVc, File, FileContent};
+use turbopack_core::environment::{
+    BrowserEnvironment, EnvironmentIntention, EnvironmentVc, ExecutionEnvironment,
+};
+
+/// Defines a `register` function, which registers turbopack and the turbo
+/// tasks functions and values of the test.
+macro_rules! register {
+    () => {
+        fn register() {
+            turbopack::register();
+            include!(concat!(
+                env!("OUT_DIR"),
+                "/register_test_",
+                module_path!(),
+                ".rs"
+            ));
+        }
+    };
+}
+
+/// Creates the empty directory a [test_fs] is based on. The directory is
+/// deleted when the [TempDir] is dropped, so it needs to be kept alive as long
+/// as the filesystem is used.
+pub fn base_dir() -> Result<(TempDir, String)> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().to_str().unwrap().to_string();
+    Ok((dir, path))
+}
+
+/// A filesystem named `test` with `files` as (path, content) pairs. The files
+/// only exist in memory, on top of the directory at `base`.
+pub async fn test_fs(base: String, files: &[(&str, &str)]) -> Result<OverlayFileSystemVc> {
+    let fs = OverlayFileSystemVc::new(
+        "test".to_string(),
+        DiskFileSystemVc::new("base".to_string(), base).into(),
+    );
+    let overlay = fs.await?;
+    for (path, content) in files {
+        overlay.set_override(path, FileContent::Content(File::from(*content)));
+    }
+    Ok(fs)
+}
+
+/// The environment of a page in a browser.
+pub fn browser_environment() -> EnvironmentVc {
+    EnvironmentVc::new(
+        Value::new(ExecutionEnvironment::Browser(
+            BrowserEnvironment {
+                dom: true,
+                web_worker: false,
+                service_worker: false,
+                browserslist_query: "Chrome 102".to_string(),
+            }
+            .into(),
+        )),
+        Value::new(EnvironmentIntention::Client),
+    )
+}
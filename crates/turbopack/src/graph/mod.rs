@@ -1,3 +1,5 @@
+pub mod module_graph;
+
 use std::collections::HashSet;
 
 use anyhow::Result;
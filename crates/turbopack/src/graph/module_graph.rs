@@ -0,0 +1,282 @@
+use std::{
+    collections::{hash_map::Entry, HashMap, VecDeque},
+    fmt::Write,
+};
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use turbo_tasks::{trace::TraceRawVcs, ValueToString};
+use turbopack_core::{
+    asset::{AssetVc, AssetsVc},
+    reference::AssetReferenceVc,
+    resolve::parse::RequestVc,
+};
+use turbopack_css::references::{
+    import::ImportAssetReferenceVc, url::UrlAssetReferenceVc as CssUrlAssetReferenceVc,
+};
+use turbopack_ecmascript::references::{
+    cjs::{CjsAssetReferenceVc, CjsRequireAssetReferenceVc, CjsRequireResolveAssetReferenceVc},
+    esm::{EsmAssetReferenceVc, EsmAsyncAssetReferenceVc, UrlAssetReferenceVc},
+};
+
+/// The kind of reference an edge of the [ModuleGraph] was created from.
+#[derive(TraceRawVcs, PartialEq, Eq, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModuleGraphEdgeKind {
+    /// A static `import` or `export ... from`.
+    Esm,
+    /// A `require()` or `require.resolve()`.
+    Cjs,
+    /// A dynamic `import()`.
+    Dynamic,
+    /// A `new URL(..., import.meta.url)` or a `url()` in CSS.
+    Url,
+    /// A CSS `@import`.
+    Css,
+    /// Any other reference, e.g. to a `package.json` or a source map.
+    Other,
+}
+
+impl ModuleGraphEdgeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModuleGraphEdgeKind::Esm => "esm",
+            ModuleGraphEdgeKind::Cjs => "cjs",
+            ModuleGraphEdgeKind::Dynamic => "dynamic",
+            ModuleGraphEdgeKind::Url => "url",
+            ModuleGraphEdgeKind::Css => "css",
+            ModuleGraphEdgeKind::Other => "other",
+        }
+    }
+}
+
+#[derive(TraceRawVcs, PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct ModuleGraphNode {
+    pub asset: AssetVc,
+    pub path: String,
+}
+
+#[derive(TraceRawVcs, PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct ModuleGraphEdge {
+    /// The index of the importing module.
+    pub from: usize,
+    /// The index of the imported module.
+    pub to: usize,
+    pub kind: ModuleGraphEdgeKind,
+    /// The import specifier as written in the source, e.g. `./foo` or
+    /// `react`. It's `None` for references without a request and for dynamic
+    /// requests like `require("./locale/" + name)`.
+    pub specifier: Option<String>,
+}
+
+/// The graph of all modules reachable from a set of entries, with the
+/// references between them.
+///
+/// Unlike the [AggregatedGraph](super::AggregatedGraph), which groups modules
+/// to compute chunks, this keeps every individual edge so that it can be
+/// queried and exported.
+///
+/// Modules are identified by their index in [ModuleGraph::modules].
+#[turbo_tasks::value(shared)]
+pub struct ModuleGraph {
+    pub modules: Vec<ModuleGraphNode>,
+    pub entries: Vec<usize>,
+    pub edges: Vec<ModuleGraphEdge>,
+}
+
+impl ModuleGraph {
+    /// Finds a module by its path, e.g. `[project]/src/index.js`.
+    pub fn find_module(&self, path: &str) -> Option<usize> {
+        self.modules.iter().position(|module| module.path == path)
+    }
+
+    /// The edges pointing to `module`, i.e. who imports it.
+    pub fn importers(&self, module: usize) -> impl Iterator<Item = &ModuleGraphEdge> {
+        self.edges.iter().filter(move |edge| edge.to == module)
+    }
+
+    /// The edges starting at `module`, i.e. what it imports.
+    pub fn imports(&self, module: usize) -> impl Iterator<Item = &ModuleGraphEdge> {
+        self.edges.iter().filter(move |edge| edge.from == module)
+    }
+
+    /// The shortest chain of imports from any entry to `module`, starting with
+    /// the entry and ending with `module`. Returns `None` when the module is
+    /// not reachable.
+    pub fn shortest_import_chain(&self, module: usize) -> Option<Vec<usize>> {
+        let mut predecessors: HashMap<usize, Option<usize>> = HashMap::new();
+        let mut queue = VecDeque::with_capacity(32);
+        for &entry in &self.entries {
+            if predecessors.insert(entry, None).is_none() {
+                queue.push_back(entry);
+            }
+        }
+        while let Some(current) = queue.pop_front() {
+            if current == module {
+                let mut chain = vec![current];
+                let mut current = current;
+                while let Some(&Some(predecessor)) = predecessors.get(&current) {
+                    chain.push(predecessor);
+                    current = predecessor;
+                }
+                chain.reverse();
+                return Some(chain);
+            }
+            for edge in self.imports(current) {
+                if let Entry::Vacant(entry) = predecessors.entry(edge.to) {
+                    entry.insert(Some(current));
+                    queue.push_back(edge.to);
+                }
+            }
+        }
+        None
+    }
+
+    /// Serializes the graph as JSON with the module paths, entries and edges.
+    pub fn to_json(&self) -> JsonValue {
+        json!({
+            "modules": self.modules.iter().map(|module| &module.path).collect::<Vec<_>>(),
+            "entries": self.entries,
+            "edges": self.edges.iter().map(|edge| json!({
+                "from": edge.from,
+                "to": edge.to,
+                "kind": edge.kind,
+                "specifier": edge.specifier,
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    /// Renders the graph in the Graphviz DOT format.
+    pub fn to_dot(&self) -> String {
+        let mut dot = "digraph modules {\n".to_string();
+        for (index, module) in self.modules.iter().enumerate() {
+            let shape = if self.entries.contains(&index) {
+                "box"
+            } else {
+                "ellipse"
+            };
+            writeln!(
+                dot,
+                "  {index} [label=\"{}\", shape={shape}];",
+                dot_escape(&module.path)
+            )
+            .unwrap();
+        }
+        for edge in &self.edges {
+            let label = match &edge.specifier {
+                Some(specifier) => format!("{} {}", edge.kind.as_str(), specifier),
+                None => edge.kind.as_str().to_string(),
+            };
+            writeln!(
+                dot,
+                "  {} -> {} [label=\"{}\"];",
+                edge.from,
+                edge.to,
+                dot_escape(&label)
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Escapes `s` for use in a quoted DOT string. Only `"` and `\` have a
+/// meaning there.
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Determines the kind and the request of a reference.
+async fn reference_kind(
+    reference: AssetReferenceVc,
+) -> Result<(ModuleGraphEdgeKind, Option<RequestVc>)> {
+    if let Some(esm) = EsmAssetReferenceVc::resolve_from(reference).await? {
+        return Ok((ModuleGraphEdgeKind::Esm, Some(esm.await?.request)));
+    }
+    if let Some(dynamic) = EsmAsyncAssetReferenceVc::resolve_from(reference).await? {
+        return Ok((ModuleGraphEdgeKind::Dynamic, Some(dynamic.await?.request)));
+    }
+    if let Some(cjs) = CjsAssetReferenceVc::resolve_from(reference).await? {
+        return Ok((ModuleGraphEdgeKind::Cjs, Some(cjs.await?.request)));
+    }
+    if let Some(cjs) = CjsRequireAssetReferenceVc::resolve_from(reference).await? {
+        return Ok((ModuleGraphEdgeKind::Cjs, Some(cjs.await?.request)));
+    }
+    if let Some(cjs) = CjsRequireResolveAssetReferenceVc::resolve_from(reference).await? {
+        return Ok((ModuleGraphEdgeKind::Cjs, Some(cjs.await?.request)));
+    }
+    if let Some(url) = UrlAssetReferenceVc::resolve_from(reference).await? {
+        return Ok((ModuleGraphEdgeKind::Url, Some(url.await?.request)));
+    }
+    if let Some(url) = CssUrlAssetReferenceVc::resolve_from(reference).await? {
+        return Ok((ModuleGraphEdgeKind::Url, Some(url.await?.request)));
+    }
+    if let Some(import) = ImportAssetReferenceVc::resolve_from(reference).await? {
+        return Ok((ModuleGraphEdgeKind::Css, Some(import.await?.request)));
+    }
+    Ok((ModuleGraphEdgeKind::Other, None))
+}
+
+/// Builds the [ModuleGraph] of all modules reachable from `entries`.
+#[turbo_tasks::function]
+pub async fn module_graph(entries: AssetsVc) -> Result<ModuleGraphVc> {
+    let mut modules: IndexMap<AssetVc, String> = IndexMap::new();
+    let mut queue = VecDeque::with_capacity(32);
+    let mut entry_indices = Vec::new();
+    for entry in entries.await?.iter() {
+        let entry = entry.resolve().await?;
+        let (index, new) = match modules.get_index_of(&entry) {
+            Some(index) => (index, false),
+            None => {
+                let path = entry.path().to_string().await?.clone_value();
+                (modules.insert_full(entry, path).0, true)
+            }
+        };
+        entry_indices.push(index);
+        if new {
+            queue.push_back(entry);
+        }
+    }
+
+    let mut edges = Vec::new();
+    while let Some(asset) = queue.pop_front() {
+        let from = modules.get_index_of(&asset).unwrap();
+        for &reference in asset.references().await?.iter() {
+            let (kind, request) = reference_kind(reference).await?;
+            let specifier = match request {
+                Some(request) => request.await?.request(),
+                None => None,
+            };
+            for &target in reference.resolve_reference().primary_assets().await?.iter() {
+                let target = target.resolve().await?;
+                let to = match modules.get_index_of(&target) {
+                    Some(index) => index,
+                    None => {
+                        let path = target.path().to_string().await?.clone_value();
+                        queue.push_back(target);
+                        modules.insert_full(target, path).0
+                    }
+                };
+                edges.push(ModuleGraphEdge {
+                    from,
+                    to,
+                    kind,
+                    specifier: specifier.clone(),
+                });
+            }
+        }
+    }
+
+    Ok(ModuleGraph {
+        modules: modules
+            .into_iter()
+            .map(|(asset, path)| ModuleGraphNode { asset, path })
+            .collect(),
+        entries: entry_indices,
+        edges,
+    }
+    .cell())
+}
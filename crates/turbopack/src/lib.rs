@@ -16,8 +16,8 @@ use std::{
 use anyhow::Result;
 use css::{CssModuleAssetVc, ModuleCssModuleAssetVc};
 use ecmascript::{
-    typescript::resolve::TypescriptTypesAssetReferenceVc, EcmascriptModuleAssetType,
-    EcmascriptModuleAssetVc,
+    typescript::resolve::TypescriptTypesAssetReferenceVc, EcmascriptInputTransform,
+    EcmascriptInputTransformsVc, EcmascriptModuleAssetType, EcmascriptModuleAssetVc,
 };
 use graph::{aggregate, AggregatedGraphNodeContent, AggregatedGraphVc};
 use lazy_static::lazy_static;
@@ -42,13 +42,13 @@ use turbopack_core::{
         origin::PlainResolveOriginVc,
         parse::{Request, RequestVc},
         pattern::Pattern,
-        resolve, ResolveResultVc,
+        resolve, ResolveResult, ResolveResultVc, SpecialType,
     },
 };
 
 pub mod condition;
 pub mod evaluate_context;
-mod graph;
+pub mod graph;
 pub mod module_options;
 pub mod rebase;
 pub mod resolve;
@@ -68,8 +68,7 @@ use self::{
 
 lazy_static! {
     static ref UNSUPPORTED_PACKAGES: HashSet<String> = ["@vercel/og".to_owned()].into();
-    static ref UNSUPPORTED_PACKAGE_PATHS: HashSet<(String, String)> =
-        [("@next/font".to_owned(), "/local".to_owned())].into();
+    static ref UNSUPPORTED_PACKAGE_PATHS: HashSet<(String, String)> = HashSet::new();
 }
 
 #[turbo_tasks::value]
@@ -153,10 +152,56 @@ async fn apply_module_type(
         ModuleType::Mdx(transforms) => {
             MdxModuleAssetVc::new(source, context.into(), *transforms).into()
         }
-        ModuleType::Custom(_) => todo!(),
+        ModuleType::Custom(custom) => custom.create_module(source, context.into()),
     })
 }
 
+fn add_ecmascript_transforms(
+    path: FileSystemPathVc,
+    module_type: Option<ModuleType>,
+    additional_transforms: EcmascriptInputTransformsVc,
+) -> Option<ModuleType> {
+    match module_type {
+        Some(ModuleType::Ecmascript(transforms)) => Some(ModuleType::Ecmascript(
+            transforms.extend(additional_transforms),
+        )),
+        Some(ModuleType::Typescript(transforms)) => Some(ModuleType::Typescript(
+            transforms.extend(additional_transforms),
+        )),
+        Some(ModuleType::TypescriptWithTypes(transforms)) => Some(ModuleType::TypescriptWithTypes(
+            transforms.extend(additional_transforms),
+        )),
+        Some(module_type) => {
+            ModuleIssue {
+                path,
+                title: StringVc::cell("Invalid module type".to_string()),
+                description: StringVc::cell(
+                    "The module type must be Ecmascript or Typescript to add Ecmascript transforms"
+                        .to_string(),
+                ),
+            }
+            .cell()
+            .as_issue()
+            .emit();
+            Some(module_type)
+        }
+        None => {
+            ModuleIssue {
+                path,
+                title: StringVc::cell("Missing module type".to_string()),
+                description: StringVc::cell(
+                    "The module type effect must be applied before adding Ecmascript transforms"
+                        .to_string(),
+                ),
+            }
+            .cell()
+            .as_issue()
+            .emit();
+            None
+        }
+    }
+}
+
 #[turbo_tasks::function]
 async fn module(
     source: AssetVc,
@@ -169,7 +214,8 @@ async fn module(
     let reference_type = reference_type.into_value();
     let mut current_source = source;
     let mut current_module_type = None;
-    for rule in options.await?.rules.iter() {
+    let options = options.await?;
+    for rule in options.rules.iter() {
         if rule.matches(&*path.await?, &reference_type) {
             for effect in rule.effects() {
                 match effect {
@@ -184,52 +230,35 @@ async fn module(
                         current_module_type = Some(*module);
                     }
                     ModuleRuleEffect::AddEcmascriptTransforms(additional_transforms) => {
-                        current_module_type = match current_module_type {
-                            Some(ModuleType::Ecmascript(transforms)) => Some(
-                                ModuleType::Ecmascript(transforms.extend(*additional_transforms)),
-                            ),
-                            Some(ModuleType::Typescript(transforms)) => Some(
-                                ModuleType::Typescript(transforms.extend(*additional_transforms)),
-                            ),
-                            Some(ModuleType::TypescriptWithTypes(transforms)) => {
-                                Some(ModuleType::TypescriptWithTypes(
-                                    transforms.extend(*additional_transforms),
-                                ))
-                            }
-                            Some(module_type) => {
-                                ModuleIssue {
-                                    path,
-                                    title: StringVc::cell("Invalid module type".to_string()),
-                                    description: StringVc::cell(
-                                        "The module type must be Ecmascript or Typescript to add \
-                                         Ecmascript transforms"
-                                            .to_string(),
-                                    ),
-                                }
-                                .cell()
-                                .as_issue()
-                                .emit();
-                                Some(module_type)
-                            }
-                            None => {
-                                ModuleIssue {
-                                    path,
-                                    title: StringVc::cell("Missing module type".to_string()),
-                                    description: StringVc::cell(
-                                        "The module type effect must be applied before adding \
-                                         Ecmascript transforms"
-                                            .to_string(),
-                                    ),
-                                }
-                                .cell()
-                                .as_issue()
-                                .emit();
-                                None
-                            }
-                        };
+                        current_module_type = add_ecmascript_transforms(
+                            path,
+                            current_module_type,
+                            *additional_transforms,
+                        );
                     }
-                    ModuleRuleEffect::Custom => {
-                        todo!("Custom module rule effects are not yet supported");
+                    ModuleRuleEffect::Custom(name) => {
+                        if let Some(custom) = options.custom_module_types.get(name) {
+                            current_module_type = Some(ModuleType::Custom(*custom));
+                        } else if let Some(transform) = options.custom_transforms.get(name) {
+                            current_module_type = add_ecmascript_transforms(
+                                path,
+                                current_module_type,
+                                EcmascriptInputTransformsVc::cell(vec![
+                                    EcmascriptInputTransform::Custom(*transform),
+                                ]),
+                            );
+                        } else {
+                            ModuleIssue {
+                                path,
+                                title: StringVc::cell("Unknown custom module rule".to_string()),
+                                description: StringVc::cell(format!(
+                                    "There is no custom module type or transform named \"{name}\""
+                                )),
+                            }
+                            .cell()
+                            .as_issue()
+                            .emit();
+                        }
                     }
                 }
             }
@@ -348,6 +377,12 @@ impl AssetContext for ModuleAssetContext {
     ) -> Result<ResolveResultVc> {
         warn_on_unsupported_modules(request, origin_path).await?;
 
+        if *self_vc.await?.environment.is_edge_function().await?
+            && is_node_builtin_request(request, origin_path).await?
+        {
+            return Ok(ResolveResult::Special(SpecialType::Empty, Vec::new()).cell());
+        }
+
         let context_path = origin_path.parent().resolve().await?;
 
         let result = resolve(context_path, request, resolve_options);
@@ -608,6 +643,35 @@ async fn warn_on_unsupported_modules(
     Ok(())
 }
 
+/// Checks if the request refers to a Node.js built-in module and emits an
+/// issue for it, since they are not available in the edge runtime.
+async fn is_node_builtin_request(
+    request: RequestVc,
+    origin_path: FileSystemPathVc,
+) -> Result<bool> {
+    let Request::Module { module, .. } = &*request.await? else {
+        return Ok(false);
+    };
+    let name = module.strip_prefix("node:").unwrap_or(module);
+    if !resolve::NODE_EXTERNALS.contains(&name) {
+        return Ok(false);
+    }
+    ModuleIssue {
+        path: origin_path,
+        title: StringVc::cell(format!(
+            "The edge runtime does not support Node.js '{name}' module"
+        )),
+        description: StringVc::cell(format!(
+            "Node.js built-in modules like '{module}' are not available in the edge runtime. Only \
+             web APIs can be used there."
+        )),
+    }
+    .cell()
+    .as_issue()
+    .emit();
+    Ok(true)
+}
+
 pub fn register() {
     turbo_tasks::register();
     turbo_tasks_fs::register();
@@ -0,0 +1,12 @@
+use turbopack_core::{asset::AssetVc, context::AssetContextVc};
+
+/// A module type implemented outside of turbopack, e.g. to compile
+/// `.graphql` files into ecmascript modules.
+///
+/// The created module is handled like any other module, so it should
+/// implement the traits needed for analysis and chunking, e.g. by wrapping
+/// the source into an ecmascript module.
+#[turbo_tasks::value_trait]
+pub trait CustomModuleType {
+    fn create_module(&self, source: AssetVc, context: AssetContextVc) -> AssetVc;
+}
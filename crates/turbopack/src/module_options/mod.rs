@@ -1,8 +1,11 @@
+pub mod custom_module_type;
 pub mod module_options_context;
 pub mod module_rule;
 pub mod rule_condition;
 
 use anyhow::{Context, Result};
+pub use custom_module_type::*;
+use indexmap::IndexMap;
 pub use module_options_context::*;
 pub use module_rule::*;
 pub use rule_condition::*;
@@ -13,14 +16,18 @@ use turbopack_core::{
     source_transform::SourceTransformsVc,
 };
 use turbopack_css::{CssInputTransform, CssInputTransformsVc};
-use turbopack_ecmascript::{EcmascriptInputTransform, EcmascriptInputTransformsVc};
-use turbopack_node::transforms::postcss::PostCssTransformVc;
+use turbopack_ecmascript::{
+    EcmascriptInputTransform, EcmascriptInputTransformsVc, TransformPluginVc,
+};
+use turbopack_node::transforms::{postcss::PostCssTransformVc, webpack::WebpackLoadersVc};
 
 use crate::evaluate_context::node_evaluate_asset_context;
 
 #[turbo_tasks::value(cell = "new", eq = "manual")]
 pub struct ModuleOptions {
     pub rules: Vec<ModuleRule>,
+    pub custom_module_types: IndexMap<String, CustomModuleTypeVc>,
+    pub custom_transforms: IndexMap<String, TransformPluginVc>,
 }
 
 #[turbo_tasks::value_impl]
@@ -39,10 +46,13 @@ impl ModuleOptionsVc {
             enable_types,
             enable_typescript_transform,
             ref enable_postcss_transform,
+            ref enable_webpack_loaders,
             preset_env_versions,
             ref custom_ecmascript_app_transforms,
             ref custom_ecmascript_transforms,
             ref custom_rules,
+            ref custom_module_types,
+            ref custom_transforms,
             execution_context,
             ref rules,
             ..
@@ -225,8 +235,45 @@ impl ModuleOptionsVc {
             ),
         ];
 
+        if let Some(webpack_loaders_options) = enable_webpack_loaders {
+            let execution_context = execution_context
+                .context("execution_context is required for webpack_loaders")?
+                .join("webpack_loaders");
+            let mut import_map = ImportMap::default();
+            import_map.insert_exact_alias(
+                "@vercel/turbopack/loader-runner",
+                if let Some(loader_runner_package) = webpack_loaders_options.loader_runner_package {
+                    loader_runner_package
+                } else {
+                    ImportMapping::External(Some("loader-runner".to_string())).cell()
+                },
+            );
+            let evaluate_context = node_evaluate_asset_context(Some(import_map.cell()));
+            for (ext, loaders) in webpack_loaders_options.extension_to_loaders.iter() {
+                rules.push(ModuleRule::new(
+                    ModuleRuleCondition::all(vec![
+                        ModuleRuleCondition::ResourcePathEndsWith(ext.to_string()),
+                        ModuleRuleCondition::not(ModuleRuleCondition::ReferenceType(
+                            ReferenceType::Url(UrlReferenceSubType::Undefined),
+                        )),
+                    ]),
+                    vec![
+                        ModuleRuleEffect::ModuleType(ModuleType::Ecmascript(app_transforms)),
+                        ModuleRuleEffect::SourceTransforms(SourceTransformsVc::cell(vec![
+                            WebpackLoadersVc::new(evaluate_context, execution_context, *loaders)
+                                .into(),
+                        ])),
+                    ],
+                ));
+            }
+        }
+
         rules.extend(custom_rules.iter().cloned());
 
-        Ok(ModuleOptionsVc::cell(ModuleOptions { rules }))
+        Ok(ModuleOptionsVc::cell(ModuleOptions {
+            rules,
+            custom_module_types: custom_module_types.clone(),
+            custom_transforms: custom_transforms.clone(),
+        }))
     }
 }
@@ -1,10 +1,13 @@
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use turbo_tasks::trace::TraceRawVcs;
 use turbopack_core::{environment::EnvironmentVc, resolve::options::ImportMappingVc};
-use turbopack_ecmascript::EcmascriptInputTransform;
-use turbopack_node::execution_context::ExecutionContextVc;
+use turbopack_ecmascript::{EcmascriptInputTransform, TransformPluginVc};
+use turbopack_node::{
+    execution_context::ExecutionContextVc, transforms::webpack::WebpackLoaderItemsVc,
+};
 
-use super::ModuleRule;
+use super::{CustomModuleTypeVc, ModuleRule};
 use crate::condition::ContextCondition;
 
 #[derive(Default, Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
@@ -13,6 +16,16 @@ pub struct PostCssTransformOptions {
     pub placeholder_for_future_extensions: (),
 }
 
+#[derive(Default, Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct WebpackLoadersOptions {
+    /// Webpack loaders to apply to files ending with a given extension, e.g.
+    /// `".svg" => [{ loader: "@svgr/webpack" }]`. The result of the loaders
+    /// is treated as an ecmascript module.
+    pub extension_to_loaders: IndexMap<String, WebpackLoaderItemsVc>,
+    pub loader_runner_package: Option<ImportMappingVc>,
+    pub placeholder_for_future_extensions: (),
+}
+
 #[turbo_tasks::value(shared)]
 #[derive(Default, Clone)]
 pub struct ModuleOptionsContext {
@@ -22,6 +35,7 @@ pub struct ModuleOptionsContext {
     pub enable_styled_components: bool,
     pub enable_styled_jsx: bool,
     pub enable_postcss_transform: Option<PostCssTransformOptions>,
+    pub enable_webpack_loaders: Option<WebpackLoadersOptions>,
     pub enable_types: bool,
     pub enable_typescript_transform: bool,
     pub preset_env_versions: Option<EnvironmentVc>,
@@ -29,6 +43,13 @@ pub struct ModuleOptionsContext {
     pub custom_ecmascript_transforms: Vec<EcmascriptInputTransform>,
     /// Custom rules to be applied after all default rules.
     pub custom_rules: Vec<ModuleRule>,
+    /// Custom module types by name, which rules can apply with
+    /// [ModuleRuleEffect::Custom](super::ModuleRuleEffect::Custom).
+    pub custom_module_types: IndexMap<String, CustomModuleTypeVc>,
+    /// Custom ecmascript transforms by name, which rules can add to the
+    /// module type with
+    /// [ModuleRuleEffect::Custom](super::ModuleRuleEffect::Custom).
+    pub custom_transforms: IndexMap<String, TransformPluginVc>,
     pub execution_context: Option<ExecutionContextVc>,
     /// A list of rules to use a different module option context for certain
     /// context paths. The first matching is used.
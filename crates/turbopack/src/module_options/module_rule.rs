@@ -6,7 +6,7 @@ use turbopack_core::{reference_type::ReferenceType, source_transform::SourceTran
 use turbopack_css::CssInputTransformsVc;
 use turbopack_ecmascript::EcmascriptInputTransformsVc;
 
-use super::ModuleRuleCondition;
+use super::{CustomModuleTypeVc, ModuleRuleCondition};
 
 #[derive(Debug, Clone, Serialize, Deserialize, TraceRawVcs, PartialEq, Eq)]
 pub struct ModuleRule {
@@ -34,7 +34,10 @@ pub enum ModuleRuleEffect {
     ModuleType(ModuleType),
     AddEcmascriptTransforms(EcmascriptInputTransformsVc),
     SourceTransforms(SourceTransformsVc),
-    Custom,
+    /// Applies the custom module type or custom ecmascript transform
+    /// registered with that name in the
+    /// [ModuleOptionsContext](super::ModuleOptionsContext).
+    Custom(String),
 }
 
 #[turbo_tasks::value(serialization = "auto_for_input", shared)]
@@ -50,6 +53,5 @@ pub enum ModuleType {
     Css(CssInputTransformsVc),
     CssModule(CssInputTransformsVc),
     Static,
-    // TODO allow custom function when we support function pointers
-    Custom(u8),
+    Custom(CustomModuleTypeVc),
 }
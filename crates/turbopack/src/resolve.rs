@@ -8,6 +8,7 @@ use turbopack_core::resolve::{
         ConditionValue, ImportMap, ImportMapping, ResolveInPackage, ResolveIntoPackage,
         ResolveModules, ResolveOptions, ResolveOptionsVc,
     },
+    pnp::find_pnp_manifest,
     AliasMap, AliasPattern, FindContextFileResult,
 };
 use turbopack_ecmascript::{
@@ -17,7 +18,7 @@ use turbopack_ecmascript::{
 
 use crate::resolve_options_context::ResolveOptionsContextVc;
 
-const NODE_EXTERNALS: [&str; 51] = [
+pub(crate) const NODE_EXTERNALS: [&str; 51] = [
     "assert",
     "async_hooks",
     "buffer",
@@ -110,6 +111,53 @@ async fn base_resolve_options(
     }
     let import_map = import_map.cell();
 
+    let conditions = {
+        let mut conditions: BTreeMap<String, ConditionValue> = [
+            ("import".to_string(), ConditionValue::Unknown),
+            ("require".to_string(), ConditionValue::Unknown),
+        ]
+        .into_iter()
+        .collect();
+        if opt.browser {
+            conditions.insert("browser".to_string(), ConditionValue::Set);
+        }
+        if opt.module {
+            conditions.insert("module".to_string(), ConditionValue::Set);
+        }
+        if let Some(environment) = emulating {
+            for condition in environment.resolve_conditions().await?.iter() {
+                conditions.insert(condition.to_string(), ConditionValue::Set);
+            }
+        }
+        for condition in opt.custom_conditions.iter() {
+            conditions.insert(condition.to_string(), ConditionValue::Set);
+        }
+        // Infer some well known conditions
+        let dev = conditions.get("development").cloned();
+        let prod = conditions.get("production").cloned();
+        if prod.is_none() {
+            conditions.insert(
+                "production".to_string(),
+                if matches!(dev, Some(ConditionValue::Set)) {
+                    ConditionValue::Unset
+                } else {
+                    ConditionValue::Unknown
+                },
+            );
+        }
+        if dev.is_none() {
+            conditions.insert(
+                "development".to_string(),
+                if matches!(prod, Some(ConditionValue::Set)) {
+                    ConditionValue::Unset
+                } else {
+                    ConditionValue::Unknown
+                },
+            );
+        }
+        conditions
+    };
+
     Ok(ResolveOptions {
         extensions: if let Some(environment) = emulating {
             environment.resolve_extensions().await?.clone_value()
@@ -142,6 +190,9 @@ async fn base_resolve_options(
             }
         } else {
             let mut mods = Vec::new();
+            if let Some((registry, locked_versions)) = opt.package_registry {
+                mods.push(ResolveModules::Registry(registry, locked_versions));
+            }
             if opt.enable_node_modules {
                 mods.push(ResolveModules::Nested(
                     root,
@@ -154,52 +205,7 @@ async fn base_resolve_options(
             let mut resolve_into = Vec::new();
             resolve_into.push(ResolveIntoPackage::ExportsField {
                 field: "exports".to_string(),
-                conditions: {
-                    let mut conditions: BTreeMap<String, ConditionValue> = [
-                        ("import".to_string(), ConditionValue::Unknown),
-                        ("require".to_string(), ConditionValue::Unknown),
-                    ]
-                    .into_iter()
-                    .collect();
-                    if opt.browser {
-                        conditions.insert("browser".to_string(), ConditionValue::Set);
-                    }
-                    if opt.module {
-                        conditions.insert("module".to_string(), ConditionValue::Set);
-                    }
-                    if let Some(environment) = emulating {
-                        for condition in environment.resolve_conditions().await?.iter() {
-                            conditions.insert(condition.to_string(), ConditionValue::Set);
-                        }
-                    }
-                    for condition in opt.custom_conditions.iter() {
-                        conditions.insert(condition.to_string(), ConditionValue::Set);
-                    }
-                    // Infer some well known conditions
-                    let dev = conditions.get("development").cloned();
-                    let prod = conditions.get("production").cloned();
-                    if prod.is_none() {
-                        conditions.insert(
-                            "production".to_string(),
-                            if matches!(dev, Some(ConditionValue::Set)) {
-                                ConditionValue::Unset
-                            } else {
-                                ConditionValue::Unknown
-                            },
-                        );
-                    }
-                    if dev.is_none() {
-                        conditions.insert(
-                            "development".to_string(),
-                            if matches!(prod, Some(ConditionValue::Set)) {
-                                ConditionValue::Unset
-                            } else {
-                                ConditionValue::Unknown
-                            },
-                        );
-                    }
-                    conditions
-                },
+                conditions: conditions.clone(),
                 unspecified_conditions: ConditionValue::Unset,
             });
             if opt.browser {
@@ -217,6 +223,10 @@ async fn base_resolve_options(
             if opt.browser {
                 resolve_in.push(ResolveInPackage::AliasField("browser".to_string()));
             }
+            resolve_in.push(ResolveInPackage::ImportsField {
+                conditions,
+                unspecified_conditions: ConditionValue::Unset,
+            });
             resolve_in
         },
         import_map: Some(import_map),
@@ -257,6 +267,15 @@ pub async fn resolve_options(
         resolve_options
     };
 
+    let resolve_options = if options_context_value.enable_node_modules {
+        match *find_pnp_manifest(context).await? {
+            Some(manifest) => resolve_options.with_pnp_manifest(manifest),
+            None => resolve_options,
+        }
+    } else {
+        resolve_options
+    };
+
     // Make sure to always apply `options_context.import_map` last, so it properly
     // overwrites any other mappings.
     let resolve_options = options_context_value
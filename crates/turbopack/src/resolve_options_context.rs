@@ -1,7 +1,8 @@
 use anyhow::Result;
+use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::{
     environment::EnvironmentVc,
-    resolve::options::{ImportMapVc, ResolvedMapVc},
+    resolve::options::{ImportMapVc, LockedVersionsVc, ResolvedMapVc},
 };
 
 use crate::condition::ContextCondition;
@@ -15,6 +16,10 @@ pub struct ResolveOptionsContext {
     pub enable_react: bool,
     pub enable_node_native_modules: bool,
     pub enable_node_modules: bool,
+    /// Resolves packages from a shared package store with the structure
+    /// `@scope/module/version/<path-in-package>`, using the versions pinned by
+    /// a lockfile. It's used before `node_modules`.
+    pub package_registry: Option<(FileSystemPathVc, LockedVersionsVc)>,
     /// Mark well-known Node.js modules as external imports and load them using
     /// native `require`. e.g. url, querystring, os
     pub enable_node_externals: bool,
@@ -0,0 +1,11 @@
+exports.ids = [1];
+exports.modules = {
+
+/***/ 20:
+/***/ (function(module, exports) {
+
+module.exports = (name) => `hello ${name}`;
+
+/***/ })
+
+};
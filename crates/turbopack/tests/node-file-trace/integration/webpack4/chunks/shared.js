@@ -0,0 +1,11 @@
+exports.ids = ["shared"];
+exports.modules = {
+
+/***/ 30:
+/***/ (function(module, exports) {
+
+exports.name = "webpack 4";
+
+/***/ })
+
+};
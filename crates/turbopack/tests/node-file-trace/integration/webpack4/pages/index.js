@@ -0,0 +1,22 @@
+var exports = {};
+exports.id = 0;
+exports.ids = [0];
+exports.modules = {
+
+/***/ 10:
+/***/ (function(module, exports, __webpack_require__) {
+
+const greet = __webpack_require__(20);
+const shared = __webpack_require__(30);
+
+console.log(greet(shared.name));
+
+/***/ })
+
+};
+
+var __webpack_require__ = require("../webpack-runtime.js");
+__webpack_require__.C(exports);
+var __webpack_exec__ = (moduleId) => (__webpack_require__(__webpack_require__.s = moduleId))
+var __webpack_exports__ = __webpack_require__.X(0, [1,"shared"], () => (__webpack_exec__(10)));
+module.exports = __webpack_exports__;